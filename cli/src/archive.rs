@@ -0,0 +1,126 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use gekko::metadata::scale::TypeRegistry;
+use gekko::rpc::archive::Archive;
+use gekko::rpc::block::RuntimeCache;
+use gekko::rpc::{HttpClient, Transport};
+use std::fs::{read_to_string, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Args)]
+pub struct ArchiveArgs {
+    /// The HTTP(S) endpoint of an archive node.
+    #[arg(long)]
+    url: String,
+    /// The number of the first block.
+    #[arg(long)]
+    from: u32,
+    /// The number of the last block, inclusive.
+    #[arg(long)]
+    to: u32,
+    /// Path to additional type definitions in the polkadot-js JSON format.
+    #[arg(long)]
+    types: Option<PathBuf>,
+    /// Write the JSON lines to the given file instead of stdout.
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+pub fn run(args: ArchiveArgs) -> Result<()> {
+    let registry = registry(args.types.as_deref())?;
+    let client = HttpClient::new(&args.url);
+
+    let count = match &args.output {
+        Some(path) => {
+            let file = File::create(path)
+                .with_context(|| format!("failed to create {}", path.display()))?;
+            archive(&client, registry, args.from, args.to, BufWriter::new(file))?
+        }
+        None => archive(&client, registry, args.from, args.to, io::stdout().lock())?,
+    };
+
+    eprintln!("Decoded {} extrinsics", count);
+    Ok(())
+}
+
+/// Decodes the extrinsics of the blocks `from..=to` and writes them as JSON
+/// lines. Returns the number of written extrinsics.
+pub fn archive<T: Transport, W: Write>(
+    transport: &T,
+    registry: TypeRegistry,
+    from: u32,
+    to: u32,
+    writer: W,
+) -> Result<usize> {
+    let mut cache = RuntimeCache::new(registry);
+    Archive::new(transport, &mut cache, from..=to)
+        .write_json_lines(writer)
+        .context("failed to archive extrinsics")
+}
+
+/// The bundled type definitions, extended by the definitions of the given
+/// file.
+fn registry(types: Option<&Path>) -> Result<TypeRegistry> {
+    let mut registry = TypeRegistry::default();
+    if let Some(path) = types {
+        let content = read_to_string(path)
+            .with_context(|| format!("failed to read types from {}", path.display()))?;
+        registry
+            .register_json(&content)
+            .context("failed to parse type definitions")?;
+    }
+
+    Ok(registry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gekko::rpc::block::Header;
+    use gekko::rpc::mock::MockTransport;
+    use gekko::rpc::RuntimeVersion;
+    use gekko::transaction::Transaction;
+    use parity_scale_codec::{Compact, Encode};
+
+    #[test]
+    fn archive_timestamps() {
+        let raw = gekko::metadata::decode_hex(
+            include_str!("../../interface/dumps/metadata_kusama_9080.hex").trim(),
+        )
+        .unwrap();
+
+        // Timestamp::set(Compact<Moment>)
+        let timestamp = Transaction::new_unsigned(([2u8, 0], Compact(1_000u64)));
+        let transport = MockTransport::new()
+            .with_metadata(raw)
+            .with_runtime_version(RuntimeVersion {
+                spec_name: "kusama".to_string(),
+                impl_name: "parity-kusama".to_string(),
+                authoring_version: 2,
+                spec_version: 9080,
+                impl_version: 0,
+                transaction_version: 5,
+                apis: vec![],
+            })
+            .with_block(
+                Header {
+                    parent_hash: [1; 32],
+                    number: 5,
+                    state_root: [2; 32],
+                    extrinsics_root: [3; 32],
+                    digest_logs: vec![],
+                },
+                vec![timestamp.encode()],
+            );
+
+        let mut output = vec![];
+        let count = archive(&transport, registry(None).unwrap(), 5, 5, &mut output).unwrap();
+        assert_eq!(count, 1);
+
+        let line: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(line["block_number"], 5);
+        assert_eq!(line["spec_version"], 9080);
+        assert_eq!(line["call"]["call_name"], "set");
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{decode_hex, read_metadata};
+use anyhow::{anyhow, Result};
+use clap::Args;
+use gekko::common::sp_core::crypto::Ss58Codec;
+use gekko::common::{Mortality, MultiAddress, MultiSignature, OpaqueCall};
+use gekko::metadata::dynamic::ExtrinsicDecoder;
+use gekko::metadata::scale::{TypeRegistry, Value};
+use gekko::metadata::MetadataVersion;
+use gekko::transaction::v4::{Payload, Transaction};
+use parity_scale_codec::Decode;
+use std::fmt;
+use std::path::PathBuf;
+
+type DecodableTransaction = Transaction<MultiAddress, OpaqueCall, MultiSignature, Payload>;
+
+#[derive(Args)]
+pub struct DecodeArgs {
+    /// Path to the HEX encoded runtime metadata.
+    #[arg(long)]
+    metadata: PathBuf,
+    /// The HEX encoded extrinsic, including its length prefix.
+    extrinsic: String,
+}
+
+pub fn run(args: DecodeArgs) -> Result<()> {
+    let metadata = read_metadata(&args.metadata)?;
+    let decoded = decode(&metadata, &decode_hex(&args.extrinsic)?)?;
+
+    print!("{}", decoded);
+    Ok(())
+}
+
+/// An extrinsic decoded with the help of the runtime metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedExtrinsic {
+    pub module_name: String,
+    pub extrinsic_name: String,
+    /// The names and type descriptions of the arguments.
+    pub args: Vec<(String, String)>,
+    /// The SCALE encoded arguments.
+    pub arg_data: Vec<u8>,
+    /// The decoded arguments, or `None` if the type of an argument is not
+    /// known.
+    pub arg_values: Option<Vec<Value>>,
+    pub signature: Option<SignatureInfo>,
+}
+
+/// The signature related fields of a signed extrinsic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureInfo {
+    pub signer: MultiAddress,
+    pub mortality: Mortality,
+    pub nonce: u32,
+    pub tip: u128,
+}
+
+pub fn decode(metadata: &MetadataVersion, bytes: &[u8]) -> Result<DecodedExtrinsic> {
+    let transaction = DecodableTransaction::decode(&mut &bytes[..])
+        .map_err(|err| anyhow!("failed to decode extrinsic: {}", err))?;
+
+    let call = transaction.call;
+    let (module_id, dispatch_id) = call
+        .module_id()
+        .zip(call.dispatch_id())
+        .ok_or_else(|| anyhow!("extrinsic does not contain a call"))?;
+
+    let info = metadata
+        .as_inner()?
+        .find_extrinsic_by_index(module_id, dispatch_id)
+        .ok_or_else(|| {
+            anyhow!(
+                "call with module Id {} and dispatch Id {} not found in metadata",
+                module_id,
+                dispatch_id
+            )
+        })?;
+
+    // Metadata V14 contains the type definitions, while the definitions of
+    // the types of V13 are registered separately.
+    let mut registry = TypeRegistry::default();
+    if let MetadataVersion::V13(metadata) = metadata {
+        registry
+            .register_calls(metadata)
+            .map_err(|err| anyhow!("failed to register calls: {}", err))?;
+    }
+
+    let arg_values = ExtrinsicDecoder::new(metadata, &registry)
+        .ok()
+        .and_then(|decoder| {
+            let mut input = call.0.as_slice();
+            let decoded = decoder.decode_call(&mut input).ok()?;
+            input.is_empty().then(|| {
+                decoded
+                    .args
+                    .into_iter()
+                    .map(|(_, value)| value)
+                    .collect::<Vec<Value>>()
+            })
+        });
+
+    Ok(DecodedExtrinsic {
+        module_name: info.module_name.to_string(),
+        extrinsic_name: info.extrinsic_name.to_string(),
+        args: info
+            .args
+            .iter()
+            .map(|(name, ty)| (name.to_string(), ty.to_string()))
+            .collect(),
+        arg_data: call.args().to_vec(),
+        arg_values,
+        signature: transaction
+            .signature
+            .map(|(signer, _, payload)| SignatureInfo {
+                signer,
+                mortality: payload.mortality,
+                nonce: payload.nonce,
+                tip: payload.tip,
+            }),
+    })
+}
+
+impl fmt::Display for DecodedExtrinsic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Pallet:    {}", self.module_name)?;
+        writeln!(f, "Call:      {}", self.extrinsic_name)?;
+
+        match &self.signature {
+            Some(sig) => {
+                let signer = match &sig.signer {
+                    MultiAddress::Id(account) => account.to_ss58check(),
+                    other => format!("{:?}", other),
+                };
+
+                let era = match sig.mortality {
+                    Mortality::Immortal => "immortal".to_string(),
+                    Mortality::Mortal(period, phase, _) => {
+                        format!("mortal (period {}, phase {})", period, phase)
+                    }
+                };
+
+                writeln!(f, "Signer:    {}", signer)?;
+                writeln!(f, "Era:       {}", era)?;
+                writeln!(f, "Nonce:     {}", sig.nonce)?;
+                writeln!(f, "Tip:       {}", sig.tip)?;
+            }
+            None => writeln!(f, "Signer:    none (unsigned)")?,
+        }
+
+        writeln!(f, "Arguments:")?;
+        for (index, (name, ty)) in self.args.iter().enumerate() {
+            match self.arg_values.as_ref().map(|values| &values[index]) {
+                Some(value) => writeln!(f, "  {}: {} = {}", name, ty, value)?,
+                None => writeln!(f, "  {}: {}", name, ty)?,
+            }
+        }
+        writeln!(f, "Argument data: 0x{}", hex::encode(&self.arg_data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gekko::common::*;
+    use gekko::metadata::parse_hex_metadata;
+    use gekko::runtime::kusama::extrinsics::balances::TransferKeepAlive;
+    use gekko::transaction::*;
+    use parity_scale_codec::Encode;
+
+    #[test]
+    fn decode_signed_transfer() {
+        let metadata = parse_hex_metadata(include_str!(
+            "../../interface/dumps/metadata_kusama_9080.hex"
+        ))
+        .unwrap();
+
+        let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+        let currency = BalanceBuilder::new(Currency::Kusama);
+
+        let transaction: PolkadotSignedExtrinsic<_> = SignedTransactionBuilder::new()
+            .signer(keypair)
+            .call(TransferKeepAlive {
+                dest: AccountId::new([1; 32]),
+                value: currency.balance(1),
+            })
+            .nonce(5)
+            .tip(currency.balance_as_metric(Metric::Milli, 10).unwrap())
+            .network(Network::Kusama)
+            .build()
+            .unwrap();
+
+        let decoded = decode(&metadata, &transaction.encode()).unwrap();
+
+        assert_eq!(decoded.module_name, "Balances");
+        assert_eq!(decoded.extrinsic_name, "transfer_keep_alive");
+        assert_eq!(decoded.args.len(), 2);
+        assert_eq!(decoded.arg_data[..33], AccountId::new([1; 32]).encode()[..]);
+        assert_eq!(
+            decoded.arg_values.unwrap(),
+            vec![
+                Value::variant("Id", Value::Bytes(vec![1; 32])),
+                Value::UInt(1_000_000_000_000),
+            ]
+        );
+
+        let sig = decoded.signature.unwrap();
+        assert_eq!(sig.mortality, Mortality::Immortal);
+        assert_eq!(sig.nonce, 5);
+        assert_eq!(sig.tip, 10_000_000_000);
+    }
+}
@@ -0,0 +1,327 @@
+use crate::read_metadata;
+use anyhow::{anyhow, Result};
+use clap::{Args, ValueEnum};
+use gekko::metadata::version::v13::{StorageEntryModifier, StorageEntryType};
+use gekko::metadata::{MetadataExt, MetadataVersion};
+use serde::Serialize;
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct InspectArgs {
+    /// Path to the HEX encoded runtime metadata.
+    #[arg(long)]
+    metadata: PathBuf,
+    /// Only show the pallet with the given name (case-insensitive).
+    #[arg(long)]
+    pallet: Option<String>,
+    /// Only show the given kind of entries.
+    #[arg(long, value_enum)]
+    kind: Option<Kind>,
+    /// Print the result as JSON.
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Kind {
+    Calls,
+    Storage,
+    Constants,
+    Errors,
+}
+
+pub fn run(args: InspectArgs) -> Result<()> {
+    let metadata = read_metadata(&args.metadata)?;
+    let pallets = inspect(&metadata, args.pallet.as_deref(), args.kind)?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&pallets)?);
+    } else {
+        pallets.iter().for_each(print_pallet);
+    }
+
+    Ok(())
+}
+
+/// A pallet (module) of the runtime, reduced to the requested entries.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PalletInfo {
+    pub name: String,
+    pub index: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calls: Option<Vec<CallInfo>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage: Option<Vec<StorageInfo>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constants: Option<Vec<ConstantInfo>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CallInfo {
+    pub name: String,
+    pub index: u8,
+    /// The names and type descriptions of the arguments.
+    pub args: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StorageInfo {
+    pub name: String,
+    /// Whether the entry returns a default value if it is not set.
+    pub optional: bool,
+    /// The type description of the entry, e.g. `Map<Twox64Concat, K, V>`.
+    pub ty: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConstantInfo {
+    pub name: String,
+    pub ty: String,
+    /// The HEX encoded SCALE value.
+    pub value: String,
+}
+
+/// Collects the entries of all pallets, optionally filtered by the pallet
+/// name and the kind of entry.
+pub fn inspect(
+    metadata: &MetadataVersion,
+    pallet: Option<&str>,
+    kind: Option<Kind>,
+) -> Result<Vec<PalletInfo>> {
+    let data = metadata.as_inner()?;
+    let pallets: Vec<PalletInfo> = pallets(metadata)?
+        .into_iter()
+        .filter(|(name, _)| {
+            pallet
+                .map(|pallet| name.eq_ignore_ascii_case(pallet))
+                .unwrap_or(true)
+        })
+        .map(|(name, index)| pallet_info(data, name, index, kind))
+        .collect();
+
+    if let (Some(name), true) = (pallet, pallets.is_empty()) {
+        return Err(anyhow!("pallet {} does not exist", name));
+    }
+
+    Ok(pallets)
+}
+
+/// The names and indices of all pallets, in the order of the metadata.
+fn pallets(metadata: &MetadataVersion) -> Result<Vec<(&str, u8)>> {
+    match metadata {
+        MetadataVersion::V13(data) => Ok(data
+            .modules
+            .iter()
+            .map(|module| (module.name.as_str(), module.index))
+            .collect()),
+        MetadataVersion::V14(data) => Ok(data
+            .pallets
+            .iter()
+            .map(|pallet| (pallet.name.as_str(), pallet.index))
+            .collect()),
+        other => Err(anyhow!(
+            "metadata version {} is not supported",
+            other.version_number()
+        )),
+    }
+}
+
+fn pallet_info(data: &dyn MetadataExt, name: &str, index: u8, kind: Option<Kind>) -> PalletInfo {
+    let show = |other: Kind| kind.map(|kind| kind == other).unwrap_or(true);
+
+    PalletInfo {
+        name: name.to_string(),
+        index,
+        calls: show(Kind::Calls).then(|| {
+            data.modules_extrinsics()
+                .into_iter()
+                .filter(|info| info.module_name == name)
+                .map(|info| CallInfo {
+                    name: info.extrinsic_name.to_string(),
+                    index: info.dispatch_id as u8,
+                    args: info
+                        .args
+                        .iter()
+                        .map(|(arg, ty)| (arg.to_string(), ty.to_string()))
+                        .collect(),
+                })
+                .collect()
+        }),
+        storage: show(Kind::Storage).then(|| {
+            data.modules_storage()
+                .into_iter()
+                .filter(|info| info.module_name == name)
+                .map(|info| StorageInfo {
+                    name: info.entry_name.to_string(),
+                    optional: *info.modifier == StorageEntryModifier::Optional,
+                    ty: storage_type(&info.ty),
+                })
+                .collect()
+        }),
+        constants: show(Kind::Constants).then(|| {
+            data.constants()
+                .into_iter()
+                .filter(|info| info.module_name == name)
+                .map(|info| ConstantInfo {
+                    name: info.constant_name.to_string(),
+                    ty: info.ty.to_string(),
+                    value: format!("0x{}", hex::encode(info.value)),
+                })
+                .collect()
+        }),
+        errors: show(Kind::Errors).then(|| {
+            data.modules_errors()
+                .into_iter()
+                .filter(|info| info.module_name == name)
+                .map(|info| info.error_name.to_string())
+                .collect()
+        }),
+    }
+}
+
+fn storage_type(ty: &StorageEntryType) -> String {
+    match ty {
+        StorageEntryType::Plain(value) => value.clone(),
+        StorageEntryType::Map {
+            hasher, key, value, ..
+        } => format!("Map<{:?}, {}, {}>", hasher, key, value),
+        StorageEntryType::DoubleMap {
+            hasher,
+            key1,
+            key2,
+            value,
+            key2_hasher,
+        } => format!(
+            "DoubleMap<{:?}, {}, {:?}, {}, {}>",
+            hasher, key1, key2_hasher, key2, value
+        ),
+        StorageEntryType::NMap {
+            keys,
+            hashers,
+            value,
+        } => format!("NMap<{:?}, {}, {}>", hashers, keys, value),
+    }
+}
+
+fn print_pallet(pallet: &PalletInfo) {
+    println!("{} ({})", pallet.name, pallet.index);
+
+    if let Some(calls) = pallet.calls.as_ref().filter(|calls| !calls.is_empty()) {
+        println!("  Calls:");
+        for call in calls {
+            let args: Vec<String> = call
+                .args
+                .iter()
+                .map(|(name, ty)| format!("{}: {}", name, ty))
+                .collect();
+
+            println!("    {} ({}): ({})", call.name, call.index, args.join(", "));
+        }
+    }
+
+    if let Some(storage) = pallet.storage.as_ref().filter(|s| !s.is_empty()) {
+        println!("  Storage:");
+        for entry in storage {
+            let modifier = if entry.optional {
+                "optional"
+            } else {
+                "default"
+            };
+            println!("    {}: {} ({})", entry.name, entry.ty, modifier);
+        }
+    }
+
+    if let Some(constants) = pallet.constants.as_ref().filter(|c| !c.is_empty()) {
+        println!("  Constants:");
+        for constant in constants {
+            println!(
+                "    {}: {} = {}",
+                constant.name, constant.ty, constant.value
+            );
+        }
+    }
+
+    if let Some(errors) = pallet.errors.as_ref().filter(|e| !e.is_empty()) {
+        println!("  Errors:");
+        for error in errors {
+            println!("    {}", error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gekko::metadata::parse_hex_metadata;
+
+    #[test]
+    fn inspect_balances_calls() {
+        let metadata = parse_hex_metadata(include_str!(
+            "../../interface/dumps/metadata_kusama_9080.hex"
+        ))
+        .unwrap();
+
+        let pallets = inspect(&metadata, Some("balances"), Some(Kind::Calls)).unwrap();
+        assert_eq!(pallets.len(), 1);
+
+        let pallet = &pallets[0];
+        assert_eq!(pallet.name, "Balances");
+        assert!(pallet.storage.is_none());
+
+        let call = pallet
+            .calls
+            .as_ref()
+            .unwrap()
+            .iter()
+            .find(|call| call.name == "transfer_keep_alive")
+            .unwrap();
+
+        assert_eq!(call.index, 3);
+        assert_eq!(
+            call.args,
+            vec![
+                (
+                    "dest".to_string(),
+                    "<T::Lookup as StaticLookup>::Source".to_string()
+                ),
+                ("value".to_string(), "Compact<T::Balance>".to_string()),
+            ]
+        );
+
+        assert!(inspect(&metadata, Some("Unknown"), None).is_err());
+    }
+
+    #[test]
+    fn inspect_balances_v14() {
+        let metadata = parse_hex_metadata(include_str!(
+            "../../interface/dumps/metadata_kusama_9111.hex"
+        ))
+        .unwrap();
+
+        let pallets = inspect(&metadata, Some("balances"), None).unwrap();
+        assert_eq!(pallets.len(), 1);
+
+        let pallet = &pallets[0];
+        assert_eq!((pallet.name.as_str(), pallet.index), ("Balances", 4));
+
+        let call = pallet
+            .calls
+            .as_ref()
+            .unwrap()
+            .iter()
+            .find(|call| call.name == "transfer_keep_alive")
+            .unwrap();
+        assert_eq!(call.index, 3);
+        assert_eq!(call.args[0].0, "dest");
+
+        let storage = pallet.storage.as_ref().unwrap();
+        assert!(storage.iter().any(|entry| entry.name == "TotalIssuance"));
+        let constants = pallet.constants.as_ref().unwrap();
+        assert!(constants
+            .iter()
+            .any(|constant| constant.name == "ExistentialDeposit"));
+        assert!(!pallet.errors.as_ref().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,56 @@
+//! Command line utilities for substrate-based blockchains, built on top of
+//! gekko.
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use gekko::metadata::{parse_hex_metadata, MetadataVersion};
+use std::fs::read_to_string;
+use std::path::Path;
+
+mod archive;
+mod decode;
+mod inspect;
+mod sign;
+
+#[derive(Parser)]
+#[command(name = "gekko", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Decode the extrinsics of a range of blocks and print them as JSON
+    /// lines.
+    Archive(archive::ArchiveArgs),
+    /// Decode a HEX encoded extrinsic.
+    Decode(decode::DecodeArgs),
+    /// List the pallets, calls, storage entries, constants and errors of the
+    /// runtime metadata.
+    Inspect(inspect::InspectArgs),
+    /// Build and sign a transaction from a JSON description.
+    Sign(sign::SignArgs),
+}
+
+fn main() -> Result<()> {
+    match Cli::parse().command {
+        Command::Archive(args) => archive::run(args),
+        Command::Decode(args) => decode::run(args),
+        Command::Inspect(args) => inspect::run(args),
+        Command::Sign(args) => sign::run(args),
+    }
+}
+
+/// Reads and parses the HEX encoded runtime metadata from the given file.
+fn read_metadata(path: &Path) -> Result<MetadataVersion> {
+    let content = read_to_string(path)
+        .with_context(|| format!("failed to read metadata from {}", path.display()))?;
+
+    parse_hex_metadata(content.trim()).context("failed to parse metadata")
+}
+
+/// Decodes a HEX string, with or without the `0x` prefix.
+fn decode_hex(hex_str: &str) -> Result<Vec<u8>> {
+    gekko::metadata::decode_hex(hex_str.trim()).context("invalid HEX string")
+}
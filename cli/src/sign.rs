@@ -0,0 +1,336 @@
+use crate::{decode_hex, read_metadata};
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Args, ValueEnum};
+use gekko::common::sp_core::crypto::Pair;
+use gekko::common::{Ecdsa, Ed25519, Mortality, MultiKeyPair, Network, OpaqueCall, Sr25519};
+use gekko::metadata::MetadataVersion;
+use gekko::transaction::{PolkadotSignedExtrinsic, SignedTransactionBuilder};
+use parity_scale_codec::Encode;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct SignArgs {
+    /// Path to the HEX encoded runtime metadata.
+    #[arg(long)]
+    metadata: PathBuf,
+    /// Path to the JSON description of the transaction.
+    spec: PathBuf,
+    /// The secret URI of the signer, such as a mnemonic phrase, a HEX encoded
+    /// seed or a dev account like "//Alice".
+    #[arg(
+        long,
+        conflicts_with = "suri_file",
+        required_unless_present = "suri_file"
+    )]
+    suri: Option<String>,
+    /// Path to a file containing the secret URI of the signer.
+    #[arg(long)]
+    suri_file: Option<PathBuf>,
+    /// The password of the secret URI, if any.
+    #[arg(long)]
+    password: Option<String>,
+    /// The signature scheme of the signer.
+    #[arg(long, value_enum, default_value = "sr25519")]
+    scheme: Scheme,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Scheme {
+    Sr25519,
+    Ed25519,
+    Ecdsa,
+}
+
+/// JSON description of a transaction.
+///
+/// ```json
+/// {
+///   "network": "polkadot",
+///   "spec_version": 9050,
+///   "transaction_version": 7,
+///   "call": {
+///     "module": "Balances",
+///     "extrinsic": "transfer_keep_alive",
+///     "args": { "dest": "0x00...", "value": "0x..." }
+///   },
+///   "nonce": 0,
+///   "tip": "10000000",
+///   "mortality": { "period": 64, "block_number": 1234, "block_hash": "0x..." }
+/// }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransactionSpec {
+    /// Either "polkadot", "kusama", "westend" or the HEX encoded genesis hash
+    /// of any other network.
+    pub network: String,
+    pub spec_version: u32,
+    pub transaction_version: u32,
+    pub call: CallSpec,
+    pub nonce: u32,
+    /// The tip in the base unit, as a number or a string.
+    #[serde(default)]
+    pub tip: Option<serde_json::Value>,
+    /// The transaction is immortal if not specified.
+    #[serde(default)]
+    pub mortality: Option<MortalitySpec>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CallSpec {
+    pub module: String,
+    pub extrinsic: String,
+    /// The HEX encoded SCALE value of each argument, by argument name.
+    #[serde(default)]
+    pub args: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MortalitySpec {
+    /// The number of blocks the transaction is valid for, must be a power of
+    /// two.
+    pub period: u64,
+    /// The number of the current block.
+    pub block_number: u64,
+    /// The HEX encoded hash of the current block.
+    pub block_hash: String,
+}
+
+pub fn run(args: SignArgs) -> Result<()> {
+    let metadata = read_metadata(&args.metadata)?;
+
+    let content = read_to_string(&args.spec)
+        .with_context(|| format!("failed to read {}", args.spec.display()))?;
+    let spec: TransactionSpec =
+        serde_json::from_str(&content).context("invalid transaction spec")?;
+
+    let suri = match (args.suri, args.suri_file) {
+        (Some(suri), _) => suri,
+        (None, Some(path)) => read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?
+            .trim()
+            .to_string(),
+        (None, None) => bail!("either --suri or --suri-file must be specified"),
+    };
+
+    let signer = signer(&suri, args.password.as_deref(), args.scheme)?;
+    let transaction = sign(&metadata, &spec, signer)?;
+
+    println!("0x{}", hex::encode(transaction.encode()));
+    Ok(())
+}
+
+/// Creates the key pair of the signer from the secret URI.
+pub fn signer(suri: &str, password: Option<&str>, scheme: Scheme) -> Result<MultiKeyPair> {
+    let err = |err| anyhow!("invalid secret URI: {:?}", err);
+
+    Ok(match scheme {
+        Scheme::Sr25519 => Sr25519::from_string(suri, password).map_err(err)?.into(),
+        Scheme::Ed25519 => Ed25519::from_string(suri, password).map_err(err)?.into(),
+        Scheme::Ecdsa => Ecdsa::from_string(suri, password).map_err(err)?.into(),
+    })
+}
+
+/// Builds and signs the transaction as described by the spec.
+pub fn sign(
+    metadata: &MetadataVersion,
+    spec: &TransactionSpec,
+    signer: MultiKeyPair,
+) -> Result<PolkadotSignedExtrinsic<OpaqueCall>> {
+    let call = encode_call(metadata, &spec.call)?;
+
+    let network = match spec.network.to_lowercase().as_str() {
+        "polkadot" => Network::Polkadot,
+        "kusama" => Network::Kusama,
+        "westend" => Network::Westend,
+        genesis => {
+            let bytes = decode_hex(genesis).context("invalid network or genesis hash")?;
+            let mut hash = [0; 32];
+            if bytes.len() != 32 {
+                bail!("genesis hash must be 32 bytes");
+            }
+            hash.copy_from_slice(&bytes);
+            Network::Custom(hash)
+        }
+    };
+
+    let mortality = match &spec.mortality {
+        Some(mortal) => {
+            if !mortal.period.is_power_of_two() || mortal.period < 4 {
+                bail!("mortality period must be a power of two and at least 4");
+            }
+
+            let bytes = decode_hex(&mortal.block_hash)?;
+            if bytes.len() != 32 {
+                bail!("block hash must be 32 bytes");
+            }
+            let mut birth = [0; 32];
+            birth.copy_from_slice(&bytes);
+
+            Mortality::Mortal(
+                mortal.period,
+                mortal.block_number % mortal.period,
+                Some(birth),
+            )
+        }
+        None => Mortality::Immortal,
+    };
+
+    let tip = match &spec.tip {
+        None => 0,
+        Some(serde_json::Value::Number(n)) => n
+            .as_u64()
+            .map(u128::from)
+            .ok_or_else(|| anyhow!("tip must be a positive integer"))?,
+        Some(serde_json::Value::String(s)) => s.parse().context("invalid tip")?,
+        Some(_) => bail!("tip must be a number or a string"),
+    };
+
+    SignedTransactionBuilder::new()
+        .signer(signer)
+        .call(call)
+        .nonce(spec.nonce)
+        .tip_base_unit(tip)
+        .network(network)
+        .mortality(mortality)
+        .spec_version(spec.spec_version)
+        .transaction_version(spec.transaction_version)
+        .build()
+        .context("failed to build transaction")
+}
+
+/// Encodes the call by looking up its indices and argument order in the
+/// metadata.
+pub fn encode_call(metadata: &MetadataVersion, spec: &CallSpec) -> Result<OpaqueCall> {
+    let info = metadata
+        .as_inner()?
+        .find_module_extrinsic(&spec.module, &spec.extrinsic)
+        .ok_or_else(|| anyhow!("{}::{} not found in metadata", spec.module, spec.extrinsic))?;
+
+    if let Some(unknown) = spec
+        .args
+        .keys()
+        .find(|name| !info.args.iter().any(|(arg, _)| arg == name))
+    {
+        bail!("unknown argument `{}`", unknown);
+    }
+
+    let mut encoded = vec![info.module_id as u8, info.dispatch_id as u8];
+    for (name, ty) in &info.args {
+        let value = spec
+            .args
+            .get(*name)
+            .ok_or_else(|| anyhow!("missing argument `{}` ({})", name, ty))?;
+
+        encoded.extend(decode_hex(value).with_context(|| format!("argument `{}`", name))?);
+    }
+
+    Ok(OpaqueCall(encoded))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode::decode;
+    use gekko::metadata::parse_hex_metadata;
+    use gekko::metadata::scale::Value;
+
+    #[test]
+    fn sign_and_decode_transfer() {
+        let metadata = parse_hex_metadata(include_str!(
+            "../../interface/dumps/metadata_kusama_9080.hex"
+        ))
+        .unwrap();
+
+        let spec: TransactionSpec = serde_json::from_str(
+            r#"{
+                "network": "kusama",
+                "spec_version": 9080,
+                "transaction_version": 5,
+                "call": {
+                    "module": "Balances",
+                    "extrinsic": "transfer_keep_alive",
+                    "args": {
+                        "dest": "0x000101010101010101010101010101010101010101010101010101010101010101",
+                        "value": "0x0b00a0724e1809"
+                    }
+                },
+                "nonce": 3,
+                "tip": "1000",
+                "mortality": { "period": 64, "block_number": 1234, "block_hash": "0x0000000000000000000000000000000000000000000000000000000000000000" }
+            }"#,
+        )
+        .unwrap();
+
+        let signer = signer("//Alice", None, Scheme::Sr25519).unwrap();
+        let transaction = sign(&metadata, &spec, signer).unwrap();
+        let decoded = decode(&metadata, &transaction.encode()).unwrap();
+
+        assert_eq!(decoded.module_name, "Balances");
+        assert_eq!(decoded.extrinsic_name, "transfer_keep_alive");
+
+        let sig = decoded.signature.unwrap();
+        assert_eq!(sig.nonce, 3);
+        assert_eq!(sig.tip, 1000);
+        assert_eq!(sig.mortality, Mortality::Mortal(64, 1234 % 64, None));
+    }
+
+    #[test]
+    fn encode_call_rejects_missing_args() {
+        let metadata = parse_hex_metadata(include_str!(
+            "../../interface/dumps/metadata_kusama_9080.hex"
+        ))
+        .unwrap();
+
+        let call = CallSpec {
+            module: "Balances".to_string(),
+            extrinsic: "transfer_keep_alive".to_string(),
+            args: BTreeMap::new(),
+        };
+
+        assert!(encode_call(&metadata, &call).is_err());
+    }
+
+    #[test]
+    fn sign_and_decode_transfer_v14() {
+        let metadata = parse_hex_metadata(include_str!(
+            "../../interface/dumps/metadata_kusama_9111.hex"
+        ))
+        .unwrap();
+
+        let spec: TransactionSpec = serde_json::from_str(
+            r#"{
+                "network": "kusama",
+                "spec_version": 9111,
+                "transaction_version": 7,
+                "call": {
+                    "module": "Balances",
+                    "extrinsic": "transfer_keep_alive",
+                    "args": {
+                        "dest": "0x000101010101010101010101010101010101010101010101010101010101010101",
+                        "value": "0x0b00a0724e1809"
+                    }
+                },
+                "nonce": 3
+            }"#,
+        )
+        .unwrap();
+
+        let signer = signer("//Alice", None, Scheme::Sr25519).unwrap();
+        let transaction = sign(&metadata, &spec, signer).unwrap();
+        let decoded = decode(&metadata, &transaction.encode()).unwrap();
+
+        assert_eq!(decoded.module_name, "Balances");
+        assert_eq!(decoded.extrinsic_name, "transfer_keep_alive");
+        assert_eq!(
+            decoded.arg_values.unwrap(),
+            vec![
+                Value::variant("Id", Value::Bytes(vec![1; 32])),
+                Value::UInt(10_000_000_000_000),
+            ]
+        );
+        assert_eq!(decoded.signature.unwrap().nonce, 3);
+    }
+}
@@ -0,0 +1,10 @@
+#![no_main]
+
+use gekko::metadata::{parse_raw_metadata, ModuleMetadataExt};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(Ok(metadata)) = parse_raw_metadata(data).map(|m| m.into_latest()) {
+        let _ = metadata.modules_extrinsics();
+    }
+});
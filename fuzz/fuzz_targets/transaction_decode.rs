@@ -0,0 +1,15 @@
+#![no_main]
+
+use gekko::common::{MultiAddress, MultiSignature, OpaqueCall};
+use gekko::transaction::v4::{Payload, Transaction};
+use libfuzzer_sys::fuzz_target;
+use parity_scale_codec::{Decode, Encode};
+
+type DecodableTransaction = Transaction<MultiAddress, OpaqueCall, MultiSignature, Payload>;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(transaction) = DecodableTransaction::decode(&mut &data[..]) {
+        // Re-encoding a decoded transaction must not panic either.
+        let _ = transaction.encode();
+    }
+});
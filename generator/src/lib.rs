@@ -1,74 +1,465 @@
 use convert_case::{Case, Casing};
-use gekko_metadata::{parse_hex_metadata, ModuleMetadataExt};
-use proc_macro::TokenTree;
-use proc_macro2::TokenStream;
+use gekko_metadata::version::v13::StorageEntryType;
+use gekko_metadata::{parse_hex_metadata, ConstantInfo, ErrorInfo, EventInfo, StorageInfo};
+use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use syn::punctuated::Punctuated;
+use syn::{parse_macro_input, AttributeArgs, DeriveInput, Lit, LitStr, Meta, NestedMeta, Token};
 
+/// Generates the runtime interfaces from the HEX encoded metadata file.
+///
+/// The first argument is the path to the file. Relative paths are resolved
+/// against the manifest directory of the crate which invokes the macro.
+/// Optionally, the versions of the runtime can be passed on as named
+/// arguments, which generates the `SPEC_VERSION` and `TRANSACTION_VERSION`
+/// constants, respectively:
+///
+/// ```ignore
+/// #[gekko_generator::parse_from_hex_file(
+///     "dumps/metadata_polkadot_9050.hex",
+///     spec_version = 9050,
+///     transaction_version = 7,
+/// )]
+/// struct A;
+/// ```
+///
+/// The extrinsics derive `serde::Serialize` and `serde::Deserialize` if the
+/// `"serde"` feature of the invoking crate is enabled. With `derive(serde)`,
+/// the extrinsics derive those unconditionally:
+///
+/// ```ignore
+/// #[gekko_generator::parse_from_hex_file(
+///     "dumps/metadata_polkadot_9050.hex",
+///     derive(serde),
+/// )]
+/// struct A;
+/// ```
+///
+/// Besides the extrinsics, the macro generates the storage keys, the events,
+/// the constants and the errors of each module.
+///
+/// By default, each argument of an extrinsic is a generic type. Arguments can
+/// be substituted by concrete types with `types(..)`, mapping the absolute
+/// path of a type to the type description of the metadata. Each type must
+/// implement `GekkoType` with a matching type description, which is verified
+/// at compile time:
+///
+/// ```ignore
+/// #[gekko_generator::parse_from_hex_file(
+///     "dumps/metadata_polkadot_9050.hex",
+///     types(crate::MyBalance = "Compact<T::Balance>"),
+/// )]
+/// struct A;
+/// ```
 #[proc_macro_attribute]
 pub fn parse_from_hex_file(
     args: proc_macro::TokenStream,
     _: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
-    // Extract path.
-    let tree = args
-        .into_iter()
-        .nth(0)
-        .expect("Expected path literal as argument. E.g \"/path/to/file\"");
+    let args = parse_macro_input!(args as AttributeArgs);
+
+    expand_from_hex(args, MetadataSource::File)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Generates the runtime interfaces from the HEX encoded metadata file, whose
+/// path is read from the given environment variable at compile time. Accepts
+/// the same named arguments as [`macro@parse_from_hex_file`]:
+///
+/// ```ignore
+/// gekko_generator::parse_from_hex_env!("GEKKO_METADATA_PATH", spec_version = 9050);
+/// ```
+#[proc_macro]
+pub fn parse_from_hex_env(args: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let args = parse_macro_input!(args with Punctuated::<NestedMeta, Token![,]>::parse_terminated);
+
+    expand_from_hex(args.into_iter().collect(), MetadataSource::Env)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Generates the runtime interfaces from the HEX encoded metadata literal.
+/// Accepts the same named arguments as [`macro@parse_from_hex_file`]:
+///
+/// ```ignore
+/// gekko_generator::parse_from_hex_str!("0x6d657461...", spec_version = 9050);
+/// ```
+#[proc_macro]
+pub fn parse_from_hex_str(args: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let args = parse_macro_input!(args with Punctuated::<NestedMeta, Token![,]>::parse_terminated);
+
+    expand_from_hex(args.into_iter().collect(), MetadataSource::Str)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Where the HEX encoded metadata is read from, as given by the first argument
+/// of the macro.
+#[derive(Clone, Copy)]
+enum MetadataSource {
+    /// The path to the file.
+    File,
+    /// The environment variable containing the path to the file.
+    Env,
+    /// The metadata itself.
+    Str,
+}
+
+impl MetadataSource {
+    fn expected_msg(&self) -> &'static str {
+        match self {
+            MetadataSource::File => "Expected path literal as argument. E.g \"/path/to/file\"",
+            MetadataSource::Env => {
+                "Expected environment variable literal as argument. E.g \"GEKKO_METADATA_PATH\""
+            }
+            MetadataSource::Str => {
+                "Expected HEX encoded metadata literal as argument. E.g \"0x6d657461...\""
+            }
+        }
+    }
+}
 
-    let path = match tree {
-        TokenTree::Literal(path) => path.to_string(),
-        _ => panic!("Expected path literal as argument. E.g \"/path/to/file\""),
+fn expand_from_hex(args: AttributeArgs, source: MetadataSource) -> syn::Result<TokenStream> {
+    let mut args = args.into_iter();
+
+    // Extract the path, environment variable or metadata.
+    let lit = match args.next() {
+        Some(NestedMeta::Lit(Lit::Str(lit))) => lit,
+        Some(other) => return Err(syn::Error::new_spanned(other, source.expected_msg())),
+        None => return Err(syn::Error::new(Span::call_site(), source.expected_msg())),
     };
 
-    let path = path.replace("\"", "");
+    // Extract the optional runtime versions, type substitutions and derives.
+    let mut versions = RuntimeVersions::default();
+    let mut substitutions = Substitutions::new();
+    let mut derives = Derives::default();
+    for arg in args {
+        match arg {
+            NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("derive") => {
+                for nested in list.nested {
+                    match nested {
+                        NestedMeta::Meta(Meta::Path(path)) if path.is_ident("serde") => {
+                            derives.serde = true;
+                        }
+                        other => {
+                            return Err(syn::Error::new_spanned(
+                                other,
+                                "Unknown derive, expected `serde`",
+                            ))
+                        }
+                    }
+                }
+            }
+            NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("types") => {
+                for nested in list.nested {
+                    match nested {
+                        NestedMeta::Meta(Meta::NameValue(pair)) => match pair.lit {
+                            Lit::Str(desc) => {
+                                substitutions.insert(desc.value(), pair.path);
+                            }
+                            other => {
+                                return Err(syn::Error::new_spanned(
+                                    other,
+                                    "Expected type description literal, e.g. \"Compact<T::Balance>\"",
+                                ))
+                            }
+                        },
+                        other => {
+                            return Err(syn::Error::new_spanned(
+                                other,
+                                "Expected type substitution, e.g. `crate::MyBalance = \"Compact<T::Balance>\"`",
+                            ))
+                        }
+                    }
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(pair)) => {
+                let value = match &pair.lit {
+                    Lit::Int(int) => int.base10_parse::<u32>()?,
+                    other => {
+                        return Err(syn::Error::new_spanned(
+                            other,
+                            "Expected integer literal as runtime version",
+                        ))
+                    }
+                };
+
+                if pair.path.is_ident("spec_version") {
+                    versions.spec_version = Some(value);
+                } else if pair.path.is_ident("transaction_version") {
+                    versions.transaction_version = Some(value);
+                } else {
+                    return Err(syn::Error::new_spanned(
+                        pair.path,
+                        "Unknown argument, expected `spec_version`, `transaction_version`, `types` or `derive`",
+                    ));
+                }
+            }
+            other => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    "Expected named argument, e.g. `spec_version = 9050`",
+                ))
+            }
+        }
+    }
+
+    let mut stream = TokenStream::new();
+    let content = match source {
+        MetadataSource::File => read_metadata_file(&lit, &lit.value())?,
+        MetadataSource::Env => {
+            let name = lit.value();
+            let path = std::env::var(&name).map_err(|err| {
+                syn::Error::new_spanned(
+                    &lit,
+                    format!("Failed to read environment variable \"{}\": {}", name, err),
+                )
+            })?;
+
+            // Rebuilds the interfaces whenever the environment variable
+            // changes.
+            stream.extend(quote! {
+                const _: Option<&str> = option_env!(#name);
+            });
+
+            read_metadata_file(&lit, &path)?
+        }
+        MetadataSource::Str => lit.value(),
+    };
+
+    stream.extend(versions.to_token_stream());
+    stream.extend(substitution_checks(&substitutions));
+    stream.extend(
+        process_runtime_metadata(content.as_str(), &substitutions, &derives)
+            .map_err(|msg| syn::Error::new_spanned(&lit, msg))?,
+    );
+    Ok(stream)
+}
+
+/// Reads the content of the metadata file. Relative paths are resolved
+/// against the manifest directory of the crate which invokes the macro.
+fn read_metadata_file(lit: &LitStr, path: &str) -> syn::Result<String> {
+    let resolved = match std::env::var_os("CARGO_MANIFEST_DIR") {
+        Some(dir) if Path::new(path).is_relative() => Path::new(&dir).join(path),
+        _ => PathBuf::from(path),
+    };
+
+    read_to_string(&resolved).map_err(|err| {
+        syn::Error::new_spanned(
+            lit,
+            format!(
+                "Failed to read runtime metadata from \"{}\": {}",
+                resolved.display(),
+                err
+            ),
+        )
+    })
+}
+
+/// Concrete types which substitute the generic arguments of extrinsics, by
+/// type description.
+type Substitutions = HashMap<String, syn::Path>;
+
+/// Verifies at compile time that the substituted types correspond to the type
+/// descriptions.
+fn substitution_checks(substitutions: &Substitutions) -> TokenStream {
+    substitutions
+        .iter()
+        .map(|(desc, path)| {
+            let msg = format!(
+                "`{}` does not correspond to the type description `{}`",
+                quote!(#path).to_string().replace(' ', ""),
+                desc
+            );
+
+            quote! {
+                const _: () = assert!(
+                    gekko::common::type_desc_matches(
+                        <#path as gekko::common::GekkoType>::TYPE_DESCS,
+                        #desc,
+                    ),
+                    #msg
+                );
+            }
+        })
+        .collect()
+}
+
+/// Implements `GekkoType`, mapping the type to one or more type descriptions
+/// of the runtime metadata. The type can then substitute the generic arguments
+/// of generated extrinsics, see [`macro@parse_from_hex_file`].
+///
+/// ```ignore
+/// #[derive(parity_scale_codec::Encode, gekko::generator::GekkoType)]
+/// #[gekko(type = "Compact<T::Balance>", type = "Compact<BalanceOf<T>>")]
+/// pub struct MyBalance(#[codec(compact)] u128);
+/// ```
+#[proc_macro_derive(GekkoType, attributes(gekko))]
+pub fn derive_gekko_type(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand_gekko_type(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+fn expand_gekko_type(input: DeriveInput) -> syn::Result<TokenStream> {
+    let mut descs: Vec<LitStr> = vec![];
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("gekko"))
+    {
+        let list =
+            match attr.parse_meta()? {
+                Meta::List(list) => list,
+                other => return Err(syn::Error::new_spanned(
+                    other,
+                    "Expected type descriptions, e.g. `#[gekko(type = \"Compact<T::Balance>\")]`",
+                )),
+            };
+
+        for nested in list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(pair)) if pair.path.is_ident("type") => {
+                    match pair.lit {
+                        Lit::Str(desc) => descs.push(desc),
+                        other => {
+                            return Err(syn::Error::new_spanned(
+                                other,
+                                "Expected type description literal, e.g. \"Compact<T::Balance>\"",
+                            ))
+                        }
+                    }
+                }
+                other => {
+                    return Err(syn::Error::new_spanned(
+                        other,
+                        "Unknown argument, expected `type`",
+                    ))
+                }
+            }
+        }
+    }
+
+    if descs.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "Missing type description, e.g. `#[gekko(type = \"Compact<T::Balance>\")]`",
+        ));
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
-    // Read content from file.
-    let content = read_to_string(&path).expect(&format!(
-        "Failed to read runtime metadata from \"{}\"",
-        path
-    ));
+    Ok(quote! {
+        impl #impl_generics gekko::common::GekkoType for #ident #ty_generics #where_clause {
+            const TYPE_DESCS: &'static [&'static str] = &[#(#descs),*];
+        }
+    })
+}
+
+/// Additional derives of the generated extrinsics.
+#[derive(Default)]
+struct Derives {
+    /// Derive `Serialize` and `Deserialize` unconditionally, instead of only
+    /// if the `"serde"` feature of the invoking crate is enabled.
+    serde: bool,
+}
+
+impl Derives {
+    fn serde_derive(&self) -> TokenStream {
+        if self.serde {
+            quote!(#[derive(serde::Serialize, serde::Deserialize)])
+        } else {
+            quote!(#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))])
+        }
+    }
+}
+
+/// Runtime versions which are not part of the metadata and must be passed on
+/// to the macro manually.
+#[derive(Default)]
+struct RuntimeVersions {
+    spec_version: Option<u32>,
+    transaction_version: Option<u32>,
+}
+
+impl RuntimeVersions {
+    fn to_token_stream(&self) -> TokenStream {
+        let mut stream = TokenStream::new();
+
+        if let Some(version) = self.spec_version {
+            stream.extend(quote! {
+                /// The `spec_version` of the runtime.
+                pub const SPEC_VERSION: u32 = #version;
+            });
+        }
+
+        if let Some(version) = self.transaction_version {
+            stream.extend(quote! {
+                /// The `transaction_version` of the runtime. Must be increased
+                /// by the runtime whenever the transaction format changes,
+                /// such as the call indices or arguments.
+                pub const TRANSACTION_VERSION: u32 = #version;
+            });
+        }
 
-    process_runtime_metadata(content.as_str()).into()
+        stream
+    }
 }
 
-fn process_runtime_metadata(content: &str) -> TokenStream {
+/// Generates the runtime interfaces. Returns an error message if the metadata
+/// cannot be processed.
+fn process_runtime_metadata(
+    content: &str,
+    substitutions: &Substitutions,
+    derives: &Derives,
+) -> Result<TokenStream, String> {
     // Parse runtime metadata
     let data = parse_hex_metadata(content)
-        .map_err(|err| panic!("Failed to parse runtime metadata: {:?}", err))
-        .unwrap()
-        .into_inner();
+        .and_then(|data| data.into_inner())
+        .map_err(|err| format!("Failed to parse runtime metadata: {:?}", err))?;
 
     let mut final_extrinsics = TokenStream::new();
     let mut modules: HashMap<syn::Ident, TokenStream> = HashMap::new();
+    let mut call_indices = TokenStream::new();
     let extrinsics = data.modules_extrinsics();
 
+    // Hash of the raw metadata, used to detect runtime upgrades.
+    let metadata_hash = metadata_hash(content)?;
+
     for ext in extrinsics {
         if ext.args.len() > 25 {
-            panic!("This macro does not support more than 25 generic variables");
+            return Err(format!(
+                "{}::{} has more than 25 arguments, which is not supported",
+                ext.module_name, ext.extrinsic_name
+            ));
         };
 
-        // Create generics, assuming there any. E.g. `<A, B, C>`
-        let generics: Vec<String> = ext
+        // The type of each argument, either a substituted type or a generic.
+        // E.g. `<A, B, C>`
+        let mut generics: Vec<String> = vec![];
+        let arg_types: Vec<syn::Type> = ext
             .args
             .iter()
-            .enumerate()
-            .map(|(offset, _)| char::from_u32(65 + offset as u32).unwrap().into())
+            .map(|(_, ty_desc)| match substitutions.get(*ty_desc) {
+                Some(path) => syn::parse_quote!(#path),
+                None => {
+                    let generic: String =
+                        char::from_u32(65 + generics.len() as u32).unwrap().into();
+                    let ident = format_ident!("{}", generic);
+                    generics.push(generic);
+                    syn::parse_quote!(#ident)
+                }
+            })
             .collect();
 
-        let generics_wrapped = format!("<{}>", {
-            let mut generics = generics
-                .iter()
-                .fold(String::new(), |a, b| format!("{}, {}", a, b));
-
-            // Remove first comma, assuming generics are present.
-            if !generics.is_empty() {
-                generics.remove(0);
-            }
-
-            generics
-        });
+        let generics_wrapped = format!("<{}>", generics.join(", "));
 
         // Prepare types.
         let generics_wrapped: syn::Generics = syn::parse_str(&generics_wrapped).unwrap();
@@ -83,11 +474,10 @@ fn process_runtime_metadata(content: &str) -> TokenStream {
         let ext_args = ext
             .args
             .iter()
-            .enumerate()
-            .map(|(offset, (name, ty_desc))| {
+            .zip(&arg_types)
+            .map(|((name, ty_desc), ty)| {
                 let msg = format!("Type description: `{}`", ty_desc);
                 let name = format_ident!("{}", name);
-                let ty = format_ident!("{}", char::from_u32(65 + offset as u32).unwrap());
                 quote! {
                     #[doc = #msg]
                     pub #name: #ty,
@@ -102,6 +492,14 @@ fn process_runtime_metadata(content: &str) -> TokenStream {
             }
         });
 
+        // Size hint of the fields, used to preallocate the encoding buffer.
+        let ext_args_size_hint = ext.args.iter().map(|(name, _)| {
+            let name = format_ident!("{}", name);
+            quote! {
+                + self.#name.size_hint()
+            }
+        });
+
         // Specialized struct field decoding used for the `parity_scale_codec::Decode` implementation.
         let ext_args_decode = ext.args.iter().map(|(name, _)| {
             let name = format_ident!("{}", name);
@@ -116,7 +514,7 @@ fn process_runtime_metadata(content: &str) -> TokenStream {
         provided by the runtime meatadata. See the [`common`](crate::common) module for common types which can be used.\n";
 
         let docs = if !ext_comments.is_empty() {
-            let intro = ext_comments.iter().nth(0).unwrap();
+            let intro = ext_comments.first().unwrap();
             let msg = "# Documentation (provided by the runtime metadata)";
 
             quote! {
@@ -139,10 +537,18 @@ fn process_runtime_metadata(content: &str) -> TokenStream {
         let ext_module_id = ext.module_id as u8;
         let ext_dispatch_id = ext.dispatch_id as u8;
 
+        let (module_name, extrinsic_name) = (ext.module_name, ext.extrinsic_name);
+        call_indices.extend(quote! {
+            (#module_name, #extrinsic_name, #ext_module_id, #ext_dispatch_id),
+        });
+
+        let serde_derive = derives.serde_derive();
+
         let type_stream: TokenStream = quote! {
             #docs
             #[doc = #disclaimer]
             #[derive(Debug, Clone, Eq, PartialEq)]
+            #serde_derive
             pub struct #ext_name #generics_wrapped
             where
                 #(#generics_idents: parity_scale_codec::Encode + parity_scale_codec::Decode, )*
@@ -154,8 +560,13 @@ fn process_runtime_metadata(content: &str) -> TokenStream {
             where
                 #(#generics_idents: parity_scale_codec::Encode + parity_scale_codec::Decode, )*
             {
+                fn size_hint(&self) -> usize {
+                    2 #(#ext_args_size_hint)*
+                }
                 fn using_encoded<SR, SF: FnOnce(&[u8]) -> SR>(&self, f: SF) -> SR {
-                    let mut buffer = vec![#ext_module_id, #ext_dispatch_id];
+                    let mut buffer = Vec::with_capacity(self.size_hint());
+                    buffer.push(#ext_module_id);
+                    buffer.push(#ext_dispatch_id);
                     #(#ext_args_encode)*
                     f(&buffer)
                 }
@@ -205,18 +616,535 @@ fn process_runtime_metadata(content: &str) -> TokenStream {
         final_extrinsics.extend(stream);
     });
 
-    quote! {
+    let storage = storage_interfaces(data.modules_storage());
+    let events = event_interfaces(data.modules_events(), substitutions);
+    let constants = constant_interfaces(data.constants());
+    let errors = error_interfaces(data.modules_errors());
+
+    Ok(quote! {
+        /// The Blake2b-256 hash of the raw runtime metadata the interfaces
+        /// were generated from.
+        pub const METADATA_HASH: [u8; 32] = [#(#metadata_hash),*];
+
         pub mod extrinsics {
+            /// All extrinsics of the runtime as `(module name, extrinsic name,
+            /// module Id, dispatch Id)`.
+            pub const CALL_INDICES: &[(&str, &str, u8, u8)] = &[#call_indices];
+
             #final_extrinsics
         }
 
-        /// TODO
-        pub mod storage {}
-        /// TODO
-        pub mod events {}
-        /// TODO
-        pub mod constants {}
-        /// TODO
-        pub mod errors {}
+        /// Storage keys of the runtime, per module.
+        pub mod storage {
+            #storage
+        }
+        /// Events of the runtime, per module.
+        pub mod events {
+            #events
+        }
+        /// Constants of the runtime, per module.
+        pub mod constants {
+            #constants
+        }
+        /// Errors of the runtime, per module.
+        pub mod errors {
+            #errors
+        }
+    })
+}
+
+/// Generates a type for each storage entry which computes the storage key from
+/// the keys of the entry, grouped by module.
+fn storage_interfaces(entries: Vec<StorageInfo<'_>>) -> TokenStream {
+    let mut modules: BTreeMap<String, TokenStream> = BTreeMap::new();
+
+    for entry in entries {
+        let entry_name = format_ident!("{}", entry.entry_name);
+        let prefix = entry.storage_prefix();
+        let value_desc = format!("Type description of the value: `{}`", entry.value_type());
+
+        // The type descriptions of the keys, if known individually.
+        let key_descs: Vec<String> = match &*entry.ty {
+            StorageEntryType::Plain(_) => vec![],
+            StorageEntryType::Map { key, .. } => vec![key.clone()],
+            StorageEntryType::DoubleMap { key1, key2, .. } => vec![key1.clone(), key2.clone()],
+            StorageEntryType::NMap { keys, hashers, .. } => {
+                vec![format!("element of `{}`", keys); hashers.len()]
+            }
+        };
+
+        // A single key is named `key`, multiple keys are numbered.
+        let key_names: Vec<syn::Ident> = match key_descs.len() {
+            1 => vec![format_ident!("key")],
+            len => (1..=len).map(|i| format_ident!("key{}", i)).collect(),
+        };
+        let key_generics: Vec<syn::Ident> = (1..=key_descs.len())
+            .map(|i| format_ident!("K{}", i))
+            .collect();
+        let hashers = entry.hashers().into_iter().map(|hasher| {
+            let hasher = format_ident!("{}", format!("{:?}", hasher));
+            quote!(gekko::common::StorageHasher::#hasher)
+        });
+        let key_docs = key_descs
+            .iter()
+            .zip(&key_names)
+            .map(|(desc, name)| format!("* `{}`: {}", name, desc));
+
+        let docs = entry
+            .documentation
+            .iter()
+            .map(|doc| doc.replace("[`", "`").replace("`]", "`"));
+
+        let stream = quote! {
+            #(#[doc = #docs])*
+            #[doc = ""]
+            #[doc = #value_desc]
+            #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+            pub struct #entry_name;
+
+            impl #entry_name {
+                /// The prefix of all keys of the entry, i.e. the TwoX-128
+                /// hashes of the module prefix and the entry name.
+                pub const PREFIX: [u8; 32] = [#(#prefix),*];
+
+                /// Computes the storage key from the keys of the entry, which
+                /// are SCALE encoded and hashed with the hashers declared by
+                /// the runtime metadata. Type descriptions of the keys:
+                ///
+                #(#[doc = #key_docs])*
+                pub fn key<#(#key_generics: parity_scale_codec::Encode),*>(
+                    #(#key_names: &#key_generics),*
+                ) -> Vec<u8> {
+                    #[allow(unused_mut)]
+                    let mut storage_key = Self::PREFIX.to_vec();
+                    #(
+                        storage_key.extend(#hashers.hash(&parity_scale_codec::Encode::encode(#key_names)));
+                    )*
+                    storage_key
+                }
+
+                /// Decodes the SCALE encoded value of the entry, as returned
+                /// by `state_getStorage`. The type must correspond to the type
+                /// description of the value.
+                pub fn decode_value<V: parity_scale_codec::Decode>(
+                    mut value: &[u8],
+                ) -> Result<V, parity_scale_codec::Error> {
+                    parity_scale_codec::Decode::decode(&mut value)
+                }
+            }
+        };
+
+        modules
+            .entry(Casing::to_case(entry.module_name, Case::Snake))
+            .or_default()
+            .extend(stream);
+    }
+
+    modules
+        .into_iter()
+        .map(|(module, stream)| {
+            let module = format_ident!("{}", module);
+            quote! {
+                pub mod #module {
+                    #stream
+                }
+            }
+        })
+        .collect()
+}
+
+/// Generates a type for each constant which embeds its SCALE encoded value,
+/// grouped by module.
+fn constant_interfaces(constants: Vec<ConstantInfo<'_>>) -> TokenStream {
+    let mut modules: BTreeMap<String, TokenStream> = BTreeMap::new();
+
+    for constant in constants {
+        let name = format_ident!("{}", constant.constant_name);
+        let value = constant.value;
+        let ty_desc = format!("Type description of the value: `{}`", constant.ty);
+        let docs = constant
+            .documentation
+            .iter()
+            .map(|doc| doc.replace("[`", "`").replace("`]", "`"));
+
+        let stream = quote! {
+            #(#[doc = #docs])*
+            #[doc = ""]
+            #[doc = #ty_desc]
+            #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+            pub struct #name;
+
+            impl #name {
+                /// The SCALE encoded value.
+                pub const VALUE: &'static [u8] = &[#(#value),*];
+
+                /// Decodes the value. The type must correspond to the type
+                /// description of the value and all bytes must be consumed.
+                pub fn decode<V: parity_scale_codec::Decode>() -> Result<V, parity_scale_codec::Error> {
+                    parity_scale_codec::DecodeAll::decode_all(Self::VALUE)
+                }
+            }
+        };
+
+        modules
+            .entry(Casing::to_case(constant.module_name, Case::Snake))
+            .or_default()
+            .extend(stream);
+    }
+
+    modules
+        .into_iter()
+        .map(|(module, stream)| {
+            let module = format_ident!("{}", module);
+            quote! {
+                pub mod #module {
+                    #stream
+                }
+            }
+        })
+        .collect()
+}
+
+/// Generates an error enum for each module and the `DispatchError` enum, which
+/// resolves the module and error index of a failed dispatch.
+fn error_interfaces(errors: Vec<ErrorInfo<'_>>) -> TokenStream {
+    if errors.is_empty() {
+        return TokenStream::new();
+    }
+
+    // Group the errors by module, in the order of the module index.
+    let mut modules: BTreeMap<u8, Vec<ErrorInfo<'_>>> = BTreeMap::new();
+    for error in errors {
+        modules.entry(error.module_index).or_default().push(error);
+    }
+
+    let mut final_modules = TokenStream::new();
+    let mut dispatch_variants = TokenStream::new();
+    let mut resolve_arms = TokenStream::new();
+
+    for (module_index, errors) in &modules {
+        let module_name = errors[0].module_name;
+        let module = format_ident!("{}", Casing::to_case(module_name, Case::Snake));
+        let module_variant = format_ident!("{}", module_name);
+        let intro = format!("The errors of the `{}` module.", module_name);
+
+        let names: Vec<syn::Ident> = errors
+            .iter()
+            .map(|error| format_ident!("{}", error.error_name))
+            .collect();
+        let indices: Vec<u8> = errors.iter().map(|error| error.error_index).collect();
+        let docs = errors.iter().map(|error| {
+            let docs = error
+                .documentation
+                .iter()
+                .map(|doc| doc.replace("[`", "`").replace("`]", "`"));
+            quote!(#(#[doc = #docs])*)
+        });
+
+        final_modules.extend(quote! {
+            pub mod #module {
+                /// The index of the module, as reported by a failed dispatch.
+                pub const MODULE_INDEX: u8 = #module_index;
+
+                #[doc = #intro]
+                #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+                #[repr(u8)]
+                pub enum Error {
+                    #(
+                        #docs
+                        #names = #indices,
+                    )*
+                }
+
+                impl Error {
+                    /// Returns the error with the given index within the
+                    /// module, if any.
+                    pub fn from_index(index: u8) -> Option<Self> {
+                        match index {
+                            #(#indices => Some(Error::#names),)*
+                            _ => None,
+                        }
+                    }
+                }
+
+                impl parity_scale_codec::Decode for Error {
+                    fn decode<SI: parity_scale_codec::Input>(input: &mut SI) -> Result<Self, parity_scale_codec::Error> {
+                        Self::from_index(input.read_byte()?)
+                            .ok_or_else(|| "Unknown error index of the module.".into())
+                    }
+                }
+            }
+        });
+
+        dispatch_variants.extend(quote! {
+            #module_variant(#module::Error),
+        });
+        resolve_arms.extend(quote! {
+            #module_index => #module::Error::from_index(error_index).map(DispatchError::#module_variant),
+        });
+    }
+
+    quote! {
+        /// The error of a module which caused a dispatch to fail, as reported
+        /// by `DispatchError::Module` of the runtime.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum DispatchError {
+            #dispatch_variants
+        }
+
+        impl DispatchError {
+            /// Resolves the error by the module index and the error index
+            /// within the module. Returns `None` if the runtime has no such
+            /// error.
+            pub fn resolve(module_index: u8, error_index: u8) -> Option<Self> {
+                match module_index {
+                    #resolve_arms
+                    _ => None,
+                }
+            }
+        }
+
+        #final_modules
+    }
+}
+
+/// Returns the concrete type of primitive type descriptions, which are not
+/// made generic in events.
+fn primitive_type(desc: &str) -> Option<syn::Type> {
+    let ty = match desc {
+        "bool" => syn::parse_quote!(bool),
+        "u8" => syn::parse_quote!(u8),
+        "u16" => syn::parse_quote!(u16),
+        "u32" => syn::parse_quote!(u32),
+        "u64" => syn::parse_quote!(u64),
+        "u128" => syn::parse_quote!(u128),
+        "Vec<u8>" => syn::parse_quote!(Vec<u8>),
+        _ => return None,
+    };
+
+    Some(ty)
+}
+
+/// Converts a type description into an identifier, e.g.
+/// `Vec<(AccountId, Balance)>` into `VecAccountIdBalance`.
+fn type_desc_ident(desc: &str) -> String {
+    desc.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            std::iter::once(first).chain(chars).collect::<String>()
+        })
+        .collect()
+}
+
+/// Generates an event enum for each module, generic over the type descriptions
+/// of the event arguments, and the `RuntimeEvent` enum of all modules.
+fn event_interfaces(events: Vec<EventInfo<'_>>, substitutions: &Substitutions) -> TokenStream {
+    if events.is_empty() {
+        return TokenStream::new();
+    }
+
+    // Group the events by module, in the order of the module index.
+    let mut modules: BTreeMap<u8, Vec<EventInfo<'_>>> = BTreeMap::new();
+    for event in events {
+        modules.entry(event.module_index).or_default().push(event);
+    }
+
+    // The associated types of `EventTypes`, by type description. Distinct
+    // type descriptions may result in the same name, which are numbered.
+    let mut assoc_types: BTreeMap<String, syn::Ident> = BTreeMap::new();
+    let mut names: HashMap<String, usize> = HashMap::new();
+    for desc in modules.values().flatten().flat_map(|event| &event.args) {
+        if substitutions.contains_key(*desc)
+            || primitive_type(desc).is_some()
+            || assoc_types.contains_key(*desc)
+        {
+            continue;
+        }
+
+        let name = type_desc_ident(desc);
+        let count = names.entry(name.clone()).or_insert(0);
+        *count += 1;
+        let ident = match count {
+            1 => format_ident!("{}", name),
+            count => format_ident!("{}{}", name, count),
+        };
+
+        assoc_types.insert(desc.to_string(), ident);
     }
+
+    let mut final_modules = TokenStream::new();
+    let mut runtime_variants = TokenStream::new();
+    let mut runtime_decode = TokenStream::new();
+
+    for (module_index, events) in &modules {
+        let module_name = events[0].module_name;
+        let module = format_ident!("{}", Casing::to_case(module_name, Case::Snake));
+        let module_variant = format_ident!("{}", module_name);
+
+        // Each type description without a concrete type is a generic of the
+        // module event, e.g. `<A, B, C>`.
+        let mut generics: Vec<(String, syn::Ident)> = vec![];
+        let mut arg_type = |desc: &str| -> syn::Type {
+            if let Some(path) = substitutions.get(desc) {
+                return syn::parse_quote!(#path);
+            }
+            if let Some(ty) = primitive_type(desc) {
+                return ty;
+            }
+
+            let ident = match generics.iter().find(|(d, _)| *d == desc) {
+                Some((_, ident)) => ident.clone(),
+                None => {
+                    let ident = format_ident!("{}", char::from(b'A' + generics.len() as u8));
+                    generics.push((desc.to_string(), ident.clone()));
+                    ident
+                }
+            };
+
+            syn::parse_quote!(#ident)
+        };
+
+        let mut variants = TokenStream::new();
+        let mut decode_arms = TokenStream::new();
+        for event in events {
+            let name = format_ident!("{}", event.event_name);
+            let index = event.event_index;
+            let docs = event
+                .documentation
+                .iter()
+                .map(|doc| doc.replace("[`", "`").replace("`]", "`"));
+            let arg_docs = event.args.iter().map(|desc| format!("* `{}`", desc));
+            let types: Vec<syn::Type> = event.args.iter().map(|desc| arg_type(desc)).collect();
+
+            if types.is_empty() {
+                variants.extend(quote! {
+                    #(#[doc = #docs])*
+                    #name,
+                });
+                decode_arms.extend(quote! {
+                    #index => Ok(Event::#name),
+                });
+            } else {
+                let decode = types
+                    .iter()
+                    .map(|_| quote!(parity_scale_codec::Decode::decode(input)?));
+
+                variants.extend(quote! {
+                    #(#[doc = #docs])*
+                    #[doc = ""]
+                    #[doc = "Type descriptions of the arguments:"]
+                    #(#[doc = #arg_docs])*
+                    #name(#(#types),*),
+                });
+                decode_arms.extend(quote! {
+                    #index => Ok(Event::#name(#(#decode),*)),
+                });
+            }
+        }
+
+        let generic_idents: Vec<&syn::Ident> = generics.iter().map(|(_, ident)| ident).collect();
+        let generic_docs = generics
+            .iter()
+            .map(|(desc, ident)| format!("* `{}`: `{}`", ident, desc));
+        let intro = format!(
+            "The events of the `{}` module, decoded from the event index followed by the arguments.",
+            module_name
+        );
+
+        final_modules.extend(quote! {
+            pub mod #module {
+                /// The index of the module, which precedes the encoded event.
+                pub const MODULE_INDEX: u8 = #module_index;
+
+                #[doc = #intro]
+                ///
+                /// Each argument type without a concrete type is a generic
+                /// type with the following type description:
+                ///
+                #(#[doc = #generic_docs])*
+                #[derive(Debug, Clone, PartialEq, Eq)]
+                pub enum Event<#(#generic_idents),*> {
+                    #variants
+                }
+
+                impl<#(#generic_idents: parity_scale_codec::Decode),*> parity_scale_codec::Decode for Event<#(#generic_idents),*> {
+                    fn decode<SI: parity_scale_codec::Input>(input: &mut SI) -> Result<Self, parity_scale_codec::Error> {
+                        match input.read_byte()? {
+                            #decode_arms
+                            _ => Err("Unknown event index of the module.".into()),
+                        }
+                    }
+                }
+            }
+        });
+
+        // The module event with the associated types of `EventTypes`.
+        let assoc_args = generics.iter().map(|(desc, _)| {
+            let ident = &assoc_types[desc];
+            quote!(T::#ident)
+        });
+        let assoc_args: Vec<TokenStream> = assoc_args.collect();
+        let variant_type = if assoc_args.is_empty() {
+            quote!(#module::Event)
+        } else {
+            quote!(#module::Event<#(#assoc_args),*>)
+        };
+
+        runtime_variants.extend(quote! {
+            #module_variant(#variant_type),
+        });
+        runtime_decode.extend(quote! {
+            #module_index => Ok(RuntimeEvent::#module_variant(parity_scale_codec::Decode::decode(input)?)),
+        });
+    }
+
+    let assoc_decls = assoc_types.iter().map(|(desc, ident)| {
+        let msg = format!("Type description: `{}`", desc);
+        quote! {
+            #[doc = #msg]
+            type #ident: parity_scale_codec::Decode;
+        }
+    });
+
+    quote! {
+        /// The types of the event arguments, by type description. Primitive
+        /// types, such as `u32`, and substituted types are not part of this
+        /// trait.
+        pub trait EventTypes {
+            #(#assoc_decls)*
+        }
+
+        /// An event of any module, decoded from the module index followed by
+        /// the module event. The events of a block, `System::Events`, decode as
+        /// `Vec<(Phase, RuntimeEvent<T>, Vec<[u8; 32]>)>`, i.e. with the phase
+        /// and the topics of each event.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub enum RuntimeEvent<T: EventTypes> {
+            #runtime_variants
+        }
+
+        impl<T: EventTypes> parity_scale_codec::Decode for RuntimeEvent<T> {
+            fn decode<SI: parity_scale_codec::Input>(input: &mut SI) -> Result<Self, parity_scale_codec::Error> {
+                match input.read_byte()? {
+                    #runtime_decode
+                    _ => Err("Unknown module index of the event.".into()),
+                }
+            }
+        }
+
+        #final_modules
+    }
+}
+
+/// Returns the Blake2b-256 hash of the raw metadata, including the magic
+/// number.
+fn metadata_hash(content: &str) -> Result<[u8; 32], String> {
+    let raw = gekko_metadata::decode_hex(content.trim())
+        .map_err(|err| format!("Failed to decode runtime metadata: {}", err))?;
+
+    let mut hash = [0; 32];
+    hash.copy_from_slice(blake2_rfc::blake2b::blake2b(32, &[], &raw).as_bytes());
+    Ok(hash)
 }
@@ -0,0 +1,45 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use gekko::common::*;
+use gekko::runtime::polkadot::extrinsics::balances::TransferKeepAlive;
+use gekko::transaction::v4::{Payload, Transaction};
+use gekko::transaction::*;
+use parity_scale_codec::{Decode, Encode};
+
+type DecodableTransaction = Transaction<MultiAddress, OpaqueCall, MultiSignature, Payload>;
+
+fn transaction() -> PolkadotSignedExtrinsic<TransferKeepAlive<MultiAddress, Balance>> {
+    let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+    let currency = BalanceBuilder::new(Currency::Polkadot);
+
+    let call = TransferKeepAlive {
+        dest: MultiAddress::from(
+            AccountId::from_ss58_address("12eDex4amEwj39T7Wz4Rkppb68YGCDYKG9QHhEhHGtNdDy7D")
+                .unwrap(),
+        ),
+        value: currency.balance(50),
+    };
+
+    SignedTransactionBuilder::new()
+        .signer(keypair)
+        .call(call)
+        .nonce(0)
+        .tip(currency.balance_as_metric(Metric::Milli, 10).unwrap())
+        .network(Network::Polkadot)
+        .build()
+        .unwrap()
+}
+
+fn encode_decode(c: &mut Criterion) {
+    let transaction = transaction();
+    let encoded = transaction.encode();
+
+    c.bench_function("transaction_encode", |b| {
+        b.iter(|| black_box(&transaction).encode())
+    });
+    c.bench_function("transaction_decode", |b| {
+        b.iter(|| DecodableTransaction::decode(&mut black_box(encoded.as_slice())).unwrap())
+    });
+}
+
+criterion_group!(benches, encode_decode);
+criterion_main!(benches);
@@ -0,0 +1,213 @@
+//! Properties of chains, such as parachains and solo-chains, which are not
+//! known to gekko.
+
+use super::{Currency, Network};
+use sp_core::crypto::Ss58AddressFormat;
+use std::convert::TryFrom;
+use std::sync::RwLock;
+
+/// The chain specifications registered with [`ChainSpec::register`].
+static REGISTRY: RwLock<Vec<ChainSpec>> = RwLock::new(Vec::new());
+
+/// The properties of a chain, identified by its genesis hash. Once
+/// registered, the properties are used for [`Network::Custom`] with the same
+/// genesis hash, e.g. for the default runtime versions of the
+/// [`SignedTransactionBuilder`](crate::transaction::SignedTransactionBuilder).
+///
+/// # Example
+///
+/// ```
+/// use gekko::common::*;
+///
+/// let spec = ChainSpec::new([1; 32], 42, 12, "UNIT").versions(100, 1);
+/// spec.clone().register();
+///
+/// let network = Network::Custom([1; 32]);
+/// assert_eq!(network.chain_spec(), Some(spec.clone()));
+///
+/// // 1 UNIT equals 10^12 in the base unit.
+/// let balance = BalanceBuilder::new(spec.currency().unwrap()).balance(1);
+/// assert_eq!(balance.as_base_unit(), 1_000_000_000_000);
+///
+/// let address = AccountId::new([0; 32]).to_ss58_address(spec.address_format());
+/// assert!(address.starts_with("5C"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChainSpec {
+    /// The genesis hash of the chain.
+    #[cfg_attr(feature = "serde", serde(with = "gekko_metadata::serde_hex"))]
+    pub genesis: [u8; 32],
+    /// The SS58 prefix of addresses, e.g. `0` for Polkadot.
+    pub ss58_prefix: u16,
+    /// The number of decimals of the native token, e.g. `10` for DOT.
+    pub token_decimals: u8,
+    /// The symbol of the native token, e.g. `"DOT"`.
+    pub token_symbol: String,
+    /// The `spec_version` of the runtime, if it should be used by default.
+    pub spec_version: Option<u32>,
+    /// The `transaction_version` of the runtime, if it should be used by
+    /// default.
+    pub transaction_version: Option<u32>,
+}
+
+impl ChainSpec {
+    /// Creates the properties of the chain, without default runtime versions.
+    pub fn new<S: Into<String>>(
+        genesis: [u8; 32],
+        ss58_prefix: u16,
+        token_decimals: u8,
+        token_symbol: S,
+    ) -> Self {
+        ChainSpec {
+            genesis,
+            ss58_prefix,
+            token_decimals,
+            token_symbol: token_symbol.into(),
+            spec_version: None,
+            transaction_version: None,
+        }
+    }
+    /// Sets the default `spec_version` and `transaction_version` of the
+    /// runtime.
+    pub fn versions(self, spec_version: u32, transaction_version: u32) -> Self {
+        ChainSpec {
+            spec_version: Some(spec_version),
+            transaction_version: Some(transaction_version),
+            ..self
+        }
+    }
+    /// Registers the chain, replacing any chain with the same genesis hash.
+    pub fn register(self) {
+        let mut registry = REGISTRY.write().unwrap_or_else(|err| err.into_inner());
+        registry.retain(|spec| spec.genesis != self.genesis);
+        registry.push(self);
+    }
+    /// Removes the registered chain with the given genesis hash, returning it
+    /// if it was registered.
+    pub fn unregister(genesis: &[u8; 32]) -> Option<ChainSpec> {
+        let mut registry = REGISTRY.write().unwrap_or_else(|err| err.into_inner());
+        let position = registry.iter().position(|spec| spec.genesis == *genesis)?;
+        Some(registry.remove(position))
+    }
+    /// Returns the registered chain with the given genesis hash, if any.
+    pub fn find(genesis: &[u8; 32]) -> Option<ChainSpec> {
+        REGISTRY
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .iter()
+            .find(|spec| spec.genesis == *genesis)
+            .cloned()
+    }
+    /// The native currency, for the [`BalanceBuilder`](super::BalanceBuilder).
+    /// Returns an error if the base unit does not fit into `u128`, see
+    /// [`Currency::from_decimals`].
+    pub fn currency(&self) -> crate::Result<Currency> {
+        Currency::from_decimals(self.token_decimals)
+    }
+    /// The SS58 address format, e.g. for
+    /// [`AccountId::to_ss58_address`](super::AccountId::to_ss58_address).
+    pub fn address_format(&self) -> Ss58AddressFormat {
+        Ss58AddressFormat::try_from(self.ss58_prefix)
+            .unwrap_or(Ss58AddressFormat::Custom(self.ss58_prefix))
+    }
+}
+
+impl Network {
+    /// The properties of the chain. Known for Polkadot, Kusama and Westend,
+    /// while custom networks must be [registered](ChainSpec::register).
+    pub fn chain_spec(&self) -> Option<ChainSpec> {
+        let spec = match self {
+            Network::Polkadot => {
+                let spec = ChainSpec::new(self.genesis(), 0, 10, "DOT");
+                #[cfg(feature = "runtime-polkadot")]
+                let spec = {
+                    use crate::runtime::polkadot;
+                    spec.versions(polkadot::SPEC_VERSION, polkadot::TRANSACTION_VERSION)
+                };
+                spec
+            }
+            Network::Kusama => {
+                let spec = ChainSpec::new(self.genesis(), 2, 12, "KSM");
+                #[cfg(feature = "runtime-kusama")]
+                let spec = {
+                    use crate::runtime::kusama;
+                    spec.versions(kusama::SPEC_VERSION, kusama::TRANSACTION_VERSION)
+                };
+                spec
+            }
+            Network::Westend => ChainSpec::new(self.genesis(), 42, 12, "WND"),
+            Network::Custom(genesis) => return ChainSpec::find(genesis),
+        };
+
+        Some(spec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{AccountId, BalanceBuilder};
+
+    #[test]
+    fn register_chain_spec() {
+        // The registry is shared by all tests, so the genesis hash must be
+        // unique.
+        let genesis = crate::blake2b("register_chain_spec");
+        assert_eq!(Network::Custom(genesis).chain_spec(), None);
+
+        ChainSpec::new(genesis, 42, 12, "UNIT").register();
+        // Replaces the previous registration.
+        let spec = ChainSpec::new(genesis, 7, 18, "TOKEN").versions(1, 2);
+        spec.clone().register();
+
+        assert_eq!(Network::Custom(genesis).chain_spec(), Some(spec.clone()));
+        assert_eq!(
+            spec.currency().unwrap().base_unit(),
+            1_000_000_000_000_000_000
+        );
+
+        let balance = BalanceBuilder::new(spec.currency().unwrap()).balance(2);
+        assert_eq!(balance.as_base_unit(), 2_000_000_000_000_000_000);
+
+        let account = AccountId::new([1; 32]);
+        let address = account.to_ss58_address(spec.address_format());
+        assert_eq!(
+            AccountId::from_ss58_address_with_version(&address).unwrap(),
+            (account, spec.address_format())
+        );
+
+        assert_eq!(ChainSpec::unregister(&genesis), Some(spec));
+        assert_eq!(ChainSpec::unregister(&genesis), None);
+        assert_eq!(Network::Custom(genesis).chain_spec(), None);
+    }
+
+    #[test]
+    fn builtin_chain_specs() {
+        let spec = Network::Polkadot.chain_spec().unwrap();
+        assert_eq!(
+            spec.currency().unwrap().base_unit(),
+            Currency::Polkadot.base_unit()
+        );
+        assert_eq!(spec.address_format(), Ss58AddressFormat::PolkadotAccount);
+
+        let spec = Network::Kusama.chain_spec().unwrap();
+        assert_eq!(
+            spec.currency().unwrap().base_unit(),
+            Currency::Kusama.base_unit()
+        );
+        assert_eq!(spec.address_format(), Ss58AddressFormat::KusamaAccount);
+    }
+
+    #[test]
+    fn invalid_token_decimals() {
+        let spec = ChainSpec::new([3; 32], 42, 38, "UNIT");
+        assert_eq!(spec.currency().unwrap().base_unit(), 10u128.pow(38));
+
+        let spec = ChainSpec::new([3; 32], 42, 39, "UNIT");
+        assert!(matches!(
+            spec.currency(),
+            Err(crate::Error::InvalidBaseUnit)
+        ));
+    }
+}
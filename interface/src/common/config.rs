@@ -0,0 +1,213 @@
+//! Per-chain configuration of primitive types.
+
+use super::{AccountId, Mortality, MultiAddress, MultiKeyPair, MultiSignature};
+use crate::transaction::v4::{AssetPayload, ExtraSignaturePayload, Payload, SignedExtra};
+use crate::transaction::v5::{MetadataHashExtra, MetadataHashPayload};
+use parity_scale_codec::{Decode, Encode, HasCompact};
+use sp_core::crypto::AccountId32;
+use std::convert::TryFrom;
+use std::fmt::Debug;
+
+/// The primitive types and signed extensions of a chain, as used by its
+/// transactions.
+///
+/// Most Substrate chains, including Kusama, Polkadot and Westend, use the
+/// types of [`PolkadotConfig`]. Chains with different primitives, such as
+/// `u64` balances or an address without the [`MultiAddress`] prefix, implement
+/// this trait and pass it to the
+/// [`SignedTransactionBuilder`](crate::transaction::SignedTransactionBuilder).
+///
+/// # Example
+///
+/// ```
+/// use gekko::common::{*, sp_core::crypto::AccountId32};
+/// use gekko::transaction::*;
+/// use gekko::transaction::v4::{ExtraSignaturePayload, Payload};
+///
+/// // A chain with `u64` balances and nonces, which uses the
+/// // `IdentityLookup` and therefore expects the plain account identifier.
+/// struct MyChain;
+///
+/// impl Config for MyChain {
+///     type Balance = u64;
+///     type Index = u64;
+///     type Hash = [u8; 32];
+///     type Address = AccountId32;
+///     type Signature = MultiSignature;
+///     type Era = Mortality;
+///     type Extra = Payload<u64, u64>;
+///     type AdditionalSigned = ExtraSignaturePayload;
+///     type Hasher = Blake2_256;
+/// }
+///
+/// let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+///
+/// let transaction: SignedExtrinsic<MyChain, _> = SignedTransactionBuilder::<_, MyChain>::default()
+///     .signer(keypair)
+///     .call(OpaqueCall(vec![0, 1]))
+///     .nonce(u64::MAX)
+///     .tip_base_unit(0)
+///     .network(Network::Custom([1; 32]))
+///     .spec_version(1)
+///     .transaction_version(1)
+///     .build()
+///     .unwrap();
+/// ```
+pub trait Config: Sized {
+    /// The balance type, e.g. of the tip. Compact encoded.
+    type Balance: HasCompact + TryFrom<u128> + Into<u128> + Copy + Debug + Eq;
+    /// The nonce of an account, referred to as "Index" in Substrate
+    /// vocabulary. Compact encoded.
+    type Index: HasCompact + Copy + Debug + Eq;
+    /// The hash of a block, such as the genesis hash.
+    type Hash: Encode + Decode + From<[u8; 32]> + AsRef<[u8]> + Copy + Debug + Eq;
+    /// The address of the signer, as expected by the lookup source of the
+    /// runtime. Created from the account of
+    /// [external signers](crate::transaction::ExternalSigner).
+    type Address: Encode + Decode + From<MultiKeyPair> + From<AccountId> + Clone + Debug + Eq;
+    /// The signature of the transaction.
+    type Signature: Encode + Decode + From<MultiSignature> + Clone + Debug + Eq;
+    /// The mortality of the transaction, set with
+    /// [`SignedTransactionBuilder::mortality`](crate::transaction::SignedTransactionBuilder::mortality).
+    type Era: Encode + Decode + From<Mortality> + Clone + Debug + Eq;
+    /// The signed extensions which are part of the transaction, e.g.
+    /// [`Payload`].
+    type Extra: SignedExtra<Self> + Decode + Clone + Debug + Eq;
+    /// The signed extensions which are only part of the signature payload,
+    /// e.g. [`ExtraSignaturePayload`].
+    type AdditionalSigned: SignedExtra<Self>;
+    /// The hash algorithm of signature payloads, see [`Hasher`].
+    type Hasher: Hasher;
+    /// The identifiers of the signed extensions which are encoded by
+    /// [`Extra`](Self::Extra) and [`AdditionalSigned`](Self::AdditionalSigned),
+    /// in the order of the `signed_extensions` of the metadata.
+    const SIGNED_EXTENSIONS: &'static [&'static str] = &[
+        "CheckSpecVersion",
+        "CheckTxVersion",
+        "CheckGenesis",
+        "CheckMortality",
+        "CheckNonce",
+        "CheckWeight",
+        "ChargeTransactionPayment",
+    ];
+    /// The identifiers of signed extensions which encode no data, neither in
+    /// the transaction nor in the signature payload. Those are ignored when
+    /// comparing the signed extensions with the metadata, since the encoding
+    /// is the same whether the chain uses them or not.
+    const EMPTY_SIGNED_EXTENSIONS: &'static [&'static str] =
+        &["CheckWeight", "CheckNonZeroSender", "PrevalidateAttests"];
+}
+
+/// A hash algorithm of signature payloads. Payloads exceeding 256 bytes are
+/// hashed before signing and ECDSA signatures are created over the hash of the
+/// (possibly hashed) payload.
+///
+/// Substrate chains use [`Blake2_256`], while Ethereum-compatible chains
+/// usually use [`Keccak256`].
+pub trait Hasher {
+    fn hash(data: &[u8]) -> [u8; 32];
+}
+
+/// The 256-bit Blake2b hash algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Blake2_256;
+
+impl Hasher for Blake2_256 {
+    fn hash(data: &[u8]) -> [u8; 32] {
+        crate::blake2b(data)
+    }
+}
+
+/// The 256-bit Keccak hash algorithm, as used by Ethereum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keccak256;
+
+impl Hasher for Keccak256 {
+    fn hash(data: &[u8]) -> [u8; 32] {
+        sp_core::hashing::keccak_256(data)
+    }
+}
+
+/// The primitive types of Kusama, Polkadot and Westend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PolkadotConfig;
+
+impl Config for PolkadotConfig {
+    type Balance = u128;
+    type Index = u32;
+    type Hash = [u8; 32];
+    type Address = MultiAddress;
+    type Signature = MultiSignature;
+    type Era = Mortality;
+    type Extra = Payload;
+    type AdditionalSigned = ExtraSignaturePayload;
+    type Hasher = Blake2_256;
+}
+
+/// The primitive types of Statemint and Statemine, which pay fees with assets.
+/// Set the asset with
+/// [`SignedTransactionBuilder::asset_id`](crate::transaction::SignedTransactionBuilder::asset_id).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatemintConfig;
+
+impl Config for StatemintConfig {
+    type Balance = u128;
+    type Index = u32;
+    type Hash = [u8; 32];
+    type Address = MultiAddress;
+    type Signature = MultiSignature;
+    type Era = Mortality;
+    type Extra = AssetPayload;
+    type AdditionalSigned = ExtraSignaturePayload;
+    type Hasher = Blake2_256;
+    const SIGNED_EXTENSIONS: &'static [&'static str] = &[
+        "CheckSpecVersion",
+        "CheckTxVersion",
+        "CheckGenesis",
+        "CheckMortality",
+        "CheckNonce",
+        "CheckWeight",
+        "ChargeAssetTxPayment",
+    ];
+}
+
+/// Like [`PolkadotConfig`], but with the `CheckMetadataHash` extension of
+/// newer runtimes. Set the hash with
+/// [`SignedTransactionBuilder::metadata_hash`](crate::transaction::SignedTransactionBuilder::metadata_hash),
+/// otherwise the extension is disabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetadataHashConfig;
+
+impl Config for MetadataHashConfig {
+    type Balance = u128;
+    type Index = u32;
+    type Hash = [u8; 32];
+    type Address = MultiAddress;
+    type Signature = MultiSignature;
+    type Era = Mortality;
+    type Extra = MetadataHashPayload;
+    type AdditionalSigned = MetadataHashExtra;
+    type Hasher = Blake2_256;
+    const SIGNED_EXTENSIONS: &'static [&'static str] = &[
+        "CheckSpecVersion",
+        "CheckTxVersion",
+        "CheckGenesis",
+        "CheckMortality",
+        "CheckNonce",
+        "CheckWeight",
+        "ChargeTransactionPayment",
+        "CheckMetadataHash",
+    ];
+}
+
+impl From<MultiKeyPair> for AccountId32 {
+    fn from(val: MultiKeyPair) -> Self {
+        AccountId::from(val).into()
+    }
+}
+
+impl From<MultiKeyPair> for MultiAddress {
+    fn from(val: MultiKeyPair) -> Self {
+        MultiAddress::Id(val.into())
+    }
+}
@@ -7,35 +7,165 @@ use sp_core::crypto::{AccountId32, Pair, Ss58AddressFormat, Ss58Codec};
 pub extern crate parity_scale_codec as scale;
 pub extern crate sp_core;
 
+pub use chain_spec::ChainSpec;
+pub use config::{
+    Blake2_256, Config, Hasher, Keccak256, MetadataHashConfig, PolkadotConfig, StatemintConfig,
+};
+pub use gekko_generator::GekkoType;
+pub use ss58::Ss58Error;
+
+mod chain_spec;
+mod config;
+mod ss58;
+
 pub type Sr25519 = sp_core::sr25519::Pair;
 pub type Ed25519 = sp_core::ed25519::Pair;
 pub type Ecdsa = sp_core::ecdsa::Pair;
 
+/// A type which corresponds to one or more type descriptions of the runtime
+/// metadata, such as `Compact<T::Balance>`. Such types can substitute the
+/// generic arguments of generated extrinsics.
+///
+/// Usually implemented with the derive macro of the same name.
+///
+/// # Example
+///
+/// ```
+/// use gekko::common::{scale::Encode, GekkoType};
+///
+/// #[derive(Encode, GekkoType)]
+/// #[gekko(type = "Compact<T::Balance>", type = "Compact<BalanceOf<T>>")]
+/// pub struct MyBalance(#[codec(compact)] u128);
+///
+/// assert!(MyBalance::matches_type_desc("Compact<BalanceOf<T>>"));
+/// assert!(!MyBalance::matches_type_desc("T::AccountId"));
+/// ```
+pub trait GekkoType {
+    /// The type descriptions, as provided by the runtime metadata.
+    const TYPE_DESCS: &'static [&'static str];
+
+    /// Whether the type corresponds to the type description, ignoring
+    /// whitespace.
+    fn matches_type_desc(desc: &str) -> bool {
+        type_desc_matches(Self::TYPE_DESCS, desc)
+    }
+}
+
+/// Whether any of the type descriptions equals the given description,
+/// ignoring whitespace. Can be evaluated at compile time.
+pub const fn type_desc_matches(descs: &[&str], desc: &str) -> bool {
+    let mut i = 0;
+    while i < descs.len() {
+        if type_desc_eq(descs[i].as_bytes(), desc.as_bytes()) {
+            return true;
+        }
+        i += 1;
+    }
+
+    false
+}
+
+const fn type_desc_eq(a: &[u8], b: &[u8]) -> bool {
+    let (mut i, mut j) = (0, 0);
+    loop {
+        while i < a.len() && a[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        while j < b.len() && b[j].is_ascii_whitespace() {
+            j += 1;
+        }
+
+        match (i < a.len(), j < b.len()) {
+            (true, true) if a[i] == b[j] => {
+                i += 1;
+                j += 1;
+            }
+            (false, false) => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// The hasher of a storage key, as declared by the runtime metadata. Used by
+/// the generated storage interfaces of the [runtime](crate::runtime) module.
+///
+/// # Example
+///
+/// ```
+/// use gekko::common::StorageHasher;
+///
+/// let hashed = StorageHasher::Twox64Concat.hash(&[1, 2]);
+/// assert_eq!(hashed.len(), 8 + 2);
+/// assert!(hashed.ends_with(&[1, 2]));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageHasher {
+    Blake2_128,
+    Blake2_256,
+    Blake2_128Concat,
+    Twox128,
+    Twox256,
+    Twox64Concat,
+    Identity,
+}
+
+impl StorageHasher {
+    /// Hashes the SCALE encoded key. The `*Concat` and `Identity` hashers
+    /// append the key itself.
+    pub fn hash(&self, key: &[u8]) -> Vec<u8> {
+        use sp_core::hashing::*;
+
+        match self {
+            StorageHasher::Blake2_128 => blake2_128(key).to_vec(),
+            StorageHasher::Blake2_256 => blake2_256(key).to_vec(),
+            StorageHasher::Blake2_128Concat => [&blake2_128(key), key].concat(),
+            StorageHasher::Twox128 => twox_128(key).to_vec(),
+            StorageHasher::Twox256 => twox_256(key).to_vec(),
+            StorageHasher::Twox64Concat => [&twox_64(key), key].concat(),
+            StorageHasher::Identity => key.to_vec(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 // TODO: Rename to "Chain" or "Blockchain"?
 pub enum Network {
     Polkadot,
     Kusama,
     Westend,
-    Custom([u8; 32]),
+    Custom(#[cfg_attr(feature = "serde", serde(with = "gekko_metadata::serde_hex"))] [u8; 32]),
 }
 
 impl Network {
     pub fn genesis(&self) -> [u8; 32] {
-        let mut genesis = [0; 32];
-
-        let hash_str = match self {
-            Self::Polkadot => "91b171bb158e2d3848fa23a9f1c25182fb8e20313b2c1eb49219da7a70ce90c3",
-            Self::Kusama => "b0a8d493285c2df73290dfb7e61f870f17b41801197a149ca93654499ea3dafe",
-            Self::Westend => "e143f23803ac50e8f6f8e62695d1ce9e4e1d68aa36c1cd2cfd15340213f3423e",
-            Self::Custom(genesis) => return *genesis,
-        };
-
-        hex::decode_to_slice(hash_str, &mut genesis).unwrap();
-        genesis
+        match self {
+            Self::Polkadot => POLKADOT_GENESIS,
+            Self::Kusama => KUSAMA_GENESIS,
+            Self::Westend => WESTEND_GENESIS,
+            Self::Custom(genesis) => *genesis,
+        }
     }
 }
 
+/// `0x91b171bb158e2d3848fa23a9f1c25182fb8e20313b2c1eb49219da7a70ce90c3`
+const POLKADOT_GENESIS: [u8; 32] = [
+    0x91, 0xb1, 0x71, 0xbb, 0x15, 0x8e, 0x2d, 0x38, 0x48, 0xfa, 0x23, 0xa9, 0xf1, 0xc2, 0x51, 0x82,
+    0xfb, 0x8e, 0x20, 0x31, 0x3b, 0x2c, 0x1e, 0xb4, 0x92, 0x19, 0xda, 0x7a, 0x70, 0xce, 0x90, 0xc3,
+];
+
+/// `0xb0a8d493285c2df73290dfb7e61f870f17b41801197a149ca93654499ea3dafe`
+const KUSAMA_GENESIS: [u8; 32] = [
+    0xb0, 0xa8, 0xd4, 0x93, 0x28, 0x5c, 0x2d, 0xf7, 0x32, 0x90, 0xdf, 0xb7, 0xe6, 0x1f, 0x87, 0x0f,
+    0x17, 0xb4, 0x18, 0x01, 0x19, 0x7a, 0x14, 0x9c, 0xa9, 0x36, 0x54, 0x49, 0x9e, 0xa3, 0xda, 0xfe,
+];
+
+/// `0xe143f23803ac50e8f6f8e62695d1ce9e4e1d68aa36c1cd2cfd15340213f3423e`
+const WESTEND_GENESIS: [u8; 32] = [
+    0xe1, 0x43, 0xf2, 0x38, 0x03, 0xac, 0x50, 0xe8, 0xf6, 0xf8, 0xe6, 0x26, 0x95, 0xd1, 0xce, 0x9e,
+    0x4e, 0x1d, 0x68, 0xaa, 0x36, 0xc1, 0xcd, 0x2c, 0xfd, 0x15, 0x34, 0x02, 0x13, 0xf3, 0x42, 0x3e,
+];
+
 pub enum Currency {
     Kusama,
     Polkadot,
@@ -44,6 +174,26 @@ pub enum Currency {
 }
 
 impl Currency {
+    /// Creates a custom currency with the given number of decimals, e.g. `12`
+    /// for a base unit of `10^12`. Returns an error if the base unit does not
+    /// fit into `u128`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gekko::common::*;
+    ///
+    /// let currency = Currency::from_decimals(12).unwrap();
+    /// assert_eq!(currency.base_unit(), 1_000_000_000_000);
+    ///
+    /// assert!(Currency::from_decimals(39).is_err());
+    /// ```
+    pub fn from_decimals(decimals: u8) -> crate::Result<Self> {
+        10u128
+            .checked_pow(decimals.into())
+            .map(Currency::Custom)
+            .ok_or(crate::Error::InvalidBaseUnit)
+    }
     pub fn base_unit(&self) -> u128 {
         match self {
             Self::Kusama | Self::Westend => 1_000_000_000_000,
@@ -51,36 +201,183 @@ impl Currency {
             Self::Custom(unit) => *unit,
         }
     }
+    /// The symbol of the token, e.g. `"DOT"`. Custom currencies have no
+    /// symbol, see [`BalanceWithUnit::symbol`].
+    pub fn symbol(&self) -> Option<&'static str> {
+        match self {
+            Self::Kusama => Some("KSM"),
+            Self::Polkadot => Some("DOT"),
+            Self::Westend => Some("WND"),
+            Self::Custom(_) => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct BalanceBuilder;
 
 impl BalanceBuilder {
+    /// Creates a builder for balances of the given currency. Custom
+    /// currencies with a base unit of zero cannot represent any token, use
+    /// [`BalanceBuilder::try_new`] to reject those.
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(currency: Currency) -> BalanceWithUnit {
-        // TODO: Make sure `unit` is never zero.
-
         BalanceWithUnit {
             unit: currency.base_unit(),
+            symbol: currency.symbol(),
+        }
+    }
+    /// Like [`BalanceBuilder::new`], but returns an error if the base unit of
+    /// the currency is zero.
+    pub fn try_new(currency: Currency) -> crate::Result<BalanceWithUnit> {
+        if currency.base_unit() == 0 {
+            return Err(crate::Error::InvalidBaseUnit);
         }
+
+        Ok(Self::new(currency))
     }
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct BalanceWithUnit {
     unit: u128,
+    symbol: Option<&'static str>,
 }
 
 impl BalanceWithUnit {
+    /// Sets the symbol of the token, which is shown when displaying the
+    /// balance.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gekko::common::*;
+    ///
+    /// let balance = BalanceBuilder::try_new(Currency::from_decimals(12).unwrap())
+    ///     .unwrap()
+    ///     .symbol("UNIT")
+    ///     .balance_base_unit(1_500_000_000_000);
+    ///
+    /// assert_eq!(balance.to_string(), "1.5 UNIT");
+    /// ```
+    pub fn symbol(self, symbol: &'static str) -> Self {
+        BalanceWithUnit {
+            symbol: Some(symbol),
+            ..self
+        }
+    }
     // TODO: Consider removing this. Metric should be explicit.
     pub fn balance(self, balance: u128) -> Balance {
-        self.balance_as_metric(Metric::One, balance).unwrap()
+        Balance {
+            balance: balance.saturating_mul(self.unit),
+            unit: self.unit,
+            symbol: self.symbol,
+        }
+    }
+    /// Like [`BalanceWithUnit::balance`], but returns an error instead of
+    /// saturating if the balance in the base unit does not fit into `u128`.
+    pub fn checked_balance(self, balance: u128) -> crate::Result<Balance> {
+        Ok(Balance {
+            balance: balance
+                .checked_mul(self.unit)
+                .ok_or(crate::Error::BalanceOverflow(balance))?,
+            unit: self.unit,
+            symbol: self.symbol,
+        })
+    }
+    /// Creates a balance which is already in the base unit of the runtime,
+    /// e.g. in "Planck" for Polkadot.
+    pub fn balance_base_unit(self, balance: u128) -> Balance {
+        Balance {
+            balance,
+            unit: self.unit,
+            symbol: self.symbol,
+        }
+    }
+    /// Creates a balance of `whole` tokens plus `fractional` in the base
+    /// unit, e.g. `balance_parts(1, 5_000_000_000)` for 1.5 DOT. Returns an
+    /// error if `fractional` is not less than one token or if the balance does
+    /// not fit into `u128`.
+    pub fn balance_parts(self, whole: u128, fractional: u128) -> crate::Result<Balance> {
+        if fractional >= self.unit {
+            return Err(crate::Error::InvalidBalance(format!(
+                "fractional part {} exceeds the base unit {}",
+                fractional, self.unit
+            )));
+        }
+
+        let balance = whole
+            .checked_mul(self.unit)
+            .and_then(|balance| balance.checked_add(fractional))
+            .ok_or(crate::Error::BalanceOverflow(whole))?;
+
+        Ok(self.balance_base_unit(balance))
+    }
+    /// Parses a decimal amount of tokens, such as `"0.1"`. The base unit of
+    /// the currency must be a power of ten and the amount must not have more
+    /// decimals than the currency.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gekko::common::*;
+    ///
+    /// let dot = BalanceBuilder::new(Currency::Polkadot);
+    ///
+    /// let balance = dot.balance_from_str("0.1").unwrap();
+    /// assert_eq!(balance.as_base_unit(), 1_000_000_000);
+    ///
+    /// // DOT has ten decimals.
+    /// assert!(dot.balance_from_str("0.00000000001").is_err());
+    /// ```
+    pub fn balance_from_str(self, amount: &str) -> crate::Result<Balance> {
+        let invalid =
+            |reason: &str| crate::Error::InvalidBalance(format!("{:?} {}", amount, reason));
+
+        let decimals = decimals_of_unit(self.unit)
+            .ok_or_else(|| invalid("cannot be represented in a non-decimal base unit"))?;
+
+        let (whole, fraction) = match amount.find('.') {
+            Some(pos) => (&amount[..pos], &amount[pos + 1..]),
+            None => (amount, ""),
+        };
+
+        let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+            return Err(invalid("is not a decimal number"));
+        }
+
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.len() > decimals {
+            return Err(invalid("has more decimals than the currency"));
+        }
+
+        let whole = if whole.is_empty() {
+            0
+        } else {
+            whole
+                .parse::<u128>()
+                .map_err(|_| invalid("exceeds the range of u128"))?
+        };
+
+        // The fraction is padded to the decimals of the currency, which fits
+        // into `u128` since it's less than the base unit.
+        let fractional = if fraction.is_empty() {
+            0
+        } else {
+            format!("{:0<width$}", fraction, width = decimals)
+                .parse::<u128>()
+                .map_err(|_| invalid("exceeds the range of u128"))?
+        };
+
+        self.balance_parts(whole, fractional)
     }
     // TODO: Rename. TODO: Should return Result
     pub fn balance_as_metric(self, metric: Metric, balance: u128) -> Option<Balance> {
         Some(Balance {
             balance: convert_metrics(metric, Metric::One, balance.saturating_mul(self.unit))?,
             unit: self.unit,
+            symbol: self.symbol,
         })
     }
 }
@@ -100,6 +397,8 @@ pub struct OpaqueBalance;
 /// # Example
 ///
 /// ```
+/// # #[cfg(feature = "runtime-polkadot")]
+/// # {
 /// use gekko::common::*;
 /// use gekko::polkadot::extrinsics::balances::TransferKeepAlive;
 ///
@@ -111,14 +410,19 @@ pub struct OpaqueBalance;
 ///
 /// // Create a `transfer_keep_alive` extrinsic.
 /// let call = TransferKeepAlive {
-///     dest: destination,
+///     dest: MultiAddress::from(destination),
 ///     value: balance,
 /// };
+/// # }
 /// ```
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, GekkoType)]
+#[gekko(type = "Compact<T::Balance>", type = "Compact<BalanceOf<T>>")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Balance {
     balance: u128,
     unit: u128,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    symbol: Option<&'static str>,
 }
 
 impl Balance {
@@ -161,18 +465,30 @@ impl Balance {
     /// assert_eq!(balance.as_metric(Metric::Kilo), None);
     /// ```
     pub fn as_metric(&self, metric: Metric) -> Option<u128> {
-        Some(convert_metrics(
-            Metric::One,
-            metric,
-            self.balance / self.unit,
-        )?)
+        convert_metrics(Metric::One, metric, self.balance / self.unit)
+    }
+}
+
+/// Returns the number of decimals of the base unit, if it's a power of ten.
+fn decimals_of_unit(unit: u128) -> Option<usize> {
+    let mut decimals = 0;
+    let mut rest = unit;
+    while rest > 1 && rest.is_multiple_of(10) {
+        rest /= 10;
+        decimals += 1;
+    }
+
+    if rest == 1 {
+        Some(decimals)
+    } else {
+        None
     }
 }
 
 fn convert_metrics(prev_metric: Metric, new_metric: Metric, balance: u128) -> Option<u128> {
     // Converts negative number to positive.
     fn pos(n: i128) -> u128 {
-        let n = if n < 0 { n * -1 } else { n };
+        let n = if n < 0 { -n } else { n };
         n as u128
     }
 
@@ -197,7 +513,62 @@ fn convert_metrics(prev_metric: Metric, new_metric: Metric, balance: u128) -> Op
     }
 }
 
+/// Displays the balance in whole tokens, followed by the symbol of the token,
+/// if known.
+///
+/// # Example
+///
+/// ```
+/// use gekko::common::*;
+///
+/// let balance = BalanceBuilder::new(Currency::Polkadot).balance_base_unit(15_000_000_000);
+/// assert_eq!(balance.to_string(), "1.5 DOT");
+/// ```
+impl std::fmt::Display for Balance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.balance.checked_div(self.unit) {
+            // A base unit of zero cannot represent any token.
+            None => write!(f, "{}", self.balance)?,
+            Some(whole) => {
+                write!(f, "{}", whole)?;
+
+                // Long division of the remainder, with as many digits as required
+                // for a decimal base unit, e.g. `10` digits for DOT.
+                let digits = (self.unit - 1).to_string().len();
+                let mut remainder = self.balance % self.unit;
+                let mut fraction = String::new();
+                for _ in 0..digits {
+                    if remainder == 0 {
+                        break;
+                    }
+
+                    remainder = match remainder.checked_mul(10) {
+                        Some(remainder) => remainder,
+                        None => break,
+                    };
+                    fraction.push(char::from(b'0' + (remainder / self.unit) as u8));
+                    remainder %= self.unit;
+                }
+
+                let fraction = fraction.trim_end_matches('0');
+                if !fraction.is_empty() {
+                    write!(f, ".{}", fraction)?;
+                }
+            }
+        }
+
+        if let Some(symbol) = self.symbol {
+            write!(f, " {}", symbol)?;
+        }
+
+        Ok(())
+    }
+}
+
 impl Encode for Balance {
+    fn size_hint(&self) -> usize {
+        Compact::from(self.balance).size_hint()
+    }
     fn using_encoded<R, F: FnOnce(&[u8]) -> R>(&self, f: F) -> R {
         f(&Compact::from(self.balance).encode())
     }
@@ -221,10 +592,126 @@ fn balance_builder() {
     assert_eq!(dot.as_metric(Metric::Mega), None);
 
     assert_eq!(dot.as_base_unit(), Currency::Polkadot.base_unit() * 50_000);
+
+    let zero = BalanceBuilder::new(Currency::Polkadot).balance(0);
+    assert_eq!(zero.as_base_unit(), 0);
+}
+
+#[test]
+fn balance_display() {
+    let dot = BalanceBuilder::new(Currency::Polkadot);
+    assert_eq!(dot.balance(50).to_string(), "50 DOT");
+    assert_eq!(dot.balance_base_unit(1).to_string(), "0.0000000001 DOT");
+    assert_eq!(dot.balance(0).to_string(), "0 DOT");
+
+    let ksm = BalanceBuilder::new(Currency::Kusama);
+    assert_eq!(
+        ksm.balance_base_unit(1_250_000_000_000).to_string(),
+        "1.25 KSM"
+    );
+
+    let custom = BalanceBuilder::try_new(Currency::from_decimals(18).unwrap()).unwrap();
+    assert_eq!(custom.balance(3).to_string(), "3");
+    assert_eq!(custom.symbol("UNIT").balance(3).to_string(), "3 UNIT");
+
+    let no_decimals = BalanceBuilder::new(Currency::from_decimals(0).unwrap());
+    assert_eq!(no_decimals.balance(7).to_string(), "7");
+}
+
+#[test]
+fn fractional_balances() {
+    let dot = BalanceBuilder::new(Currency::Polkadot);
+    let parse = |amount| dot.balance_from_str(amount).map(|b| b.as_base_unit());
+
+    assert_eq!(parse("0.1").unwrap(), 1_000_000_000);
+    assert_eq!(parse("1.5").unwrap(), 15_000_000_000);
+    assert_eq!(parse(".5").unwrap(), 5_000_000_000);
+    assert_eq!(parse("2.").unwrap(), 20_000_000_000);
+    assert_eq!(parse("3").unwrap(), 30_000_000_000);
+    assert_eq!(parse("0.0000000001").unwrap(), 1);
+    // Trailing zeros beyond the decimals of the currency are accepted.
+    assert_eq!(parse("0.100000000000").unwrap(), 1_000_000_000);
+    assert_eq!(dot.balance_from_str("1.5").unwrap().to_string(), "1.5 DOT");
+
+    for invalid in &["", ".", "-1", "1.2.3", "1,5", " 1", "1e3", "0.00000000001"] {
+        assert!(
+            matches!(parse(invalid), Err(crate::Error::InvalidBalance(_))),
+            "{:?}",
+            invalid
+        );
+    }
+    assert!(matches!(
+        parse("340282366920938463463374607431768211456"),
+        Err(crate::Error::InvalidBalance(_))
+    ));
+    assert!(matches!(
+        parse("340282366920938463463374607431768211455"),
+        Err(crate::Error::BalanceOverflow(_))
+    ));
+
+    assert_eq!(
+        dot.balance_parts(1, 5_000_000_000).unwrap(),
+        dot.balance_base_unit(15_000_000_000)
+    );
+    assert!(matches!(
+        dot.balance_parts(1, 10_000_000_000),
+        Err(crate::Error::InvalidBalance(_))
+    ));
+    assert!(matches!(
+        dot.balance_parts(u128::MAX / 10_000_000_000, 9_999_999_999),
+        Err(crate::Error::BalanceOverflow(_))
+    ));
+
+    let odd = BalanceBuilder::new(Currency::Custom(3));
+    assert!(matches!(
+        odd.balance_from_str("1"),
+        Err(crate::Error::InvalidBalance(_))
+    ));
+    assert_eq!(decimals_of_unit(1), Some(0));
+    assert_eq!(decimals_of_unit(0), None);
+}
+
+#[test]
+fn checked_balance_builder() {
+    assert!(matches!(
+        BalanceBuilder::try_new(Currency::Custom(0)),
+        Err(crate::Error::InvalidBaseUnit)
+    ));
+    assert_eq!(
+        Currency::from_decimals(38).unwrap().base_unit(),
+        10u128.pow(38)
+    );
+    assert!(matches!(
+        Currency::from_decimals(39),
+        Err(crate::Error::InvalidBaseUnit)
+    ));
+
+    let dot = BalanceBuilder::try_new(Currency::Polkadot).unwrap();
+    assert_eq!(dot.checked_balance(2).unwrap(), dot.balance(2));
+    assert!(matches!(
+        dot.checked_balance(u128::MAX),
+        Err(crate::Error::BalanceOverflow(u128::MAX))
+    ));
+}
+
+#[test]
+fn invalid_ss58_address() {
+    assert!(
+        AccountId::from_ss58_address("12eDex4amEwj39T7Wz4Rkppb68YGCDYKG9QHhEhHGtNdDy7D").is_ok()
+    );
+    assert!(matches!(
+        AccountId::from_ss58_address("12eDex4amEwj39T7Wz4Rkppb68YGCDYKG9QHhEhHGtNdDy7E"),
+        Err(crate::Error::InvalidSs58Address(Ss58Error::InvalidChecksum))
+    ));
+    assert!(matches!(
+        AccountId::from_ss58_address("not an address"),
+        Err(crate::Error::InvalidSs58Address(Ss58Error::InvalidBase58))
+    ));
 }
 
 // TODO: Add convenience handlers for DOT/KSM.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[repr(i64)]
 #[rustfmt::skip]
 pub enum Metric {
     Peta  =  1_000_000_000_000_000,
@@ -256,6 +743,81 @@ impl<T: Pair> KeyPairBuilder<T> {
     ) -> Result<(T, T::Seed), sp_core::crypto::SecretStringError> {
         T::from_phrase(phase, password)
     }
+    /// Creates the key pair from a secret URI like Substrate tooling, i.e. a
+    /// mnemonic phrase or a `0x` prefixed HEX seed, followed by an optional
+    /// derivation path and `///password`. Without a phrase or seed, the
+    /// development phrase is used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gekko::common::*;
+    /// use gekko::common::sp_core::crypto::{Pair, Ss58AddressFormat};
+    ///
+    /// let alice = KeyPairBuilder::<Sr25519>::from_uri("//Alice", None).unwrap();
+    ///
+    /// assert_eq!(
+    ///     AccountId::from(alice.public()).to_ss58_address(Ss58AddressFormat::SubstrateAccount),
+    ///     "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+    /// );
+    /// ```
+    pub fn from_uri(
+        uri: &str,
+        password: Option<&str>,
+    ) -> Result<T, sp_core::crypto::SecretStringError> {
+        T::from_string(uri, password)
+    }
+    /// Derives a child key pair along the path, where `//` precedes hard and
+    /// `/` precedes soft junctions, e.g. `"//polkadot//0/soft"`. Ed25519 and
+    /// ECDSA only support hard junctions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gekko::common::*;
+    /// use gekko::common::sp_core::crypto::Pair;
+    ///
+    /// let alice = KeyPairBuilder::<Sr25519>::from_uri("//Alice", None).unwrap();
+    /// let stash = KeyPairBuilder::derive(&alice, "//stash").unwrap();
+    ///
+    /// let expected = KeyPairBuilder::<Sr25519>::from_uri("//Alice//stash", None).unwrap();
+    /// assert_eq!(stash.public(), expected.public());
+    /// ```
+    pub fn derive(pair: &T, path: &str) -> Result<T, sp_core::crypto::SecretStringError> {
+        let junctions = parse_derivation_path(path)?;
+        pair.derive(junctions.into_iter(), None)
+            .map(|(pair, _)| pair)
+            .map_err(|_| sp_core::crypto::SecretStringError::InvalidPath)
+    }
+}
+
+/// Parses the junctions of a derivation path like `"//polkadot//0/soft"`.
+fn parse_derivation_path(
+    path: &str,
+) -> Result<Vec<sp_core::crypto::DeriveJunction>, sp_core::crypto::SecretStringError> {
+    let mut junctions = vec![];
+    let mut rest = path;
+    while !rest.is_empty() {
+        // Keeps the leading `/` of hard junctions, as expected by
+        // `DeriveJunction::from`.
+        let junction = rest
+            .strip_prefix('/')
+            .ok_or(sp_core::crypto::SecretStringError::InvalidPath)?;
+        let offset = usize::from(junction.starts_with('/'));
+        let end = junction[offset..]
+            .find('/')
+            .map_or(junction.len(), |end| end + offset);
+
+        let (junction, next) = junction.split_at(end);
+        if junction.len() == offset {
+            return Err(sp_core::crypto::SecretStringError::InvalidPath);
+        }
+
+        junctions.push(junction.into());
+        rest = next;
+    }
+
+    Ok(junctions)
 }
 
 #[derive(Clone)]
@@ -283,13 +845,93 @@ impl From<Ecdsa> for MultiKeyPair {
     }
 }
 
+impl MultiKeyPair {
+    /// Derives a child key pair along the path, see
+    /// [`KeyPairBuilder::derive`].
+    pub fn derive(&self, path: &str) -> Result<Self, sp_core::crypto::SecretStringError> {
+        Ok(match self {
+            MultiKeyPair::Ed25519(pair) => KeyPairBuilder::derive(pair, path)?.into(),
+            MultiKeyPair::Sr25519(pair) => KeyPairBuilder::derive(pair, path)?.into(),
+            MultiKeyPair::Ecdsa(pair) => KeyPairBuilder::derive(pair, path)?.into(),
+        })
+    }
+}
+
+#[test]
+fn derivation_paths() {
+    use sp_core::crypto::SecretStringError;
+
+    let alice = KeyPairBuilder::<Sr25519>::from_uri("//Alice", None).unwrap();
+    assert_eq!(
+        hex::encode(alice.public()),
+        "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+    );
+
+    let stash = KeyPairBuilder::derive(&alice, "//stash").unwrap();
+    assert_eq!(
+        hex::encode(stash.public()),
+        "be5ddb1579b72e84524fc29e78609e3caf42e85aa118ebfe0b0ad404b5bdd25f"
+    );
+
+    // Hard, numeric and soft junctions match the secret URI.
+    let root = KeyPairBuilder::<Sr25519>::from_uri("", None).unwrap();
+    let derived = KeyPairBuilder::derive(&root, "//polkadot//0/soft").unwrap();
+    let expected = KeyPairBuilder::<Sr25519>::from_uri("//polkadot//0/soft", None).unwrap();
+    assert_eq!(derived.public(), expected.public());
+    assert_eq!(
+        KeyPairBuilder::derive(&root, "").unwrap().public(),
+        root.public()
+    );
+
+    let ed25519 = MultiKeyPair::from(KeyPairBuilder::<Ed25519>::from_uri("", None).unwrap());
+    let expected = KeyPairBuilder::<Ed25519>::from_uri("//Alice", None).unwrap();
+    assert_eq!(
+        AccountId::from(ed25519.derive("//Alice").unwrap()),
+        AccountId::from(expected.public())
+    );
+    assert!(matches!(
+        ed25519.derive("/soft"),
+        Err(SecretStringError::InvalidPath)
+    ));
+
+    let ecdsa = MultiKeyPair::from(KeyPairBuilder::<Ecdsa>::from_uri("", None).unwrap());
+    let expected = KeyPairBuilder::<Ecdsa>::from_uri("//Alice//1", None).unwrap();
+    assert_eq!(
+        AccountId::from(ecdsa.derive("//Alice//1").unwrap()),
+        AccountId::from(expected.public())
+    );
+
+    for invalid in &["Alice", "//", "//Alice/", "///password"] {
+        assert!(
+            matches!(
+                KeyPairBuilder::derive(&root, invalid),
+                Err(SecretStringError::InvalidPath)
+            ),
+            "{}",
+            invalid
+        );
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Mortality {
     Immortal,
-    Mortal(u64, u64, Option<[u8; 32]>),
+    Mortal(
+        u64,
+        u64,
+        #[cfg_attr(feature = "serde", serde(with = "gekko_metadata::serde_hex::option"))]
+        Option<[u8; 32]>,
+    ),
 }
 
 impl Encode for Mortality {
+    fn size_hint(&self) -> usize {
+        match self {
+            Mortality::Immortal => 1,
+            Mortality::Mortal(..) => 2,
+        }
+    }
     fn using_encoded<R, F: FnOnce(&[u8]) -> R>(&self, f: F) -> R {
         // The code within this block was copied from the
         // [Substrate](https://github.com/paritytech/substrate) project, created
@@ -305,8 +947,13 @@ impl Encode for Mortality {
         match self {
             Self::Immortal => enc.push(0),
             Self::Mortal(period, phase, _) => {
-                let quantize_factor = (*period as u64 >> 12).max(1);
-                let encoded = (period.trailing_zeros() - 1).max(1).min(15) as u16
+                let period = period
+                    .checked_next_power_of_two()
+                    .unwrap_or(1 << 16)
+                    .clamp(4, 1 << 16);
+                let phase = phase % period;
+                let quantize_factor = (period >> 12).max(1);
+                let encoded = (period.trailing_zeros() - 1).clamp(1, 15) as u16
                     | ((phase / quantize_factor) << 4) as u16;
                 encoded.encode_to(&mut enc);
             }
@@ -351,15 +998,89 @@ impl Mortality {
     pub fn mortal(current: u64, period: u64, phase: u64) -> u64 {
         (current.max(phase) - phase) / period * period + phase
     }
+    /// A mortality of `period` blocks, starting at the current block whose
+    /// hash is the birth hash, e.g. the latest finalized block. The period is
+    /// rounded to a power of two between 4 and 65536, as required by the
+    /// runtime.
+    ///
+    /// The phase of periods exceeding 4096 blocks is quantized when encoded,
+    /// which would move the birth block before the given block and result in
+    /// an invalid signature. In that case, the period is halved until the
+    /// given block is a valid birth block.
+    ///
+    /// With the `"rpc"` feature, `rpc::block::finalized_mortality` starts at
+    /// the latest finalized block of a node.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gekko::common::Mortality;
+    ///
+    /// let mortality = Mortality::from_current_block(1_000, [1; 32], 64);
+    /// assert_eq!(mortality, Mortality::Mortal(64, 1_000 % 64, Some([1; 32])));
+    ///
+    /// // The phase of 8193 % 8192 is quantized to a multiple of two.
+    /// let mortality = Mortality::from_current_block(8193, [1; 32], 8192);
+    /// assert_eq!(mortality, Mortality::Mortal(4096, 8193 % 4096, Some([1; 32])));
+    /// ```
+    pub fn from_current_block(block_number: u64, block_hash: [u8; 32], period: u64) -> Self {
+        let mut period = period.clamp(4, 1 << 16).next_power_of_two();
+        while !(block_number % period).is_multiple_of((period >> 12).max(1)) {
+            period >>= 1;
+        }
+
+        Mortality::Mortal(period, block_number % period, Some(block_hash))
+    }
+}
+
+#[test]
+fn mortality_clamps_period() {
+    // Periods are rounded to a power of two between 4 and 65536 when encoded.
+    assert_eq!(
+        Mortality::Mortal(1, 0, None).encode(),
+        Mortality::Mortal(4, 0, None).encode()
+    );
+    assert_eq!(
+        Mortality::decode(&mut Mortality::Mortal(1, 0, None).encode().as_slice()).unwrap(),
+        Mortality::Mortal(4, 0, None)
+    );
+    assert_eq!(
+        Mortality::Mortal(1 << 20, 0, None).encode(),
+        Mortality::Mortal(1 << 16, 0, None).encode()
+    );
+    assert_eq!(
+        Mortality::Mortal(64, 1_000 % 64, None).encode(),
+        Mortality::Mortal(64, 1_000, None).encode()
+    );
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MultiSignature {
     Ed25519(sp_core::ed25519::Signature),
     Sr25519(sp_core::sr25519::Signature),
     Ecdsa(sp_core::ecdsa::Signature),
 }
 
+impl MultiSignature {
+    /// Verifies the signature of the message against the account. For ECDSA,
+    /// the public key is recovered from the signature and its Blake2 hash must
+    /// match the account, as derived by Substrate.
+    pub fn verify(&self, message: &[u8], account: &AccountId) -> bool {
+        match self {
+            MultiSignature::Ed25519(sig) => {
+                Ed25519::verify(sig, message, &sp_core::ed25519::Public(account.0))
+            }
+            MultiSignature::Sr25519(sig) => {
+                Sr25519::verify(sig, message, &sp_core::sr25519::Public(account.0))
+            }
+            MultiSignature::Ecdsa(sig) => {
+                sig.recover(message).map(crate::blake2b) == Some(account.0)
+            }
+        }
+    }
+}
+
 impl From<sp_core::ed25519::Signature> for MultiSignature {
     fn from(val: sp_core::ed25519::Signature) -> Self {
         MultiSignature::Ed25519(val)
@@ -378,9 +1099,73 @@ impl From<sp_core::ecdsa::Signature> for MultiSignature {
     }
 }
 
-/// A multi-format address wrapper for on-chain accounts. This is the
-/// recommended type to decode transactions, while [`AccountId`] can be used for
-/// convenience when encoding.
+/// A call which is already SCALE encoded, including the module and dispatch
+/// Id. Decoding consumes all of the remaining input, making this type suitable
+/// for decoding transactions of unknown calls.
+///
+/// # Example
+///
+/// ```
+/// use gekko::common::{*, scale::{Decode, Encode}};
+/// use gekko::transaction::Transaction;
+///
+/// let transaction = Transaction::new_unsigned(OpaqueCall(vec![4, 3, 1, 2]));
+/// let encoded = transaction.encode();
+///
+/// let decoded: Transaction<(), OpaqueCall, (), ()> =
+///     Decode::decode(&mut encoded.as_slice()).unwrap();
+///
+/// assert_eq!(decoded.call.module_id(), Some(4));
+/// assert_eq!(decoded.call.dispatch_id(), Some(3));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct OpaqueCall(
+    #[cfg_attr(feature = "serde", serde(with = "gekko_metadata::serde_hex"))] pub Vec<u8>,
+);
+
+impl OpaqueCall {
+    /// The module Id of the call.
+    pub fn module_id(&self) -> Option<u8> {
+        self.0.first().copied()
+    }
+    /// The dispatch Id of the call.
+    pub fn dispatch_id(&self) -> Option<u8> {
+        self.0.get(1).copied()
+    }
+    /// The encoded arguments of the call, without the module and dispatch Id.
+    pub fn args(&self) -> &[u8] {
+        self.0.get(2..).unwrap_or_default()
+    }
+}
+
+impl Encode for OpaqueCall {
+    fn size_hint(&self) -> usize {
+        self.0.len()
+    }
+    fn using_encoded<R, F: FnOnce(&[u8]) -> R>(&self, f: F) -> R {
+        f(&self.0)
+    }
+}
+
+impl Decode for OpaqueCall {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, parity_scale_codec::Error> {
+        let len = input
+            .remaining_len()?
+            .ok_or("cannot decode OpaqueCall from input of unknown length")?;
+
+        let mut buffer = vec![0; len];
+        input.read(&mut buffer)?;
+
+        Ok(OpaqueCall(buffer))
+    }
+}
+
+/// A multi-format address wrapper for on-chain accounts, generic over the
+/// account identifier and the account index of the chain. This is the address
+/// type of signed transactions of Kusama, Polkadot and Westend, see
+/// [`PolkadotConfig`].
 ///
 /// # Example
 ///
@@ -391,17 +1176,28 @@ impl From<sp_core::ecdsa::Signature> for MultiSignature {
 ///     AccountId::from_ss58_address("D12RroVkrWavttGJ1g3iHNmDa68kyMsSeXvoZ1xPm8828kk")
 ///     .unwrap();
 ///
-/// let multi = MultiAddress::Id(AccountId32::from(account_id));
+/// let multi: MultiAddress = MultiAddress::Id(AccountId32::from(account_id));
+/// assert_eq!(multi, MultiAddress::from(account_id));
 ///
-/// assert_eq!(account_id.encode(), multi.encode());
+/// // An Ethereum-style address of a chain with `u32` account indices.
+/// let address20 = MultiAddress::<AccountId32, u32>::Address20([1; 20]);
+/// assert_eq!(address20.encode(), [&[4][..], &[1; 20]].concat());
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
-pub enum MultiAddress {
-    Id(sp_core::crypto::AccountId32),
-    Index(#[codec(compact)] u64),
-    Raw(Vec<u8>),
-    Address32([u8; 32]),
-    Address20([u8; 20]),
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, GekkoType)]
+#[gekko(type = "<T::Lookup as StaticLookup>::Source")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MultiAddress<AccountId = AccountId32, AccountIndex = u64> {
+    Id(AccountId),
+    Index(#[codec(compact)] AccountIndex),
+    Raw(#[cfg_attr(feature = "serde", serde(with = "gekko_metadata::serde_hex"))] Vec<u8>),
+    Address32(#[cfg_attr(feature = "serde", serde(with = "gekko_metadata::serde_hex"))] [u8; 32]),
+    Address20(#[cfg_attr(feature = "serde", serde(with = "gekko_metadata::serde_hex"))] [u8; 20]),
+}
+
+impl From<AccountId> for MultiAddress {
+    fn from(val: AccountId) -> Self {
+        MultiAddress::Id(val.into())
+    }
 }
 
 /// An opaque 32-byte identifier of an on-chain account.
@@ -417,6 +1213,13 @@ pub enum MultiAddress {
 /// those. Officially, Kusama and Polkadot support multiple account
 /// identifiers and [`MultiAddress`] should therefore be used for decoding.
 ///
+/// **Deprecated**: Using this type as an address in transactions is
+/// deprecated. Its SCALE encoding prepends the index of [`MultiAddress::Id`],
+/// which only matches chains whose lookup source is a `MultiAddress`. Convert
+/// it into a [`MultiAddress`] instead, e.g. with
+/// `MultiAddress::from(account_id)`. The prefixed encoding will be removed in
+/// a future release, while the type itself remains.
+///
 /// ```
 /// use gekko::common::{*, sp_core::crypto::AccountId32};
 ///
@@ -430,7 +1233,8 @@ pub enum MultiAddress {
 /// // Convert it back into the native type.
 /// let account_id: AccountId = sub.into();
 /// ```
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, GekkoType)]
+#[gekko(type = "<T::Lookup as StaticLookup>::Source")]
 pub struct AccountId([u8; 32]);
 
 // TODO: Consider adding hex handler.
@@ -439,7 +1243,9 @@ impl AccountId {
     pub fn new(bytes: [u8; 32]) -> Self {
         AccountId(bytes)
     }
-    /// Creates a new account identifier from a SS58 encoded string.
+    /// Creates a new account identifier from a SS58 encoded string. Returns
+    /// [`Error::InvalidSs58Address`](crate::Error::InvalidSs58Address) with
+    /// the [`Ss58Error`] if the address is invalid.
     ///
     /// # Example
     ///
@@ -450,8 +1256,8 @@ impl AccountId {
     ///     AccountId::from_ss58_address("D12RroVkrWavttGJ1g3iHNmDa68kyMsSeXvoZ1xPm8828kk")
     ///         .unwrap();
     /// ```
-    pub fn from_ss58_address(addr: &str) -> Result<Self, ()> {
-        let (account, _) = Self::from_ss58check_with_version(addr).unwrap();
+    pub fn from_ss58_address(addr: &str) -> crate::Result<Self> {
+        let (account, _) = Self::from_ss58_address_with_version(addr)?;
         Ok(account)
     }
     /// Creates a new account identifier from a SS58 encoded string and returns
@@ -468,9 +1274,9 @@ impl AccountId {
     ///
     /// assert_eq!(version, Ss58AddressFormat::KusamaAccount);
     /// ```
-    pub fn from_ss58_address_with_version(addr: &str) -> Result<(Self, Ss58AddressFormat), ()> {
-        let (account, format) = Self::from_ss58check_with_version(addr).unwrap();
-        Ok((account, format))
+    pub fn from_ss58_address_with_version(addr: &str) -> crate::Result<(Self, Ss58AddressFormat)> {
+        let (account, format) = ss58::decode(addr)?;
+        Ok((AccountId(account), format))
     }
     /// Returns the SS58 encoded representation of the account identifiers,
     /// based on the specified format.
@@ -496,7 +1302,12 @@ impl From<AccountId32> for AccountId {
     }
 }
 
+/// **Deprecated**: Encodes the account identifier as [`MultiAddress::Id`].
+/// Encode a [`MultiAddress`] instead.
 impl Encode for AccountId {
+    fn size_hint(&self) -> usize {
+        33
+    }
     fn using_encoded<R, F: FnOnce(&[u8]) -> R>(&self, f: F) -> R {
         let mut buffer = [0; 33];
 
@@ -508,6 +1319,8 @@ impl Encode for AccountId {
     }
 }
 
+/// **Deprecated**: Decodes a [`MultiAddress::Id`]. Decode a [`MultiAddress`]
+/// instead.
 impl Decode for AccountId {
     fn decode<I: Input>(input: &mut I) -> Result<Self, parity_scale_codec::Error> {
         let mut buffer = [0; 32];
@@ -524,6 +1337,31 @@ impl Decode for AccountId {
 
 impl Ss58Codec for AccountId {}
 
+/// Serializes the account identifier as `0x`-prefixed HEX string.
+#[cfg(feature = "serde")]
+impl serde::Serialize for AccountId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        gekko_metadata::serde_hex::serialize(self.0, serializer)
+    }
+}
+
+/// Deserializes the account identifier from either a `0x`-prefixed HEX string
+/// or a SS58 encoded address.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for AccountId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{Error, IntoDeserializer};
+
+        let addr = String::deserialize(deserializer)?;
+        if addr.starts_with("0x") {
+            gekko_metadata::serde_hex::deserialize::<_, [u8; 32]>(addr.into_deserializer())
+                .map(AccountId)
+        } else {
+            AccountId::from_ss58_address(&addr).map_err(D::Error::custom)
+        }
+    }
+}
+
 impl AsRef<[u8]> for AccountId {
     fn as_ref(&self) -> &[u8] {
         &self.0[..]
@@ -548,12 +1386,34 @@ impl From<sp_core::ed25519::Public> for AccountId {
     }
 }
 
+/// Substrate derives the account of ECDSA keys by hashing the 33-byte
+/// compressed public key with Blake2-256.
+impl From<sp_core::ecdsa::Public> for AccountId {
+    fn from(val: sp_core::ecdsa::Public) -> Self {
+        AccountId(crate::blake2b(val))
+    }
+}
+
 impl From<MultiKeyPair> for AccountId {
     fn from(val: MultiKeyPair) -> Self {
         match val {
             MultiKeyPair::Ed25519(pair) => pair.public().into(),
             MultiKeyPair::Sr25519(pair) => pair.public().into(),
-            MultiKeyPair::Ecdsa(_) => unimplemented!(),
+            MultiKeyPair::Ecdsa(pair) => pair.public().into(),
         }
     }
 }
+
+#[test]
+fn ecdsa_account_id() {
+    let pair = KeyPairBuilder::<Ecdsa>::from_seed(&[1; 32]);
+    let public = pair.public();
+    assert_eq!(public.as_ref().len(), 33);
+
+    let expected = sp_core::hashing::blake2_256(public.as_ref());
+    assert_eq!(AccountId::from(public), AccountId::new(expected));
+    assert_eq!(
+        AccountId::from(MultiKeyPair::from(pair)),
+        AccountId::new(expected)
+    );
+}
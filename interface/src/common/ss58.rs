@@ -0,0 +1,146 @@
+//! Decoding of SS58 addresses, see the
+//! [specification](https://github.com/paritytech/substrate/wiki/External-Address-Format-(SS58)).
+
+use base58::FromBase58;
+use sp_core::crypto::Ss58AddressFormat;
+use std::convert::TryFrom;
+
+/// The length of the checksum appended to the address.
+const CHECKSUM_LEN: usize = 2;
+
+/// Errors that can occur when parsing SS58 addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum Ss58Error {
+    /// The address contains characters outside of the Base58 alphabet.
+    #[error("invalid Base58 encoding")]
+    InvalidBase58,
+    /// The decoded address has the wrong length for its prefix.
+    #[error("invalid length of address")]
+    InvalidLength,
+    /// The checksum does not match the prefix and account.
+    #[error("invalid checksum")]
+    InvalidChecksum,
+    /// The prefix is reserved and does not identify any network.
+    #[error("unknown address prefix {0}")]
+    UnknownPrefix(u16),
+    /// The first byte is not a valid encoding of a prefix.
+    #[error("invalid address prefix")]
+    InvalidPrefix,
+}
+
+/// The length of the account identifier.
+const ACCOUNT_LEN: usize = 32;
+
+/// Decodes the SS58 address into the account identifier and the address
+/// format.
+pub(crate) fn decode(addr: &str) -> Result<([u8; ACCOUNT_LEN], Ss58AddressFormat), Ss58Error> {
+    let data = addr.from_base58().map_err(|_| Ss58Error::InvalidBase58)?;
+    if data.len() < 2 {
+        return Err(Ss58Error::InvalidLength);
+    }
+
+    let (prefix_len, prefix) = match data[0] {
+        0..=63 => (1, data[0] as u16),
+        64..=127 => {
+            // The bytes `01aaaaaa bbcccccc` make the little-endian prefix
+            // `aaaaaabb 00cccccc`.
+            let lower = (data[0] << 2) | (data[1] >> 6);
+            let upper = data[1] & 0b0011_1111;
+            (2, lower as u16 | (upper as u16) << 8)
+        }
+        _ => return Err(Ss58Error::InvalidPrefix),
+    };
+
+    if data.len() != prefix_len + ACCOUNT_LEN + CHECKSUM_LEN {
+        return Err(Ss58Error::InvalidLength);
+    }
+
+    let format =
+        Ss58AddressFormat::try_from(prefix).map_err(|_| Ss58Error::UnknownPrefix(prefix))?;
+    if matches!(
+        format,
+        Ss58AddressFormat::Reserved46 | Ss58AddressFormat::Reserved47
+    ) {
+        return Err(Ss58Error::UnknownPrefix(prefix));
+    }
+
+    let (body, checksum) = data.split_at(prefix_len + ACCOUNT_LEN);
+    if checksum != &ss58_hash(body)[..CHECKSUM_LEN] {
+        return Err(Ss58Error::InvalidChecksum);
+    }
+
+    let mut account = [0; ACCOUNT_LEN];
+    account.copy_from_slice(&body[prefix_len..]);
+    Ok((account, format))
+}
+
+fn ss58_hash(data: &[u8]) -> Vec<u8> {
+    let mut context = blake2_rfc::blake2b::Blake2b::new(64);
+    context.update(b"SS58PRE");
+    context.update(data);
+    context.finalize().as_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::AccountId;
+
+    fn decode_account(addr: &str) -> Result<[u8; 32], Ss58Error> {
+        decode(addr).map(|(account, _)| account)
+    }
+
+    #[test]
+    fn decode_addresses() {
+        let account = AccountId::new([7; 32]);
+        for format in [
+            Ss58AddressFormat::PolkadotAccount,
+            Ss58AddressFormat::KusamaAccount,
+            Ss58AddressFormat::SubstrateAccount,
+            Ss58AddressFormat::Custom(1337),
+        ] {
+            let addr = account.to_ss58_address(format);
+            assert_eq!(decode(&addr), Ok((account.to_bytes(), format)));
+        }
+    }
+
+    #[test]
+    fn decode_invalid_addresses() {
+        let valid = "12eDex4amEwj39T7Wz4Rkppb68YGCDYKG9QHhEhHGtNdDy7D";
+        assert!(decode_account(valid).is_ok());
+
+        assert_eq!(
+            decode_account("12eDex4amEwj39T7Wz4Rkppb68YGCDYKG9QHhEhHGtNdDy7E"),
+            Err(Ss58Error::InvalidChecksum)
+        );
+        assert_eq!(
+            decode_account("not an address"),
+            Err(Ss58Error::InvalidBase58)
+        );
+        assert_eq!(decode_account("0OIl"), Err(Ss58Error::InvalidBase58));
+        assert_eq!(decode_account(""), Err(Ss58Error::InvalidLength));
+
+        let encode = |bytes: &[u8]| base58::ToBase58::to_base58(bytes);
+
+        assert_eq!(
+            decode_account(&encode(&[0; 20])),
+            Err(Ss58Error::InvalidLength)
+        );
+
+        let mut data = vec![128];
+        data.extend_from_slice(&[0; 34]);
+        assert_eq!(
+            decode_account(&encode(&data)),
+            Err(Ss58Error::InvalidPrefix)
+        );
+
+        let mut data = vec![46];
+        data.extend_from_slice(&[0; 32]);
+        let checksum = ss58_hash(&data);
+        data.extend_from_slice(&checksum[..CHECKSUM_LEN]);
+        assert_eq!(
+            decode_account(&encode(&data)),
+            Err(Ss58Error::UnknownPrefix(46))
+        );
+    }
+}
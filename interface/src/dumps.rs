@@ -0,0 +1,96 @@
+//! Kusama and Polkadot runtime metadata dumps, embedded into the binary.
+//! Requires the `"dumps"` feature.
+//!
+//! The dumps are parsed on first access and cached for the lifetime of the
+//! program.
+//!
+//! # Example
+//!
+//! ```
+//! use gekko::dumps;
+//! use gekko::metadata::ModuleMetadataExt;
+//!
+//! let extr = dumps::kusama_9080()
+//!     .find_module_extrinsic("Balances", "transfer_keep_alive")
+//!     .unwrap();
+//!
+//! assert_eq!(extr.module_id, 4);
+//! assert_eq!(extr.dispatch_id, 3);
+//! ```
+
+use gekko_metadata::parse_hex_metadata;
+use gekko_metadata::version::MetadataV13;
+use std::sync::OnceLock;
+
+/// The HEX encoded metadata of the Kusama runtime, spec version 9080.
+pub const KUSAMA_9080_HEX: &[u8] = include_bytes!("../dumps/metadata_kusama_9080.hex");
+
+/// The HEX encoded metadata of the Polkadot runtime, spec version 9050.
+pub const POLKADOT_9050_HEX: &[u8] = include_bytes!("../dumps/metadata_polkadot_9050.hex");
+
+/// The parsed metadata of the Kusama runtime, spec version 9080.
+pub fn kusama_9080() -> &'static MetadataV13 {
+    static METADATA: OnceLock<MetadataV13> = OnceLock::new();
+    METADATA.get_or_init(|| parse_dump(KUSAMA_9080_HEX))
+}
+
+/// The parsed metadata of the Polkadot runtime, spec version 9050.
+pub fn polkadot_9050() -> &'static MetadataV13 {
+    static METADATA: OnceLock<MetadataV13> = OnceLock::new();
+    METADATA.get_or_init(|| parse_dump(POLKADOT_9050_HEX))
+}
+
+fn parse_dump(hex: &[u8]) -> MetadataV13 {
+    parse_hex_metadata(hex.trim_ascii())
+        .and_then(|metadata| metadata.into_latest())
+        .expect("the bundled metadata dumps are valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gekko_metadata::ModuleMetadataExt;
+
+    #[test]
+    fn extrinsic_info_by_index() {
+        let data = kusama_9080();
+
+        // Staking is declared with index 6, but is the eighth module.
+        let info = data.find_extrinsic_by_index(6, 0).unwrap();
+        assert_eq!(info.module_name, "Staking");
+        assert_eq!(info.extrinsic_name, "bond");
+        assert_eq!(info.module_id, 6);
+        assert_eq!(info.module_position, 7);
+        assert_eq!(data.find_module_extrinsic("Staking", "bond"), Some(info));
+
+        let info = data.find_extrinsic_by_index(4, 3).unwrap();
+        assert_eq!(info.module_name, "Balances");
+        assert_eq!(info.extrinsic_name, "transfer_keep_alive");
+
+        // TransactionPayment has no calls.
+        assert!(data.find_extrinsic_by_index(33, 0).is_none());
+        assert!(data.find_extrinsic_by_index(4, 200).is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn metadata_json_roundtrip() {
+        use gekko_metadata::MetadataVersion;
+
+        let metadata = MetadataVersion::V13(kusama_9080().clone());
+
+        let json = serde_json::to_value(&metadata).unwrap();
+        assert_eq!(json["version"], "V13");
+
+        let balances = &json["metadata"]["modules"][4];
+        assert_eq!(balances["name"], "Balances");
+        assert_eq!(balances["index"], 4);
+        assert_eq!(
+            balances["constants"][0]["value"],
+            "0x55a0fc01000000000000000000000000"
+        );
+
+        let decoded: MetadataVersion = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, metadata);
+    }
+}
@@ -0,0 +1,140 @@
+//! The primitives of the polkadot-js keystore: scrypt for deriving the key from
+//! the password and the NaCl `secretbox` (XSalsa20-Poly1305) for encrypting
+//! the secret key, as implemented by the `scrypt` and `crypto_secretbox`
+//! crates.
+
+use crypto_secretbox::aead::{Aead, KeyInit};
+use crypto_secretbox::XSalsa20Poly1305;
+
+/// The length of the XSalsa20 nonce.
+pub const NONCE_LEN: usize = 24;
+
+/// The cost parameters of scrypt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScryptParams {
+    pub n: u32,
+    pub p: u32,
+    pub r: u32,
+}
+
+impl ScryptParams {
+    /// The parameters used by polkadot-js.
+    pub const DEFAULT: ScryptParams = ScryptParams {
+        n: 1 << 15,
+        p: 1,
+        r: 8,
+    };
+
+    /// Whether the parameters are valid and do not require more than 1 GiB of
+    /// memory, which prevents keystore files from exhausting the memory.
+    pub fn is_valid(&self) -> bool {
+        self.n > 1
+            && self.n.is_power_of_two()
+            && self.p > 0
+            && self.r > 0
+            && (self.n as u64) * (self.r as u64) * 128 <= 1 << 30
+            && (self.p as u64) * (self.r as u64) <= 1 << 10
+            && self.to_scrypt().is_some()
+    }
+
+    /// The parameters of the `scrypt` crate, which additionally requires `n`
+    /// to be below `2^(16 * r)`.
+    fn to_scrypt(self) -> Option<scrypt::Params> {
+        scrypt::Params::new(
+            self.n.trailing_zeros() as u8,
+            self.r,
+            self.p,
+            scrypt::Params::RECOMMENDED_LEN,
+        )
+        .ok()
+    }
+}
+
+/// Derives a key of `len` bytes from the password. The parameters must be
+/// [valid](ScryptParams::is_valid).
+pub fn scrypt(password: &[u8], salt: &[u8], params: ScryptParams, len: usize) -> Vec<u8> {
+    debug_assert!(params.is_valid());
+
+    let params = params.to_scrypt().expect("valid scrypt parameters");
+    let mut key = vec![0; len];
+    scrypt::scrypt(password, salt, &params, &mut key).expect("non-empty key");
+    key
+}
+
+/// Encrypts the message with the NaCl `secretbox`. The authentication tag is
+/// prepended to the ciphertext.
+pub fn secretbox_seal(key: &[u8; 32], nonce: &[u8; NONCE_LEN], message: &[u8]) -> Vec<u8> {
+    XSalsa20Poly1305::new(key.into())
+        .encrypt(nonce.into(), message)
+        .expect("message fits into memory")
+}
+
+/// Decrypts the NaCl `secretbox`. Returns `None` if the authentication fails,
+/// e.g. because of a wrong password.
+pub fn secretbox_open(key: &[u8; 32], nonce: &[u8; NONCE_LEN], sealed: &[u8]) -> Option<Vec<u8>> {
+    XSalsa20Poly1305::new(key.into())
+        .decrypt(nonce.into(), sealed)
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryInto;
+
+    fn unhex<const N: usize>(hex: &str) -> [u8; N] {
+        hex::decode(hex).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn scrypt_params() {
+        assert!(ScryptParams::DEFAULT.is_valid());
+        assert!(!ScryptParams { n: 3, p: 1, r: 8 }.is_valid());
+        // Exceeds the memory limit.
+        assert!(!ScryptParams {
+            n: 1 << 21,
+            p: 1,
+            r: 8
+        }
+        .is_valid());
+        // Rejected by scrypt, `n` must be below `2^(16 * r)`.
+        assert!(!ScryptParams {
+            n: 1 << 16,
+            p: 1,
+            r: 1
+        }
+        .is_valid());
+    }
+
+    #[test]
+    fn secretbox_nacl() {
+        // The test vector of the NaCl distribution, with the key derived by
+        // HSalsa20 from the shared secret of `crypto_box`.
+        let key = unhex("1b27556473e985d462cd51197a9a46c76009549eac6474f206c4ee0844f68389");
+
+        let nonce = unhex("69696ee955b62b73cd62bda875fc73d68219e0036b7a0b37");
+        let message = hex::decode(
+            "be075fc53c81f2d5cf141316ebeb0c7b5228c52a4c62cbd44b66849b64244ffce5ecbaaf33bd751a\
+             1ac728d45e6c61296cdc3c01233561f41db66cce314adb310e3be8250c46f06dceea3a7fa1348057\
+             e2f6556ad6b1318a024a838f21af1fde048977eb48f59ffd4924ca1c60902e52f0a089bc76897040\
+             e082f937763848645e0705",
+        )
+        .unwrap();
+
+        let sealed = secretbox_seal(&key, &nonce, &message);
+        assert_eq!(
+            hex::encode(&sealed),
+            "f3ffc7703f9400e52a7dfb4b3d3305d98e993b9f48681273c29650ba32fc76ce48332ea7164d96a4\
+             476fb8c531a1186ac0dfc17c98dce87b4da7f011ec48c97271d2c20f9b928fe2270d6fb863d51738\
+             b48eeee314a7cc8ab932164548e526ae90224368517acfeabd6bb3732bc0e9da99832b61ca01b6de\
+             56244a9e88d5f9b37973f622a43d14a6599b1f654cb45a74e355a5"
+        );
+
+        assert_eq!(secretbox_open(&key, &nonce, &sealed), Some(message));
+
+        let mut tampered = sealed;
+        tampered[20] ^= 1;
+        assert_eq!(secretbox_open(&key, &nonce, &tampered), None);
+        assert_eq!(secretbox_open(&key, &nonce, &[0; 8]), None);
+    }
+}
@@ -0,0 +1,559 @@
+//! Encrypted keys in the JSON keystore format of polkadot-js, as exported by
+//! wallets such as the polkadot-js extension. Requires the `"keystore"`
+//! feature.
+//!
+//! A [`KeystoreJson`] contains a single key, encrypted with a key derived from
+//! the password via scrypt and XSalsa20-Poly1305. The [`Keystore`] manages
+//! multiple keys, which can be loaded from and saved to a directory.
+//!
+//! # Example
+//!
+//! ```
+//! use gekko::common::*;
+//! use gekko::keystore::*;
+//! use gekko::common::sp_core::crypto::Ss58AddressFormat;
+//!
+//! let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+//! let keypair = MultiKeyPair::from(keypair);
+//!
+//! let mut keystore = Keystore::new();
+//! let account = keystore
+//!     .insert(&keypair, "password", Ss58AddressFormat::PolkadotAccount, Some("Alice"))
+//!     .unwrap();
+//!
+//! assert_eq!(keystore.accounts(), vec![account]);
+//!
+//! // Compatible with wallets such as the polkadot-js extension.
+//! let json = keystore.export(&account).unwrap();
+//!
+//! let mut other = Keystore::new();
+//! assert_eq!(other.import(&json).unwrap(), account);
+//!
+//! // The key pair for signing transactions.
+//! let keypair = other.key_pair(&account, "password").unwrap();
+//! assert_eq!(AccountId::from(keypair), account);
+//! ```
+
+use crate::common::{AccountId, Ecdsa, Ed25519, MultiKeyPair, Sr25519};
+use crate::{Error, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use crypto::ScryptParams;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sp_core::crypto::{Pair, Ss58AddressFormat};
+use std::convert::TryInto;
+use std::path::Path;
+
+mod crypto;
+
+/// The version of the keystore format.
+const VERSION: &str = "3";
+/// The encryption of the keystore, as listed in the `encoding` field.
+const ENCRYPTION: [&str; 2] = ["scrypt", "xsalsa20-poly1305"];
+/// The length of the scrypt salt.
+const SALT_LEN: usize = 32;
+/// The length of the scrypt salt and parameters, preceding the nonce.
+const SCRYPT_LEN: usize = SALT_LEN + 12;
+
+/// The PKCS#8 header preceding the secret key.
+const PKCS8_HEADER: [u8; 16] = [48, 83, 2, 1, 1, 48, 5, 6, 3, 43, 101, 112, 4, 34, 4, 32];
+/// The PKCS#8 divider between the secret key and the public key.
+const PKCS8_DIVIDER: [u8; 5] = [161, 35, 3, 33, 0];
+
+/// Errors that can occur when importing or decrypting keys.
+#[derive(Debug, thiserror::Error)]
+pub enum KeystoreError {
+    #[error("invalid keystore JSON")]
+    InvalidJson(#[from] serde_json::Error),
+    /// The keystore uses an encoding other than version 3 with scrypt and
+    /// XSalsa20-Poly1305, or an unsupported key type.
+    #[error("unsupported keystore encoding: {0}")]
+    UnsupportedEncoding(String),
+    /// The encrypted key could not be decoded.
+    #[error("invalid encoded key: {0}")]
+    InvalidEncoded(&'static str),
+    /// The key could not be decrypted, usually because of a wrong password.
+    #[error("invalid password")]
+    InvalidPassword,
+    /// The decrypted key does not belong to the address of the keystore.
+    #[error("decrypted key does not match the address {0}")]
+    AccountMismatch(String),
+    /// The keystore has no key of the account.
+    #[error("no key for account {0:?}")]
+    UnknownAccount(AccountId),
+}
+
+/// The type of the key, as listed in the `encoding` field.
+fn key_type(keypair: &MultiKeyPair) -> &'static str {
+    match keypair {
+        MultiKeyPair::Ed25519(_) => "ed25519",
+        MultiKeyPair::Sr25519(_) => "sr25519",
+        MultiKeyPair::Ecdsa(_) => "ecdsa",
+    }
+}
+
+/// The `encoding` field of the keystore.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeystoreEncoding {
+    /// The format and the type of the key, e.g. `["pkcs8", "sr25519"]`.
+    pub content: Vec<String>,
+    /// The encryption, e.g. `["scrypt", "xsalsa20-poly1305"]`.
+    #[serde(rename = "type")]
+    pub ty: Vec<String>,
+    pub version: String,
+}
+
+/// A single key in the JSON keystore format of polkadot-js.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeystoreJson {
+    /// The Base64 encoded salt, scrypt parameters, nonce and encrypted key.
+    pub encoded: String,
+    pub encoding: KeystoreEncoding,
+    /// The SS58 address of the account.
+    pub address: String,
+    /// Arbitrary metadata, such as the `name` of the account.
+    #[serde(default)]
+    pub meta: Map<String, Value>,
+}
+
+impl KeystoreJson {
+    /// Encrypts the key pair with the password. The address of the account is
+    /// encoded with the given format.
+    pub fn encrypt(
+        keypair: &MultiKeyPair,
+        password: &str,
+        format: Ss58AddressFormat,
+        name: Option<&str>,
+    ) -> Self {
+        let mut rng = rand::thread_rng();
+        let mut salt = [0; SALT_LEN];
+        let mut nonce = [0; crypto::NONCE_LEN];
+        rng.fill_bytes(&mut salt);
+        rng.fill_bytes(&mut nonce);
+
+        Self::encrypt_with(
+            keypair,
+            password,
+            format,
+            name,
+            ScryptParams::DEFAULT,
+            salt,
+            nonce,
+        )
+    }
+    fn encrypt_with(
+        keypair: &MultiKeyPair,
+        password: &str,
+        format: Ss58AddressFormat,
+        name: Option<&str>,
+        params: ScryptParams,
+        salt: [u8; SALT_LEN],
+        nonce: [u8; crypto::NONCE_LEN],
+    ) -> Self {
+        let key = password_key(password, &salt, params);
+
+        let mut encoded = salt.to_vec();
+        for param in &[params.n, params.p, params.r] {
+            encoded.extend_from_slice(&param.to_le_bytes());
+        }
+        encoded.extend_from_slice(&nonce);
+        encoded.extend(crypto::secretbox_seal(&key, &nonce, &encode_pkcs8(keypair)));
+
+        let mut meta = Map::new();
+        if let Some(name) = name {
+            meta.insert("name".to_string(), Value::String(name.to_string()));
+        }
+
+        KeystoreJson {
+            encoded: BASE64.encode(encoded),
+            encoding: KeystoreEncoding {
+                content: vec!["pkcs8".to_string(), key_type(keypair).to_string()],
+                ty: ENCRYPTION.iter().map(|ty| ty.to_string()).collect(),
+                version: VERSION.to_string(),
+            },
+            address: AccountId::from(keypair.clone()).to_ss58_address(format),
+            meta,
+        }
+    }
+    /// Parses the keystore from JSON.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let keystore: KeystoreJson = serde_json::from_str(json).map_err(KeystoreError::from)?;
+        // Fails early on invalid addresses.
+        keystore.account_id()?;
+        Ok(keystore)
+    }
+    /// Serializes the keystore to JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("keystore is always serializable")
+    }
+    /// The account of the key.
+    pub fn account_id(&self) -> Result<AccountId> {
+        AccountId::from_ss58_address(&self.address)
+    }
+    /// The name of the account, if any.
+    pub fn name(&self) -> Option<&str> {
+        self.meta.get("name").and_then(Value::as_str)
+    }
+    /// Decrypts the key pair with the password.
+    pub fn decrypt(&self, password: &str) -> Result<MultiKeyPair> {
+        let unsupported = || KeystoreError::UnsupportedEncoding(format!("{:?}", self.encoding));
+
+        if self.encoding.version != VERSION
+            || self.encoding.ty != ENCRYPTION
+            || self.encoding.content.first().map(String::as_str) != Some("pkcs8")
+        {
+            return Err(unsupported().into());
+        }
+
+        let encoded = BASE64
+            .decode(&self.encoded)
+            .map_err(|_| KeystoreError::InvalidEncoded("invalid Base64"))?;
+        if encoded.len() < SCRYPT_LEN + crypto::NONCE_LEN {
+            return Err(KeystoreError::InvalidEncoded("too short").into());
+        }
+
+        let (scrypt, encrypted) = encoded.split_at(SCRYPT_LEN);
+        let param = |i: usize| {
+            let offset = SALT_LEN + i * 4;
+            u32::from_le_bytes(scrypt[offset..offset + 4].try_into().unwrap())
+        };
+        let params = ScryptParams {
+            n: param(0),
+            p: param(1),
+            r: param(2),
+        };
+        if !params.is_valid() {
+            return Err(KeystoreError::InvalidEncoded("invalid scrypt parameters").into());
+        }
+
+        let key = password_key(password, &scrypt[..SALT_LEN], params);
+        let (nonce, sealed) = encrypted.split_at(crypto::NONCE_LEN);
+        let decrypted = crypto::secretbox_open(&key, nonce.try_into().unwrap(), sealed)
+            .ok_or(KeystoreError::InvalidPassword)?;
+
+        let key_type = self.encoding.content.get(1).map(String::as_str);
+        let keypair = decode_pkcs8(&decrypted, key_type.ok_or_else(unsupported)?)?;
+
+        if AccountId::from(keypair.clone()) != self.account_id()? {
+            return Err(KeystoreError::AccountMismatch(self.address.clone()).into());
+        }
+
+        Ok(keypair)
+    }
+}
+
+/// Derives the encryption key from the password.
+fn password_key(password: &str, salt: &[u8], params: ScryptParams) -> [u8; 32] {
+    let key = crypto::scrypt(password.as_bytes(), salt, params, 64);
+    key[..32].try_into().unwrap()
+}
+
+/// Encodes the secret key in the PKCS#8 layout of polkadot-js.
+fn encode_pkcs8(keypair: &MultiKeyPair) -> Vec<u8> {
+    let (secret, public) = match keypair {
+        // The seed followed by the public key, like NaCl.
+        MultiKeyPair::Ed25519(pair) => {
+            let public = pair.public().0.to_vec();
+            ([&pair.seed()[..], &public].concat(), public)
+        }
+        // The secret key in the Ed25519 compatible encoding of schnorrkel.
+        MultiKeyPair::Sr25519(pair) => {
+            let keypair: &schnorrkel::Keypair = pair.as_ref();
+            (
+                keypair.secret.to_ed25519_bytes().to_vec(),
+                pair.public().0.to_vec(),
+            )
+        }
+        MultiKeyPair::Ecdsa(pair) => (pair.seed().to_vec(), pair.public().as_ref().to_vec()),
+    };
+
+    [&PKCS8_HEADER[..], &secret, &PKCS8_DIVIDER, &public].concat()
+}
+
+/// Decodes the secret key of the given type from the PKCS#8 layout.
+fn decode_pkcs8(decrypted: &[u8], key_type: &str) -> Result<MultiKeyPair> {
+    let invalid = |reason| Error::from(KeystoreError::InvalidEncoded(reason));
+
+    let secret_len = match key_type {
+        "ed25519" | "sr25519" => 64,
+        "ecdsa" => 32,
+        _ => return Err(KeystoreError::UnsupportedEncoding(key_type.to_string()).into()),
+    };
+
+    let body = decrypted
+        .strip_prefix(&PKCS8_HEADER[..])
+        .ok_or_else(|| invalid("invalid PKCS#8 header"))?;
+    if body.len() < secret_len || !body[secret_len..].starts_with(&PKCS8_DIVIDER) {
+        return Err(invalid("invalid PKCS#8 divider"));
+    }
+
+    let secret = &body[..secret_len];
+    let keypair = match key_type {
+        "ed25519" => Ed25519::from_seed_slice(&secret[..32])
+            .map_err(|_| invalid("invalid secret key"))?
+            .into(),
+        "sr25519" => {
+            let secret = schnorrkel::SecretKey::from_ed25519_bytes(secret)
+                .map_err(|_| invalid("invalid secret key"))?;
+            Sr25519::from(secret.to_keypair()).into()
+        }
+        _ => Ecdsa::from_seed_slice(secret)
+            .map_err(|_| invalid("invalid secret key"))?
+            .into(),
+    };
+
+    Ok(keypair)
+}
+
+/// A collection of encrypted keys. See the [module documentation](self).
+#[derive(Debug, Clone, Default)]
+pub struct Keystore {
+    keys: Vec<(AccountId, KeystoreJson)>,
+}
+
+impl Keystore {
+    /// Creates an empty keystore.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Loads all `*.json` files of the directory.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let mut keystore = Self::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                keystore.import(&std::fs::read_to_string(path)?)?;
+            }
+        }
+
+        Ok(keystore)
+    }
+    /// Saves every key to a file named after its address in the directory.
+    pub fn save<P: AsRef<Path>>(&self, dir: P) -> Result<()> {
+        for (_, json) in &self.keys {
+            let path = dir.as_ref().join(format!("{}.json", json.address));
+            std::fs::write(path, json.to_json())?;
+        }
+
+        Ok(())
+    }
+    /// Imports a key in the JSON keystore format, replacing any key of the
+    /// same account. The key is not decrypted.
+    pub fn import(&mut self, json: &str) -> Result<AccountId> {
+        let json = KeystoreJson::from_json(json)?;
+        let account = json.account_id()?;
+
+        self.keys.retain(|(existing, _)| *existing != account);
+        self.keys.push((account, json));
+        Ok(account)
+    }
+    /// Encrypts the key pair with the password and adds it to the keystore,
+    /// see [`KeystoreJson::encrypt`].
+    pub fn insert(
+        &mut self,
+        keypair: &MultiKeyPair,
+        password: &str,
+        format: Ss58AddressFormat,
+        name: Option<&str>,
+    ) -> Result<AccountId> {
+        let json = KeystoreJson::encrypt(keypair, password, format, name);
+        self.import(&json.to_json())
+    }
+    /// Exports the key of the account in the JSON keystore format.
+    pub fn export(&self, account: &AccountId) -> Option<String> {
+        self.get(account).map(KeystoreJson::to_json)
+    }
+    /// Removes the key of the account.
+    pub fn remove(&mut self, account: &AccountId) -> Option<KeystoreJson> {
+        let pos = self
+            .keys
+            .iter()
+            .position(|(existing, _)| existing == account)?;
+        Some(self.keys.remove(pos).1)
+    }
+    /// The encrypted key of the account.
+    pub fn get(&self, account: &AccountId) -> Option<&KeystoreJson> {
+        self.keys
+            .iter()
+            .find(|(existing, _)| existing == account)
+            .map(|(_, json)| json)
+    }
+    /// The accounts of the keystore, in the order of insertion.
+    pub fn accounts(&self) -> Vec<AccountId> {
+        self.keys.iter().map(|(account, _)| *account).collect()
+    }
+    /// Decrypts the key pair of the account with the password, e.g. for the
+    /// [`SignedTransactionBuilder`](crate::transaction::SignedTransactionBuilder).
+    pub fn key_pair(&self, account: &AccountId, password: &str) -> Result<MultiKeyPair> {
+        self.get(account)
+            .ok_or(KeystoreError::UnknownAccount(*account))?
+            .decrypt(password)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::KeyPairBuilder;
+
+    /// Cheap parameters, the defaults take a while in debug builds.
+    const TEST_PARAMS: ScryptParams = ScryptParams { n: 16, p: 1, r: 8 };
+
+    fn encrypt(keypair: &MultiKeyPair, password: &str) -> KeystoreJson {
+        KeystoreJson::encrypt_with(
+            keypair,
+            password,
+            Ss58AddressFormat::SubstrateAccount,
+            Some("test"),
+            TEST_PARAMS,
+            [1; SALT_LEN],
+            [2; crypto::NONCE_LEN],
+        )
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let keypairs: Vec<MultiKeyPair> = vec![
+            KeyPairBuilder::<Ed25519>::generate().0.into(),
+            KeyPairBuilder::<Sr25519>::generate().0.into(),
+            KeyPairBuilder::<Ecdsa>::generate().0.into(),
+        ];
+
+        for keypair in keypairs {
+            let json = encrypt(&keypair, "password");
+            assert_eq!(json.encoding.content[1], key_type(&keypair));
+            assert_eq!(json.name(), Some("test"));
+
+            let json = KeystoreJson::from_json(&json.to_json()).unwrap();
+            let decrypted = json.decrypt("password").unwrap();
+
+            let account = AccountId::from(keypair.clone());
+            assert_eq!(json.account_id().unwrap(), account);
+            assert_eq!(AccountId::from(decrypted.clone()), account);
+            assert_eq!(encode_pkcs8(&decrypted), encode_pkcs8(&keypair));
+
+            assert!(matches!(
+                json.decrypt("wrong"),
+                Err(Error::Keystore(KeystoreError::InvalidPassword))
+            ));
+        }
+    }
+
+    #[test]
+    fn encoded_layout() {
+        let keypair = MultiKeyPair::from(KeyPairBuilder::<Ed25519>::from_seed(&[3; 32]));
+        let json = encrypt(&keypair, "password");
+
+        let encoded = BASE64.decode(&json.encoded).unwrap();
+        assert_eq!(&encoded[..SALT_LEN], &[1; SALT_LEN]);
+        // N, p and r as little-endian `u32`.
+        assert_eq!(
+            &encoded[SALT_LEN..SCRYPT_LEN],
+            &[16, 0, 0, 0, 1, 0, 0, 0, 8, 0, 0, 0]
+        );
+        assert_eq!(&encoded[SCRYPT_LEN..SCRYPT_LEN + 24], &[2; 24]);
+
+        // Header, seed and public key, divider, public key and the 16 bytes
+        // of the Poly1305 tag.
+        let pkcs8_len = 16 + 64 + 5 + 32;
+        assert_eq!(encoded.len(), SCRYPT_LEN + 24 + 16 + pkcs8_len);
+
+        let value: Value = serde_json::from_str(&json.to_json()).unwrap();
+        assert_eq!(
+            value["encoding"],
+            serde_json::json!({
+                "content": ["pkcs8", "ed25519"],
+                "type": ["scrypt", "xsalsa20-poly1305"],
+                "version": "3",
+            })
+        );
+    }
+
+    #[test]
+    fn reject_invalid_keystores() {
+        let keypair = MultiKeyPair::from(KeyPairBuilder::<Sr25519>::from_seed(&[4; 32]));
+        let json = encrypt(&keypair, "password");
+
+        let mut unsupported = json.clone();
+        unsupported.encoding.ty = vec!["none".to_string()];
+        assert!(matches!(
+            unsupported.decrypt("password"),
+            Err(Error::Keystore(KeystoreError::UnsupportedEncoding(_)))
+        ));
+
+        let mut truncated = json.clone();
+        truncated.encoded = BASE64.encode([0; 10]);
+        assert!(matches!(
+            truncated.decrypt("password"),
+            Err(Error::Keystore(KeystoreError::InvalidEncoded(_)))
+        ));
+
+        // Parameters which would allocate 128 GiB.
+        let mut expensive = BASE64.decode(&json.encoded).unwrap();
+        expensive[SALT_LEN..SALT_LEN + 4].copy_from_slice(&(1u32 << 30).to_le_bytes());
+        let mut expensive_json = json.clone();
+        expensive_json.encoded = BASE64.encode(expensive);
+        assert!(matches!(
+            expensive_json.decrypt("password"),
+            Err(Error::Keystore(KeystoreError::InvalidEncoded(_)))
+        ));
+
+        let mut other_account = json;
+        other_account.address =
+            AccountId::new([5; 32]).to_ss58_address(Ss58AddressFormat::SubstrateAccount);
+        assert!(matches!(
+            other_account.decrypt("password"),
+            Err(Error::Keystore(KeystoreError::AccountMismatch(_)))
+        ));
+
+        assert!(matches!(
+            KeystoreJson::from_json("{}"),
+            Err(Error::Keystore(KeystoreError::InvalidJson(_)))
+        ));
+    }
+
+    #[test]
+    fn manage_keys() {
+        let first = MultiKeyPair::from(KeyPairBuilder::<Sr25519>::from_seed(&[6; 32]));
+        let second = MultiKeyPair::from(KeyPairBuilder::<Ed25519>::from_seed(&[7; 32]));
+
+        let mut keystore = Keystore::new();
+        let first_account = keystore.import(&encrypt(&first, "one").to_json()).unwrap();
+        let second_account = keystore.import(&encrypt(&second, "two").to_json()).unwrap();
+        // Replaces the key of the same account.
+        keystore
+            .import(&encrypt(&first, "three").to_json())
+            .unwrap();
+
+        assert_eq!(keystore.accounts(), vec![second_account, first_account]);
+        assert!(matches!(
+            keystore.key_pair(&first_account, "one"),
+            Err(Error::Keystore(KeystoreError::InvalidPassword))
+        ));
+        assert!(matches!(
+            keystore.key_pair(&AccountId::new([0; 32]), "one"),
+            Err(Error::Keystore(KeystoreError::UnknownAccount(_)))
+        ));
+        let keypair = keystore.key_pair(&first_account, "three").unwrap();
+        assert_eq!(AccountId::from(keypair), first_account);
+
+        let dir = std::env::temp_dir().join(format!("gekko-keystore-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        keystore.save(&dir).unwrap();
+
+        let mut loaded = Keystore::open(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let mut accounts = loaded.accounts();
+        accounts.sort_by_key(|account| account.to_bytes());
+        let mut expected = keystore.accounts();
+        expected.sort_by_key(|account| account.to_bytes());
+        assert_eq!(accounts, expected);
+
+        assert!(loaded.remove(&second_account).is_some());
+        assert_eq!(loaded.accounts(), vec![first_account]);
+        assert_eq!(loaded.export(&second_account), None);
+    }
+}
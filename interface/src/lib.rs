@@ -14,6 +14,32 @@
 //!   based on the the parsed substrate metadata.
 //!   * Can also be enabled in `gekko` with the `"generator"` feature.
 //!
+//! Spans around metadata parsing, transaction building and RPC calls can be
+//! emitted with the [`tracing`](https://docs.rs/tracing) crate by enabling the
+//! `"tracing"` feature.
+//!
+//! The `"ws"` feature adds a WebSocket client supporting JSON-RPC
+//! subscriptions, such as streams of the decoded events of new blocks. The
+//! `"async"` feature adds asynchronous helpers, which can be awaited on any
+//! executor.
+//!
+//! The `"dev-node"` feature adds a harness for end-to-end tests, which spawns or
+//! connects to a development node, submits transactions signed by the
+//! development accounts and returns the events they emitted.
+//!
+//! The `"keystore"` feature adds the [`keystore`] module, which imports and
+//! exports keys in the encrypted JSON format of polkadot-js.
+//!
+//! The `"uos"` feature adds the [`uos`] module, which encodes transactions
+//! for signing on air-gapped devices running Polkadot Vault.
+//!
+//! The `"dumps"` feature embeds the bundled Kusama and Polkadot metadata dumps
+//! into the binary, accessible as parsed metadata via the `dumps` module.
+//!
+//! Metadata, transactions and common types implement `Serialize` and
+//! `Deserialize` with the `"serde"` feature. Byte arrays are represented as
+//! `0x`-prefixed HEX strings.
+//!
 //! # Interacting with the runtime
 //!
 //! Gekko exposes multiple interfaces to interact with Kusama/Polkadot, such as
@@ -41,6 +67,8 @@
 //! ###  Example
 //!
 //! ```
+//! # #[cfg(feature = "runtime-polkadot")]
+//! # {
 //! use gekko::common::*;
 //! use gekko::transaction::*;
 //! use gekko::runtime::polkadot::extrinsics::balances::TransferKeepAlive;
@@ -56,23 +84,24 @@
 //!
 //! // Send 50 DOT to the destination.
 //! let call = TransferKeepAlive {
-//!     dest: destination,
+//!     dest: MultiAddress::from(destination),
 //!     value: currency.balance(50),
 //! };
 //!
-//! // Transaction fee.
-//! let payment = currency.balance_as_metric(Metric::Milli, 10).unwrap();
+//! // Tip for the block author, in addition to the transaction fee.
+//! let tip = currency.balance_as_metric(Metric::Milli, 10).unwrap();
 //!
 //! // Build the final transaction.
 //! let transaction: PolkadotSignedExtrinsic<_> = SignedTransactionBuilder::new()
 //!     .signer(keypair)
 //!     .call(call)
 //!     .nonce(0)
-//!     .payment(payment)
+//!     .tip(tip)
 //!     .network(Network::Polkadot)
 //!     .spec_version(9050)
 //!     .build()
 //!     .unwrap();
+//! # }
 //! ```
 //!
 //! # Parsing Metadata
@@ -83,11 +112,13 @@
 //! ## Example
 //!
 //! ```no_run
+//! # #[cfg(feature = "metadata")]
+//! # {
 //! use gekko::metadata::*;
 //!
 //! // Parse runtime metadata
 //! let content = std::fs::read_to_string("metadata_kusama_9080.hex").unwrap();
-//! let data = parse_hex_metadata(content).unwrap().into_inner();
+//! let data = parse_hex_metadata(content).unwrap().into_inner().unwrap();
 //!
 //! // Get information about the extrinsic.
 //! let extr = data
@@ -103,18 +134,18 @@
 //!         ("value", "Compact<T::Balance>"),
 //!     ]
 //! );
+//! # }
 //! ```
 //!
 //! A macro available in `gekko::generator` will parse the metadata
 //! automatically for you and generate the Rust interfaces at compile time.
 
-pub use runtime::*;
+// Allows the derive macros of `gekko-generator` to be used within this crate.
+extern crate self as gekko;
 
-#[cfg(feature = "dumps")]
-/// Raw Kusama and Polkadot runtime metadata dumps.
-pub mod dumps {
-    pub use gekko_metadata::*;
-}
+// Empty if all `runtime-*` features are disabled.
+#[allow(unused_imports)]
+pub use runtime::*;
 
 #[cfg(feature = "generator")]
 /// Substrate runtime metadata generator for creating Rust interfaces.
@@ -128,44 +159,119 @@ pub mod metadata {
     pub use gekko_metadata::*;
 }
 
+#[cfg(feature = "dumps")]
+pub mod dumps;
+#[cfg(feature = "keystore")]
+pub mod keystore;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+pub mod runtime;
 pub mod transaction;
+#[cfg(feature = "uos")]
+pub mod uos;
 // TODO: Rename to "primitives"?
 pub mod common;
 
-/// Types and interfaces to interact with runtimes.
-pub mod runtime {
-    pub mod polkadot {
-        pub use latest::*;
-
-        /// The latest runtime types and interfaces.
-        mod latest {
-            /// The latest spec version.
-            pub const SPEC_VERSION: u32 = 9050;
-
-            #[gekko_generator::parse_from_hex_file("dumps/metadata_polkadot_9050.hex")]
-            struct A;
-        }
-    }
-
-    pub mod kusama {
-        pub use latest::*;
-
-        /// The latest runtime types and interfaces.
-        mod latest {
-            /// The latest spec version.
-            pub const SPEC_VERSION: u32 = 9080;
-
-            #[gekko_generator::parse_from_hex_file("dumps/metadata_kusama_9080.hex")]
-            struct A;
-        }
-    }
-}
-
 pub type Result<T> = std::result::Result<T, Error>;
 
-#[derive(Debug, Clone)]
+/// Errors that can occur when building transactions or interacting with a
+/// node.
+#[derive(Debug, thiserror::Error)]
 pub enum Error {
+    #[error("missing field in builder: {0}")]
     BuilderMissingField(&'static str),
+    #[error("invalid SS58 address: {0}")]
+    InvalidSs58Address(#[from] common::Ss58Error),
+    /// The JSON-RPC request could not be sent or the response could not be
+    /// received.
+    #[error("JSON-RPC transport failed")]
+    RpcTransport(#[source] Box<dyn std::error::Error + Send + Sync>),
+    /// The node responded with a JSON-RPC error.
+    #[error("JSON-RPC error {code}: {message}")]
+    RpcResponse { code: i64, message: String },
+    /// The node responded with an unexpected result.
+    #[error("invalid JSON-RPC response: {0}")]
+    RpcInvalidResponse(String),
+    #[error("failed to decode SCALE value")]
+    Decode(#[from] parity_scale_codec::Error),
+    #[error("storage entry {module}::{entry} not found in metadata")]
+    StorageEntryNotFound { module: String, entry: String },
+    /// The genesis hash of the node does not match the configured network.
+    #[error("node is connected to a different network, genesis hash 0x{}", hex::encode(.0))]
+    NetworkMismatch([u8; 32]),
+    /// The balance exceeds the range of the balance type of the chain, see
+    /// [`Config::Balance`](common::Config::Balance).
+    #[error("balance {0} exceeds the balance type of the chain")]
+    BalanceOverflow(u128),
+    /// The base unit of the [`Currency`](common::Currency) is zero or does
+    /// not fit into `u128`.
+    #[error("invalid base unit of currency")]
+    InvalidBaseUnit,
+    /// The balance could not be parsed or cannot be represented in the base
+    /// unit of the currency.
+    #[error("invalid balance: {0}")]
+    InvalidBalance(String),
+    #[error("I/O operation failed")]
+    Io(#[from] std::io::Error),
+    /// The transaction was signed for another runtime version than the one of
+    /// the node.
+    #[error(
+        "transaction was signed for spec version {spec_version} and transaction version \
+         {transaction_version}, but the node runs {node_spec_version} and {node_transaction_version}"
+    )]
+    RuntimeVersionMismatch {
+        spec_version: u32,
+        transaction_version: u32,
+        node_spec_version: u32,
+        node_transaction_version: u32,
+    },
+    /// The transaction was signed by another account than the signer.
+    #[error("transaction was signed by another address: {0:?}")]
+    SignerMismatch(common::MultiAddress),
+    /// The [`ExternalSigner`](transaction::ExternalSigner) failed to sign the
+    /// transaction.
+    #[error("external signer failed")]
+    ExternalSigner(#[source] Box<dyn std::error::Error + Send + Sync>),
+    /// The signature does not match the signature payload and the account of
+    /// the signer.
+    #[error("invalid signature of signer {0:?}")]
+    InvalidSignature(common::AccountId),
+    /// The extrinsic was included in a block, but its dispatch failed.
+    #[error("extrinsic {index} of block 0x{} failed", hex::encode(.block_hash))]
+    ExtrinsicFailed { block_hash: [u8; 32], index: u32 },
+    /// The call does not match the call of the same index in the metadata.
+    #[cfg(feature = "metadata")]
+    #[error("call {module}::{call} does not match the metadata: {reason}")]
+    CallMismatch {
+        module: String,
+        call: String,
+        reason: String,
+    },
+    /// The signed extensions of the chain do not match the signed extensions
+    /// encoded by the [`Config`](common::Config), e.g. after a runtime upgrade
+    /// added an extension. Transactions would fail with an invalid signature.
+    #[cfg(feature = "metadata")]
+    #[error("signed extensions {encoded:?} do not match the signed extensions of the chain {expected:?}")]
+    SignedExtensionMismatch {
+        expected: Vec<String>,
+        encoded: Vec<String>,
+    },
+    /// The runtime has no call with the given name.
+    #[error("call {module}::{call} not found in the runtime")]
+    UnknownCall { module: String, call: String },
+    /// The signed extension with the given identifier has no implementation,
+    /// see [`SignedExtensions::with`](transaction::SignedExtensions::with).
+    #[error("unknown signed extension {0}")]
+    UnknownSignedExtension(String),
+    #[cfg(feature = "metadata")]
+    #[error("failed to process metadata")]
+    Metadata(#[from] gekko_metadata::Error),
+    #[cfg(feature = "keystore")]
+    #[error("keystore error")]
+    Keystore(#[from] keystore::KeystoreError),
+    #[cfg(feature = "uos")]
+    #[error("UOS error")]
+    Uos(#[from] uos::UosError),
 }
 
 /// Convenience function for crate internals.
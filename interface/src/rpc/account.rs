@@ -0,0 +1,231 @@
+//! Fetching the nonce and balances of an account, as stored in
+//! `System::Account`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use gekko::common::{AccountId, Currency, Metric};
+//! use gekko::rpc::account::account_info;
+//! use gekko::rpc::HttpClient;
+//!
+//! let client = HttpClient::new("https://rpc.polkadot.io");
+//! let account =
+//!     AccountId::from_ss58_address("12eDex4amEwj39T7Wz4Rkppb68YGCDYKG9QHhEhHGtNdDy7D")
+//!         .unwrap();
+//!
+//! let info = account_info(&client, &account, Currency::Polkadot, None).unwrap();
+//! println!("Nonce: {}", info.nonce);
+//! println!("Free: {:?} DOT", info.free.as_metric(Metric::One));
+//! ```
+
+use super::storage::storage_raw;
+use super::Transport;
+use crate::common::{AccountId, Balance, BalanceBuilder, Currency};
+use crate::{Error, Result};
+use gekko_metadata::version::v13::StorageHasher;
+use gekko_metadata::StorageMetadataExt;
+use parity_scale_codec::Decode;
+
+/// The nonce, reference counters and balances of an account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountInfo {
+    /// The number of transactions the account has sent, i.e. the nonce of
+    /// the next transaction.
+    pub nonce: u32,
+    pub consumers: u32,
+    pub providers: u32,
+    /// Always zero on runtimes which do not track sufficient references.
+    pub sufficients: u32,
+    /// The balance which can be used for transfers and fees, unless frozen.
+    pub free: Balance,
+    /// The balance which is reserved, e.g. for deposits.
+    pub reserved: Balance,
+    /// The amount of the free balance which cannot be used for anything
+    /// except transaction fees.
+    pub misc_frozen: Balance,
+    /// The amount of the free balance which cannot be used for transaction
+    /// fees.
+    pub fee_frozen: Balance,
+}
+
+#[derive(Decode)]
+struct AccountData {
+    free: u128,
+    reserved: u128,
+    misc_frozen: u128,
+    fee_frozen: u128,
+}
+
+/// Computes the storage key of the account in `System::Account`.
+pub fn account_key(account: &AccountId) -> Vec<u8> {
+    [
+        StorageHasher::Twox128.hash(b"System"),
+        StorageHasher::Twox128.hash(b"Account"),
+        StorageHasher::Blake2_128Concat.hash(&account.to_bytes()),
+    ]
+    .concat()
+}
+
+/// Fetches the account info at the given block, or at the best block if `at`
+/// is `None`. Accounts which do not exist have a nonce and balances of zero.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(transport, currency), err)
+)]
+pub fn account_info<T: Transport>(
+    transport: &T,
+    account: &AccountId,
+    currency: Currency,
+    at: Option<[u8; 32]>,
+) -> Result<AccountInfo> {
+    let value = storage_raw(transport, &account_key(account), at)?;
+    decode_account_info(value.as_deref(), currency)
+}
+
+/// Like [`account_info`], but computes the storage key with the hashers of
+/// `System::Account` as declared by the metadata, instead of assuming the
+/// hashers of the Polkadot runtimes.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(transport, metadata, currency), err)
+)]
+pub fn account_info_with_metadata<T: Transport, M: StorageMetadataExt>(
+    transport: &T,
+    metadata: &M,
+    account: &AccountId,
+    currency: Currency,
+    at: Option<[u8; 32]>,
+) -> Result<AccountInfo> {
+    let key = metadata
+        .find_module_storage("System", "Account")
+        .ok_or_else(|| Error::StorageEntryNotFound {
+            module: "System".to_string(),
+            entry: "Account".to_string(),
+        })?
+        .storage_key(&[&account.to_bytes()])?;
+
+    let value = storage_raw(transport, &key, at)?;
+    decode_account_info(value.as_deref(), currency)
+}
+
+/// Fetches the nonce of the next transaction of the account at the given
+/// block, or at the best block if `at` is `None`. Unlike
+/// [`account_next_index`](super::account_next_index), this ignores the
+/// transactions in the transaction pool of the node.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(transport), err)
+)]
+pub fn account_nonce<T: Transport>(
+    transport: &T,
+    account: &AccountId,
+    at: Option<[u8; 32]>,
+) -> Result<u32> {
+    // The currency is irrelevant for the nonce.
+    account_info(transport, account, Currency::Polkadot, at).map(|info| info.nonce)
+}
+
+/// Decodes the value of `System::Account`, or the default value if `None`.
+/// Supports the layouts with and without the `sufficients` counter.
+pub fn decode_account_info(value: Option<&[u8]>, currency: Currency) -> Result<AccountInfo> {
+    let input = &mut value.unwrap_or(&[0; 80]);
+
+    let nonce = u32::decode(input)?;
+    let consumers = u32::decode(input)?;
+    let providers = u32::decode(input)?;
+    let sufficients = match input.len() {
+        68 => u32::decode(input)?,
+        64 => 0,
+        _ => {
+            return Err(Error::RpcInvalidResponse(format!(
+                "unsupported layout of account info with {} bytes",
+                value.map(<[u8]>::len).unwrap_or_default()
+            )))
+        }
+    };
+
+    let data = AccountData::decode(input)?;
+    let unit = BalanceBuilder::new(currency);
+
+    Ok(AccountInfo {
+        nonce,
+        consumers,
+        providers,
+        sufficients,
+        free: unit.balance_base_unit(data.free),
+        reserved: unit.balance_base_unit(data.reserved),
+        misc_frozen: unit.balance_base_unit(data.misc_frozen),
+        fee_frozen: unit.balance_base_unit(data.fee_frozen),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::mock::MockTransport;
+    use parity_scale_codec::Encode;
+
+    /// Serves the given account info of Alice.
+    fn mock_transport(value: Vec<u8>) -> MockTransport {
+        let key = hex::decode(
+            "26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da9\
+             de1e86a9a8c739864cf3cc5ec2bea59f\
+             d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d",
+        )
+        .unwrap();
+
+        MockTransport::new().with_storage(key, value)
+    }
+
+    fn alice() -> AccountId {
+        AccountId::from_ss58_address("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY").unwrap()
+    }
+
+    #[test]
+    fn fetch_account_info() {
+        let value = (5u32, 1u32, 1u32, 0u32, [1_000u128, 200, 50, 50]).encode();
+        let transport = mock_transport(value);
+
+        let info = account_info(&transport, &alice(), Currency::Kusama, None).unwrap();
+        assert_eq!(info.nonce, 5);
+        assert_eq!(info.providers, 1);
+        assert_eq!(info.free.as_base_unit(), 1_000);
+        assert_eq!(info.reserved.as_base_unit(), 200);
+        assert_eq!(info.fee_frozen.as_base_unit(), 50);
+
+        // Runtimes without sufficient references.
+        let value = (7u32, 0u32, 1u32, [1u128, 0, 0, 0]).encode();
+        let info = decode_account_info(Some(&value), Currency::Kusama).unwrap();
+        assert_eq!(info.nonce, 7);
+        assert_eq!(info.free.as_base_unit(), 1);
+    }
+
+    #[test]
+    fn fetch_account_info_with_metadata() {
+        let metadata = gekko_metadata::parse_hex_metadata(
+            include_str!("../../dumps/metadata_kusama_9080.hex").trim(),
+        )
+        .unwrap()
+        .into_latest()
+        .unwrap();
+
+        let value = (5u32, 1u32, 1u32, 0u32, [1_000u128, 200, 50, 50]).encode();
+        let transport = mock_transport(value);
+
+        let info =
+            account_info_with_metadata(&transport, &metadata, &alice(), Currency::Kusama, None)
+                .unwrap();
+        assert_eq!(info.nonce, 5);
+        assert_eq!(info.reserved.as_base_unit(), 200);
+        assert_eq!(account_nonce(&transport, &alice(), None).unwrap(), 5);
+    }
+
+    #[test]
+    fn fetch_missing_account() {
+        let transport = MockTransport::new();
+
+        let info = account_info(&transport, &alice(), Currency::Kusama, None).unwrap();
+        assert_eq!(info.nonce, 0);
+        assert_eq!(info.free.as_base_unit(), 0);
+    }
+}
@@ -0,0 +1,624 @@
+//! A batteries-included entry point, combining the JSON-RPC client, the
+//! runtime metadata and a signer.
+//!
+//! [`GekkoApi`] verifies that the node runs the configured network, keeps
+//! track of the runtime version and metadata and signs and submits calls with
+//! the correct nonce, `spec_version` and `transaction_version`. Transactions
+//! whose mortality expired or whose nonce was consumed can be
+//! [resubmitted](GekkoApi::resubmit). Optionally, the runtime version is
+//! [checked](GekkoApi::check_runtime_version) right before each submission.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # #[cfg(feature = "runtime-polkadot")]
+//! # {
+//! use gekko::common::*;
+//! use gekko::polkadot::extrinsics::balances::TransferKeepAlive;
+//! use gekko::rpc::api::GekkoApi;
+//!
+//! let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+//! let api = GekkoApi::connect("https://rpc.polkadot.io", Network::Polkadot)
+//!     .unwrap()
+//!     .signer(keypair)
+//!     .mortality_period(64);
+//!
+//! let destination =
+//!     AccountId::from_ss58_address("12eDex4amEwj39T7Wz4Rkppb68YGCDYKG9QHhEhHGtNdDy7D")
+//!         .unwrap();
+//!
+//! let tx_hash = api
+//!     .submit(TransferKeepAlive {
+//!         dest: MultiAddress::from(destination),
+//!         value: BalanceBuilder::new(Currency::Polkadot).balance(1),
+//!     })
+//!     .unwrap();
+//! # }
+//! ```
+
+use super::block::{block_hash, finalized_mortality};
+#[cfg(feature = "ws")]
+use super::watch::{submit_and_watch, StatusStream};
+use super::{
+    account_next_index, metadata, runtime_version, submit_extrinsic, submit_extrinsic_checked,
+    HttpClient, RuntimeVersion, Transport,
+};
+#[cfg(feature = "ws")]
+use super::{check_runtime_version, SubscriptionTransport};
+use crate::common::{AccountId, Mortality, MultiAddress, MultiKeyPair, Network};
+use crate::transaction::{PolkadotSignedExtrinsic, SignedTransactionBuilder};
+use crate::{Error, Result};
+use gekko_metadata::parse_raw_metadata;
+use gekko_metadata::MetadataVersion;
+use parity_scale_codec::Encode;
+use sp_core::crypto::Ss58AddressFormat;
+use std::sync::Mutex;
+
+/// Provides the nonces of the transactions of a signer.
+pub trait NonceProvider: Send + Sync {
+    /// Returns the nonce of the next transaction of the account.
+    fn next_nonce(&self, transport: &dyn Transport, account: &AccountId) -> Result<u32>;
+    /// Invalidates any locally tracked nonce, e.g. after a failed submission.
+    fn reset(&self) {}
+}
+
+/// Fetches the nonce from the node for every transaction, including the
+/// transactions in its transaction pool.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChainNonce;
+
+impl NonceProvider for ChainNonce {
+    fn next_nonce(&self, transport: &dyn Transport, account: &AccountId) -> Result<u32> {
+        account_next_index(
+            transport,
+            &account.to_ss58_address(Ss58AddressFormat::SubstrateAccount),
+        )
+    }
+}
+
+/// Fetches the nonce from the node once and increments it locally, allowing
+/// multiple transactions per block even with nodes that do not include the
+/// transaction pool.
+#[derive(Debug, Default)]
+pub struct LocalNonce {
+    next: Mutex<Option<u32>>,
+}
+
+impl NonceProvider for LocalNonce {
+    fn next_nonce(&self, transport: &dyn Transport, account: &AccountId) -> Result<u32> {
+        let mut next = self.next.lock().unwrap();
+        let nonce = match *next {
+            Some(nonce) => nonce,
+            None => ChainNonce.next_nonce(transport, account)?,
+        };
+
+        *next = Some(nonce + 1);
+        Ok(nonce)
+    }
+    fn reset(&self) {
+        *self.next.lock().unwrap() = None;
+    }
+}
+
+/// A high-level client of a node. See the [module documentation](self).
+pub struct GekkoApi<T: Transport = HttpClient> {
+    transport: T,
+    network: Network,
+    runtime_version: RuntimeVersion,
+    metadata: MetadataVersion,
+    signer: Option<MultiKeyPair>,
+    nonces: Box<dyn NonceProvider>,
+    tip: u128,
+    mortality_period: Option<u64>,
+    check_runtime_version: bool,
+}
+
+impl GekkoApi<HttpClient> {
+    /// Connects to the endpoint over HTTP(S). See [`new`](Self::new).
+    pub fn connect(url: &str, network: Network) -> Result<Self> {
+        Self::new(HttpClient::new(url), network)
+    }
+}
+
+impl<T: Transport> GekkoApi<T> {
+    /// Creates the client, verifying that the node runs the given network and
+    /// fetching the current runtime version and metadata.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(transport, network), err)
+    )]
+    pub fn new(transport: T, network: Network) -> Result<Self> {
+        let genesis = block_hash(&transport, Some(0))?
+            .ok_or_else(|| Error::RpcInvalidResponse("missing genesis block".to_string()))?;
+        if genesis != network.genesis() {
+            return Err(Error::NetworkMismatch(genesis));
+        }
+
+        let runtime_version = runtime_version(&transport, None)?;
+        let metadata = parse_raw_metadata(metadata(&transport, None)?)?;
+
+        Ok(GekkoApi {
+            transport,
+            network,
+            runtime_version,
+            metadata,
+            signer: None,
+            nonces: Box::new(LocalNonce::default()),
+            tip: 0,
+            mortality_period: None,
+            check_runtime_version: false,
+        })
+    }
+    /// Sets the signer of submitted transactions.
+    pub fn signer<S: Into<MultiKeyPair>>(self, signer: S) -> Self {
+        Self {
+            signer: Some(signer.into()),
+            ..self
+        }
+    }
+    /// Sets the provider of nonces. Uses [`LocalNonce`] by default.
+    pub fn nonce_provider<N: NonceProvider + 'static>(self, nonces: N) -> Self {
+        Self {
+            nonces: Box::new(nonces),
+            ..self
+        }
+    }
+    /// Sets the tip of submitted transactions, in the base unit of the
+    /// runtime. Zero by default.
+    pub fn tip(self, tip: u128) -> Self {
+        Self { tip, ..self }
+    }
+    /// Makes submitted transactions mortal, valid for the given number of
+    /// blocks after the last finalized block. Transactions are immortal by
+    /// default.
+    pub fn mortality_period(self, period: u64) -> Self {
+        Self {
+            mortality_period: Some(period),
+            ..self
+        }
+    }
+    /// Fetches the runtime version right before each submission and refuses
+    /// to submit transactions signed for an outdated runtime version with
+    /// [`Error::RuntimeVersionMismatch`], in which case the client should be
+    /// [refreshed](Self::refresh). Costs an additional request per
+    /// submission. Disabled by default.
+    pub fn check_runtime_version(self, check: bool) -> Self {
+        Self {
+            check_runtime_version: check,
+            ..self
+        }
+    }
+    /// The underlying transport.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+    /// The runtime version, as of the creation or last
+    /// [`refresh`](Self::refresh).
+    pub fn runtime_version(&self) -> &RuntimeVersion {
+        &self.runtime_version
+    }
+    /// The metadata, as of the creation or last [`refresh`](Self::refresh).
+    pub fn metadata(&self) -> &MetadataVersion {
+        &self.metadata
+    }
+    /// The account of the signer, if set.
+    pub fn account(&self) -> Option<AccountId> {
+        self.signer.clone().map(AccountId::from)
+    }
+    /// Fetches the runtime version and, if the runtime was upgraded, the new
+    /// metadata. Returns whether the runtime was upgraded.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), err)
+    )]
+    pub fn refresh(&mut self) -> Result<bool> {
+        let runtime_version = runtime_version(&self.transport, None)?;
+        if runtime_version == self.runtime_version {
+            return Ok(false);
+        }
+
+        self.metadata = parse_raw_metadata(metadata(&self.transport, None)?)?;
+        self.runtime_version = runtime_version;
+        Ok(true)
+    }
+    /// Signs the call with the next nonce of the signer, without submitting
+    /// it.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, call), err)
+    )]
+    pub fn sign<C: Encode>(&self, call: C) -> Result<PolkadotSignedExtrinsic<C>> {
+        let signer = self
+            .signer
+            .clone()
+            .ok_or(Error::BuilderMissingField("signer"))?;
+
+        let mortality = self.mortality(self.mortality_period)?;
+        let nonce = self
+            .nonces
+            .next_nonce(&self.transport, &signer.clone().into())?;
+
+        SignedTransactionBuilder::new()
+            .signer(signer)
+            .call(call)
+            .nonce(nonce)
+            .tip_base_unit(self.tip)
+            .network(self.network)
+            .mortality(mortality)
+            .spec_version(self.runtime_version.spec_version)
+            .transaction_version(self.runtime_version.transaction_version)
+            .validate_signed_extensions(&self.metadata)?
+            .build()
+    }
+    /// Signs and submits the call, returning the hash of the transaction.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, call), err)
+    )]
+    pub fn submit<C: Encode>(&self, call: C) -> Result<[u8; 32]> {
+        let extrinsic = self.sign(call)?.encode();
+        let result = if self.check_runtime_version {
+            submit_extrinsic_checked(
+                &self.transport,
+                &extrinsic,
+                self.runtime_version.spec_version,
+                self.runtime_version.transaction_version,
+            )
+        } else {
+            submit_extrinsic(&self.transport, &extrinsic)
+        };
+
+        result.inspect_err(|_| {
+            // The nonce was not used.
+            self.nonces.reset();
+        })
+    }
+    /// Re-signs and submits a previously built transaction, e.g. because its
+    /// mortality expired or its nonce was consumed by another transaction.
+    /// Returns the hash of the new transaction.
+    ///
+    /// The call, the tip and the mortality period are kept, while the nonce,
+    /// the birth block and the runtime version are fetched from the node. If
+    /// the runtime was upgraded in the meantime, the metadata is
+    /// [refreshed](Self::refresh) before the transaction is validated against
+    /// it. The transaction must have been signed by the [signer](Self::signer).
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, extrinsic), err)
+    )]
+    pub fn resubmit<C: Encode>(
+        &mut self,
+        extrinsic: PolkadotSignedExtrinsic<C>,
+    ) -> Result<[u8; 32]> {
+        let signer = self
+            .signer
+            .clone()
+            .ok_or(Error::BuilderMissingField("signer"))?;
+
+        let (address, _, payload) = extrinsic
+            .signature
+            .ok_or(Error::BuilderMissingField("signature"))?;
+        let account = AccountId::from(signer.clone());
+        if address != MultiAddress::from(account) {
+            return Err(Error::SignerMismatch(address));
+        }
+
+        let mortality = self.mortality(match payload.mortality {
+            Mortality::Mortal(period, _, _) => Some(period),
+            Mortality::Immortal => None,
+        })?;
+        self.refresh()?;
+
+        // The tracked nonce is outdated if the transaction was not included.
+        self.nonces.reset();
+        let nonce = self.nonces.next_nonce(&self.transport, &account)?;
+
+        let extrinsic = SignedTransactionBuilder::new()
+            .signer(signer)
+            .call(extrinsic.call)
+            .nonce(nonce)
+            .tip_base_unit(payload.tip)
+            .network(self.network)
+            .mortality(mortality)
+            .spec_version(self.runtime_version.spec_version)
+            .transaction_version(self.runtime_version.transaction_version)
+            .validate_signed_extensions(&self.metadata)?
+            .build()?;
+
+        submit_extrinsic(&self.transport, &extrinsic.encode()).inspect_err(|_| {
+            self.nonces.reset();
+        })
+    }
+    /// A mortality of the given period, starting at the last finalized block,
+    /// or an immortal one if `None`.
+    fn mortality(&self, period: Option<u64>) -> Result<Mortality> {
+        let period = match period {
+            Some(period) => period,
+            None => return Ok(Mortality::Immortal),
+        };
+
+        finalized_mortality(&self.transport, period)
+    }
+}
+
+#[cfg(feature = "ws")]
+impl<T: SubscriptionTransport> GekkoApi<T> {
+    /// Signs and submits the call like [`submit`](Self::submit), but watches
+    /// the status of the transaction in the transaction pool. Requires the
+    /// `"ws"` feature.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, call), err)
+    )]
+    pub fn submit_and_watch<C: Encode>(&self, call: C) -> Result<StatusStream> {
+        let extrinsic = self.sign(call)?.encode();
+        let result = if self.check_runtime_version {
+            check_runtime_version(
+                &self.transport,
+                self.runtime_version.spec_version,
+                self.runtime_version.transaction_version,
+            )
+            .and_then(|_| submit_and_watch(&self.transport, &extrinsic))
+        } else {
+            submit_and_watch(&self.transport, &extrinsic)
+        };
+
+        result.inspect_err(|_| {
+            // The nonce was not used.
+            self.nonces.reset();
+        })
+    }
+}
+
+impl<T: Transport + std::fmt::Debug> std::fmt::Debug for GekkoApi<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GekkoApi")
+            .field("transport", &self.transport)
+            .field("network", &self.network)
+            .field("runtime_version", &self.runtime_version)
+            .field("account", &self.account())
+            .field("tip", &self.tip)
+            .field("mortality_period", &self.mortality_period)
+            .field("check_runtime_version", &self.check_runtime_version)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{KeyPairBuilder, OpaqueCall, Sr25519};
+    use crate::rpc::mock::MockTransport;
+    use parity_scale_codec::Decode;
+    use serde_json::json;
+
+    /// A Polkadot node with the 9050 runtime.
+    fn transport() -> MockTransport {
+        let raw = gekko_metadata::decode_hex(
+            include_str!("../../dumps/metadata_polkadot_9050.hex").trim(),
+        )
+        .unwrap();
+
+        MockTransport::new()
+            .with_genesis(Network::Polkadot.genesis())
+            .with_metadata(raw)
+            .with_runtime_version(RuntimeVersion {
+                spec_name: "polkadot".to_string(),
+                impl_name: "parity-polkadot".to_string(),
+                authoring_version: 0,
+                spec_version: 9050,
+                impl_version: 0,
+                transaction_version: 7,
+                apis: vec![],
+            })
+            .respond("system_accountNextIndex", json!(3))
+    }
+
+    #[test]
+    fn submit_with_local_nonces() {
+        let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+        let api = GekkoApi::new(transport(), Network::Polkadot)
+            .unwrap()
+            .signer(keypair);
+
+        assert_eq!(api.runtime_version().spec_version, 9050);
+        api.submit(OpaqueCall(vec![0, 1])).unwrap();
+        api.submit(OpaqueCall(vec![0, 1])).unwrap();
+
+        let nonces: Vec<u32> = api
+            .transport()
+            .submitted()
+            .iter()
+            .map(|extrinsic| {
+                PolkadotSignedExtrinsic::<OpaqueCall>::decode(&mut extrinsic.as_slice())
+                    .unwrap()
+                    .signature
+                    .unwrap()
+                    .2
+                    .nonce
+            })
+            .collect();
+
+        assert_eq!(nonces, vec![3, 4]);
+    }
+
+    #[test]
+    #[cfg(feature = "ws")]
+    fn submit_and_watch_status() {
+        use crate::rpc::watch::TransactionStatus;
+        use futures::executor::block_on_stream;
+
+        let block = format!("0x{}", hex::encode([1; 32]));
+        let transport = transport().with_notifications(
+            "author_submitAndWatchExtrinsic",
+            vec![json!("ready"), json!({ "finalized": block })],
+        );
+
+        let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+        let api = GekkoApi::new(transport, Network::Polkadot)
+            .unwrap()
+            .signer(keypair);
+
+        let statuses = block_on_stream(api.submit_and_watch(OpaqueCall(vec![0, 1])).unwrap())
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            statuses,
+            vec![
+                TransactionStatus::Ready,
+                TransactionStatus::Finalized([1; 32])
+            ]
+        );
+
+        let (method, params) = api.transport().requests().pop().unwrap();
+        assert_eq!(method, "author_submitAndWatchExtrinsic");
+
+        let extrinsic = crate::rpc::decode_hex_value(&params[0]).unwrap();
+        let decoded = PolkadotSignedExtrinsic::<OpaqueCall>::decode(&mut extrinsic.as_slice());
+        assert_eq!(decoded.unwrap().signature.unwrap().2.nonce, 3);
+    }
+
+    #[test]
+    fn resubmit_refreshes_nonce_and_mortality() {
+        use crate::rpc::block::Header;
+
+        let finalized = Header {
+            parent_hash: [1; 32],
+            number: 100,
+            state_root: [2; 32],
+            extrinsics_root: [3; 32],
+            digest_logs: vec![],
+        };
+        let transport = transport()
+            .respond(
+                "chain_getFinalizedHead",
+                json!(format!("0x{}", hex::encode(finalized.hash()))),
+            )
+            .with_block(finalized, vec![]);
+
+        let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+        let mut api = GekkoApi::new(transport, Network::Polkadot)
+            .unwrap()
+            .signer(keypair.clone());
+
+        // An expired transaction with a consumed nonce.
+        let expired = SignedTransactionBuilder::new()
+            .signer(keypair)
+            .call(OpaqueCall(vec![0, 1]))
+            .nonce(1)
+            .tip_base_unit(5)
+            .network(Network::Polkadot)
+            .mortality(Mortality::Mortal(64, 10, Some([9; 32])))
+            .build()
+            .unwrap();
+
+        api.resubmit(expired.clone()).unwrap();
+
+        let submitted = PolkadotSignedExtrinsic::<OpaqueCall>::decode(
+            &mut api.transport().submitted()[0].as_slice(),
+        )
+        .unwrap();
+        let (_, _, payload) = submitted.signature.unwrap();
+        assert_eq!(submitted.call, expired.call);
+        assert_eq!(payload.nonce, 3);
+        assert_eq!(payload.tip, 5);
+        assert_eq!(payload.mortality, Mortality::Mortal(64, 100 % 64, None));
+
+        // Signed by another account.
+        let (other, _) = KeyPairBuilder::<Sr25519>::generate();
+        let mut api = api.signer(other);
+        assert!(matches!(
+            api.resubmit(expired),
+            Err(Error::SignerMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn resubmit_after_runtime_upgrade() {
+        let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+        let mut api = GekkoApi::new(transport(), Network::Polkadot)
+            .unwrap()
+            .signer(keypair.clone());
+
+        let transaction = SignedTransactionBuilder::new()
+            .signer(keypair)
+            .call(OpaqueCall(vec![0, 1]))
+            .nonce(1)
+            .tip_base_unit(0)
+            .network(Network::Polkadot)
+            .mortality(Mortality::Immortal)
+            .build()
+            .unwrap();
+
+        // The upgraded runtime requires a signed extension that is not encoded.
+        let mut upgraded = api.metadata().clone().into_latest().unwrap();
+        upgraded
+            .extrinsics
+            .signed_extensions
+            .push("CheckMetadataHash".to_string());
+        let upgraded = MetadataVersion::V13(upgraded);
+
+        api.transport().respond_once(
+            "state_getRuntimeVersion",
+            json!({
+                "specName": "polkadot",
+                "implName": "parity-polkadot",
+                "authoringVersion": 0,
+                "specVersion": 9060,
+                "implVersion": 0,
+                "transactionVersion": 8,
+            }),
+        );
+        api.transport()
+            .respond_once("state_getMetadata", json!(upgraded.to_hex()));
+
+        let err = api.resubmit(transaction).unwrap_err();
+        assert!(matches!(err, Error::SignedExtensionMismatch { .. }));
+        assert!(api.transport().submitted().is_empty());
+
+        assert_eq!(api.runtime_version().spec_version, 9060);
+        assert_eq!(api.runtime_version().transaction_version, 8);
+        assert_eq!(api.metadata(), &upgraded);
+    }
+
+    #[test]
+    fn submit_with_runtime_version_check() {
+        let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+        let api = GekkoApi::new(transport(), Network::Polkadot)
+            .unwrap()
+            .signer(keypair)
+            .check_runtime_version(true);
+
+        api.submit(OpaqueCall(vec![0, 1])).unwrap();
+
+        // The runtime was upgraded after the client was created.
+        api.transport().respond_once(
+            "state_getRuntimeVersion",
+            json!({
+                "specName": "polkadot",
+                "implName": "parity-polkadot",
+                "authoringVersion": 0,
+                "specVersion": 9060,
+                "implVersion": 0,
+                "transactionVersion": 7,
+            }),
+        );
+
+        let err = api.submit(OpaqueCall(vec![0, 1])).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::RuntimeVersionMismatch {
+                spec_version: 9050,
+                node_spec_version: 9060,
+                ..
+            }
+        ));
+        assert_eq!(api.transport().submitted().len(), 1);
+    }
+
+    #[test]
+    fn reject_other_network() {
+        let err = GekkoApi::new(transport(), Network::Kusama).unwrap_err();
+        assert!(matches!(err, Error::NetworkMismatch(_)));
+    }
+}
@@ -0,0 +1,263 @@
+//! Decoding the extrinsics of a range of historic blocks, effectively a small
+//! indexer built on [`decode_block`].
+//!
+//! Each block is decoded against the runtime which executed it, fetching the
+//! metadata once per spec version. With the `"serde"` feature, the decoded
+//! extrinsics can be written as JSON lines, one object per extrinsic.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use gekko::metadata::scale::TypeRegistry;
+//! use gekko::rpc::archive::Archive;
+//! use gekko::rpc::block::RuntimeCache;
+//! use gekko::rpc::HttpClient;
+//!
+//! let client = HttpClient::new("https://rpc.polkadot.io");
+//! let mut cache = RuntimeCache::new(TypeRegistry::default());
+//!
+//! for extrinsic in Archive::new(&client, &mut cache, 6_000_000..=6_000_100).extrinsics() {
+//!     let extrinsic = extrinsic.unwrap();
+//!     println!(
+//!         "#{}-{}: {}::{}",
+//!         extrinsic.block_number,
+//!         extrinsic.index,
+//!         extrinsic.extrinsic.call.module_name,
+//!         extrinsic.extrinsic.call.call_name
+//!     );
+//! }
+//! ```
+
+use super::block::{decode_block, BlockId, DecodedBlock, RuntimeCache};
+use super::Transport;
+use crate::Result;
+use gekko_metadata::extrinsics::ExtrinsicRecord;
+use std::collections::VecDeque;
+use std::ops::RangeInclusive;
+
+/// A decoded extrinsic with its position in the chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArchivedExtrinsic {
+    pub block_number: u32,
+    #[cfg_attr(feature = "serde", serde(with = "gekko_metadata::serde_hex"))]
+    pub block_hash: [u8; 32],
+    /// The index of the extrinsic within the block.
+    pub index: u32,
+    /// The spec version of the runtime which executed the block.
+    pub spec_version: u32,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub extrinsic: ExtrinsicRecord,
+}
+
+/// An iterator over the decoded blocks of a range of block numbers. Stops
+/// after the first error.
+pub struct Archive<'a, T> {
+    transport: &'a T,
+    cache: &'a mut RuntimeCache,
+    blocks: RangeInclusive<u32>,
+    failed: bool,
+}
+
+impl<'a, T: Transport> Archive<'a, T> {
+    /// Creates an iterator over the blocks of the range, in ascending order.
+    pub fn new(transport: &'a T, cache: &'a mut RuntimeCache, blocks: RangeInclusive<u32>) -> Self {
+        Archive {
+            transport,
+            cache,
+            blocks,
+            failed: false,
+        }
+    }
+    /// Converts the iterator into an iterator over the individual extrinsics
+    /// of the blocks.
+    pub fn extrinsics(self) -> Extrinsics<'a, T> {
+        Extrinsics {
+            blocks: self,
+            pending: VecDeque::new(),
+        }
+    }
+    /// Writes the extrinsics as JSON lines, one object per extrinsic.
+    /// Returns the number of written extrinsics. Requires the `"serde"`
+    /// feature.
+    #[cfg(feature = "serde")]
+    pub fn write_json_lines<W: std::io::Write>(self, mut writer: W) -> Result<usize> {
+        let mut count = 0;
+        for extrinsic in self.extrinsics() {
+            serde_json::to_writer(&mut writer, &extrinsic?).map_err(std::io::Error::from)?;
+            writer.write_all(b"\n")?;
+            count += 1;
+        }
+
+        writer.flush()?;
+        Ok(count)
+    }
+}
+
+impl<'a, T: Transport> Iterator for Archive<'a, T> {
+    type Item = Result<DecodedBlock>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let number = self.blocks.next()?;
+        let block = decode_block(self.transport, self.cache, BlockId::Number(number));
+        self.failed = block.is_err();
+
+        Some(block)
+    }
+}
+
+/// An iterator over the decoded extrinsics of a range of blocks. Created by
+/// [`Archive::extrinsics`].
+pub struct Extrinsics<'a, T> {
+    blocks: Archive<'a, T>,
+    pending: VecDeque<ArchivedExtrinsic>,
+}
+
+impl<'a, T: Transport> Iterator for Extrinsics<'a, T> {
+    type Item = Result<ArchivedExtrinsic>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(extrinsic) = self.pending.pop_front() {
+                return Some(Ok(extrinsic));
+            }
+
+            let block = match self.blocks.next()? {
+                Ok(block) => block,
+                Err(err) => return Some(Err(err)),
+            };
+
+            let (number, hash, spec_version) = (block.number, block.hash, block.spec_version);
+            self.pending
+                .extend(
+                    block
+                        .extrinsics
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, extrinsic)| ArchivedExtrinsic {
+                            block_number: number,
+                            block_hash: hash,
+                            index: index as u32,
+                            spec_version,
+                            extrinsic,
+                        }),
+                );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::block::Header;
+    use crate::rpc::mock::MockTransport;
+    use crate::rpc::RuntimeVersion;
+    use crate::transaction::Transaction;
+    use gekko_metadata::scale::TypeRegistry;
+    use parity_scale_codec::{Compact, Encode};
+
+    /// Two Kusama blocks with the 9080 runtime, each with a timestamp
+    /// inherent.
+    fn transport() -> MockTransport {
+        let raw =
+            gekko_metadata::decode_hex(include_str!("../../dumps/metadata_kusama_9080.hex").trim())
+                .unwrap();
+
+        let header = |number: u32| Header {
+            parent_hash: [number as u8 - 1; 32],
+            number,
+            state_root: [1; 32],
+            extrinsics_root: [2; 32],
+            digest_logs: vec![],
+        };
+        // Timestamp::set(Compact<Moment>)
+        let timestamp =
+            |moment: u64| Transaction::new_unsigned(([2u8, 0], Compact(moment))).encode();
+
+        MockTransport::new()
+            .with_metadata(raw)
+            .with_runtime_version(RuntimeVersion {
+                spec_name: "kusama".to_string(),
+                impl_name: "parity-kusama".to_string(),
+                authoring_version: 2,
+                spec_version: 9080,
+                impl_version: 0,
+                transaction_version: 5,
+                apis: vec![],
+            })
+            .with_block(header(10), vec![timestamp(1_000)])
+            .with_block(header(11), vec![timestamp(7_000), timestamp(7_001)])
+    }
+
+    #[test]
+    fn archive_extrinsics() {
+        let transport = transport();
+        let mut cache = RuntimeCache::new(TypeRegistry::default());
+
+        let extrinsics: Vec<ArchivedExtrinsic> = Archive::new(&transport, &mut cache, 10..=11)
+            .extrinsics()
+            .collect::<Result<_>>()
+            .unwrap();
+
+        let positions: Vec<(u32, u32)> = extrinsics
+            .iter()
+            .map(|extrinsic| (extrinsic.block_number, extrinsic.index))
+            .collect();
+
+        assert_eq!(positions, vec![(10, 0), (11, 0), (11, 1)]);
+        assert!(extrinsics
+            .iter()
+            .all(|extrinsic| extrinsic.spec_version == 9080
+                && extrinsic.extrinsic.call.is("Timestamp", "set")));
+
+        // The metadata is fetched once per spec version.
+        let metadata_requests = transport
+            .requests()
+            .iter()
+            .filter(|(method, _)| method == "state_getMetadata")
+            .count();
+        assert_eq!(metadata_requests, 1);
+    }
+
+    #[test]
+    fn archive_stops_at_missing_block() {
+        let transport = transport();
+        let mut cache = RuntimeCache::new(TypeRegistry::default());
+
+        let blocks: Vec<Result<DecodedBlock>> =
+            Archive::new(&transport, &mut cache, 11..=20).collect();
+
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks[0].is_ok());
+        assert!(blocks[1].is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn write_json_lines() {
+        let transport = transport();
+        let mut cache = RuntimeCache::new(TypeRegistry::default());
+
+        let mut output = vec![];
+        let count = Archive::new(&transport, &mut cache, 10..=11)
+            .write_json_lines(&mut output)
+            .unwrap();
+
+        assert_eq!(count, 3);
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[2]["block_number"], 11);
+        assert_eq!(lines[2]["index"], 1);
+        assert_eq!(lines[2]["call"]["module_name"], "Timestamp");
+    }
+}
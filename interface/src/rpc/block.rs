@@ -0,0 +1,626 @@
+//! Fetching and decoding of blocks, the core primitive for indexers.
+//!
+//! The extrinsics of a block are decoded against the metadata of the runtime
+//! which executed the block, so historic blocks are decoded correctly across
+//! runtime upgrades. The metadata of each runtime version is only fetched once
+//! and cached by the [`RuntimeCache`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use gekko::metadata::scale::TypeRegistry;
+//! use gekko::rpc::HttpClient;
+//! use gekko::rpc::block::{decode_block, BlockId, RuntimeCache};
+//!
+//! let client = HttpClient::new("https://rpc.polkadot.io");
+//! let mut cache = RuntimeCache::new(TypeRegistry::default());
+//!
+//! let block = decode_block(&client, &mut cache, BlockId::Number(6_000_000)).unwrap();
+//! for extrinsic in &block.extrinsics {
+//!     println!("{}::{}", extrinsic.call.module_name, extrinsic.call.call_name);
+//! }
+//! ```
+
+use super::{
+    block_hash_param, decode_hex_value, metadata, runtime_version, RuntimeVersion, Transport,
+};
+use crate::common::Mortality;
+use crate::{blake2b, Error, Result};
+use gekko_metadata::extrinsics::{decode_extrinsic, ExtrinsicRecord};
+use gekko_metadata::parse_raw_metadata;
+use gekko_metadata::scale::TypeRegistry;
+use gekko_metadata::version::MetadataV13;
+use parity_scale_codec::{Compact, Encode};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::TryInto;
+
+/// Identifies a block by its hash or its number on the canonical chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockId {
+    Hash([u8; 32]),
+    Number(u32),
+}
+
+impl From<[u8; 32]> for BlockId {
+    fn from(hash: [u8; 32]) -> Self {
+        BlockId::Hash(hash)
+    }
+}
+
+impl From<u32> for BlockId {
+    fn from(number: u32) -> Self {
+        BlockId::Number(number)
+    }
+}
+
+/// A runtime version with its metadata and type definitions.
+#[derive(Debug, Clone)]
+pub struct Runtime {
+    pub version: RuntimeVersion,
+    pub metadata: MetadataV13,
+    /// The type definitions, including the `Call` type of the metadata.
+    pub registry: TypeRegistry,
+}
+
+impl Runtime {
+    /// Decodes the HEX encoded extrinsics of a block, such as the `extrinsics`
+    /// array returned by `chain_getBlock`. The extrinsics are decoded lazily,
+    /// one at a time. See [`decode_extrinsics`](gekko_metadata::extrinsics::decode_extrinsics)
+    /// for decoding a SCALE encoded block body.
+    pub fn decode_extrinsics<'a>(
+        &'a self,
+        extrinsics: &'a [Value],
+    ) -> impl Iterator<Item = Result<ExtrinsicRecord>> + 'a {
+        extrinsics.iter().map(move |extrinsic| {
+            decode_extrinsic(
+                &self.metadata,
+                &self.registry,
+                &decode_hex_value(extrinsic)?,
+            )
+            .map_err(Error::from)
+        })
+    }
+}
+
+/// A cache of the runtimes of a chain, indexed by their spec version.
+#[derive(Debug, Clone)]
+pub struct RuntimeCache {
+    types: TypeRegistry,
+    runtimes: HashMap<u32, Runtime>,
+}
+
+impl RuntimeCache {
+    /// Creates an empty cache. The type definitions are used for all runtimes.
+    pub fn new(types: TypeRegistry) -> Self {
+        RuntimeCache {
+            types,
+            runtimes: HashMap::new(),
+        }
+    }
+    /// Returns the cached runtime with the given spec version, if any.
+    pub fn get(&self, spec_version: u32) -> Option<&Runtime> {
+        self.runtimes.get(&spec_version)
+    }
+    /// Adds a runtime to the cache, e.g. from a metadata dump. Overwrites the
+    /// runtime with the same spec version, if any.
+    pub fn insert(&mut self, version: RuntimeVersion, metadata: MetadataV13) -> Result<&Runtime> {
+        let mut registry = self.types.clone();
+        registry.register_calls(&metadata)?;
+
+        let spec_version = version.spec_version;
+        self.runtimes.insert(
+            spec_version,
+            Runtime {
+                version,
+                metadata,
+                registry,
+            },
+        );
+
+        Ok(&self.runtimes[&spec_version])
+    }
+    /// Returns the runtime whose state is at the given block, fetching its
+    /// metadata if the spec version is not cached yet.
+    pub fn runtime_at<T: Transport>(&mut self, transport: &T, at: [u8; 32]) -> Result<&Runtime> {
+        let version = runtime_version(transport, Some(at))?;
+        if self.runtimes.contains_key(&version.spec_version) {
+            return Ok(&self.runtimes[&version.spec_version]);
+        }
+
+        let metadata = parse_raw_metadata(metadata(transport, Some(at))?)?.into_latest()?;
+        self.insert(version, metadata)
+    }
+}
+
+/// The header of a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    pub parent_hash: [u8; 32],
+    pub number: u32,
+    pub state_root: [u8; 32],
+    pub extrinsics_root: [u8; 32],
+    /// The SCALE encoded digest items.
+    pub digest_logs: Vec<Vec<u8>>,
+}
+
+impl Header {
+    /// Parses the JSON representation of a header, as returned by
+    /// `chain_getHeader` or the notifications of `chain_subscribeNewHeads`.
+    pub fn from_json(value: &Value) -> Result<Self> {
+        Ok(Header {
+            parent_hash: hash_value(&value["parentHash"])?,
+            number: number_value(&value["number"])?,
+            state_root: hash_value(&value["stateRoot"])?,
+            extrinsics_root: hash_value(&value["extrinsicsRoot"])?,
+            digest_logs: value["digest"]["logs"]
+                .as_array()
+                .ok_or_else(|| Error::RpcInvalidResponse("missing digest of header".to_string()))?
+                .iter()
+                .map(decode_hex_value)
+                .collect::<Result<_>>()?,
+        })
+    }
+    /// Converts the header into its JSON representation, the inverse of
+    /// [`from_json`](Self::from_json).
+    pub fn to_json(&self) -> Value {
+        let hex = |bytes: &[u8]| format!("0x{}", hex::encode(bytes));
+        json!({
+            "parentHash": hex(&self.parent_hash),
+            "number": format!("0x{:x}", self.number),
+            "stateRoot": hex(&self.state_root),
+            "extrinsicsRoot": hex(&self.extrinsics_root),
+            "digest": {
+                "logs": self.digest_logs.iter().map(|log| hex(log)).collect::<Vec<String>>(),
+            },
+        })
+    }
+    /// The hash of the block, i.e. the Blake2b-256 hash of the SCALE encoded
+    /// header.
+    pub fn hash(&self) -> [u8; 32] {
+        blake2b(self.encode())
+    }
+}
+
+impl Encode for Header {
+    fn encode_to<T: parity_scale_codec::Output + ?Sized>(&self, dest: &mut T) {
+        self.parent_hash.encode_to(dest);
+        Compact(self.number).encode_to(dest);
+        self.state_root.encode_to(dest);
+        self.extrinsics_root.encode_to(dest);
+        // The digest items are already encoded.
+        Compact(self.digest_logs.len() as u32).encode_to(dest);
+        for log in &self.digest_logs {
+            dest.write(log);
+        }
+    }
+}
+
+/// A block whose extrinsics are decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedBlock {
+    pub hash: [u8; 32],
+    pub number: u32,
+    pub parent_hash: [u8; 32],
+    /// The spec version of the runtime which executed the block.
+    pub spec_version: u32,
+    /// The extrinsics, in the order of execution.
+    pub extrinsics: Vec<ExtrinsicRecord>,
+}
+
+/// Fetches the hash of the block with the given number, or of the best block
+/// if `number` is `None`. Returns `None` if the block does not exist.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(transport), err)
+)]
+pub fn block_hash<T: Transport>(transport: &T, number: Option<u32>) -> Result<Option<[u8; 32]>> {
+    let resp = transport.request("chain_getBlockHash", json!([number]))?;
+    if resp.is_null() {
+        return Ok(None);
+    }
+
+    hash_value(&resp).map(Some)
+}
+
+/// Fetches the header of the block with the given hash, or of the best block
+/// if `at` is `None`. Returns `None` if the block does not exist.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(transport), err)
+)]
+pub fn header<T: Transport>(transport: &T, at: Option<[u8; 32]>) -> Result<Option<Header>> {
+    let resp = transport.request("chain_getHeader", json!([block_hash_param(at)]))?;
+    if resp.is_null() {
+        return Ok(None);
+    }
+
+    Header::from_json(&resp).map(Some)
+}
+
+/// Fetches the hash of the last finalized block.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(transport), err)
+)]
+pub fn finalized_head<T: Transport>(transport: &T) -> Result<[u8; 32]> {
+    hash_value(&transport.request("chain_getFinalizedHead", json!([]))?)
+}
+
+/// Fetches the last finalized block and creates a mortality of `period`
+/// blocks, starting at that block. See [`Mortality::from_current_block`].
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(transport), err)
+)]
+pub fn finalized_mortality<T: Transport>(transport: &T, period: u64) -> Result<Mortality> {
+    let hash = finalized_head(transport)?;
+    let number = header(transport, Some(hash))?
+        .ok_or_else(|| Error::RpcInvalidResponse("missing finalized header".to_string()))?
+        .number;
+
+    Ok(Mortality::from_current_block(number as u64, hash, period))
+}
+
+/// Fetches the block and decodes all of its extrinsics against the metadata of
+/// the runtime which executed the block, i.e. the runtime at the parent block.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(transport, cache), err)
+)]
+pub fn decode_block<T: Transport>(
+    transport: &T,
+    cache: &mut RuntimeCache,
+    at: BlockId,
+) -> Result<DecodedBlock> {
+    let hash = match at {
+        BlockId::Hash(hash) => hash,
+        BlockId::Number(number) => block_hash(transport, Some(number))?
+            .ok_or_else(|| Error::RpcInvalidResponse(format!("block {} does not exist", number)))?,
+    };
+
+    let resp = transport.request("chain_getBlock", json!([block_hash_param(Some(hash))]))?;
+    if resp.is_null() {
+        return Err(Error::RpcInvalidResponse(format!(
+            "block 0x{} does not exist",
+            hex::encode(hash)
+        )));
+    }
+
+    let header = Header::from_json(&resp["block"]["header"])?;
+    let (number, parent_hash) = (header.number, header.parent_hash);
+
+    // The genesis block has no parent.
+    let runtime = cache.runtime_at(transport, if number == 0 { hash } else { parent_hash })?;
+
+    let extrinsics = resp["block"]["extrinsics"]
+        .as_array()
+        .ok_or_else(|| Error::RpcInvalidResponse("missing extrinsics of block".to_string()))?;
+    let extrinsics = runtime
+        .decode_extrinsics(extrinsics)
+        .collect::<Result<_>>()?;
+
+    Ok(DecodedBlock {
+        hash,
+        number,
+        parent_hash,
+        spec_version: runtime.version.spec_version,
+        extrinsics,
+    })
+}
+
+fn hash_value(value: &Value) -> Result<[u8; 32]> {
+    decode_hex_value(value)?
+        .try_into()
+        .map_err(|_| Error::RpcInvalidResponse(format!("invalid block hash: {}", value)))
+}
+
+/// Block numbers of headers are HEX encoded.
+fn number_value(value: &Value) -> Result<u32> {
+    value
+        .as_str()
+        .and_then(|number| u32::from_str_radix(number.trim_start_matches("0x"), 16).ok())
+        .ok_or_else(|| Error::RpcInvalidResponse(format!("invalid block number: {}", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::*;
+    use crate::rpc::mock::MockTransport;
+    use crate::transaction::{PolkadotSignedExtrinsic, SignedTransactionBuilder, Transaction};
+    use gekko_metadata::parse_hex_metadata;
+
+    /// Serves a single block with the Kusama 9080 runtime.
+    fn mock_transport(extrinsics: Vec<Vec<u8>>) -> (MockTransport, Header) {
+        let header = Header {
+            parent_hash: [1; 32],
+            number: 100,
+            state_root: [3; 32],
+            extrinsics_root: [4; 32],
+            digest_logs: vec![],
+        };
+
+        let transport = MockTransport::new()
+            .with_block(header.clone(), extrinsics)
+            .with_runtime_version(RuntimeVersion {
+                spec_name: "kusama".to_string(),
+                impl_name: "parity-kusama".to_string(),
+                authoring_version: 2,
+                spec_version: 9080,
+                impl_version: 0,
+                transaction_version: 5,
+                apis: vec![],
+            })
+            .with_metadata(
+                decode_hex_value(&json!(
+                    include_str!("../../dumps/metadata_kusama_9080.hex").trim()
+                ))
+                .unwrap(),
+            );
+
+        (transport, header)
+    }
+
+    #[test]
+    #[cfg(feature = "runtime-kusama")]
+    fn decode_block_extrinsics() {
+        use crate::runtime::kusama::extrinsics::balances::TransferKeepAlive;
+
+        let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+        let balance = BalanceBuilder::new(Currency::Kusama).balance(1);
+        let destination =
+            AccountId::from_ss58_address("12eDex4amEwj39T7Wz4Rkppb68YGCDYKG9QHhEhHGtNdDy7D")
+                .unwrap();
+
+        let transfer: PolkadotSignedExtrinsic<_> = SignedTransactionBuilder::new()
+            .signer(keypair)
+            .call(TransferKeepAlive {
+                dest: destination,
+                value: balance,
+            })
+            .nonce(7)
+            .tip_base_unit(10)
+            .network(Network::Kusama)
+            .spec_version(9080)
+            .transaction_version(5)
+            .build()
+            .unwrap();
+
+        // Timestamp::set(Compact<Moment>)
+        let timestamp = Transaction::new_unsigned(([2u8, 0], Compact(1_000_000u64)));
+
+        let (transport, header) = mock_transport(vec![timestamp.encode(), transfer.encode()]);
+
+        let mut cache = RuntimeCache::new(TypeRegistry::default());
+        let block = decode_block(&transport, &mut cache, BlockId::Number(100)).unwrap();
+
+        assert_eq!(block.hash, header.hash());
+        assert_eq!(block.parent_hash, [1; 32]);
+        assert_eq!(block.number, 100);
+        assert_eq!(block.spec_version, 9080);
+        assert_eq!(block.extrinsics.len(), 2);
+
+        let inherent = &block.extrinsics[0];
+        assert!(!inherent.is_signed());
+        assert!(inherent.call.is("Timestamp", "set"));
+        assert_eq!(
+            inherent
+                .call
+                .arg("now")
+                .unwrap()
+                .decode::<Compact<u64>>()
+                .unwrap()
+                .0,
+            1_000_000
+        );
+
+        let extrinsic = &block.extrinsics[1];
+        let signature = extrinsic.signature.as_ref().unwrap();
+        assert_eq!(signature.extra.len(), 7);
+        // CheckNonce
+        assert_eq!(signature.extra[4], Compact(7u32).encode());
+        assert!(extrinsic.call.is("Balances", "transfer_keep_alive"));
+        assert_eq!(
+            extrinsic.call.args[0].decode::<AccountId>().unwrap(),
+            destination
+        );
+        assert_eq!(
+            extrinsic.call.args[1].decode::<Compact<u128>>().unwrap().0,
+            balance.as_base_unit()
+        );
+
+        // The runtime of the parent block executes the block.
+        let hex = |bytes: &[u8]| json!(format!("0x{}", hex::encode(bytes)));
+        assert!(transport.requests().contains(&(
+            "state_getRuntimeVersion".to_string(),
+            json!([hex(&[1; 32])])
+        )));
+
+        // The metadata is cached.
+        decode_block(&transport, &mut cache, BlockId::Hash(header.hash())).unwrap();
+        assert_eq!(
+            transport
+                .requests()
+                .iter()
+                .filter(|(method, _)| method == "state_getMetadata")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "runtime-kusama")]
+    fn decode_dynamic_extrinsic() {
+        use crate::runtime::kusama::extrinsics::balances::TransferKeepAlive;
+        use gekko_metadata::dynamic::ExtrinsicDecoder;
+        use gekko_metadata::scale::Value;
+
+        let metadata =
+            parse_hex_metadata(include_str!("../../dumps/metadata_kusama_9080.hex").trim())
+                .unwrap()
+                .into_latest()
+                .unwrap();
+        let registry = TypeRegistry::default();
+
+        let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+        let destination =
+            AccountId::from_ss58_address("12eDex4amEwj39T7Wz4Rkppb68YGCDYKG9QHhEhHGtNdDy7D")
+                .unwrap();
+
+        let transfer: PolkadotSignedExtrinsic<_> = SignedTransactionBuilder::new()
+            .signer(keypair)
+            .call(TransferKeepAlive {
+                dest: destination,
+                value: BalanceBuilder::new(Currency::Kusama).balance(1),
+            })
+            .nonce(7)
+            .tip_base_unit(10)
+            .network(Network::Kusama)
+            .spec_version(9080)
+            .transaction_version(5)
+            .build()
+            .unwrap();
+
+        let extrinsic = ExtrinsicDecoder::v13(&metadata, &registry)
+            .decode_extrinsic(&transfer.encode())
+            .unwrap();
+
+        let signature = extrinsic.signature.as_ref().unwrap();
+        assert_eq!(
+            signature.extra[4],
+            ("CheckNonce".to_string(), Value::UInt(7))
+        );
+        assert_eq!(
+            signature.extra[6],
+            ("ChargeTransactionPayment".to_string(), Value::UInt(10))
+        );
+
+        assert!(extrinsic.call.is("Balances", "transfer_keep_alive"));
+        assert_eq!(
+            extrinsic.call.arg("dest"),
+            Some(&Value::variant(
+                "Id",
+                Value::Bytes(destination.as_ref().to_vec())
+            ))
+        );
+        assert_eq!(
+            extrinsic.call.arg("value"),
+            Some(&Value::UInt(1_000_000_000_000))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "runtime-kusama")]
+    fn encode_dynamic_call() {
+        use crate::runtime::kusama::extrinsics::balances::TransferKeepAlive;
+        use gekko_metadata::dynamic::{DynamicCall, ExtrinsicDecoder};
+        use gekko_metadata::scale::Value;
+
+        let metadata =
+            parse_hex_metadata(include_str!("../../dumps/metadata_kusama_9080.hex").trim())
+                .unwrap()
+                .into_latest()
+                .unwrap();
+        let registry = TypeRegistry::default();
+        let decoder = ExtrinsicDecoder::v13(&metadata, &registry);
+
+        let destination =
+            AccountId::from_ss58_address("12eDex4amEwj39T7Wz4Rkppb68YGCDYKG9QHhEhHGtNdDy7D")
+                .unwrap();
+
+        // The arguments are ordered by the metadata.
+        let call = DynamicCall::new("Balances", "transfer_keep_alive")
+            .arg("value", 10u128)
+            .arg(
+                "dest",
+                Value::variant("Id", Value::Bytes(destination.as_ref().to_vec())),
+            );
+
+        let expected = TransferKeepAlive {
+            dest: destination,
+            value: Compact(10u128),
+        };
+        assert_eq!(call.encode(&decoder).unwrap(), expected.encode());
+
+        // Usable as the call of a transaction.
+        let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+        let transaction: PolkadotSignedExtrinsic<_> = SignedTransactionBuilder::new()
+            .signer(keypair)
+            .call(OpaqueCall(call.encode(&decoder).unwrap()))
+            .nonce(0)
+            .tip_base_unit(0)
+            .network(Network::Kusama)
+            .spec_version(9080)
+            .transaction_version(5)
+            .build()
+            .unwrap();
+
+        let decoded = decoder.decode_extrinsic(&transaction.encode()).unwrap();
+        assert_eq!(
+            DynamicCall::from(decoded.call).encode(&decoder).unwrap(),
+            expected.encode()
+        );
+
+        assert!(DynamicCall::new("Balances", "transfer_keep_alive")
+            .arg("value", 10u128)
+            .encode(&decoder)
+            .is_err());
+        assert!(DynamicCall::new("Balances", "unknown")
+            .encode(&decoder)
+            .is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "runtime-kusama")]
+    fn decode_block_body() {
+        use gekko_metadata::extrinsics::decode_extrinsics;
+
+        let metadata =
+            parse_hex_metadata(include_str!("../../dumps/metadata_kusama_9080.hex").trim())
+                .unwrap()
+                .into_latest()
+                .unwrap();
+        let mut registry = TypeRegistry::default();
+        registry.register_calls(&metadata).unwrap();
+
+        let timestamp = Transaction::new_unsigned(([2u8, 0], Compact(1_000_000u64))).encode();
+
+        // Length-prefixed extrinsics, preceded by their count.
+        let mut body = Compact(2u32).encode();
+        body.extend(&timestamp);
+        body.extend(&timestamp);
+
+        let mut extrinsics = decode_extrinsics(&metadata, &registry, &body).unwrap();
+        assert!(extrinsics
+            .next()
+            .unwrap()
+            .unwrap()
+            .call
+            .is("Timestamp", "set"));
+        assert!(extrinsics
+            .next()
+            .unwrap()
+            .unwrap()
+            .call
+            .is("Timestamp", "set"));
+        assert!(extrinsics.next().is_none());
+
+        // Trailing bytes fail to decode as a final extrinsic.
+        body.push(0);
+        let extrinsics = decode_extrinsics(&metadata, &registry, &body).unwrap();
+        let results: Vec<_> = extrinsics.collect();
+        assert_eq!(results.len(), 3);
+        assert!(results[2].is_err());
+
+        // Missing extrinsics.
+        let mut body = Compact(3u32).encode();
+        body.extend(&timestamp);
+        let results: Vec<_> = decode_extrinsics(&metadata, &registry, &body)
+            .unwrap()
+            .collect();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+    }
+}
@@ -0,0 +1,379 @@
+//! Asynchronous helpers, which connect to the node at the given URL. Requires
+//! the `"async"` feature.
+//!
+//! HTTP(S) URLs are served by the [`HttpClient`], WebSocket URLs by the
+//! [`WsClient`](super::WsClient) if the `"ws"` feature is enabled. The requests
+//! are sent by a background thread, so the returned futures can be awaited on
+//! any executor.
+//!
+//! Watching the status of a submitted extrinsic with [`submit_and_watch`]
+//! requires a WebSocket URL and the `"ws"` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use gekko::common::Currency;
+//! use gekko::rpc::client;
+//! use futures::executor::block_on;
+//!
+//! # let extrinsic: Vec<u8> = vec![];
+//! let estimate = block_on(client::query_fee(
+//!     "https://rpc.polkadot.io",
+//!     &extrinsic,
+//!     Currency::Polkadot,
+//!     None,
+//! ))
+//! .unwrap();
+//!
+//! println!("Fee: {} Planck", estimate.partial_fee.as_base_unit());
+//! ```
+//!
+//! ```no_run
+//! # #[cfg(feature = "ws")]
+//! # {
+//! use gekko::rpc::client;
+//! use futures::executor::block_on_stream;
+//!
+//! # let extrinsic: Vec<u8> = vec![];
+//! let statuses = futures::executor::block_on(client::submit_and_watch(
+//!     "wss://rpc.polkadot.io",
+//!     &extrinsic,
+//! ))
+//! .unwrap();
+//!
+//! // Ends after the final status, such as `Finalized`.
+//! for status in block_on_stream(statuses) {
+//!     println!("{:?}", status.unwrap());
+//! }
+//! # }
+//! ```
+
+use super::block::finalized_mortality;
+use super::fee::{self, FeeDetails, FeeEstimate};
+#[cfg(feature = "ws")]
+use super::watch::{self, StatusStream};
+use super::{submit_extrinsic, HttpClient, Transport};
+use crate::common::{Currency, Mortality};
+use crate::{Error, Result};
+use futures::channel::oneshot;
+use serde_json::Value;
+use std::future::Future;
+use std::thread;
+
+/// The transport for the scheme of the URL.
+enum Client {
+    Http(HttpClient),
+    #[cfg(feature = "ws")]
+    Ws(super::WsClient),
+}
+
+impl Client {
+    fn connect(url: &str) -> Result<Self> {
+        if url.starts_with("ws://") || url.starts_with("wss://") {
+            #[cfg(feature = "ws")]
+            return super::WsClient::connect(url).map(Client::Ws);
+
+            #[cfg(not(feature = "ws"))]
+            return Err(Error::RpcTransport(
+                "WebSocket URLs require the \"ws\" feature".into(),
+            ));
+        }
+
+        Ok(Client::Http(HttpClient::new(url)))
+    }
+}
+
+impl Transport for Client {
+    fn request(&self, method: &str, params: Value) -> Result<Value> {
+        match self {
+            Client::Http(client) => client.request(method, params),
+            #[cfg(feature = "ws")]
+            Client::Ws(client) => client.request(method, params),
+        }
+    }
+}
+
+/// Connects to the URL and calls `f` on a background thread. The returned
+/// future resolves to its result.
+fn spawn<T, F>(url: &str, f: F) -> impl Future<Output = Result<T>>
+where
+    T: Send + 'static,
+    F: FnOnce(&Client) -> Result<T> + Send + 'static,
+{
+    let url = url.to_string();
+    let (tx, rx) = oneshot::channel();
+    thread::spawn(move || {
+        // The receiver might have been dropped.
+        let _ = tx.send(Client::connect(&url).and_then(|client| f(&client)));
+    });
+
+    async move {
+        rx.await
+            .unwrap_or_else(|err| Err(Error::RpcTransport(Box::new(err))))
+    }
+}
+
+/// Estimates the fee of the SCALE encoded extrinsic. The asynchronous
+/// counterpart of [`fee::query_fee`].
+pub fn query_fee(
+    url: &str,
+    extrinsic: &[u8],
+    currency: Currency,
+    at: Option<[u8; 32]>,
+) -> impl Future<Output = Result<FeeEstimate>> {
+    let extrinsic = extrinsic.to_vec();
+    spawn(url, move |client| {
+        fee::query_fee(client, &extrinsic, currency, at)
+    })
+}
+
+/// Fetches the components of the fee of the SCALE encoded extrinsic. The
+/// asynchronous counterpart of [`fee::query_fee_details`].
+pub fn query_fee_details(
+    url: &str,
+    extrinsic: &[u8],
+    currency: Currency,
+    at: Option<[u8; 32]>,
+) -> impl Future<Output = Result<FeeDetails>> {
+    let extrinsic = extrinsic.to_vec();
+    spawn(url, move |client| {
+        fee::query_fee_details(client, &extrinsic, currency, at)
+    })
+}
+
+/// Fetches the last finalized block and resolves to a mortality of `period`
+/// blocks, starting at that block. The asynchronous counterpart of
+/// [`finalized_mortality`], see [`Mortality::from_current_block`].
+pub fn current_mortality(url: &str, period: u64) -> impl Future<Output = Result<Mortality>> {
+    spawn(url, move |client| finalized_mortality(client, period))
+}
+
+/// Submits the SCALE encoded extrinsic and resolves to its hash. The
+/// asynchronous counterpart of [`submit_extrinsic`](super::submit_extrinsic).
+pub fn submit(url: &str, extrinsic: &[u8]) -> impl Future<Output = Result<[u8; 32]>> {
+    let extrinsic = extrinsic.to_vec();
+    spawn(url, move |client| submit_extrinsic(client, &extrinsic))
+}
+
+/// Submits the SCALE encoded extrinsic and resolves to a stream of its status,
+/// which ends after the final status. The URL must be a WebSocket URL. The
+/// asynchronous counterpart of [`watch::submit_and_watch`].
+#[cfg(feature = "ws")]
+pub fn submit_and_watch(url: &str, extrinsic: &[u8]) -> impl Future<Output = Result<StatusStream>> {
+    let extrinsic = extrinsic.to_vec();
+    spawn(url, move |client| match client {
+        Client::Ws(client) => watch::submit_and_watch(client, &extrinsic),
+        Client::Http(_) => Err(Error::RpcTransport(
+            "watching an extrinsic requires a WebSocket URL".into(),
+        )),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::fee::DispatchClass;
+    use futures::executor::block_on;
+    use serde_json::json;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    /// Answers one HTTP request per result, in order, and returns the received
+    /// requests.
+    fn serve(results: Vec<Value>) -> (String, JoinHandle<Vec<Value>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let server = thread::spawn(move || {
+            results
+                .into_iter()
+                .map(|result| {
+                    let (stream, _) = listener.accept().unwrap();
+                    let mut reader = BufReader::new(stream);
+
+                    let mut len = 0;
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        let line = line.trim_end().to_ascii_lowercase();
+                        if line.is_empty() {
+                            break;
+                        }
+                        if let Some(value) = line.strip_prefix("content-length:") {
+                            len = value.trim().parse().unwrap();
+                        }
+                    }
+
+                    let mut body = vec![0; len];
+                    reader.read_exact(&mut body).unwrap();
+                    let req: Value = serde_json::from_slice(&body).unwrap();
+
+                    let resp = json!({ "jsonrpc": "2.0", "id": req["id"], "result": result })
+                        .to_string();
+                    write!(
+                        reader.get_mut(),
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        resp.len(),
+                        resp
+                    )
+                    .unwrap();
+
+                    req
+                })
+                .collect()
+        });
+
+        (url, server)
+    }
+
+    #[test]
+    fn query_fee_over_http() {
+        let (url, server) = serve(vec![json!({
+            "weight": 195_000_000,
+            "class": "normal",
+            "partialFee": "155000000",
+        })]);
+
+        let estimate = block_on(query_fee(&url, &[4, 0], Currency::Polkadot, None)).unwrap();
+        assert_eq!(estimate.partial_fee.as_base_unit(), 155_000_000);
+        assert_eq!(estimate.weight, 195_000_000);
+        assert_eq!(estimate.class, DispatchClass::Normal);
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0]["method"], "payment_queryInfo");
+        assert_eq!(requests[0]["params"], json!(["0x0400", null]));
+    }
+
+    #[test]
+    fn query_fee_details_over_http() {
+        let (url, server) = serve(vec![json!({
+            "inclusionFee": {
+                "baseFee": "0x0a",
+                "lenFee": "0x14",
+                "adjustedWeightFee": "0x1e",
+            },
+        })]);
+
+        let details = block_on(query_fee_details(&url, &[4, 0], Currency::Kusama, None)).unwrap();
+        assert_eq!(details.total(), 60);
+        assert_eq!(
+            server.join().unwrap()[0]["method"],
+            "payment_queryFeeDetails"
+        );
+    }
+
+    #[test]
+    fn current_mortality_over_http() {
+        let hash = format!("0x{}", hex::encode([1; 32]));
+        let (url, server) = serve(vec![
+            json!(hash),
+            json!({
+                "parentHash": format!("0x{}", hex::encode([0; 32])),
+                "number": "0x3e8",
+                "stateRoot": format!("0x{}", hex::encode([2; 32])),
+                "extrinsicsRoot": format!("0x{}", hex::encode([3; 32])),
+                "digest": { "logs": [] },
+            }),
+        ]);
+
+        assert_eq!(
+            block_on(current_mortality(&url, 64)).unwrap(),
+            Mortality::from_current_block(1_000, [1; 32], 64)
+        );
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0]["method"], "chain_getFinalizedHead");
+        assert_eq!(requests[1]["method"], "chain_getHeader");
+        assert_eq!(requests[1]["params"], json!([hash]));
+    }
+
+    #[test]
+    fn submit_over_http() {
+        let hash = format!("0x{}", hex::encode([1; 32]));
+        let (url, server) = serve(vec![json!(hash)]);
+
+        assert_eq!(block_on(submit(&url, &[4, 0])).unwrap(), [1; 32]);
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0]["method"], "author_submitExtrinsic");
+        assert_eq!(requests[0]["params"], json!(["0x0400"]));
+    }
+
+    #[cfg(feature = "ws")]
+    #[test]
+    fn submit_and_watch_over_ws() {
+        use crate::rpc::watch::TransactionStatus;
+        use futures::executor::block_on_stream;
+        use tungstenite::Message;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tungstenite::accept(stream).unwrap();
+
+            let req: Value =
+                serde_json::from_str(socket.read().unwrap().to_text().unwrap()).unwrap();
+            assert_eq!(req["method"], "author_submitAndWatchExtrinsic");
+            assert_eq!(req["params"], json!(["0x0400"]));
+
+            let block = format!("0x{}", hex::encode([1; 32]));
+            let messages = vec![
+                json!({ "jsonrpc": "2.0", "id": req["id"], "result": "sub" }),
+                json!({ "jsonrpc": "2.0", "method": "author_extrinsicUpdate",
+                    "params": { "subscription": "sub", "result": "ready" } }),
+                json!({ "jsonrpc": "2.0", "method": "author_extrinsicUpdate",
+                    "params": { "subscription": "sub", "result": { "inBlock": block } } }),
+                json!({ "jsonrpc": "2.0", "method": "author_extrinsicUpdate",
+                    "params": { "subscription": "sub", "result": { "finalized": block } } }),
+            ];
+            for msg in messages {
+                socket.send(Message::Text(msg.to_string())).unwrap();
+            }
+
+            let req: Value =
+                serde_json::from_str(socket.read().unwrap().to_text().unwrap()).unwrap();
+            assert_eq!(req["method"], "author_unwatchExtrinsic");
+        });
+
+        let statuses = block_on(submit_and_watch(&url, &[4, 0])).unwrap();
+        let statuses = block_on_stream(statuses)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(
+            statuses,
+            vec![
+                TransactionStatus::Ready,
+                TransactionStatus::InBlock([1; 32]),
+                TransactionStatus::Finalized([1; 32]),
+            ]
+        );
+        server.join().unwrap();
+    }
+
+    #[cfg(feature = "ws")]
+    #[test]
+    fn watch_requires_ws_url() {
+        assert!(matches!(
+            block_on(submit_and_watch("http://127.0.0.1:1", &[4, 0])),
+            Err(Error::RpcTransport(_))
+        ));
+    }
+
+    #[test]
+    fn connection_error() {
+        // Nothing listens on the port once the listener is dropped.
+        let url = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+
+        assert!(matches!(
+            block_on(query_fee(&url, &[4, 0], Currency::Polkadot, None)),
+            Err(Error::RpcTransport(_))
+        ));
+    }
+}
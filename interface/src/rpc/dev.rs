@@ -0,0 +1,538 @@
+//! A harness for end-to-end tests against a development node. Requires the
+//! `"dev-node"` feature.
+//!
+//! [`DevNode`] either spawns a `substrate --dev` node, which is killed once
+//! the harness is dropped, or connects to a running node, e.g. one started by
+//! zombienet. It signs transactions with the well-known development accounts,
+//! waits for their inclusion and returns the events they emitted, so tests can
+//! assert on the outcome of a transaction in a few lines.
+//!
+//! Note that only nodes providing metadata V13 are supported.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use gekko::common::AccountId;
+//! use gekko::rpc::dev::{DevAccount, DevNode};
+//!
+//! let node = DevNode::spawn("substrate").unwrap();
+//! let account = AccountId::new([1; 32]);
+//!
+//! let events = node.fund(&account, 1_000_000_000_000).unwrap();
+//! assert!(events.has("Balances", "Endowed"));
+//!
+//! // A call of `System::remark`, for which the indices are looked up in the
+//! // metadata of the node.
+//! let remark = node.metadata_call_indices("System", "remark").unwrap();
+//! let events = node
+//!     .submit_and_wait(DevAccount::Bob, (remark, b"hello".to_vec()))
+//!     .unwrap();
+//! assert!(events.is_success());
+//! ```
+
+use super::block::{block_hash, header};
+use super::storage::storage_raw;
+use super::{
+    account_next_index, decode_hex_value, metadata, runtime_version, submit_extrinsic, HttpClient,
+    RuntimeVersion, Transport,
+};
+use crate::common::{AccountId, Mortality, MultiAddress, Network, Sr25519};
+use crate::transaction::SignedTransactionBuilder;
+use crate::{blake2b, Error, Result};
+use gekko_metadata::events::{decode_events, EventRecord, Phase};
+use gekko_metadata::scale::TypeRegistry;
+use gekko_metadata::version::MetadataV13;
+use gekko_metadata::{parse_raw_metadata, ModuleMetadataExt, StorageMetadataExt};
+use parity_scale_codec::{Compact, Encode};
+use serde_json::json;
+use sp_core::crypto::{Pair, Ss58AddressFormat};
+use std::ffi::OsStr;
+use std::io;
+use std::net::TcpListener;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long to wait for a spawned node to serve JSON-RPC requests.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The well-known development accounts, which are endowed in the genesis
+/// block of development chains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DevAccount {
+    Alice,
+    Bob,
+    Charlie,
+    Dave,
+    Eve,
+    Ferdie,
+}
+
+impl DevAccount {
+    /// All development accounts.
+    pub const ALL: [DevAccount; 6] = [
+        DevAccount::Alice,
+        DevAccount::Bob,
+        DevAccount::Charlie,
+        DevAccount::Dave,
+        DevAccount::Eve,
+        DevAccount::Ferdie,
+    ];
+
+    /// The name of the account, e.g. `"Alice"`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DevAccount::Alice => "Alice",
+            DevAccount::Bob => "Bob",
+            DevAccount::Charlie => "Charlie",
+            DevAccount::Dave => "Dave",
+            DevAccount::Eve => "Eve",
+            DevAccount::Ferdie => "Ferdie",
+        }
+    }
+    /// The keypair, derived from the development phrase with the `//<name>`
+    /// path.
+    pub fn keypair(&self) -> Sr25519 {
+        // The development phrase and the path are valid.
+        Sr25519::from_string(&format!("//{}", self.name()), None).unwrap()
+    }
+    /// The account identifier, i.e. the public key.
+    pub fn account_id(&self) -> AccountId {
+        self.keypair().public().into()
+    }
+}
+
+/// The events emitted by an extrinsic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtrinsicEvents {
+    /// The hash of the block which includes the extrinsic.
+    pub block_hash: [u8; 32],
+    /// The index of the extrinsic within the block.
+    pub index: u32,
+    /// The events, in the order of emission.
+    pub events: Vec<EventRecord>,
+}
+
+impl ExtrinsicEvents {
+    /// The first event of the given module and name.
+    pub fn find(&self, module: &str, event: &str) -> Option<&EventRecord> {
+        self.events.iter().find(|record| record.is(module, event))
+    }
+    /// Whether the extrinsic emitted the event of the given module and name.
+    pub fn has(&self, module: &str, event: &str) -> bool {
+        self.find(module, event).is_some()
+    }
+    /// Whether the extrinsic was dispatched successfully, i.e. emitted
+    /// `System::ExtrinsicSuccess`.
+    pub fn is_success(&self) -> bool {
+        self.has("System", "ExtrinsicSuccess")
+    }
+}
+
+/// Kills the spawned node once dropped.
+struct NodeProcess(Child);
+
+impl Drop for NodeProcess {
+    fn drop(&mut self) {
+        // The node might have exited already.
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// A connection to a development node. See the [module
+/// documentation](self).
+pub struct DevNode<T: Transport = HttpClient> {
+    transport: T,
+    genesis: [u8; 32],
+    runtime_version: RuntimeVersion,
+    metadata: MetadataV13,
+    registry: TypeRegistry,
+    timeout: Duration,
+    poll_interval: Duration,
+    process: Option<NodeProcess>,
+}
+
+impl DevNode<HttpClient> {
+    /// Spawns the node binary, e.g. `"substrate"` or `"polkadot"`, with a
+    /// temporary development chain. See [`spawn_command`](Self::spawn_command).
+    pub fn spawn<P: AsRef<OsStr>>(binary: P) -> Result<Self> {
+        let mut command = Command::new(binary);
+        command
+            .args(["--dev", "--tmp", "--port", "0", "--no-prometheus"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+
+        Self::spawn_command(command)
+    }
+    /// Spawns the node with the given command, to which the `--rpc-port`
+    /// argument with a free port is appended. Waits until the node serves
+    /// JSON-RPC requests. The node is killed once the harness is dropped.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(command), err)
+    )]
+    pub fn spawn_command(mut command: Command) -> Result<Self> {
+        let port = TcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+        let mut process = NodeProcess(command.arg("--rpc-port").arg(port.to_string()).spawn()?);
+
+        let transport = HttpClient::new(format!("http://127.0.0.1:{}", port));
+        let deadline = Instant::now() + STARTUP_TIMEOUT;
+        while let Err(err) = block_hash(&transport, Some(0)) {
+            if let Some(status) = process.0.try_wait()? {
+                return Err(
+                    io::Error::other(format!("development node exited with {}", status)).into(),
+                );
+            }
+
+            if Instant::now() >= deadline {
+                return Err(err);
+            }
+
+            thread::sleep(Duration::from_millis(100));
+        }
+
+        Ok(DevNode {
+            process: Some(process),
+            ..Self::new(transport)?
+        })
+    }
+    /// Connects to a running node over HTTP(S), e.g.
+    /// `"http://127.0.0.1:9933"`. See [`new`](Self::new).
+    pub fn connect(url: &str) -> Result<Self> {
+        Self::new(HttpClient::new(url))
+    }
+}
+
+impl<T: Transport> DevNode<T> {
+    /// Creates the harness, fetching the genesis hash, the current runtime
+    /// version and the metadata of the node.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(transport), err)
+    )]
+    pub fn new(transport: T) -> Result<Self> {
+        let genesis = block_hash(&transport, Some(0))?
+            .ok_or_else(|| Error::RpcInvalidResponse("missing genesis block".to_string()))?;
+        let runtime_version = runtime_version(&transport, None)?;
+        let metadata = parse_raw_metadata(metadata(&transport, None)?)?.into_latest()?;
+
+        Ok(DevNode {
+            transport,
+            genesis,
+            runtime_version,
+            metadata,
+            registry: TypeRegistry::default(),
+            timeout: Duration::from_secs(60),
+            poll_interval: Duration::from_millis(500),
+            process: None,
+        })
+    }
+    /// Sets the registry used to decode events. Uses
+    /// [`TypeRegistry::default`] by default.
+    pub fn registry(self, registry: TypeRegistry) -> Self {
+        Self { registry, ..self }
+    }
+    /// Sets how long to wait for the inclusion of a transaction. One minute
+    /// by default.
+    pub fn timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+    /// Sets the interval in which new blocks are polled. Half a second by
+    /// default.
+    pub fn poll_interval(self, poll_interval: Duration) -> Self {
+        Self {
+            poll_interval,
+            ..self
+        }
+    }
+    /// The underlying transport.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+    /// The runtime version of the node.
+    pub fn runtime_version(&self) -> &RuntimeVersion {
+        &self.runtime_version
+    }
+    /// The metadata of the node.
+    pub fn metadata(&self) -> &MetadataV13 {
+        &self.metadata
+    }
+    /// The module and call index of the call, as declared by the metadata of
+    /// the node. Can be used as the first field of dynamically constructed
+    /// calls.
+    pub fn metadata_call_indices(&self, module: &str, call: &str) -> Result<[u8; 2]> {
+        self.metadata
+            .find_module_extrinsic(module, call)
+            .map(|info| [info.module_id as u8, info.dispatch_id as u8])
+            .ok_or_else(|| {
+                gekko_metadata::Error::UnknownCallName {
+                    module: module.to_string(),
+                    call: call.to_string(),
+                }
+                .into()
+            })
+    }
+    /// Signs the call with the development account and submits it, returning
+    /// the hash of the transaction. The transaction is immortal and uses the
+    /// next nonce of the account, including the transaction pool.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, call), err)
+    )]
+    pub fn submit<C: Encode>(&self, signer: DevAccount, call: C) -> Result<[u8; 32]> {
+        let nonce = account_next_index(
+            &self.transport,
+            &signer
+                .account_id()
+                .to_ss58_address(Ss58AddressFormat::SubstrateAccount),
+        )?;
+
+        let extrinsic = SignedTransactionBuilder::new()
+            .signer(signer.keypair())
+            .call(call)
+            .nonce(nonce)
+            .network(Network::Custom(self.genesis))
+            .mortality(Mortality::Immortal)
+            .spec_version(self.runtime_version.spec_version)
+            .transaction_version(self.runtime_version.transaction_version)
+            .build()?;
+
+        submit_extrinsic(&self.transport, &extrinsic.encode())
+    }
+    /// Signs and submits the call, waits for its inclusion in a block and
+    /// returns the emitted events. See [`wait_for`](Self::wait_for).
+    pub fn submit_and_wait<C: Encode>(
+        &self,
+        signer: DevAccount,
+        call: C,
+    ) -> Result<ExtrinsicEvents> {
+        let best = self.best_number()?;
+        let hash = self.submit(signer, call)?;
+        self.wait_for(hash, best + 1)
+    }
+    /// Transfers the amount, in the base unit of the runtime, from Alice to
+    /// the account with `Balances::transfer`. Fails if the transfer was not
+    /// successful.
+    pub fn fund(&self, account: &AccountId, amount: u128) -> Result<ExtrinsicEvents> {
+        let call = (
+            self.metadata_call_indices("Balances", "transfer")?,
+            MultiAddress::from(*account),
+            Compact(amount),
+        );
+
+        let events = self.submit_and_wait(DevAccount::Alice, call)?;
+        if !events.is_success() {
+            return Err(Error::ExtrinsicFailed {
+                block_hash: events.block_hash,
+                index: events.index,
+            });
+        }
+
+        Ok(events)
+    }
+    /// Polls the blocks starting at the given number until the extrinsic with
+    /// the given hash is included and returns its events. Fails if the
+    /// [timeout](Self::timeout) elapsed.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), err)
+    )]
+    pub fn wait_for(&self, hash: [u8; 32], from: u32) -> Result<ExtrinsicEvents> {
+        let deadline = Instant::now() + self.timeout;
+        let mut next = from;
+
+        loop {
+            let best = self.best_number()?;
+            while next <= best {
+                let block = block_hash(&self.transport, Some(next))?.ok_or_else(|| {
+                    Error::RpcInvalidResponse(format!("block {} does not exist", next))
+                })?;
+
+                if let Some(index) = self.find_extrinsic(block, &hash)? {
+                    return self.extrinsic_events(block, index);
+                }
+
+                next += 1;
+            }
+
+            if Instant::now() >= deadline {
+                return Err(Error::RpcTransport(Box::new(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!(
+                        "timed out waiting for the inclusion of extrinsic 0x{}",
+                        hex::encode(hash)
+                    ),
+                ))));
+            }
+
+            thread::sleep(self.poll_interval);
+        }
+    }
+    fn best_number(&self) -> Result<u32> {
+        header(&self.transport, None)?
+            .map(|header| header.number)
+            .ok_or_else(|| Error::RpcInvalidResponse("missing best header".to_string()))
+    }
+    /// The index of the extrinsic with the given hash within the block.
+    fn find_extrinsic(&self, block: [u8; 32], hash: &[u8; 32]) -> Result<Option<u32>> {
+        let resp = self.transport.request(
+            "chain_getBlock",
+            json!([format!("0x{}", hex::encode(block))]),
+        )?;
+
+        let extrinsics = resp["block"]["extrinsics"]
+            .as_array()
+            .ok_or_else(|| Error::RpcInvalidResponse("missing extrinsics of block".to_string()))?;
+
+        for (index, extrinsic) in extrinsics.iter().enumerate() {
+            if blake2b(decode_hex_value(extrinsic)?) == *hash {
+                return Ok(Some(index as u32));
+            }
+        }
+
+        Ok(None)
+    }
+    fn extrinsic_events(&self, block: [u8; 32], index: u32) -> Result<ExtrinsicEvents> {
+        let key = self
+            .metadata
+            .find_module_storage("System", "Events")
+            .ok_or_else(|| Error::StorageEntryNotFound {
+                module: "System".to_string(),
+                entry: "Events".to_string(),
+            })?
+            .storage_prefix();
+
+        let events = match storage_raw(&self.transport, &key, Some(block))? {
+            Some(data) => decode_events(&self.metadata, &self.registry, &data)?,
+            None => vec![],
+        };
+
+        Ok(ExtrinsicEvents {
+            block_hash: block,
+            index,
+            events: events
+                .into_iter()
+                .filter(|record| record.phase == Phase::ApplyExtrinsic(index))
+                .collect(),
+        })
+    }
+}
+
+impl<T: Transport + std::fmt::Debug> std::fmt::Debug for DevNode<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DevNode")
+            .field("transport", &self.transport)
+            .field("runtime_version", &self.runtime_version)
+            .field(
+                "process",
+                &self.process.as_ref().map(|process| process.0.id()),
+            )
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::block::Header;
+    use crate::rpc::mock::MockTransport;
+    use crate::transaction::Transaction;
+
+    #[test]
+    fn dev_accounts() {
+        assert_eq!(
+            DevAccount::Alice
+                .account_id()
+                .to_ss58_address(Ss58AddressFormat::SubstrateAccount),
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+        );
+        assert_eq!(
+            DevAccount::Bob
+                .account_id()
+                .to_ss58_address(Ss58AddressFormat::SubstrateAccount),
+            "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty"
+        );
+    }
+
+    #[test]
+    fn wait_for_extrinsic_events() {
+        let raw =
+            gekko_metadata::decode_hex(include_str!("../../dumps/metadata_kusama_9080.hex").trim())
+                .unwrap();
+
+        let header = |number: u32| Header {
+            parent_hash: [number as u8; 32],
+            number,
+            state_root: [1; 32],
+            extrinsics_root: [2; 32],
+            digest_logs: vec![],
+        };
+        // Timestamp::set(Compact<Moment>)
+        let timestamp =
+            |moment: u64| Transaction::new_unsigned(([2u8, 0], Compact(moment))).encode();
+
+        let events = (
+            Compact(3u32),
+            // System::ExtrinsicSuccess(DispatchInfo)
+            (
+                Phase::ApplyExtrinsic(0),
+                [0u8, 0],
+                (1_000u64, 0u8, 0u8),
+                Vec::<[u8; 32]>::new(),
+            ),
+            // Balances::Transfer(AccountId, AccountId, Balance)
+            (
+                Phase::ApplyExtrinsic(1),
+                [4u8, 2],
+                ([1u8; 32], [2u8; 32], 500u128),
+                Vec::<[u8; 32]>::new(),
+            ),
+            (
+                Phase::ApplyExtrinsic(1),
+                [0u8, 0],
+                (1_000u64, 0u8, 0u8),
+                Vec::<[u8; 32]>::new(),
+            ),
+        )
+            .encode();
+
+        let key = hex::decode("26aa394eea5630e07c48ae0c9558cef780d41e5e16056765bc8461851072c9d7")
+            .unwrap();
+
+        let transport = MockTransport::new()
+            .with_genesis([9; 32])
+            .with_metadata(raw)
+            .with_runtime_version(RuntimeVersion {
+                spec_name: "node".to_string(),
+                impl_name: "substrate-node".to_string(),
+                authoring_version: 10,
+                spec_version: 100,
+                impl_version: 0,
+                transaction_version: 1,
+                apis: vec![],
+            })
+            .with_storage(key, events)
+            .with_block(header(1), vec![timestamp(1_000)])
+            .with_block(header(2), vec![timestamp(7_000), timestamp(7_001)]);
+
+        let node = DevNode::new(transport)
+            .unwrap()
+            .poll_interval(Duration::from_millis(1))
+            .timeout(Duration::from_millis(10));
+
+        let events = node.wait_for(blake2b(timestamp(7_001)), 1).unwrap();
+        assert_eq!(events.block_hash, header(2).hash());
+        assert_eq!(events.index, 1);
+        assert_eq!(events.events.len(), 2);
+        assert!(events.has("Balances", "Transfer"));
+        assert!(events.is_success());
+
+        // Never included.
+        assert!(matches!(
+            node.wait_for(blake2b(timestamp(1)), 1),
+            Err(Error::RpcTransport(_))
+        ));
+    }
+}
@@ -0,0 +1,422 @@
+//! Subscriptions to the events of new blocks, decoded with the runtime
+//! metadata. Requires the `"ws"` feature.
+//!
+//! Events are delivered as asynchronous streams, optionally filtered by
+//! module and event name. Typed events are decoded into any type implementing
+//! [`StaticEvent`].
+//!
+//! Note that the subscription only notifies about changes of `System::Events`,
+//! so blocks with exactly the same events as their parent are skipped.
+//!
+//! # Example
+//!
+//! Waiting for a transfer to an account.
+//!
+//! ```no_run
+//! use gekko::common::scale::Decode;
+//! use gekko::common::sp_core::crypto::AccountId32;
+//! use gekko::metadata::{parse_raw_metadata, scale::TypeRegistry};
+//! use gekko::rpc::events::{subscribe_typed_events, StaticEvent};
+//! use gekko::rpc::{metadata, WsClient};
+//! use futures::executor::block_on_stream;
+//!
+//! #[derive(Decode)]
+//! struct Transfer {
+//!     from: AccountId32,
+//!     to: AccountId32,
+//!     amount: u128,
+//! }
+//!
+//! impl StaticEvent for Transfer {
+//!     const MODULE: &'static str = "Balances";
+//!     const EVENT: &'static str = "Transfer";
+//! }
+//!
+//! let client = WsClient::connect("wss://rpc.polkadot.io").unwrap();
+//! let data = parse_raw_metadata(metadata(&client, None).unwrap())
+//!     .unwrap()
+//!     .into_latest()
+//!     .unwrap();
+//! let registry = TypeRegistry::default();
+//!
+//! let me: AccountId32 = [0; 32].into();
+//! let events = subscribe_typed_events::<_, Transfer>(&client, &data, &registry).unwrap();
+//!
+//! for event in block_on_stream(events) {
+//!     let event = event.unwrap();
+//!     if event.event.to == me {
+//!         println!("Received {} in block 0x{}", event.event.amount, hex::encode(event.block_hash));
+//!         break;
+//!     }
+//! }
+//! ```
+
+use super::{decode_hex_value, Subscription, SubscriptionTransport};
+use crate::{Error, Result};
+use futures::task::{Context, Poll};
+use futures::{ready, Stream};
+use gekko_metadata::events::{decode_events, EventRecord, Phase};
+use gekko_metadata::scale::TypeRegistry;
+use gekko_metadata::version::MetadataV13;
+use gekko_metadata::StorageMetadataExt;
+use parity_scale_codec::Decode;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::convert::TryInto;
+use std::marker::PhantomData;
+use std::pin::Pin;
+
+/// An event with a static module and event name, which can be decoded from
+/// the SCALE encoded arguments of the event.
+pub trait StaticEvent: Decode {
+    /// The name of the module, e.g. `"Balances"`.
+    const MODULE: &'static str;
+    /// The name of the event, e.g. `"Transfer"`.
+    const EVENT: &'static str;
+}
+
+/// Selects the events to be delivered by an [`EventStream`]. An empty filter
+/// accepts all events.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventFilter {
+    events: Vec<(String, Option<String>)>,
+}
+
+impl EventFilter {
+    /// Creates a filter which accepts all events.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Accepts all events of the module.
+    pub fn module<T: Into<String>>(mut self, module: T) -> Self {
+        self.events.push((module.into(), None));
+        self
+    }
+    /// Accepts the event of the module.
+    pub fn event<T: Into<String>, E: Into<String>>(mut self, module: T, event: E) -> Self {
+        self.events.push((module.into(), Some(event.into())));
+        self
+    }
+    /// Whether the event is accepted by this filter.
+    pub fn matches(&self, record: &EventRecord) -> bool {
+        self.events.is_empty()
+            || self.events.iter().any(|(module, event)| {
+                record.module_name == *module
+                    && event
+                        .as_ref()
+                        .map(|event| record.event_name == *event)
+                        .unwrap_or(true)
+            })
+    }
+}
+
+/// An event of a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockEvent {
+    /// The hash of the block which emitted the event.
+    pub block_hash: [u8; 32],
+    pub record: EventRecord,
+}
+
+/// A typed event of a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypedEvent<E> {
+    /// The hash of the block which emitted the event.
+    pub block_hash: [u8; 32],
+    /// The phase in which the event was emitted.
+    pub phase: Phase,
+    pub event: E,
+}
+
+/// Subscribes to the events of new blocks which match the filter. The events
+/// are decoded with the given metadata, which must match the runtime.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(transport, metadata, registry), err)
+)]
+pub fn subscribe_events<'a, T: SubscriptionTransport>(
+    transport: &T,
+    metadata: &'a MetadataV13,
+    registry: &'a TypeRegistry,
+    filter: EventFilter,
+) -> Result<EventStream<'a>> {
+    let key = metadata
+        .find_module_storage("System", "Events")
+        .ok_or_else(|| Error::StorageEntryNotFound {
+            module: "System".to_string(),
+            entry: "Events".to_string(),
+        })?
+        .storage_prefix();
+
+    let subscription = transport.subscribe(
+        "state_subscribeStorage",
+        json!([[format!("0x{}", hex::encode(&key))]]),
+        "state_unsubscribeStorage",
+    )?;
+
+    Ok(EventStream {
+        subscription,
+        metadata,
+        registry,
+        key,
+        filter,
+        pending: VecDeque::new(),
+    })
+}
+
+/// Subscribes to the events of new blocks of type `E`. The events are decoded
+/// with the given metadata, which must match the runtime.
+pub fn subscribe_typed_events<'a, T: SubscriptionTransport, E: StaticEvent>(
+    transport: &T,
+    metadata: &'a MetadataV13,
+    registry: &'a TypeRegistry,
+) -> Result<TypedEventStream<'a, E>> {
+    let filter = EventFilter::new().event(E::MODULE, E::EVENT);
+
+    Ok(TypedEventStream {
+        events: subscribe_events(transport, metadata, registry, filter)?,
+        _p: PhantomData,
+    })
+}
+
+/// A stream of the events of new blocks. Created by [`subscribe_events`].
+#[derive(Debug)]
+pub struct EventStream<'a> {
+    subscription: Subscription,
+    metadata: &'a MetadataV13,
+    registry: &'a TypeRegistry,
+    key: Vec<u8>,
+    filter: EventFilter,
+    pending: VecDeque<BlockEvent>,
+}
+
+impl<'a> EventStream<'a> {
+    /// Decodes the notification of `state_subscribeStorage`, adding all
+    /// events which match the filter to the pending events.
+    fn process(&mut self, notification: Value) -> Result<()> {
+        let block_hash: [u8; 32] = decode_hex_value(&notification["block"])?
+            .try_into()
+            .map_err(|_| Error::RpcInvalidResponse("invalid block hash".to_string()))?;
+
+        let changes = notification["changes"].as_array().ok_or_else(|| {
+            Error::RpcInvalidResponse(format!("invalid storage changes: {}", notification))
+        })?;
+
+        for change in changes {
+            if decode_hex_value(&change[0])? != self.key || change[1].is_null() {
+                continue;
+            }
+
+            let records =
+                decode_events(self.metadata, self.registry, &decode_hex_value(&change[1])?)?;
+            let filter = &self.filter;
+            self.pending.extend(
+                records
+                    .into_iter()
+                    .filter(|record| filter.matches(record))
+                    .map(|record| BlockEvent { block_hash, record }),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Stream for EventStream<'a> {
+    type Item = Result<BlockEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Poll::Ready(Some(Ok(event)));
+            }
+
+            match ready!(Pin::new(&mut self.subscription).poll_next(cx)) {
+                Some(Ok(notification)) => {
+                    if let Err(err) = self.process(notification) {
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                }
+                Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+/// A stream of typed events of new blocks. Created by
+/// [`subscribe_typed_events`].
+#[derive(Debug)]
+pub struct TypedEventStream<'a, E> {
+    events: EventStream<'a>,
+    _p: PhantomData<fn() -> E>,
+}
+
+impl<'a, E: StaticEvent> Stream for TypedEventStream<'a, E> {
+    type Item = Result<TypedEvent<E>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let event = ready!(Pin::new(&mut self.events).poll_next(cx));
+
+        Poll::Ready(event.map(|event| {
+            let BlockEvent { block_hash, record } = event?;
+
+            Ok(TypedEvent {
+                block_hash,
+                phase: record.phase,
+                event: record.decode_args()?,
+            })
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::mock::MockTransport;
+    use crate::rpc::Result;
+    use futures::executor::block_on_stream;
+    use gekko_metadata::parse_hex_metadata;
+    use parity_scale_codec::{Compact, Encode};
+
+    #[derive(Debug, PartialEq, Eq, Decode)]
+    struct Transfer([u8; 32], [u8; 32], u128);
+
+    impl StaticEvent for Transfer {
+        const MODULE: &'static str = "Balances";
+        const EVENT: &'static str = "Transfer";
+    }
+
+    #[test]
+    fn event_info_by_index() {
+        use gekko_metadata::EventMetadataExt;
+
+        let data = parse_hex_metadata(include_str!("../../dumps/metadata_kusama_9080.hex").trim())
+            .unwrap()
+            .into_latest()
+            .unwrap();
+
+        let info = data.find_event_by_index(4, 2).unwrap();
+        assert_eq!(info.module_name, "Balances");
+        assert_eq!(info.event_name, "Transfer");
+        assert_eq!(info.args, vec!["AccountId", "AccountId", "Balance"]);
+        assert_eq!(data.find_module_event("Balances", "Transfer"), Some(info));
+
+        assert!(data.find_event_by_index(4, 200).is_none());
+    }
+
+    #[test]
+    fn error_info_by_index() {
+        use gekko_metadata::ErrorBuilderExt;
+
+        let data = parse_hex_metadata(include_str!("../../dumps/metadata_kusama_9080.hex").trim())
+            .unwrap()
+            .into_latest()
+            .unwrap();
+
+        let info = data.find_error(4, 2).unwrap();
+        assert_eq!(info.module_name, "Balances");
+        assert_eq!(info.error_name, "InsufficientBalance");
+        assert!(!info.documentation.is_empty());
+
+        assert!(data.find_error(4, 200).is_none());
+    }
+
+    #[test]
+    fn decode_constants() {
+        use gekko_metadata::ConstantsBuilderExt;
+
+        let data = parse_hex_metadata(include_str!("../../dumps/metadata_kusama_9080.hex").trim())
+            .unwrap()
+            .into_latest()
+            .unwrap();
+
+        let info = data
+            .find_constant("Balances", "ExistentialDeposit")
+            .unwrap();
+        assert_eq!(info.ty, "T::Balance");
+        assert_eq!(info.decode::<u128>().unwrap(), 33_333_333);
+        assert_eq!(
+            data.decode_constant::<u128>("Balances", "ExistentialDeposit")
+                .unwrap(),
+            33_333_333
+        );
+
+        // The value is a `u128`, which must be consumed entirely.
+        assert!(info.decode::<u64>().is_err());
+        assert!(data.decode_constant::<u32>("Balances", "Unknown").is_err());
+    }
+
+    #[test]
+    fn typed_events_of_block() {
+        let data = parse_hex_metadata(include_str!("../../dumps/metadata_kusama_9080.hex").trim())
+            .unwrap()
+            .into_latest()
+            .unwrap();
+        let registry = TypeRegistry::default();
+
+        let events = (
+            Compact(2u32),
+            // System::ExtrinsicSuccess(DispatchInfo)
+            (
+                Phase::ApplyExtrinsic(0),
+                [0u8, 0],
+                (1_000u64, 0u8, 0u8),
+                Vec::<[u8; 32]>::new(),
+            ),
+            // Balances::Transfer(AccountId, AccountId, Balance)
+            (
+                Phase::ApplyExtrinsic(1),
+                [4u8, 2],
+                ([1u8; 32], [2u8; 32], 500u128),
+                Vec::<[u8; 32]>::new(),
+            ),
+        )
+            .encode();
+
+        let transport = MockTransport::new().with_notifications(
+            "state_subscribeStorage",
+            vec![json!({
+                "block": format!("0x{}", hex::encode([9; 32])),
+                "changes": [[
+                    "0x26aa394eea5630e07c48ae0c9558cef780d41e5e16056765bc8461851072c9d7",
+                    format!("0x{}", hex::encode(&events)),
+                ]],
+            })],
+        );
+
+        // All events.
+        let events: Vec<BlockEvent> = block_on_stream(
+            subscribe_events(&transport, &data, &registry, EventFilter::new()).unwrap(),
+        )
+        .collect::<Result<_>>()
+        .unwrap();
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            transport.requests(),
+            vec![(
+                "state_subscribeStorage".to_string(),
+                json!([["0x26aa394eea5630e07c48ae0c9558cef780d41e5e16056765bc8461851072c9d7"]])
+            )]
+        );
+        assert!(events[0].record.is("System", "ExtrinsicSuccess"));
+        assert_eq!(events[1].block_hash, [9; 32]);
+        assert_eq!(events[1].record.args[2], 500u128.encode());
+
+        // Filtered and typed events.
+        let events: Vec<TypedEvent<Transfer>> =
+            block_on_stream(subscribe_typed_events(&transport, &data, &registry).unwrap())
+                .collect::<Result<_>>()
+                .unwrap();
+
+        assert_eq!(
+            events,
+            vec![TypedEvent {
+                block_hash: [9; 32],
+                phase: Phase::ApplyExtrinsic(1),
+                event: Transfer([1; 32], [2; 32], 500),
+            }]
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! Estimating the fee of a transaction before submitting it, via the
+//! `payment_queryInfo` and `payment_queryFeeDetails` methods of the
+//! transaction payment pallet.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use gekko::common::{Currency, Metric};
+//! use gekko::rpc::fee::query_fee;
+//! use gekko::rpc::HttpClient;
+//!
+//! # let extrinsic: Vec<u8> = vec![];
+//! let client = HttpClient::new("https://rpc.polkadot.io");
+//!
+//! // The signed extrinsic, which is not submitted.
+//! let estimate = query_fee(&client, &extrinsic, Currency::Polkadot, None).unwrap();
+//! println!("Fee: {:?} DOT", estimate.partial_fee.as_metric(Metric::One));
+//! ```
+
+use super::{block_hash_param, Transport};
+use crate::common::{Balance, BalanceBuilder, Currency};
+use crate::{Error, Result};
+use serde_json::{json, Value};
+use std::convert::TryFrom;
+
+/// The class of a dispatch, which determines the share of the block weight it
+/// may use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DispatchClass {
+    Normal,
+    Operational,
+    Mandatory,
+}
+
+/// The estimated fee of a transaction, as returned by `payment_queryInfo`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeEstimate {
+    /// The fee of the transaction, excluding the tip.
+    pub partial_fee: Balance,
+    /// The weight of the transaction. For runtimes with two-dimensional
+    /// weights, this is the reference time.
+    pub weight: u64,
+    pub class: DispatchClass,
+}
+
+/// The components of the fee of a transaction, as returned by
+/// `payment_queryFeeDetails`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeDetails {
+    /// The fee for the inclusion of the transaction, or `None` for unsigned
+    /// transactions, which pay no fee.
+    pub inclusion_fee: Option<InclusionFee>,
+    pub tip: Balance,
+}
+
+impl FeeDetails {
+    /// The total fee, including the tip.
+    pub fn total(&self) -> u128 {
+        let inclusion_fee = self.inclusion_fee.as_ref().map_or(0, |fee| {
+            fee.base_fee.as_base_unit()
+                + fee.len_fee.as_base_unit()
+                + fee.adjusted_weight_fee.as_base_unit()
+        });
+
+        inclusion_fee + self.tip.as_base_unit()
+    }
+}
+
+/// The fee for the inclusion of a transaction in a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InclusionFee {
+    /// The minimum fee of every transaction.
+    pub base_fee: Balance,
+    /// The fee for the length of the encoded transaction.
+    pub len_fee: Balance,
+    /// The fee for the weight of the transaction, adjusted by the congestion
+    /// of the chain.
+    pub adjusted_weight_fee: Balance,
+}
+
+/// Estimates the fee of the SCALE encoded extrinsic at the given block, or at
+/// the best block if `at` is `None`. The extrinsic must be signed, but is not
+/// submitted.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(transport, extrinsic, currency), err)
+)]
+pub fn query_fee<T: Transport>(
+    transport: &T,
+    extrinsic: &[u8],
+    currency: Currency,
+    at: Option<[u8; 32]>,
+) -> Result<FeeEstimate> {
+    let resp = transport.request(
+        "payment_queryInfo",
+        json!([
+            format!("0x{}", hex::encode(extrinsic)),
+            block_hash_param(at)
+        ]),
+    )?;
+
+    let weight = match &resp["weight"] {
+        // Two-dimensional weights.
+        Value::Object(weight) => weight.get("refTime").or_else(|| weight.get("ref_time")),
+        weight => Some(weight),
+    }
+    .and_then(|weight| parse_number(weight).ok())
+    .and_then(|weight| u64::try_from(weight).ok())
+    .ok_or_else(|| invalid_response("weight", &resp))?;
+
+    let class = match resp["class"].as_str() {
+        Some("normal") => DispatchClass::Normal,
+        Some("operational") => DispatchClass::Operational,
+        Some("mandatory") => DispatchClass::Mandatory,
+        _ => return Err(invalid_response("dispatch class", &resp)),
+    };
+
+    Ok(FeeEstimate {
+        partial_fee: BalanceBuilder::new(currency)
+            .balance_base_unit(parse_number(&resp["partialFee"])?),
+        weight,
+        class,
+    })
+}
+
+/// Fetches the components of the fee of the SCALE encoded extrinsic at the
+/// given block, or at the best block if `at` is `None`. The extrinsic is not
+/// submitted.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(transport, extrinsic, currency), err)
+)]
+pub fn query_fee_details<T: Transport>(
+    transport: &T,
+    extrinsic: &[u8],
+    currency: Currency,
+    at: Option<[u8; 32]>,
+) -> Result<FeeDetails> {
+    let resp = transport.request(
+        "payment_queryFeeDetails",
+        json!([
+            format!("0x{}", hex::encode(extrinsic)),
+            block_hash_param(at)
+        ]),
+    )?;
+
+    let unit = BalanceBuilder::new(currency);
+    let inclusion_fee = match &resp["inclusionFee"] {
+        Value::Null => None,
+        fee => Some(InclusionFee {
+            base_fee: unit.balance_base_unit(parse_number(&fee["baseFee"])?),
+            len_fee: unit.balance_base_unit(parse_number(&fee["lenFee"])?),
+            adjusted_weight_fee: unit.balance_base_unit(parse_number(&fee["adjustedWeightFee"])?),
+        }),
+    };
+
+    Ok(FeeDetails {
+        inclusion_fee,
+        // Older runtimes do not report the tip.
+        tip: unit.balance_base_unit(match &resp["tip"] {
+            Value::Null => 0,
+            tip => parse_number(tip)?,
+        }),
+    })
+}
+
+/// Parses a balance or weight, which nodes encode either as a JSON number, a
+/// decimal string or a `0x`-prefixed HEX string.
+fn parse_number(value: &Value) -> Result<u128> {
+    let parsed = match value {
+        Value::Number(number) => number.as_u64().map(u128::from),
+        Value::String(string) => match string.strip_prefix("0x") {
+            Some(hex) => u128::from_str_radix(hex, 16).ok(),
+            None => string.parse().ok(),
+        },
+        _ => None,
+    };
+
+    parsed.ok_or_else(|| Error::RpcInvalidResponse(format!("invalid number: {}", value)))
+}
+
+fn invalid_response(field: &str, resp: &Value) -> Error {
+    Error::RpcInvalidResponse(format!("invalid {} in fee info: {}", field, resp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::mock::MockTransport;
+
+    #[test]
+    fn query_fee_estimate() {
+        let transport = MockTransport::new().respond(
+            "payment_queryInfo",
+            json!({
+                "weight": 195_000_000,
+                "class": "normal",
+                "partialFee": "155000000",
+            }),
+        );
+
+        let estimate = query_fee(&transport, &[4, 0], Currency::Polkadot, None).unwrap();
+        assert_eq!(estimate.partial_fee.as_base_unit(), 155_000_000);
+        assert_eq!(estimate.weight, 195_000_000);
+        assert_eq!(estimate.class, DispatchClass::Normal);
+        assert_eq!(
+            transport.requests(),
+            vec![("payment_queryInfo".to_string(), json!(["0x0400", null]))]
+        );
+
+        // Two-dimensional weights and numeric fees.
+        let transport = MockTransport::new().respond(
+            "payment_queryInfo",
+            json!({
+                "weight": { "refTime": 1_000, "proofSize": 2_000 },
+                "class": "operational",
+                "partialFee": 10,
+            }),
+        );
+
+        let estimate = query_fee(&transport, &[4, 0], Currency::Polkadot, None).unwrap();
+        assert_eq!(estimate.partial_fee.as_base_unit(), 10);
+        assert_eq!(estimate.weight, 1_000);
+        assert_eq!(estimate.class, DispatchClass::Operational);
+
+        let transport = MockTransport::new().respond(
+            "payment_queryInfo",
+            json!({ "weight": 1, "class": "unknown" }),
+        );
+        assert!(matches!(
+            query_fee(&transport, &[4, 0], Currency::Polkadot, None),
+            Err(Error::RpcInvalidResponse(_))
+        ));
+    }
+
+    #[test]
+    fn query_fee_components() {
+        let transport = MockTransport::new().respond(
+            "payment_queryFeeDetails",
+            json!({
+                "inclusionFee": {
+                    "baseFee": "0x0a",
+                    "lenFee": "0x14",
+                    "adjustedWeightFee": "0x1e",
+                },
+                "tip": "0x05",
+            }),
+        );
+
+        let details = query_fee_details(&transport, &[4, 0], Currency::Kusama, None).unwrap();
+        let inclusion_fee = details.inclusion_fee.unwrap();
+        assert_eq!(inclusion_fee.base_fee.as_base_unit(), 10);
+        assert_eq!(inclusion_fee.len_fee.as_base_unit(), 20);
+        assert_eq!(inclusion_fee.adjusted_weight_fee.as_base_unit(), 30);
+        assert_eq!(details.tip.as_base_unit(), 5);
+        assert_eq!(details.total(), 65);
+
+        // Unsigned transactions pay no fee.
+        let transport = MockTransport::new().respond(
+            "payment_queryFeeDetails",
+            json!({ "inclusionFee": null, "tip": "0x00" }),
+        );
+
+        let details = query_fee_details(&transport, &[4, 0], Currency::Kusama, None).unwrap();
+        assert_eq!(details.inclusion_fee, None);
+        assert_eq!(details.total(), 0);
+    }
+}
@@ -0,0 +1,281 @@
+//! Following the finalized heads of the chain. Requires the `"ws"` feature.
+//!
+//! [`FinalizedFollower`] keeps track of the latest finalized block in a
+//! background thread and invokes the registered callbacks for each new
+//! finalized head. The latest finalized block is the recommended birth block
+//! of mortal transactions, see [`FinalizedHead::mortality`].
+//!
+//! Note that GRANDPA can finalize multiple blocks at once, in which case only
+//! the last finalized block is reported.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use gekko::rpc::follow::FinalizedFollower;
+//! use gekko::rpc::WsClient;
+//! use std::time::Duration;
+//!
+//! let client = WsClient::connect("wss://rpc.polkadot.io").unwrap();
+//! let follower = FinalizedFollower::start(&client).unwrap();
+//!
+//! follower.on_finalized(|head| println!("Finalized block #{}", head.number));
+//!
+//! // Wait for the first finalized head.
+//! let head = follower.wait_for(0, Duration::from_secs(30)).unwrap();
+//! let mortality = head.mortality(64);
+//! ```
+
+use super::block::Header;
+use super::{Subscription, SubscriptionTransport};
+use crate::common::Mortality;
+use crate::{Error, Result};
+use futures::executor::block_on_stream;
+use futures::task::{Context, Poll};
+use futures::{ready, Stream};
+use serde_json::json;
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The number and hash of a finalized block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FinalizedHead {
+    pub number: u32,
+    pub hash: [u8; 32],
+}
+
+impl FinalizedHead {
+    /// The mortality of a transaction which is valid for `period` blocks,
+    /// starting at this block. See [`Mortality::from_current_block`].
+    pub fn mortality(&self, period: u64) -> Mortality {
+        Mortality::from_current_block(self.number as u64, self.hash, period)
+    }
+}
+
+/// Subscribes to the headers of newly finalized blocks.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(transport), err)
+)]
+pub fn subscribe_finalized_heads<T: SubscriptionTransport>(transport: &T) -> Result<HeadStream> {
+    Ok(HeadStream {
+        subscription: transport.subscribe(
+            "chain_subscribeFinalizedHeads",
+            json!([]),
+            "chain_unsubscribeFinalizedHeads",
+        )?,
+    })
+}
+
+/// A stream of block headers. Created by [`subscribe_finalized_heads`].
+#[derive(Debug)]
+pub struct HeadStream {
+    subscription: Subscription,
+}
+
+impl Stream for HeadStream {
+    type Item = Result<Header>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let notification = ready!(Pin::new(&mut self.subscription).poll_next(cx));
+        Poll::Ready(notification.map(|header| Header::from_json(&header?)))
+    }
+}
+
+type Callback = Box<dyn FnMut(&FinalizedHead) + Send>;
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    updated: Condvar,
+    callbacks: Mutex<Vec<Callback>>,
+}
+
+#[derive(Default)]
+struct State {
+    latest: Option<FinalizedHead>,
+    /// The reason why the subscription ended, if it did.
+    closed: Option<String>,
+}
+
+/// Follows the finalized heads of the chain in a background thread. The
+/// subscription is cancelled once the follower is dropped and the next head
+/// is received.
+pub struct FinalizedFollower {
+    shared: Arc<Shared>,
+}
+
+impl FinalizedFollower {
+    /// Subscribes to the finalized heads and starts following those.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(transport), err)
+    )]
+    pub fn start<T: SubscriptionTransport>(transport: &T) -> Result<Self> {
+        let heads = subscribe_finalized_heads(transport)?;
+        let shared = Arc::new(Shared::default());
+
+        let weak = Arc::downgrade(&shared);
+        thread::Builder::new()
+            .name("gekko-follower".to_string())
+            .spawn(move || follow(weak, heads))
+            .map_err(|err| Error::RpcTransport(Box::new(err)))?;
+
+        Ok(FinalizedFollower { shared })
+    }
+    /// The latest finalized block, or `None` if no head was received yet.
+    pub fn latest(&self) -> Option<FinalizedHead> {
+        self.shared.state.lock().unwrap().latest
+    }
+    /// Registers a callback which is invoked for each new finalized head, from
+    /// the background thread. The callback must not register further
+    /// callbacks.
+    pub fn on_finalized<F>(&self, callback: F)
+    where
+        F: FnMut(&FinalizedHead) + Send + 'static,
+    {
+        self.shared
+            .callbacks
+            .lock()
+            .unwrap()
+            .push(Box::new(callback));
+    }
+    /// Blocks until a block with at least the given number is finalized and
+    /// returns the latest finalized block. Fails if the subscription ended or
+    /// the timeout elapsed.
+    pub fn wait_for(&self, number: u32, timeout: Duration) -> Result<FinalizedHead> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.shared.state.lock().unwrap();
+
+        loop {
+            if let Some(head) = state.latest.filter(|head| head.number >= number) {
+                return Ok(head);
+            }
+
+            if let Some(reason) = &state.closed {
+                return Err(transport_err(io::ErrorKind::ConnectionAborted, reason));
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(transport_err(
+                    io::ErrorKind::TimedOut,
+                    "timed out waiting for finalized head",
+                ));
+            }
+
+            state = self
+                .shared
+                .updated
+                .wait_timeout(state, deadline - now)
+                .unwrap()
+                .0;
+        }
+    }
+}
+
+impl std::fmt::Debug for FinalizedFollower {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FinalizedFollower")
+            .field("latest", &self.latest())
+            .finish()
+    }
+}
+
+/// Processes the heads until the follower is dropped or the subscription
+/// ends.
+fn follow(shared: Weak<Shared>, heads: HeadStream) {
+    for header in block_on_stream(heads) {
+        let shared = match shared.upgrade() {
+            Some(shared) => shared,
+            None => return,
+        };
+
+        let head = match header {
+            Ok(header) => FinalizedHead {
+                number: header.number,
+                hash: header.hash(),
+            },
+            Err(err) => return close(&shared, err.to_string()),
+        };
+
+        shared.state.lock().unwrap().latest = Some(head);
+        shared.updated.notify_all();
+
+        for callback in shared.callbacks.lock().unwrap().iter_mut() {
+            callback(&head);
+        }
+    }
+
+    if let Some(shared) = shared.upgrade() {
+        close(&shared, "subscription ended".to_string());
+    }
+}
+
+fn close(shared: &Shared, reason: String) {
+    shared.state.lock().unwrap().closed = Some(reason);
+    shared.updated.notify_all();
+}
+
+fn transport_err(kind: io::ErrorKind, message: &str) -> Error {
+    Error::RpcTransport(Box::new(io::Error::new(kind, message.to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::mock::MockTransport;
+    use parity_scale_codec::{Compact, Encode};
+    use serde_json::Value;
+    use std::sync::mpsc as std_mpsc;
+
+    fn header(number: u32) -> Value {
+        let hex = |bytes: &[u8]| format!("0x{}", hex::encode(bytes));
+        json!({
+            "parentHash": hex(&[number as u8 - 1; 32]),
+            "number": format!("0x{:x}", number),
+            "stateRoot": hex(&[1; 32]),
+            "extrinsicsRoot": hex(&[2; 32]),
+            "digest": { "logs": [hex(&[6, 0x42, 0x41, 0x42, 0x45, 0])] },
+        })
+    }
+
+    #[test]
+    fn follow_finalized_heads() {
+        let transport = MockTransport::new().with_notifications(
+            "chain_subscribeFinalizedHeads",
+            vec![header(10), header(11)],
+        );
+
+        let follower = FinalizedFollower::start(&transport).unwrap();
+        let (sender, receiver) = std_mpsc::channel();
+        follower.on_finalized(move |head| sender.send(head.number).unwrap());
+
+        let head = follower.wait_for(11, Duration::from_secs(5)).unwrap();
+        assert_eq!(head.number, 11);
+        assert_eq!(follower.latest(), Some(head));
+
+        let encoded = (
+            [10u8; 32],
+            Compact(11u32),
+            [1u8; 32],
+            [2u8; 32],
+            vec![(6u8, *b"BABE", Vec::<u8>::new())],
+        )
+            .encode();
+        assert_eq!(head.hash, crate::blake2b(encoded));
+
+        // The subscription ended after both heads.
+        assert!(follower.wait_for(12, Duration::from_secs(5)).is_err());
+        assert!(receiver
+            .try_iter()
+            .all(|number| number == 10 || number == 11));
+
+        assert_eq!(
+            head.mortality(50),
+            Mortality::Mortal(64, 11, Some(head.hash))
+        );
+    }
+}
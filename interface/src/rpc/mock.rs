@@ -0,0 +1,312 @@
+//! An in-memory [`Transport`] for testing applications without a live node.
+//!
+//! [`MockTransport`] serves canned responses for the runtime version,
+//! metadata, storage and any other method, records all requests and accepts
+//! submitted extrinsics. With the `"ws"` feature, it also delivers canned
+//! notifications of subscriptions.
+//!
+//! # Example
+//!
+//! ```
+//! use gekko::rpc::mock::MockTransport;
+//! use gekko::rpc::storage::storage_raw;
+//! use gekko::rpc::submit_extrinsic;
+//!
+//! let transport = MockTransport::new().with_storage(vec![1, 2], vec![3, 4]);
+//!
+//! assert_eq!(storage_raw(&transport, &[1, 2], None).unwrap(), Some(vec![3, 4]));
+//! assert_eq!(storage_raw(&transport, &[5], None).unwrap(), None);
+//!
+//! submit_extrinsic(&transport, &[4, 0, 1]).unwrap();
+//! assert_eq!(transport.submitted(), vec![vec![4, 0, 1]]);
+//! ```
+
+use super::block::Header;
+use super::{decode_hex_value, RuntimeVersion, Transport};
+use crate::{blake2b, Error, Result};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+#[cfg(feature = "ws")]
+use super::{Subscription, SubscriptionTransport};
+
+/// A canned response to a JSON-RPC request.
+#[derive(Debug, Clone)]
+enum Response {
+    Result(Value),
+    Error { code: i64, message: String },
+}
+
+impl Response {
+    fn to_result(&self) -> Result<Value> {
+        match self {
+            Response::Result(value) => Ok(value.clone()),
+            Response::Error { code, message } => Err(Error::RpcResponse {
+                code: *code,
+                message: message.clone(),
+            }),
+        }
+    }
+}
+
+/// An in-memory transport with canned responses. See the
+/// [module documentation](self).
+///
+/// Responses are looked up in the following order:
+///
+/// 1. The responses queued with [`respond_once`](Self::respond_once).
+/// 2. The responses set with [`respond`](Self::respond) or
+///    [`respond_error`](Self::respond_error).
+/// 3. The built-in handling of `state_getStorage`, `state_getMetadata`,
+///    `state_getRuntimeVersion`, `chain_getBlockHash`, `chain_getBlock`,
+///    `chain_getHeader` and `author_submitExtrinsic`, if configured.
+///
+/// Any other request fails with a JSON-RPC "method not found" error.
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    once: Mutex<HashMap<String, VecDeque<Response>>>,
+    responses: HashMap<String, Response>,
+    storage: HashMap<Vec<u8>, Vec<u8>>,
+    metadata: Option<Vec<u8>>,
+    runtime_version: Option<RuntimeVersion>,
+    genesis: Option<[u8; 32]>,
+    blocks: Vec<(Header, Vec<Vec<u8>>)>,
+    #[cfg(feature = "ws")]
+    notifications: HashMap<String, Vec<Value>>,
+    requests: Mutex<Vec<(String, Value)>>,
+    submitted: Mutex<Vec<Vec<u8>>>,
+}
+
+impl MockTransport {
+    /// Creates a transport without any responses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Responds to every request of the method with the given result.
+    pub fn respond<T: Into<String>>(mut self, method: T, result: Value) -> Self {
+        self.responses
+            .insert(method.into(), Response::Result(result));
+        self
+    }
+    /// Responds to every request of the method with a JSON-RPC error.
+    pub fn respond_error<T: Into<String>, M: Into<String>>(
+        mut self,
+        method: T,
+        code: i64,
+        message: M,
+    ) -> Self {
+        self.responses.insert(
+            method.into(),
+            Response::Error {
+                code,
+                message: message.into(),
+            },
+        );
+        self
+    }
+    /// Queues a result, which is returned once for the next request of the
+    /// method. Takes precedence over all other responses.
+    pub fn respond_once<T: Into<String>>(&self, method: T, result: Value) {
+        self.once
+            .lock()
+            .unwrap()
+            .entry(method.into())
+            .or_default()
+            .push_back(Response::Result(result));
+    }
+    /// Sets the value of the storage key, as returned by `state_getStorage`.
+    pub fn with_storage(mut self, key: Vec<u8>, value: Vec<u8>) -> Self {
+        self.storage.insert(key, value);
+        self
+    }
+    /// Sets the raw metadata, as returned by `state_getMetadata`, e.g. one of
+    /// the dumps of the `gekko-metadata` crate.
+    pub fn with_metadata(mut self, raw: Vec<u8>) -> Self {
+        self.metadata = Some(raw);
+        self
+    }
+    /// Sets the runtime version, as returned by `state_getRuntimeVersion`.
+    pub fn with_runtime_version(mut self, version: RuntimeVersion) -> Self {
+        self.runtime_version = Some(version);
+        self
+    }
+    /// Sets the genesis hash, as returned by `chain_getBlockHash` for block
+    /// zero.
+    pub fn with_genesis(mut self, genesis: [u8; 32]) -> Self {
+        self.genesis = Some(genesis);
+        self
+    }
+    /// Adds a block with the given header and SCALE encoded extrinsics, as
+    /// returned by `chain_getBlockHash`, `chain_getBlock` and
+    /// `chain_getHeader`. The last added block is the best block.
+    pub fn with_block(mut self, header: Header, extrinsics: Vec<Vec<u8>>) -> Self {
+        self.blocks.push((header, extrinsics));
+        self
+    }
+    /// Sets the notifications delivered to each subscription with the given
+    /// method. Requires the `"ws"` feature.
+    #[cfg(feature = "ws")]
+    pub fn with_notifications<T: Into<String>>(
+        mut self,
+        method: T,
+        notifications: Vec<Value>,
+    ) -> Self {
+        self.notifications.insert(method.into(), notifications);
+        self
+    }
+    /// All requests received so far, as pairs of the method and the
+    /// parameters.
+    pub fn requests(&self) -> Vec<(String, Value)> {
+        self.requests.lock().unwrap().clone()
+    }
+    /// All extrinsics submitted with `author_submitExtrinsic` so far.
+    pub fn submitted(&self) -> Vec<Vec<u8>> {
+        self.submitted.lock().unwrap().clone()
+    }
+    fn builtin(&self, method: &str, params: &Value) -> Option<Result<Value>> {
+        let hex = |bytes: &[u8]| json!(format!("0x{}", hex::encode(bytes)));
+
+        match method {
+            "state_getStorage" => Some(decode_hex_value(&params[0]).map(|key| {
+                self.storage
+                    .get(&key)
+                    .map(|value| hex(value))
+                    .unwrap_or(Value::Null)
+            })),
+            "state_getMetadata" => self.metadata.as_deref().map(|raw| Ok(hex(raw))),
+            "state_getRuntimeVersion" => self.runtime_version.as_ref().map(|version| {
+                serde_json::to_value(version).map_err(|err| Error::RpcTransport(Box::new(err)))
+            }),
+            "chain_getBlockHash" => {
+                let number = params[0].as_u64()?;
+                if number == 0 && self.genesis.is_some() {
+                    return self.genesis.map(|genesis| Ok(hex(&genesis)));
+                }
+
+                Some(Ok(self
+                    .blocks
+                    .iter()
+                    .find(|(header, _)| header.number as u64 == number)
+                    .map(|(header, _)| hex(&header.hash()))
+                    .unwrap_or(Value::Null)))
+            }
+            "chain_getHeader" if params[0].is_null() => Some(Ok(self
+                .blocks
+                .last()
+                .map(|(header, _)| header.to_json())
+                .unwrap_or(Value::Null))),
+            "chain_getBlock" | "chain_getHeader" => {
+                let block = decode_hex_value(&params[0])
+                    .map(|hash| {
+                        self.blocks
+                            .iter()
+                            .find(|(header, _)| header.hash()[..] == hash[..])
+                    })
+                    .transpose()?;
+
+                Some(block.map(|(header, extrinsics)| {
+                    if method == "chain_getHeader" {
+                        return header.to_json();
+                    }
+
+                    json!({
+                        "block": {
+                            "header": header.to_json(),
+                            "extrinsics": extrinsics.iter().map(|e| hex(e)).collect::<Vec<Value>>(),
+                        },
+                        "justifications": null,
+                    })
+                }))
+            }
+            "author_submitExtrinsic" => Some(decode_hex_value(&params[0]).map(|extrinsic| {
+                let hash = blake2b(&extrinsic);
+                self.submitted.lock().unwrap().push(extrinsic);
+                hex(&hash)
+            })),
+            _ => None,
+        }
+    }
+}
+
+impl Transport for MockTransport {
+    fn request(&self, method: &str, params: Value) -> Result<Value> {
+        self.requests
+            .lock()
+            .unwrap()
+            .push((method.to_string(), params.clone()));
+
+        let once = self
+            .once
+            .lock()
+            .unwrap()
+            .get_mut(method)
+            .and_then(VecDeque::pop_front);
+
+        if let Some(response) = once.as_ref().or_else(|| self.responses.get(method)) {
+            return response.to_result();
+        }
+
+        self.builtin(method, &params).unwrap_or_else(|| {
+            Err(Error::RpcResponse {
+                code: -32601,
+                message: format!("Method not found: {}", method),
+            })
+        })
+    }
+}
+
+#[cfg(feature = "ws")]
+impl SubscriptionTransport for MockTransport {
+    /// Delivers the notifications set with
+    /// [`with_notifications`](Self::with_notifications), after which the
+    /// subscription ends.
+    fn subscribe(&self, method: &str, params: Value, _unsubscribe: &str) -> Result<Subscription> {
+        self.requests
+            .lock()
+            .unwrap()
+            .push((method.to_string(), params));
+
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        for notification in self.notifications.get(method).into_iter().flatten() {
+            // The receiver is alive.
+            let _ = sender.unbounded_send(Ok(notification.clone()));
+        }
+
+        Ok(Subscription::new(receiver, || {}))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canned_responses() {
+        let transport = MockTransport::new()
+            .respond("system_name", json!("gekko"))
+            .respond_error("system_health", 1, "unhealthy");
+
+        transport.respond_once("system_name", json!("once"));
+
+        assert_eq!(transport.request("system_name", json!([])).unwrap(), "once");
+        assert_eq!(
+            transport.request("system_name", json!([])).unwrap(),
+            "gekko"
+        );
+        assert!(matches!(
+            transport.request("system_health", json!([])),
+            Err(Error::RpcResponse { code: 1, .. })
+        ));
+        assert!(matches!(
+            transport.request("system_chain", json!([])),
+            Err(Error::RpcResponse { code: -32601, .. })
+        ));
+
+        assert_eq!(transport.requests().len(), 4);
+        assert_eq!(
+            transport.requests()[0],
+            ("system_name".to_string(), json!([]))
+        );
+    }
+}
@@ -0,0 +1,277 @@
+//! Utilities to interact with Substrate nodes via JSON-RPC. Requires the
+//! `"rpc"` feature.
+//!
+//! All functionality is generic over the [`Transport`] trait, which sends a
+//! single JSON-RPC request and returns its result. [`HttpClient`] is the
+//! default implementation.
+//!
+//! With the `"ws"` feature, [`WsClient`] additionally implements
+//! [`SubscriptionTransport`], which delivers the notifications of JSON-RPC
+//! subscriptions as an asynchronous [`Stream`](futures::Stream). Streams can be
+//! consumed synchronously with
+//! [`block_on_stream`](futures::executor::block_on_stream).
+//!
+//! With the `"async"` feature, the [`client`] module offers asynchronous
+//! counterparts of the common helpers, which connect to a URL.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use gekko::rpc::*;
+//!
+//! let client = HttpClient::new("https://rpc.polkadot.io");
+//! let version = runtime_version(&client, None).unwrap();
+//!
+//! println!("Current spec version: {}", version.spec_version);
+//! ```
+
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::convert::{TryFrom, TryInto};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "ws")]
+pub use self::subscription::{Subscription, SubscriptionTransport};
+#[cfg(feature = "ws")]
+pub use self::ws::WsClient;
+
+pub mod account;
+pub mod api;
+pub mod archive;
+pub mod block;
+#[cfg(feature = "async")]
+pub mod client;
+#[cfg(feature = "dev-node")]
+pub mod dev;
+#[cfg(feature = "ws")]
+pub mod events;
+pub mod fee;
+#[cfg(feature = "ws")]
+pub mod follow;
+pub mod mock;
+pub mod storage;
+#[cfg(feature = "ws")]
+mod subscription;
+pub mod upgrade;
+#[cfg(feature = "ws")]
+pub mod watch;
+#[cfg(feature = "ws")]
+mod ws;
+
+/// A transport which sends JSON-RPC requests to a Substrate node.
+pub trait Transport {
+    /// Sends the request with the given method and parameters and returns the
+    /// `result` field of the response.
+    fn request(&self, method: &str, params: Value) -> Result<Value>;
+}
+
+/// A blocking JSON-RPC client over HTTP(S).
+#[derive(Debug)]
+pub struct HttpClient {
+    url: String,
+    agent: ureq::Agent,
+    next_id: AtomicU64,
+}
+
+impl HttpClient {
+    /// Creates a new client for the given endpoint, e.g.
+    /// `"https://rpc.polkadot.io"`.
+    pub fn new<T: Into<String>>(url: T) -> Self {
+        HttpClient {
+            url: url.into(),
+            agent: ureq::Agent::new(),
+            next_id: AtomicU64::new(1),
+        }
+    }
+}
+
+impl Transport for HttpClient {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, params), fields(url = %self.url), err)
+    )]
+    fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+
+        let resp: Value = self
+            .agent
+            .post(&self.url)
+            .send_json(body)
+            .map_err(|err| Error::RpcTransport(Box::new(err)))?
+            .into_json()
+            .map_err(|err| Error::RpcTransport(Box::new(err)))?;
+
+        parse_response(resp)
+    }
+}
+
+/// Extracts the `result` field of a JSON-RPC response, or converts the `error`
+/// field into [`Error::RpcResponse`].
+pub(crate) fn parse_response(mut resp: Value) -> Result<Value> {
+    if let Some(err) = resp.get("error") {
+        return Err(Error::RpcResponse {
+            code: err["code"].as_i64().unwrap_or_default(),
+            message: err["message"].as_str().unwrap_or_default().to_string(),
+        });
+    }
+
+    match resp.get_mut("result") {
+        Some(result) => Ok(result.take()),
+        None => Err(Error::RpcInvalidResponse(
+            "missing result in JSON-RPC response".to_string(),
+        )),
+    }
+}
+
+/// The version of a runtime, as returned by `state_getRuntimeVersion`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeVersion {
+    pub spec_name: String,
+    pub impl_name: String,
+    pub authoring_version: u32,
+    pub spec_version: u32,
+    pub impl_version: u32,
+    pub transaction_version: u32,
+    /// The supported runtime APIs as pairs of the HEX encoded API identifier
+    /// and its version.
+    #[serde(default)]
+    pub apis: Vec<(String, u32)>,
+}
+
+/// Converts the optional block hash into a JSON-RPC parameter.
+pub(crate) fn block_hash_param(at: Option<[u8; 32]>) -> Value {
+    match at {
+        Some(hash) => Value::String(format!("0x{}", hex::encode(hash))),
+        None => Value::Null,
+    }
+}
+
+/// Decodes a HEX encoded (`0x`-prefixed) JSON string.
+pub(crate) fn decode_hex_value(value: &Value) -> Result<Vec<u8>> {
+    let hex_str = value
+        .as_str()
+        .ok_or_else(|| Error::RpcInvalidResponse(format!("expected HEX string, got {}", value)))?;
+
+    gekko_metadata::decode_hex(hex_str).map_err(|err| Error::RpcInvalidResponse(err.to_string()))
+}
+
+/// Fetches the runtime version at the given block, or at the best block if
+/// `at` is `None`.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(transport), err)
+)]
+pub fn runtime_version<T: Transport>(
+    transport: &T,
+    at: Option<[u8; 32]>,
+) -> Result<RuntimeVersion> {
+    let resp = transport.request("state_getRuntimeVersion", json!([block_hash_param(at)]))?;
+    serde_json::from_value(resp).map_err(|err| Error::RpcInvalidResponse(err.to_string()))
+}
+
+/// Fetches the raw runtime metadata at the given block, or at the best block
+/// if `at` is `None`. The metadata can be parsed with
+/// [`parse_raw_metadata`](gekko_metadata::parse_raw_metadata).
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(transport), err)
+)]
+pub fn metadata<T: Transport>(transport: &T, at: Option<[u8; 32]>) -> Result<Vec<u8>> {
+    let resp = transport.request("state_getMetadata", json!([block_hash_param(at)]))?;
+    decode_hex_value(&resp)
+}
+
+/// Fetches the next nonce of the account, including the transactions in the
+/// transaction pool of the node. The account must be SS58 encoded.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(transport), err)
+)]
+pub fn account_next_index<T: Transport + ?Sized>(transport: &T, account: &str) -> Result<u32> {
+    let resp = transport.request("system_accountNextIndex", json!([account]))?;
+    resp.as_u64()
+        .and_then(|nonce| u32::try_from(nonce).ok())
+        .ok_or_else(|| Error::RpcInvalidResponse(format!("invalid nonce: {}", resp)))
+}
+
+/// Submits the SCALE encoded extrinsic to the transaction pool of the node and
+/// returns its hash.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(transport, extrinsic), err)
+)]
+pub fn submit_extrinsic<T: Transport>(transport: &T, extrinsic: &[u8]) -> Result<[u8; 32]> {
+    let extrinsic = format!("0x{}", hex::encode(extrinsic));
+    let resp = transport.request("author_submitExtrinsic", json!([extrinsic]))?;
+
+    decode_hex_value(&resp)?
+        .try_into()
+        .map_err(|_| Error::RpcInvalidResponse(format!("invalid extrinsic hash: {}", resp)))
+}
+
+/// Fetches the current runtime version and verifies that it matches the
+/// `spec_version` and `transaction_version` a transaction was signed with.
+/// Transactions signed for another runtime version are invalid, so this should
+/// be called right before submission.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(transport), err)
+)]
+pub fn check_runtime_version<T: Transport>(
+    transport: &T,
+    spec_version: u32,
+    transaction_version: u32,
+) -> Result<()> {
+    let version = runtime_version(transport, None)?;
+    if version.spec_version != spec_version || version.transaction_version != transaction_version {
+        return Err(Error::RuntimeVersionMismatch {
+            spec_version,
+            transaction_version,
+            node_spec_version: version.spec_version,
+            node_transaction_version: version.transaction_version,
+        });
+    }
+
+    Ok(())
+}
+
+/// Like [`submit_extrinsic`], but refuses to submit the extrinsic if the
+/// runtime version of the node does not match the given versions. See
+/// [`check_runtime_version`].
+pub fn submit_extrinsic_checked<T: Transport>(
+    transport: &T,
+    extrinsic: &[u8],
+    spec_version: u32,
+    transaction_version: u32,
+) -> Result<[u8; 32]> {
+    check_runtime_version(transport, spec_version, transaction_version)?;
+    submit_extrinsic(transport, extrinsic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mock::MockTransport;
+    use super::*;
+
+    #[test]
+    fn account_next_index_out_of_range() {
+        let account = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+        let transport = MockTransport::new().respond("system_accountNextIndex", json!(7));
+        assert_eq!(account_next_index(&transport, account).unwrap(), 7);
+
+        let transport =
+            MockTransport::new().respond("system_accountNextIndex", json!(u64::from(u32::MAX) + 1));
+        assert!(matches!(
+            account_next_index(&transport, account),
+            Err(Error::RpcInvalidResponse(_))
+        ));
+    }
+}
@@ -0,0 +1,169 @@
+//! Queries of storage entries, combining the storage key computation, the
+//! JSON-RPC request and decoding of the value.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use gekko::common::scale::Encode;
+//! use gekko::metadata::parse_raw_metadata;
+//! use gekko::rpc::{metadata, HttpClient};
+//! use gekko::rpc::storage::storage;
+//!
+//! let client = HttpClient::new("https://rpc.polkadot.io");
+//! let data = parse_raw_metadata(metadata(&client, None).unwrap())
+//!     .unwrap()
+//!     .into_latest()
+//!     .unwrap();
+//!
+//! // The current block number.
+//! let number: Option<u32> = storage(&client, &data, "System", "Number", &[], None).unwrap();
+//! ```
+
+use super::{block_hash_param, decode_hex_value, Transport};
+use crate::{Error, Result};
+use gekko_metadata::version::v13::StorageEntryModifier;
+use gekko_metadata::StorageMetadataExt;
+use parity_scale_codec::Decode;
+use serde_json::json;
+
+/// Fetches the raw value of the storage key at the given block, or at the best
+/// block if `at` is `None`. Returns `None` if the value is not set.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(transport, key), err)
+)]
+pub fn storage_raw<T: Transport>(
+    transport: &T,
+    key: &[u8],
+    at: Option<[u8; 32]>,
+) -> Result<Option<Vec<u8>>> {
+    let key = format!("0x{}", hex::encode(key));
+    let resp = transport.request("state_getStorage", json!([key, block_hash_param(at)]))?;
+
+    if resp.is_null() {
+        Ok(None)
+    } else {
+        decode_hex_value(&resp).map(Some)
+    }
+}
+
+/// Fetches and decodes the value of the storage entry at the given block, or
+/// at the best block if `at` is `None`.
+///
+/// The keys must be SCALE encoded, one for each hasher of the entry. Returns
+/// `None` if the value is not set and the entry has no default value.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(transport, metadata, keys), err)
+)]
+pub fn storage<T: Transport, M: StorageMetadataExt, V: Decode>(
+    transport: &T,
+    metadata: &M,
+    module: &str,
+    entry: &str,
+    keys: &[&[u8]],
+    at: Option<[u8; 32]>,
+) -> Result<Option<V>> {
+    let info =
+        metadata
+            .find_module_storage(module, entry)
+            .ok_or_else(|| Error::StorageEntryNotFound {
+                module: module.to_string(),
+                entry: entry.to_string(),
+            })?;
+
+    let key = info.storage_key(keys)?;
+    let value = match storage_raw(transport, &key, at)? {
+        Some(value) => value,
+        None => match info.modifier {
+            StorageEntryModifier::Default => info.default.to_vec(),
+            StorageEntryModifier::Optional => return Ok(None),
+        },
+    };
+
+    Ok(Some(V::decode(&mut value.as_slice())?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::mock::MockTransport;
+    use gekko_metadata::{parse_hex_metadata, version::MetadataV13};
+    use std::convert::TryFrom;
+
+    fn metadata() -> MetadataV13 {
+        parse_hex_metadata(include_str!("../../dumps/metadata_kusama_9080.hex").trim())
+            .unwrap()
+            .into_latest()
+            .unwrap()
+    }
+
+    #[test]
+    fn storage_key_of_map() {
+        let data = metadata();
+        let info = data.find_module_storage("System", "Account").unwrap();
+
+        // Alice
+        let account =
+            hex::decode("d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d")
+                .unwrap();
+
+        assert_eq!(
+            hex::encode(info.storage_key(&[&account]).unwrap()),
+            "26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da9\
+             de1e86a9a8c739864cf3cc5ec2bea59f\
+             d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+        );
+        assert!(info.storage_key(&[]).is_err());
+
+        // The keys are encoded by the builder.
+        let account = <[u8; 32]>::try_from(account).unwrap();
+        assert_eq!(
+            info.key().arg(&account).build().unwrap(),
+            info.storage_key(&[&account]).unwrap()
+        );
+        assert_eq!(
+            info.key_for(&[account]).unwrap(),
+            info.storage_key(&[&account]).unwrap()
+        );
+        assert!(info
+            .key()
+            .build_hex()
+            .unwrap_err()
+            .to_string()
+            .contains("expected 1 storage keys"));
+    }
+
+    #[test]
+    fn storage_value_and_default() {
+        let data = metadata();
+
+        let key = hex::decode("26aa394eea5630e07c48ae0c9558cef702a5c1b19ab7a04f536c519aca4983ac")
+            .unwrap();
+        let transport = MockTransport::new().with_storage(key, vec![0x2a, 0, 0, 0]);
+        let number: Option<u32> =
+            storage(&transport, &data, "System", "Number", &[], None).unwrap();
+
+        assert_eq!(number, Some(42));
+        assert_eq!(
+            transport.requests(),
+            vec![(
+                "state_getStorage".to_string(),
+                json!([
+                    "0x26aa394eea5630e07c48ae0c9558cef702a5c1b19ab7a04f536c519aca4983ac",
+                    null
+                ])
+            )]
+        );
+
+        // Falls back to the default value, or `None` for optional entries.
+        let transport = MockTransport::new();
+        let number: Option<u32> =
+            storage(&transport, &data, "System", "Number", &[], None).unwrap();
+        let count: Option<u32> =
+            storage(&transport, &data, "System", "ExtrinsicCount", &[], None).unwrap();
+
+        assert_eq!(number, Some(0));
+        assert_eq!(count, None);
+    }
+}
@@ -0,0 +1,57 @@
+use super::Transport;
+use crate::Result;
+use futures::channel::mpsc::UnboundedReceiver;
+use futures::task::{Context, Poll};
+use futures::Stream;
+use serde_json::Value;
+use std::pin::Pin;
+
+/// A transport which additionally supports JSON-RPC subscriptions.
+pub trait SubscriptionTransport: Transport {
+    /// Subscribes with the given method and parameters. The subscription is
+    /// cancelled with the `unsubscribe` method once it is dropped.
+    fn subscribe(&self, method: &str, params: Value, unsubscribe: &str) -> Result<Subscription>;
+}
+
+/// The notifications of a JSON-RPC subscription, i.e. the `result` field of
+/// each notification. Unsubscribes when dropped.
+pub struct Subscription {
+    notifications: UnboundedReceiver<Result<Value>>,
+    unsubscribe: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl Subscription {
+    /// Creates a subscription from the channel of notifications. `unsubscribe`
+    /// is called once the subscription is dropped.
+    pub fn new<F>(notifications: UnboundedReceiver<Result<Value>>, unsubscribe: F) -> Self
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        Subscription {
+            notifications,
+            unsubscribe: Some(Box::new(unsubscribe)),
+        }
+    }
+}
+
+impl Stream for Subscription {
+    type Item = Result<Value>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.notifications).poll_next(cx)
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if let Some(unsubscribe) = self.unsubscribe.take() {
+            unsubscribe();
+        }
+    }
+}
+
+impl std::fmt::Debug for Subscription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Subscription").finish_non_exhaustive()
+    }
+}
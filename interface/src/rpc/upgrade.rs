@@ -0,0 +1,214 @@
+//! Detection of runtime upgrades which might break the compiled-in runtime
+//! interfaces.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # #[cfg(feature = "runtime-polkadot")]
+//! # {
+//! use gekko::rpc::HttpClient;
+//! use gekko::rpc::upgrade::{check_runtime_upgrade, Compatibility};
+//! use gekko::runtime::polkadot::SpecVersion;
+//!
+//! let client = HttpClient::new("https://rpc.polkadot.io");
+//! let compiled = SpecVersion::latest().compiled_runtime();
+//!
+//! let report = check_runtime_upgrade(&client, &compiled).unwrap();
+//! match report.compatibility {
+//!     Compatibility::Compatible => println!("Ready to sign"),
+//!     Compatibility::CallIndicesChanged(changes) => panic!("Outdated calls: {:?}", changes),
+//!     Compatibility::Unknown => panic!("Cannot verify the runtime"),
+//! }
+//! # }
+//! ```
+
+use super::{metadata, runtime_version, RuntimeVersion, Transport};
+use crate::runtime::CompiledRuntime;
+use crate::{blake2b, Result};
+use gekko_metadata::parse_raw_metadata;
+
+/// The result of comparing a live runtime against a compiled-in runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpgradeReport {
+    /// The version of the live runtime.
+    pub runtime_version: RuntimeVersion,
+    /// The Blake2b-256 hash of the raw metadata of the live runtime.
+    pub metadata_hash: [u8; 32],
+    /// Whether the compiled-in interfaces can be used with the live runtime.
+    pub compatibility: Compatibility,
+}
+
+/// Whether the compiled-in interfaces can be used with the live runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Compatibility {
+    /// The live runtime is the same as the compiled-in runtime, or all call
+    /// indices and the `transaction_version` are unchanged.
+    Compatible,
+    /// The indices of some calls changed or the calls no longer exist.
+    CallIndicesChanged(Vec<CallIndexChange>),
+    /// Compatibility cannot be determined. Either the metadata could not be
+    /// parsed or the `transaction_version` changed while all call indices
+    /// remained the same, meaning the call arguments might have changed.
+    Unknown,
+}
+
+/// A call whose indices differ between the compiled-in and the live runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallIndexChange {
+    pub module_name: &'static str,
+    pub extrinsic_name: &'static str,
+    /// The `(module Id, dispatch Id)` of the compiled-in runtime.
+    pub expected: (u8, u8),
+    /// The `(module Id, dispatch Id)` of the live runtime, or `None` if the
+    /// call no longer exists.
+    pub actual: Option<(u8, u8)>,
+}
+
+/// Fetches the live runtime version and metadata and compares those against
+/// the compiled-in runtime.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip(transport, compiled),
+        fields(spec_version = compiled.spec_version),
+        err
+    )
+)]
+pub fn check_runtime_upgrade<T: Transport>(
+    transport: &T,
+    compiled: &CompiledRuntime,
+) -> Result<UpgradeReport> {
+    let runtime_version = runtime_version(transport, None)?;
+    let raw_metadata = metadata(transport, None)?;
+
+    Ok(UpgradeReport {
+        compatibility: compatibility(compiled, &runtime_version, &raw_metadata),
+        metadata_hash: blake2b(&raw_metadata),
+        runtime_version,
+    })
+}
+
+/// Compares the given runtime version and raw metadata (including the magic
+/// number) against the compiled-in runtime. Does not require a connection to
+/// a node.
+pub fn compatibility(
+    compiled: &CompiledRuntime,
+    runtime_version: &RuntimeVersion,
+    raw_metadata: &[u8],
+) -> Compatibility {
+    if blake2b(raw_metadata) == compiled.metadata_hash {
+        return Compatibility::Compatible;
+    }
+
+    let metadata = match parse_raw_metadata(raw_metadata) {
+        Ok(metadata) => metadata,
+        Err(_) => return Compatibility::Unknown,
+    };
+    let data = match metadata.as_inner() {
+        Ok(data) => data,
+        Err(_) => return Compatibility::Unknown,
+    };
+
+    let changes: Vec<CallIndexChange> = compiled
+        .call_indices
+        .iter()
+        .filter_map(|&(module_name, extrinsic_name, module_id, dispatch_id)| {
+            let actual = data
+                .find_module_extrinsic(module_name, extrinsic_name)
+                .map(|info| (info.module_id as u8, info.dispatch_id as u8));
+
+            if actual == Some((module_id, dispatch_id)) {
+                None
+            } else {
+                Some(CallIndexChange {
+                    module_name,
+                    extrinsic_name,
+                    expected: (module_id, dispatch_id),
+                    actual,
+                })
+            }
+        })
+        .collect();
+
+    if !changes.is_empty() {
+        Compatibility::CallIndicesChanged(changes)
+    } else if runtime_version.transaction_version != compiled.transaction_version {
+        Compatibility::Unknown
+    } else {
+        Compatibility::Compatible
+    }
+}
+
+#[cfg(all(test, feature = "runtime-kusama", feature = "runtime-polkadot"))]
+mod tests {
+    use super::*;
+    use crate::runtime::{kusama, polkadot};
+
+    fn raw_metadata(content: &str) -> Vec<u8> {
+        gekko_metadata::decode_hex(content.trim()).unwrap()
+    }
+
+    fn runtime_version(compiled: &CompiledRuntime) -> RuntimeVersion {
+        RuntimeVersion {
+            spec_name: "kusama".to_string(),
+            impl_name: "parity-kusama".to_string(),
+            authoring_version: 2,
+            spec_version: compiled.spec_version,
+            impl_version: 0,
+            transaction_version: compiled.transaction_version,
+            apis: vec![],
+        }
+    }
+
+    #[test]
+    fn same_runtime_is_compatible() {
+        let compiled = kusama::SpecVersion::latest().compiled_runtime();
+        let raw = raw_metadata(include_str!("../../dumps/metadata_kusama_9080.hex"));
+
+        assert_eq!(
+            compatibility(&compiled, &runtime_version(&compiled), &raw),
+            Compatibility::Compatible
+        );
+    }
+
+    #[test]
+    fn different_runtime_changes_call_indices() {
+        let compiled = kusama::SpecVersion::latest().compiled_runtime();
+        let raw = raw_metadata(include_str!("../../dumps/metadata_polkadot_9050.hex"));
+        let version = runtime_version(&polkadot::SpecVersion::latest().compiled_runtime());
+
+        match compatibility(&compiled, &version, &raw) {
+            Compatibility::CallIndicesChanged(changes) => {
+                // Kusama has modules which do not exist on Polkadot.
+                assert!(changes
+                    .iter()
+                    .any(|change| change.module_name == "Society" && change.actual.is_none()));
+            }
+            other => panic!("unexpected compatibility: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn compare_against_metadata_v14() {
+        let compiled = polkadot::SpecVersion::latest().compiled_runtime();
+        let raw = raw_metadata(include_str!("../../dumps/metadata_polkadot_1001002.hex"));
+        let version = runtime_version(&compiled);
+
+        match compatibility(&compiled, &version, &raw) {
+            Compatibility::CallIndicesChanged(changes) => {
+                let change = |module: &str, call: &str| {
+                    changes.iter().find(|change| {
+                        change.module_name == module && change.extrinsic_name == call
+                    })
+                };
+
+                // Removed from the live runtime.
+                assert_eq!(change("Balances", "transfer").unwrap().actual, None);
+                // Unchanged.
+                assert_eq!(change("Balances", "transfer_keep_alive"), None);
+            }
+            other => panic!("unexpected compatibility: {:?}", other),
+        }
+    }
+}
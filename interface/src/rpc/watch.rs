@@ -0,0 +1,224 @@
+//! Submitting extrinsics and watching their status in the transaction pool,
+//! via `author_submitAndWatchExtrinsic`. Requires the `"ws"` feature.
+//!
+//! Unlike [`submit_extrinsic`](super::submit_extrinsic), which only returns
+//! the hash of the extrinsic, the status stream reports whether the extrinsic
+//! was included in a block and finalized, or dropped from the pool.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use gekko::rpc::watch::{submit_and_watch, TransactionStatus};
+//! use gekko::rpc::WsClient;
+//! use futures::executor::block_on_stream;
+//!
+//! # let extrinsic: Vec<u8> = vec![];
+//! let client = WsClient::connect("wss://rpc.polkadot.io").unwrap();
+//!
+//! for status in block_on_stream(submit_and_watch(&client, &extrinsic).unwrap()) {
+//!     match status.unwrap() {
+//!         TransactionStatus::InBlock(hash) => println!("Included in 0x{}", hex::encode(hash)),
+//!         TransactionStatus::Finalized(hash) => println!("Finalized in 0x{}", hex::encode(hash)),
+//!         status => println!("{:?}", status),
+//!     }
+//! }
+//! ```
+
+use super::{decode_hex_value, Subscription, SubscriptionTransport};
+use crate::{Error, Result};
+use futures::task::{Context, Poll};
+use futures::{ready, Stream};
+use serde_json::{json, Value};
+use std::convert::TryInto;
+use std::pin::Pin;
+
+/// The status of an extrinsic in the transaction pool of the node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionStatus {
+    /// The extrinsic is part of the future queue, e.g. because of a nonce gap.
+    Future,
+    /// The extrinsic is part of the ready queue.
+    Ready,
+    /// The extrinsic was broadcast to the given peers.
+    Broadcast(Vec<String>),
+    /// The extrinsic was included in the block with the given hash.
+    InBlock([u8; 32]),
+    /// The block with the given hash, which included the extrinsic, was
+    /// retracted from the best chain.
+    Retracted([u8; 32]),
+    /// The block with the given hash, which included the extrinsic, was not
+    /// finalized within the timeout. The extrinsic is no longer watched.
+    FinalityTimeout([u8; 32]),
+    /// The block with the given hash, which included the extrinsic, was
+    /// finalized.
+    Finalized([u8; 32]),
+    /// The extrinsic was replaced by the extrinsic with the given hash, e.g.
+    /// one with the same nonce and a higher tip.
+    Usurped([u8; 32]),
+    /// The extrinsic was dropped from the pool, e.g. because the pool is full.
+    Dropped,
+    /// The extrinsic is invalid, e.g. because of a bad signature.
+    Invalid,
+}
+
+impl TransactionStatus {
+    /// Converts the JSON representation of a status notification.
+    pub fn from_json(value: &Value) -> Result<Self> {
+        let status = match value {
+            Value::String(status) => match status.as_str() {
+                "future" => TransactionStatus::Future,
+                "ready" => TransactionStatus::Ready,
+                "dropped" => TransactionStatus::Dropped,
+                "invalid" => TransactionStatus::Invalid,
+                _ => return Err(invalid_status(value)),
+            },
+            Value::Object(status) if status.len() == 1 => {
+                let (kind, data) = status.iter().next().unwrap();
+                match kind.as_str() {
+                    "broadcast" => TransactionStatus::Broadcast(
+                        serde_json::from_value(data.clone()).map_err(|_| invalid_status(value))?,
+                    ),
+                    "inBlock" => TransactionStatus::InBlock(hash_value(data)?),
+                    "retracted" => TransactionStatus::Retracted(hash_value(data)?),
+                    "finalityTimeout" => TransactionStatus::FinalityTimeout(hash_value(data)?),
+                    "finalized" => TransactionStatus::Finalized(hash_value(data)?),
+                    "usurped" => TransactionStatus::Usurped(hash_value(data)?),
+                    _ => return Err(invalid_status(value)),
+                }
+            }
+            _ => return Err(invalid_status(value)),
+        };
+
+        Ok(status)
+    }
+    /// Whether the extrinsic is no longer watched after this status, i.e. the
+    /// status stream ends.
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            TransactionStatus::FinalityTimeout(_)
+                | TransactionStatus::Finalized(_)
+                | TransactionStatus::Usurped(_)
+                | TransactionStatus::Dropped
+                | TransactionStatus::Invalid
+        )
+    }
+}
+
+fn hash_value(value: &Value) -> Result<[u8; 32]> {
+    decode_hex_value(value)?
+        .try_into()
+        .map_err(|_| Error::RpcInvalidResponse(format!("invalid hash: {}", value)))
+}
+
+fn invalid_status(value: &Value) -> Error {
+    Error::RpcInvalidResponse(format!("invalid transaction status: {}", value))
+}
+
+/// Submits the SCALE encoded extrinsic to the transaction pool of the node and
+/// watches its status. The stream ends after the final status, see
+/// [`TransactionStatus::is_final`].
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(transport, extrinsic), err)
+)]
+pub fn submit_and_watch<T: SubscriptionTransport>(
+    transport: &T,
+    extrinsic: &[u8],
+) -> Result<StatusStream> {
+    let extrinsic = format!("0x{}", hex::encode(extrinsic));
+
+    Ok(StatusStream {
+        subscription: Some(transport.subscribe(
+            "author_submitAndWatchExtrinsic",
+            json!([extrinsic]),
+            "author_unwatchExtrinsic",
+        )?),
+    })
+}
+
+/// A stream of the status of an extrinsic. Created by [`submit_and_watch`].
+#[derive(Debug)]
+pub struct StatusStream {
+    /// `None` after the final status, which unsubscribes.
+    subscription: Option<Subscription>,
+}
+
+impl Stream for StatusStream {
+    type Item = Result<TransactionStatus>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let subscription = match self.subscription.as_mut() {
+            Some(subscription) => subscription,
+            None => return Poll::Ready(None),
+        };
+
+        let status = ready!(Pin::new(subscription).poll_next(cx))
+            .map(|status| TransactionStatus::from_json(&status?));
+
+        if let Some(Ok(status)) = &status {
+            if status.is_final() {
+                self.subscription = None;
+            }
+        }
+
+        Poll::Ready(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::mock::MockTransport;
+    use futures::executor::block_on_stream;
+
+    #[test]
+    fn watch_extrinsic_status() {
+        let block = format!("0x{}", hex::encode([1; 32]));
+        let transport = MockTransport::new().with_notifications(
+            "author_submitAndWatchExtrinsic",
+            vec![
+                json!("ready"),
+                json!({ "broadcast": ["12D3KooW"] }),
+                json!({ "inBlock": block }),
+                json!({ "finalized": block }),
+                // Not delivered after the final status.
+                json!("dropped"),
+            ],
+        );
+
+        let statuses = block_on_stream(submit_and_watch(&transport, &[4, 0]).unwrap())
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(
+            statuses,
+            vec![
+                TransactionStatus::Ready,
+                TransactionStatus::Broadcast(vec!["12D3KooW".to_string()]),
+                TransactionStatus::InBlock([1; 32]),
+                TransactionStatus::Finalized([1; 32]),
+            ]
+        );
+        assert_eq!(
+            transport.requests(),
+            vec![(
+                "author_submitAndWatchExtrinsic".to_string(),
+                json!(["0x0400"])
+            )]
+        );
+    }
+
+    #[test]
+    fn parse_transaction_status() {
+        assert_eq!(
+            TransactionStatus::from_json(&json!("invalid")).unwrap(),
+            TransactionStatus::Invalid
+        );
+        assert!(TransactionStatus::Invalid.is_final());
+        assert!(!TransactionStatus::InBlock([0; 32]).is_final());
+
+        assert!(TransactionStatus::from_json(&json!("unknown")).is_err());
+        assert!(TransactionStatus::from_json(&json!({ "inBlock": "0x01" })).is_err());
+    }
+}
@@ -0,0 +1,395 @@
+//! A JSON-RPC client over WebSocket, supporting subscriptions.
+
+use super::{parse_response, Subscription, SubscriptionTransport, Transport};
+use crate::{Error, Result};
+use futures::channel::mpsc as stream;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io;
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+/// How long the connection thread waits for incoming messages before
+/// processing outgoing requests.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+type Socket = WebSocket<MaybeTlsStream<TcpStream>>;
+
+/// A blocking JSON-RPC client over WebSocket, e.g. `"wss://rpc.polkadot.io"`.
+///
+/// The connection is handled by a background thread, which forwards the
+/// notifications of subscriptions to the corresponding [`Subscription`]. The
+/// thread exits once the client and all subscriptions are dropped.
+#[derive(Debug)]
+pub struct WsClient {
+    url: String,
+    commands: mpsc::Sender<Command>,
+    next_id: AtomicU64,
+}
+
+enum Command {
+    Request {
+        id: u64,
+        body: String,
+        response: mpsc::Sender<Result<Value>>,
+    },
+    Subscribe {
+        id: u64,
+        body: String,
+        response: mpsc::Sender<Result<Value>>,
+        notifications: stream::UnboundedSender<Result<Value>>,
+    },
+    Unsubscribe {
+        subscription: String,
+        body: String,
+    },
+}
+
+impl WsClient {
+    /// Connects to the given endpoint.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+    pub fn connect(url: &str) -> Result<Self> {
+        let (socket, _) = tungstenite::connect(url).map_err(transport_err)?;
+
+        // The connection thread must regularly process outgoing requests.
+        match socket.get_ref() {
+            MaybeTlsStream::Plain(stream) => stream.set_read_timeout(Some(POLL_INTERVAL)),
+            MaybeTlsStream::Rustls(stream) => {
+                stream.get_ref().set_read_timeout(Some(POLL_INTERVAL))
+            }
+            _ => Ok(()),
+        }
+        .map_err(transport_err)?;
+
+        let (commands, receiver) = mpsc::channel();
+        thread::Builder::new()
+            .name("gekko-ws".to_string())
+            .spawn(move || Connection::new(socket, receiver).run())
+            .map_err(transport_err)?;
+
+        Ok(WsClient {
+            url: url.to_string(),
+            commands,
+            next_id: AtomicU64::new(1),
+        })
+    }
+    /// The URL of the endpoint.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+    fn body(&self, method: &str, params: Value) -> (u64, String) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+
+        (id, body.to_string())
+    }
+    fn send(&self, command: Command) -> Result<()> {
+        self.commands.send(command).map_err(|_| closed())
+    }
+}
+
+impl Transport for WsClient {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, params), fields(url = %self.url), err)
+    )]
+    fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let (id, body) = self.body(method, params);
+        let (response, receiver) = mpsc::channel();
+        self.send(Command::Request { id, body, response })?;
+
+        receiver.recv().map_err(|_| closed())?
+    }
+}
+
+impl SubscriptionTransport for WsClient {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, params), fields(url = %self.url), err)
+    )]
+    fn subscribe(&self, method: &str, params: Value, unsubscribe: &str) -> Result<Subscription> {
+        let (id, body) = self.body(method, params);
+        let (response, receiver) = mpsc::channel();
+        let (notifications, stream) = stream::unbounded();
+        self.send(Command::Subscribe {
+            id,
+            body,
+            response,
+            notifications,
+        })?;
+
+        let subscription = subscription_id(&receiver.recv().map_err(|_| closed())??)?;
+        let (_, body) = self.body(unsubscribe, json!([subscription]));
+
+        let commands = self.commands.clone();
+        Ok(Subscription::new(stream, move || {
+            // The connection might already be closed.
+            let _ = commands.send(Command::Unsubscribe { subscription, body });
+        }))
+    }
+}
+
+/// Subscription Ids are either strings or numbers.
+fn subscription_id(value: &Value) -> Result<String> {
+    match value {
+        Value::String(id) => Ok(id.clone()),
+        Value::Number(id) => Ok(id.to_string()),
+        _ => Err(Error::RpcInvalidResponse(format!(
+            "invalid subscription Id: {}",
+            value
+        ))),
+    }
+}
+
+fn transport_err<E: std::error::Error + Send + Sync + 'static>(err: E) -> Error {
+    Error::RpcTransport(Box::new(err))
+}
+
+fn closed() -> Error {
+    transport_err(io::Error::new(
+        io::ErrorKind::ConnectionAborted,
+        "WebSocket connection closed",
+    ))
+}
+
+enum Pending {
+    Request(mpsc::Sender<Result<Value>>),
+    Subscribe(
+        mpsc::Sender<Result<Value>>,
+        stream::UnboundedSender<Result<Value>>,
+    ),
+}
+
+/// The state of the connection thread.
+struct Connection {
+    socket: Socket,
+    commands: mpsc::Receiver<Command>,
+    pending: HashMap<u64, Pending>,
+    subscriptions: HashMap<String, stream::UnboundedSender<Result<Value>>>,
+}
+
+impl Connection {
+    fn new(socket: Socket, commands: mpsc::Receiver<Command>) -> Self {
+        Connection {
+            socket,
+            commands,
+            pending: HashMap::new(),
+            subscriptions: HashMap::new(),
+        }
+    }
+    fn run(mut self) {
+        loop {
+            loop {
+                match self.commands.try_recv() {
+                    Ok(command) => {
+                        if let Err(err) = self.handle(command) {
+                            return self.shutdown(err);
+                        }
+                    }
+                    Err(mpsc::TryRecvError::Empty) => break,
+                    // The client and all subscriptions were dropped.
+                    Err(mpsc::TryRecvError::Disconnected) => {
+                        let _ = self.socket.close(None);
+                        return;
+                    }
+                }
+            }
+
+            match self.socket.read() {
+                Ok(Message::Text(text)) => self.dispatch(&text),
+                Ok(Message::Binary(bytes)) => self.dispatch(&String::from_utf8_lossy(&bytes)),
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(err))
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    // Replies to pings are sent on flush.
+                    match self.socket.flush() {
+                        Err(tungstenite::Error::Io(err))
+                            if err.kind() == io::ErrorKind::WouldBlock => {}
+                        Err(err) => return self.shutdown(transport_err(err)),
+                        Ok(()) => {}
+                    }
+                }
+                Err(err) => return self.shutdown(transport_err(err)),
+            }
+        }
+    }
+    fn handle(&mut self, command: Command) -> Result<()> {
+        let body = match command {
+            Command::Request { id, body, response } => {
+                self.pending.insert(id, Pending::Request(response));
+                body
+            }
+            Command::Subscribe {
+                id,
+                body,
+                response,
+                notifications,
+            } => {
+                self.pending
+                    .insert(id, Pending::Subscribe(response, notifications));
+                body
+            }
+            Command::Unsubscribe { subscription, body } => {
+                self.subscriptions.remove(&subscription);
+                body
+            }
+        };
+
+        self.socket.send(Message::Text(body)).map_err(transport_err)
+    }
+    fn dispatch(&mut self, text: &str) {
+        let resp: Value = match serde_json::from_str(text) {
+            Ok(resp) => resp,
+            Err(_) => return,
+        };
+
+        // Notification of a subscription.
+        if let Some(params) = resp.get("params") {
+            if let Ok(id) = subscription_id(&params["subscription"]) {
+                let unsubscribed = self
+                    .subscriptions
+                    .get(&id)
+                    .map(|sender| sender.unbounded_send(Ok(params["result"].clone())).is_err())
+                    .unwrap_or(false);
+
+                // The subscription is unsubscribed once it is dropped.
+                if unsubscribed {
+                    self.subscriptions.remove(&id);
+                }
+            }
+
+            return;
+        }
+
+        let pending = match resp["id"].as_u64().and_then(|id| self.pending.remove(&id)) {
+            Some(pending) => pending,
+            None => return,
+        };
+
+        match pending {
+            Pending::Request(response) => {
+                let _ = response.send(parse_response(resp));
+            }
+            Pending::Subscribe(response, notifications) => {
+                let result = parse_response(resp);
+                if let Ok(Ok(id)) = result.as_ref().map(subscription_id) {
+                    self.subscriptions.insert(id, notifications);
+                }
+
+                let _ = response.send(result);
+            }
+        }
+    }
+    fn shutdown(self, err: Error) {
+        let message = err.to_string();
+        let err = || {
+            transport_err(io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                message.clone(),
+            ))
+        };
+
+        for (_, pending) in self.pending {
+            match pending {
+                Pending::Request(response) | Pending::Subscribe(response, _) => {
+                    let _ = response.send(Err(err()));
+                }
+            }
+        }
+
+        for (_, sender) in self.subscriptions {
+            let _ = sender.unbounded_send(Err(err()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on_stream;
+    use std::net::TcpListener;
+
+    fn read(socket: &mut WebSocket<TcpStream>) -> Value {
+        let msg = socket.read().unwrap();
+        serde_json::from_str(msg.to_text().unwrap()).unwrap()
+    }
+
+    fn write(socket: &mut WebSocket<TcpStream>, msg: Value) {
+        socket.send(Message::Text(msg.to_string())).unwrap();
+    }
+
+    /// Answers a request and a subscription with two notifications, then
+    /// expects the subscription to be cancelled.
+    fn serve(listener: TcpListener) {
+        let (stream, _) = listener.accept().unwrap();
+        let mut socket = tungstenite::accept(stream).unwrap();
+
+        let req = read(&mut socket);
+        assert_eq!(req["method"], "system_name");
+        write(
+            &mut socket,
+            json!({ "jsonrpc": "2.0", "id": req["id"], "result": "gekko" }),
+        );
+
+        let req = read(&mut socket);
+        assert_eq!(req["method"], "chain_subscribeNewHeads");
+        write(
+            &mut socket,
+            json!({ "jsonrpc": "2.0", "id": req["id"], "result": "sub" }),
+        );
+        for number in 1..=2 {
+            write(
+                &mut socket,
+                json!({
+                    "jsonrpc": "2.0",
+                    "method": "chain_newHead",
+                    "params": { "subscription": "sub", "result": number },
+                }),
+            );
+        }
+
+        let req = read(&mut socket);
+        assert_eq!(req["method"], "chain_unsubscribeNewHeads");
+        assert_eq!(req["params"], json!(["sub"]));
+    }
+
+    #[test]
+    fn request_and_subscribe() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let server = thread::spawn(move || serve(listener));
+
+        let client = WsClient::connect(&url).unwrap();
+        assert_eq!(client.request("system_name", json!([])).unwrap(), "gekko");
+
+        let subscription = client
+            .subscribe(
+                "chain_subscribeNewHeads",
+                json!([]),
+                "chain_unsubscribeNewHeads",
+            )
+            .unwrap();
+
+        let heads: Vec<Value> = block_on_stream(subscription)
+            .take(2)
+            .collect::<Result<_>>()
+            .unwrap();
+
+        assert_eq!(heads, vec![json!(1), json!(2)]);
+        server.join().unwrap();
+    }
+}
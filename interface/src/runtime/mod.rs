@@ -0,0 +1,475 @@
+//! Types and interfaces to interact with runtimes.
+//!
+//! Each runtime is behind its own feature (`runtime-polkadot` and
+//! `runtime-kusama`, both enabled by default), so crates targeting a single
+//! chain only need to compile the interfaces of that chain.
+//!
+//! # Spec versions
+//!
+//! The interfaces of each supported spec version are available in their own
+//! module, such as `polkadot::v9050`, while the latest version is re-exported
+//! by the runtime module itself. When dealing with historic data, the
+//! `SpecVersion` type of each runtime can be used to select the matching
+//! interfaces at runtime.
+//!
+//! # Example
+//!
+//! ```
+//! # #[cfg(feature = "runtime-polkadot")]
+//! # {
+//! use gekko::runtime::polkadot::{self, SpecVersion};
+//!
+//! // The spec version as retrieved from the block, for example.
+//! let spec_version = 9050;
+//!
+//! match SpecVersion::from_spec_version(spec_version) {
+//!     Some(SpecVersion::V9050) => {
+//!         // Use the types of `polkadot::v9050`.
+//!         assert_eq!(polkadot::v9050::SPEC_VERSION, spec_version);
+//!     }
+//!     None => panic!("no interfaces available for spec version {}", spec_version),
+//! }
+//! # }
+//! ```
+
+/// Information about the runtime which the interfaces were generated from.
+/// Can be compared against a live runtime to detect runtime upgrades.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompiledRuntime {
+    /// The `spec_version` of the runtime.
+    pub spec_version: u32,
+    /// The `transaction_version` of the runtime.
+    pub transaction_version: u32,
+    /// The Blake2b-256 hash of the raw runtime metadata.
+    pub metadata_hash: [u8; 32],
+    /// All extrinsics as `(module name, extrinsic name, module Id, dispatch
+    /// Id)`.
+    pub call_indices: &'static [(&'static str, &'static str, u8, u8)],
+}
+
+impl CompiledRuntime {
+    /// The module Id and dispatch Id of the extrinsic with the given module
+    /// and extrinsic name, e.g. `("Utility", "batch")`.
+    pub fn find_call_indices(&self, module: &str, extrinsic: &str) -> Option<(u8, u8)> {
+        self.call_indices
+            .iter()
+            .find(|(module_name, extrinsic_name, _, _)| {
+                *module_name == module && *extrinsic_name == extrinsic
+            })
+            .map(|(_, _, module_id, dispatch_id)| (*module_id, *dispatch_id))
+    }
+}
+
+/// Creates the `SpecVersion` type which maps the spec version to the
+/// corresponding module of generated interfaces.
+// Unused if all `runtime-*` features are disabled.
+#[allow(unused_macros)]
+macro_rules! spec_versions {
+    ($($module:ident => $variant:ident),+ $(,)?) => {
+        /// The spec versions of the runtime for which interfaces are available.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum SpecVersion {
+            $(
+                #[doc = concat!("Interfaces available in [`", stringify!($module), "`].")]
+                $variant,
+            )*
+        }
+
+        impl SpecVersion {
+            /// All available spec versions, from oldest to latest.
+            pub const ALL: &'static [SpecVersion] = &[$(SpecVersion::$variant),*];
+
+            /// The latest available spec version.
+            pub fn latest() -> Self {
+                Self::ALL[Self::ALL.len() - 1]
+            }
+            /// Returns the matching spec version, if interfaces are available
+            /// for it.
+            pub fn from_spec_version(spec_version: u32) -> Option<Self> {
+                $(
+                    if spec_version == $module::SPEC_VERSION {
+                        return Some(SpecVersion::$variant);
+                    }
+                )*
+
+                None
+            }
+            /// Returns the `spec_version` of the runtime.
+            pub fn spec_version(&self) -> u32 {
+                match self {
+                    $(SpecVersion::$variant => $module::SPEC_VERSION,)*
+                }
+            }
+            /// Returns the `transaction_version` of the runtime.
+            pub fn transaction_version(&self) -> u32 {
+                match self {
+                    $(SpecVersion::$variant => $module::TRANSACTION_VERSION,)*
+                }
+            }
+            /// Returns information about the runtime the interfaces were
+            /// generated from.
+            pub fn compiled_runtime(&self) -> $crate::runtime::CompiledRuntime {
+                match self {
+                    $(
+                        SpecVersion::$variant => $crate::runtime::CompiledRuntime {
+                            spec_version: $module::SPEC_VERSION,
+                            transaction_version: $module::TRANSACTION_VERSION,
+                            metadata_hash: $module::METADATA_HASH,
+                            call_indices: $module::extrinsics::CALL_INDICES,
+                        },
+                    )*
+                }
+            }
+        }
+    };
+}
+
+#[cfg(feature = "runtime-polkadot")]
+pub mod polkadot {
+    //! Types and interfaces of the Polkadot runtime.
+
+    pub use v9050::*;
+
+    spec_versions! {
+        v9050 => V9050,
+    }
+
+    /// Runtime types and interfaces of spec version 9050.
+    pub mod v9050 {
+        #[gekko_generator::parse_from_hex_file(
+            "dumps/metadata_polkadot_9050.hex",
+            spec_version = 9050,
+            transaction_version = 7
+        )]
+        struct A;
+    }
+}
+
+#[cfg(feature = "runtime-kusama")]
+pub mod kusama {
+    //! Types and interfaces of the Kusama runtime.
+
+    pub use v9080::*;
+
+    spec_versions! {
+        v9080 => V9080,
+    }
+
+    /// Runtime types and interfaces of spec version 9080.
+    pub mod v9080 {
+        #[gekko_generator::parse_from_hex_file(
+            "dumps/metadata_kusama_9080.hex",
+            spec_version = 9080,
+            transaction_version = 5
+        )]
+        struct A;
+    }
+}
+
+#[cfg(all(test, feature = "runtime-kusama"))]
+mod tests {
+    use crate::common::*;
+    use parity_scale_codec::Encode;
+
+    /// The Kusama interfaces with concrete types for balances and addresses.
+    mod substituted {
+        #[gekko_generator::parse_from_hex_file(
+            "dumps/metadata_kusama_9080.hex",
+            types(
+                crate::common::Balance = "Compact<T::Balance>",
+                crate::common::Balance = "Compact<BalanceOf<T>>",
+                crate::common::AccountId = "<T::Lookup as StaticLookup>::Source",
+            )
+        )]
+        struct A;
+    }
+
+    #[test]
+    fn extrinsic_encoding_roundtrip() {
+        use super::kusama::extrinsics::balances::TransferKeepAlive;
+        use parity_scale_codec::{Compact, Decode};
+
+        let call = TransferKeepAlive {
+            dest: AccountId::new([1; 32]),
+            value: Compact(100u128),
+        };
+
+        // The indices, followed by the arguments in declaration order.
+        let mut expected = vec![4, 3];
+        expected.extend(AccountId::new([1; 32]).encode());
+        expected.extend(Compact(100u128).encode());
+
+        let encoded = call.encode();
+        assert_eq!(encoded, expected);
+        assert_eq!(call.size_hint(), encoded.len());
+        assert_eq!(
+            TransferKeepAlive::decode(&mut encoded.as_slice()).unwrap(),
+            call
+        );
+
+        // Different indices are rejected.
+        let mut invalid = encoded;
+        invalid[1] = 0;
+        assert!(
+            TransferKeepAlive::<AccountId, Compact<u128>>::decode(&mut invalid.as_slice()).is_err()
+        );
+    }
+
+    /// The Kusama interfaces, serializable regardless of the `"serde"` feature.
+    mod with_serde {
+        #[gekko_generator::parse_from_hex_file("dumps/metadata_kusama_9080.hex", derive(serde))]
+        struct A;
+    }
+
+    #[test]
+    fn serialize_extrinsics() {
+        let call = with_serde::extrinsics::system::Remark {
+            _remark: vec![1, 2],
+        };
+
+        let json = serde_json::to_value(&call).unwrap();
+        assert_eq!(json, serde_json::json!({ "_remark": [1, 2] }));
+        assert_eq!(
+            serde_json::from_value::<with_serde::extrinsics::system::Remark<Vec<u8>>>(json)
+                .unwrap(),
+            call
+        );
+    }
+
+    /// The interfaces of a runtime without modules.
+    mod from_str {
+        // The magic number, V13 and the extrinsic metadata.
+        gekko_generator::parse_from_hex_str!("0x6d6574610d000400", spec_version = 1);
+    }
+
+    #[test]
+    fn parse_metadata_literal() {
+        assert_eq!(from_str::SPEC_VERSION, 1);
+        assert!(from_str::extrinsics::CALL_INDICES.is_empty());
+    }
+
+    #[test]
+    fn substitute_generic_arguments() {
+        let controller = AccountId::new([1; 32]);
+        let value = BalanceBuilder::new(Currency::Kusama).balance(1);
+
+        // Only the reward destination remains generic.
+        let call: substituted::extrinsics::staking::Bond<u8> =
+            substituted::extrinsics::staking::Bond {
+                controller,
+                value,
+                payee: 0,
+            };
+
+        let generic = super::kusama::extrinsics::staking::Bond {
+            controller,
+            value,
+            payee: 0u8,
+        };
+
+        assert_eq!(call.encode(), generic.encode());
+    }
+
+    #[test]
+    fn storage_keys() {
+        use super::kusama::storage;
+
+        // The well-known key of `System::Account`.
+        let account = [1; 32];
+        let key = storage::system::Account::key(&account);
+        assert_eq!(
+            hex::encode(&key[..32]),
+            "26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da9"
+        );
+        // Blake2-128 concat.
+        assert_eq!(key.len(), 32 + 16 + 32);
+        assert_eq!(key[48..], account);
+
+        // Plain storage values consist of the prefix only.
+        assert_eq!(
+            storage::timestamp::Now::key(),
+            storage::timestamp::Now::PREFIX.to_vec()
+        );
+        assert_eq!(
+            storage::timestamp::Now::decode_value::<u64>(&1_000u64.encode()).unwrap(),
+            1_000
+        );
+
+        #[cfg(feature = "metadata")]
+        {
+            use gekko_metadata::StorageMetadataExt;
+
+            let metadata = gekko_metadata::parse_hex_metadata(
+                include_str!("../../dumps/metadata_kusama_9080.hex").trim(),
+            )
+            .unwrap()
+            .into_latest()
+            .unwrap();
+
+            let expected = metadata
+                .find_module_storage("Staking", "ErasStakers")
+                .unwrap()
+                .key()
+                .arg(&2000u32)
+                .arg(&account)
+                .build()
+                .unwrap();
+
+            assert_eq!(
+                storage::staking::ErasStakers::key(&2000u32, &account),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn decode_constants() {
+        use super::kusama::constants::balances::ExistentialDeposit;
+
+        assert_eq!(ExistentialDeposit::decode::<u128>().unwrap(), 33_333_333);
+        // All bytes must be consumed.
+        assert!(ExistentialDeposit::decode::<u64>().is_err());
+
+        #[cfg(feature = "metadata")]
+        {
+            use gekko_metadata::ConstantsBuilderExt;
+
+            let metadata = gekko_metadata::parse_hex_metadata(
+                include_str!("../../dumps/metadata_kusama_9080.hex").trim(),
+            )
+            .unwrap()
+            .into_latest()
+            .unwrap();
+
+            let constant = metadata
+                .find_constant("Balances", "ExistentialDeposit")
+                .unwrap();
+            assert_eq!(ExistentialDeposit::VALUE, constant.value);
+        }
+    }
+
+    #[test]
+    fn resolve_errors() {
+        use super::kusama::errors::{balances, DispatchError};
+
+        let error = DispatchError::resolve(balances::MODULE_INDEX, 2).unwrap();
+        assert_eq!(
+            error,
+            DispatchError::Balances(balances::Error::InsufficientBalance)
+        );
+        assert_eq!(balances::Error::InsufficientBalance as u8, 2);
+
+        assert_eq!(DispatchError::resolve(balances::MODULE_INDEX, 255), None);
+        assert_eq!(DispatchError::resolve(255, 0), None);
+
+        #[cfg(feature = "metadata")]
+        {
+            use gekko_metadata::ErrorBuilderExt;
+
+            let metadata = gekko_metadata::parse_hex_metadata(
+                include_str!("../../dumps/metadata_kusama_9080.hex").trim(),
+            )
+            .unwrap()
+            .into_latest()
+            .unwrap();
+
+            let info = metadata.find_error(balances::MODULE_INDEX, 2).unwrap();
+            assert_eq!(info.error_name, "InsufficientBalance");
+        }
+    }
+
+    /// Only the types of the `Balances` events are concrete.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct KusamaEventTypes;
+
+    macro_rules! unit_types {
+        ($($name:ident),*) => {
+            $(type $name = ();)*
+        };
+    }
+
+    impl super::kusama::events::EventTypes for KusamaEventTypes {
+        type AccountId = [u8; 32];
+        type Balance = u128;
+        type Status = u8;
+
+        unit_types!(
+            AccountIndex,
+            ActiveIndex,
+            AuctionIndex,
+            AuthorityId,
+            AuthorityList,
+            BalanceOfT,
+            BlockNumber,
+            BountyIndex,
+            CallHash,
+            CandidateReceiptHash,
+            CoreIndex,
+            DispatchError,
+            DispatchInfo,
+            DispatchResult,
+            ElectionCompute,
+            EraIndex,
+            EthereumAddress,
+            GroupIndex,
+            Hash,
+            HeadData,
+            HrmpChannelId,
+            Kind,
+            LeasePeriod,
+            MemberCount,
+            MessageId,
+            MultiLocation,
+            OpaqueTimeSlot,
+            OptionElectionCompute,
+            OptionVecU8,
+            Outcome,
+            ParaId,
+            PropIndex,
+            ProposalIndex,
+            ProxyType,
+            ReferendumIndex,
+            RegistrarIndex,
+            SessionIndex,
+            TBlockNumber,
+            TaskAddressBlockNumber,
+            TimepointBlockNumber,
+            VecAccountIdBalance,
+            VecAccountId,
+            VecIdentificationTuple,
+            VoteThreshold,
+            Weight,
+            Xcm,
+            SpStdMarkerPhantomDataAccountIdEvent,
+            XcmV0Outcome
+        );
+    }
+
+    #[test]
+    fn decode_events() {
+        use super::kusama::events::{self, balances, RuntimeEvent};
+        use parity_scale_codec::Decode;
+
+        // `Balances::Transfer(from, to, value)`
+        let mut encoded = vec![2];
+        encoded.extend_from_slice(&[1; 32]);
+        encoded.extend_from_slice(&[2; 32]);
+        encoded.extend(100u128.encode());
+
+        let expected = balances::Event::Transfer([1; 32], [2; 32], 100);
+        let event = balances::Event::<[u8; 32], u128, u8>::decode(&mut encoded.as_slice());
+        assert_eq!(event.unwrap(), expected);
+
+        // Prefixed with the module index.
+        let mut record = vec![balances::MODULE_INDEX];
+        record.extend(&encoded);
+        let event = RuntimeEvent::<KusamaEventTypes>::decode(&mut record.as_slice()).unwrap();
+        assert_eq!(event, RuntimeEvent::Balances(expected));
+
+        // Events without arguments, e.g. `System::CodeUpdated`.
+        let event = events::system::Event::<(), (), [u8; 32], [u8; 32]>::decode(&mut &[2][..]);
+        assert_eq!(event.unwrap(), events::system::Event::CodeUpdated);
+
+        assert!(RuntimeEvent::<KusamaEventTypes>::decode(&mut &[255, 0][..]).is_err());
+    }
+}
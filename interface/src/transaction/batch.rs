@@ -0,0 +1,201 @@
+//! Batches of calls, dispatched by a single transaction via the `Utility`
+//! module.
+//!
+//! [`BatchBuilder`] accepts calls of any type and encodes those as the
+//! `calls` argument of `Utility::batch`, `Utility::batch_all` or
+//! `Utility::force_batch`. The indices of the batch call are resolved from the
+//! generated interfaces of a runtime, from the metadata or set explicitly.
+//!
+//! # Example
+//!
+//! ```
+//! # #[cfg(feature = "runtime-polkadot")]
+//! # {
+//! use gekko::common::*;
+//! use gekko::runtime::polkadot::{self, SpecVersion};
+//! use gekko::runtime::polkadot::extrinsics::balances::TransferKeepAlive;
+//! use gekko::transaction::batch::{BatchBuilder, BatchMode};
+//!
+//! let currency = BalanceBuilder::new(Currency::Polkadot);
+//! let transfer = |dest: [u8; 32]| TransferKeepAlive {
+//!     dest: MultiAddress::from(AccountId::new(dest)),
+//!     value: currency.balance(1),
+//! };
+//!
+//! // Either all transfers succeed, or none.
+//! let call = BatchBuilder::new(BatchMode::BatchAll)
+//!     .call(transfer([1; 32]))
+//!     .call(transfer([2; 32]))
+//!     .build_for_runtime(&SpecVersion::latest().compiled_runtime())
+//!     .unwrap();
+//!
+//! // The call can be passed to the `SignedTransactionBuilder`.
+//! assert_eq!(call.module_id(), Some(26));
+//! # }
+//! ```
+
+use crate::common::OpaqueCall;
+use crate::runtime::CompiledRuntime;
+use crate::{Error, Result};
+#[cfg(feature = "metadata")]
+use gekko_metadata::ModuleMetadataExt;
+use parity_scale_codec::Encode;
+
+/// The call of the `Utility` module which dispatches the batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchMode {
+    /// `Utility::batch`, which stops at the first failed call. Calls before
+    /// the failed call are not reverted.
+    Batch,
+    /// `Utility::batch_all`, which reverts all calls if any call fails.
+    BatchAll,
+    /// `Utility::force_batch`, which continues after failed calls. Not
+    /// available on older runtimes.
+    ForceBatch,
+}
+
+impl BatchMode {
+    /// The name of the call in the metadata.
+    pub fn call_name(&self) -> &'static str {
+        match self {
+            BatchMode::Batch => "batch",
+            BatchMode::BatchAll => "batch_all",
+            BatchMode::ForceBatch => "force_batch",
+        }
+    }
+}
+
+/// Builder of a batch of calls. See the [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchBuilder {
+    mode: BatchMode,
+    calls: Vec<OpaqueCall>,
+}
+
+impl BatchBuilder {
+    /// Creates an empty batch, dispatched with the given call of the
+    /// `Utility` module.
+    pub fn new(mode: BatchMode) -> Self {
+        BatchBuilder {
+            mode,
+            calls: vec![],
+        }
+    }
+    /// Adds the call to the batch. The call must be encoded including its
+    /// module and dispatch Id, like the interfaces in the
+    /// [runtime](crate::runtime) module.
+    pub fn call<C: Encode>(mut self, call: C) -> Self {
+        self.calls.push(OpaqueCall(call.encode()));
+        self
+    }
+    /// The number of calls in the batch.
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+    /// Whether the batch contains no calls.
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+    /// Encodes the batch call with the given module and dispatch Id of the
+    /// batch call.
+    pub fn build_with_indices(self, module_id: u8, dispatch_id: u8) -> OpaqueCall {
+        let mut encoded = vec![module_id, dispatch_id];
+        // Encoded like a `Vec` of calls, i.e. prefixed with the number of
+        // calls.
+        self.calls.encode_to(&mut encoded);
+
+        OpaqueCall(encoded)
+    }
+    /// Encodes the batch call with the indices of the runtime, as generated
+    /// from its metadata. See [`SpecVersion::compiled_runtime`].
+    ///
+    /// [`SpecVersion::compiled_runtime`]: crate::runtime::polkadot::SpecVersion::compiled_runtime
+    pub fn build_for_runtime(self, runtime: &CompiledRuntime) -> Result<OpaqueCall> {
+        let name = self.mode.call_name();
+        let (module_id, dispatch_id) = runtime
+            .find_call_indices("Utility", name)
+            .ok_or_else(|| unknown_call(name))?;
+
+        Ok(self.build_with_indices(module_id, dispatch_id))
+    }
+    /// Encodes the batch call with the indices of the metadata. Requires the
+    /// `"metadata"` feature.
+    #[cfg(feature = "metadata")]
+    pub fn build_with_metadata<M: ModuleMetadataExt>(self, metadata: &M) -> Result<OpaqueCall> {
+        let name = self.mode.call_name();
+        let info = metadata
+            .find_module_extrinsic("Utility", name)
+            .ok_or_else(|| unknown_call(name))?;
+
+        let (module_id, dispatch_id) = (info.module_id as u8, info.dispatch_id as u8);
+        Ok(self.build_with_indices(module_id, dispatch_id))
+    }
+}
+
+fn unknown_call(name: &str) -> Error {
+    Error::UnknownCall {
+        module: "Utility".to_string(),
+        call: name.to_string(),
+    }
+}
+
+#[cfg(all(test, feature = "runtime-kusama"))]
+mod tests {
+    use super::*;
+    use crate::common::{AccountId, BalanceBuilder, Currency};
+    use crate::runtime::kusama::{self, SpecVersion};
+    use kusama::extrinsics::balances::TransferKeepAlive;
+    use kusama::extrinsics::system::Remark;
+    use kusama::extrinsics::utility::BatchAll;
+
+    #[test]
+    fn encode_heterogeneous_batch() {
+        let transfer = TransferKeepAlive {
+            dest: AccountId::new([1; 32]),
+            value: BalanceBuilder::new(Currency::Kusama).balance(1),
+        };
+        let remark = Remark {
+            _remark: b"gekko".to_vec(),
+        };
+
+        let batch = BatchBuilder::new(BatchMode::BatchAll)
+            .call(transfer.clone())
+            .call(remark.clone());
+        assert_eq!(batch.len(), 2);
+
+        let call = batch
+            .clone()
+            .build_for_runtime(&SpecVersion::latest().compiled_runtime())
+            .unwrap();
+
+        let expected = BatchAll {
+            calls: vec![OpaqueCall(transfer.encode()), OpaqueCall(remark.encode())],
+        };
+        assert_eq!(call.0, expected.encode());
+
+        #[cfg(feature = "metadata")]
+        {
+            let metadata = gekko_metadata::parse_hex_metadata(
+                include_str!("../../dumps/metadata_kusama_9080.hex").trim(),
+            )
+            .unwrap()
+            .into_latest()
+            .unwrap();
+
+            assert_eq!(batch.build_with_metadata(&metadata).unwrap(), call);
+        }
+    }
+
+    #[test]
+    fn batch_call_not_in_runtime() {
+        // Added to the runtime after spec version 9080.
+        let err = BatchBuilder::new(BatchMode::ForceBatch)
+            .build_for_runtime(&SpecVersion::latest().compiled_runtime())
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            Error::UnknownCall { ref module, ref call } if module == "Utility" && call == "force_batch"
+        ));
+    }
+}
@@ -0,0 +1,214 @@
+//! Human-readable summaries of raw transactions, for verifying what is about
+//! to be signed or submitted. Requires the `"metadata"` feature.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use gekko::metadata::*;
+//! use gekko::transaction::decode_and_describe;
+//!
+//! let content = std::fs::read_to_string("metadata_kusama_9080.hex").unwrap();
+//! let metadata = parse_hex_metadata(content).unwrap();
+//!
+//! let summary = decode_and_describe(&transaction.encode(), &metadata).unwrap();
+//! assert_eq!(summary.call, "transfer_keep_alive");
+//!
+//! println!("{}", summary);
+//! ```
+
+use crate::common::Mortality;
+use crate::Result;
+use gekko_metadata::dynamic::{DecodedExtrinsic, ExtrinsicDecoder};
+use gekko_metadata::scale::{TypeRegistry, Value};
+use gekko_metadata::MetadataVersion;
+use parity_scale_codec::DecodeAll;
+use std::fmt;
+
+/// A summary of a transaction, with the values formatted as display strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransactionSummary {
+    /// The address of the signer, or `None` if the transaction is unsigned.
+    pub signer: Option<String>,
+    /// The nonce of the `CheckNonce` extension.
+    pub nonce: Option<u128>,
+    /// The tip of the `ChargeTransactionPayment` or `ChargeAssetTxPayment`
+    /// extension, in the base unit of the runtime.
+    pub tip: Option<u128>,
+    /// The mortality of the `CheckMortality` or `CheckEra` extension.
+    pub era: Option<String>,
+    /// The name of the pallet, referred to as module in metadata V13.
+    pub pallet: String,
+    /// The name of the call.
+    pub call: String,
+    /// The names and values of the arguments, in order.
+    pub args: Vec<(String, String)>,
+}
+
+impl From<DecodedExtrinsic> for TransactionSummary {
+    fn from(extrinsic: DecodedExtrinsic) -> Self {
+        let signature = extrinsic.signature.as_ref();
+        let extra = |names: &[&str]| {
+            signature?
+                .extra
+                .iter()
+                .find(|(name, _)| names.contains(&name.as_str()))
+                .map(|(_, value)| value)
+        };
+
+        TransactionSummary {
+            signer: signature.map(|sig| sig.address.to_string()),
+            nonce: extra(&["CheckNonce"]).and_then(Value::as_u128),
+            tip: extra(&["ChargeTransactionPayment"])
+                .or_else(|| extra(&["ChargeAssetTxPayment"])?.field("tip"))
+                .and_then(Value::as_u128),
+            era: extra(&["CheckMortality", "CheckEra"]).map(describe_era),
+            pallet: extrinsic.call.module_name,
+            call: extrinsic.call.call_name,
+            args: extrinsic
+                .call
+                .args
+                .into_iter()
+                .map(|(name, value)| (name, value.to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for TransactionSummary {
+    /// Formats the summary with one entry per line, the arguments indented
+    /// below the call.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.signer {
+            Some(signer) => writeln!(f, "signer: {}", signer)?,
+            None => writeln!(f, "unsigned")?,
+        }
+        if let Some(nonce) = self.nonce {
+            writeln!(f, "nonce: {}", nonce)?;
+        }
+        if let Some(tip) = self.tip {
+            writeln!(f, "tip: {}", tip)?;
+        }
+        if let Some(era) = &self.era {
+            writeln!(f, "era: {}", era)?;
+        }
+
+        write!(f, "call: {}::{}", self.pallet, self.call)?;
+        for (name, value) in &self.args {
+            write!(f, "\n  {}: {}", name, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Decodes the length-prefixed transaction and resolves its call and signed
+/// extensions through the metadata, which must be either V13 or V14.
+pub fn decode_and_describe(bytes: &[u8], metadata: &MetadataVersion) -> Result<TransactionSummary> {
+    let mut registry = TypeRegistry::default();
+    if let MetadataVersion::V13(metadata) = metadata {
+        registry.register_calls(metadata)?;
+    }
+
+    let extrinsic = ExtrinsicDecoder::new(metadata, &registry)?.decode_extrinsic(bytes)?;
+    Ok(extrinsic.into())
+}
+
+/// Formats the era, which is decoded as raw bytes with metadata V13 and as a
+/// variant with metadata V14.
+fn describe_era(value: &Value) -> String {
+    match value.as_bytes().map(Mortality::decode_all) {
+        Some(Ok(Mortality::Immortal)) => "immortal".to_string(),
+        Some(Ok(Mortality::Mortal(period, phase, _))) => {
+            format!("mortal for {} blocks, phase {}", period, phase)
+        }
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "runtime-kusama")]
+mod tests {
+    use super::*;
+    use crate::common::*;
+    use crate::runtime::kusama::extrinsics::balances::TransferKeepAlive;
+    use crate::transaction::*;
+    use gekko_metadata::parse_hex_metadata;
+    use parity_scale_codec::Encode;
+    use sp_core::crypto::Pair;
+
+    fn metadata() -> MetadataVersion {
+        parse_hex_metadata(include_str!("../../dumps/metadata_kusama_9080.hex").trim()).unwrap()
+    }
+
+    #[test]
+    fn describe_signed_transaction() {
+        let keypair = KeyPairBuilder::<Sr25519>::from_seed(&[1; 32]);
+        let destination = AccountId::new([2; 32]);
+
+        let transaction: PolkadotSignedExtrinsic<_> = SignedTransactionBuilder::new()
+            .signer(keypair.clone())
+            .call(TransferKeepAlive {
+                dest: destination,
+                value: BalanceBuilder::new(Currency::Kusama).balance(1),
+            })
+            .nonce(7)
+            .tip_base_unit(10)
+            .mortality(Mortality::from_current_block(100, [3; 32], 64))
+            .network(Network::Kusama)
+            .spec_version(9080)
+            .transaction_version(5)
+            .build()
+            .unwrap();
+
+        let summary = decode_and_describe(&transaction.encode(), &metadata()).unwrap();
+        let signer = format!("Id(0x{})", hex::encode(keypair.public()));
+
+        assert_eq!(summary.signer.as_ref(), Some(&signer));
+        assert_eq!(summary.nonce, Some(7));
+        assert_eq!(summary.tip, Some(10));
+        assert_eq!(
+            summary.era.as_deref(),
+            Some("mortal for 64 blocks, phase 36")
+        );
+        assert_eq!(summary.pallet, "Balances");
+        assert_eq!(summary.call, "transfer_keep_alive");
+        assert_eq!(
+            summary.args,
+            vec![
+                ("dest".to_string(), format!("Id(0x{})", "02".repeat(32))),
+                ("value".to_string(), "1000000000000".to_string()),
+            ]
+        );
+
+        assert_eq!(
+            summary.to_string(),
+            format!(
+                "signer: {}\nnonce: 7\ntip: 10\nera: mortal for 64 blocks, phase 36\n\
+                 call: Balances::transfer_keep_alive\n  dest: Id(0x{})\n  value: 1000000000000",
+                signer,
+                "02".repeat(32)
+            )
+        );
+    }
+
+    #[test]
+    fn describe_unsigned_transaction() {
+        let transaction = Transaction::new_unsigned(TransferKeepAlive {
+            dest: AccountId::new([2; 32]),
+            value: BalanceBuilder::new(Currency::Kusama).balance(1),
+        });
+
+        let summary = decode_and_describe(&transaction.encode(), &metadata()).unwrap();
+        assert_eq!(summary.signer, None);
+        assert_eq!(summary.nonce, None);
+        assert_eq!(summary.era, None);
+        assert!(summary
+            .to_string()
+            .starts_with("unsigned\ncall: Balances::"));
+
+        let mut encoded = transaction.encode();
+        encoded.push(0);
+        assert!(decode_and_describe(&encoded, &metadata()).is_err());
+    }
+}
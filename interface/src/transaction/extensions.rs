@@ -0,0 +1,261 @@
+//! Signed extensions which are assembled at runtime, based on the identifiers
+//! of the `signed_extensions` of the metadata.
+//!
+//! Substrate chains differ in their signed extensions, e.g. some chains pay
+//! fees with assets while others omit tips entirely. The [`Config::Extra`] and
+//! [`Config::AdditionalSigned`] types encode the extensions of a specific
+//! chain, while [`SignedExtensions`] encodes the extensions in the order
+//! declared by the metadata of any chain. See
+//! [`SignedTransactionBuilder::build_with_extensions`].
+//!
+//! [`SignedTransactionBuilder::build_with_extensions`]: super::SignedTransactionBuilder::build_with_extensions
+
+use super::v4::ExtrinsicParams;
+use crate::common::Config;
+use crate::{Error, Result};
+#[cfg(feature = "metadata")]
+use gekko_metadata::MetadataVersion;
+use parity_scale_codec::{Encode, EncodeAsRef, HasCompact, Output};
+use std::fmt;
+use std::sync::Arc;
+
+/// A signed extension which encodes its data from the parameters of the
+/// transaction. Extensions which encode no data, such as `CheckWeight`, can
+/// rely on the default implementations.
+pub trait SignedExtension<C: Config>: Send + Sync {
+    /// Encodes the data which is part of the transaction.
+    fn encode_extra(&self, _params: &ExtrinsicParams<C>, _dest: &mut Vec<u8>) {}
+    /// Encodes the data which is only part of the signature payload.
+    fn encode_additional_signed(&self, _params: &ExtrinsicParams<C>, _dest: &mut Vec<u8>) {}
+}
+
+/// The signed extensions of Substrate and the Polkadot runtimes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BuiltinExtension {
+    CheckSpecVersion,
+    CheckTxVersion,
+    CheckGenesis,
+    CheckMortality,
+    CheckNonce,
+    ChargeTransactionPayment,
+    ChargeAssetTxPayment,
+    CheckMetadataHash,
+    /// Encodes no data, e.g. `CheckWeight`.
+    Empty,
+}
+
+impl BuiltinExtension {
+    fn from_identifier(identifier: &str) -> Option<Self> {
+        let ext = match identifier {
+            "CheckSpecVersion" => BuiltinExtension::CheckSpecVersion,
+            "CheckTxVersion" => BuiltinExtension::CheckTxVersion,
+            "CheckGenesis" => BuiltinExtension::CheckGenesis,
+            // `CheckEra` is the former name of `CheckMortality`.
+            "CheckMortality" | "CheckEra" => BuiltinExtension::CheckMortality,
+            "CheckNonce" => BuiltinExtension::CheckNonce,
+            "ChargeTransactionPayment" => BuiltinExtension::ChargeTransactionPayment,
+            "ChargeAssetTxPayment" => BuiltinExtension::ChargeAssetTxPayment,
+            "CheckMetadataHash" => BuiltinExtension::CheckMetadataHash,
+            "CheckWeight" | "CheckNonZeroSender" | "PrevalidateAttests" => BuiltinExtension::Empty,
+            _ => return None,
+        };
+
+        Some(ext)
+    }
+}
+
+impl<C: Config> SignedExtension<C> for BuiltinExtension {
+    fn encode_extra(&self, params: &ExtrinsicParams<C>, dest: &mut Vec<u8>) {
+        match self {
+            BuiltinExtension::CheckMortality => params.era.encode_to(dest),
+            BuiltinExtension::CheckNonce => encode_compact(&params.nonce, dest),
+            BuiltinExtension::ChargeTransactionPayment => encode_compact(&params.tip, dest),
+            BuiltinExtension::ChargeAssetTxPayment => {
+                encode_compact(&params.tip, dest);
+                params.asset_id.encode_to(dest);
+            }
+            // Whether the metadata hash is enabled.
+            BuiltinExtension::CheckMetadataHash => {
+                (params.metadata_hash.is_some() as u8).encode_to(dest)
+            }
+            _ => {}
+        }
+    }
+    fn encode_additional_signed(&self, params: &ExtrinsicParams<C>, dest: &mut Vec<u8>) {
+        match self {
+            BuiltinExtension::CheckSpecVersion => params.spec_version.encode_to(dest),
+            BuiltinExtension::CheckTxVersion => params.transaction_version.encode_to(dest),
+            BuiltinExtension::CheckGenesis => params.genesis.encode_to(dest),
+            BuiltinExtension::CheckMortality => params.birth.encode_to(dest),
+            BuiltinExtension::CheckMetadataHash => params.metadata_hash.encode_to(dest),
+            _ => {}
+        }
+    }
+}
+
+fn encode_compact<T: HasCompact>(value: &T, dest: &mut Vec<u8>) {
+    <T::Type as EncodeAsRef<'_, T>>::RefType::from(value).encode_to(dest);
+}
+
+/// The signed extensions of a chain, in the order of the `signed_extensions`
+/// of its metadata. The extensions of Substrate and the Polkadot runtimes are
+/// recognized by their identifier, any other extension must be provided with
+/// [`with`](Self::with).
+///
+/// # Example
+///
+/// ```
+/// use gekko::transaction::extensions::{SignedExtension, SignedExtensions};
+/// use gekko::transaction::v4::ExtrinsicParams;
+/// use gekko::common::PolkadotConfig;
+///
+/// // An extension which adds a fixed value to the signature payload.
+/// struct CheckFlag;
+///
+/// impl SignedExtension<PolkadotConfig> for CheckFlag {
+///     fn encode_additional_signed(
+///         &self,
+///         _params: &ExtrinsicParams<PolkadotConfig>,
+///         dest: &mut Vec<u8>,
+///     ) {
+///         dest.push(1);
+///     }
+/// }
+///
+/// let extensions = SignedExtensions::<PolkadotConfig>::new([
+///     "CheckSpecVersion",
+///     "CheckGenesis",
+///     "CheckNonce",
+///     "CheckFlag",
+/// ])
+/// .with("CheckFlag", CheckFlag);
+///
+/// assert!(extensions.unknown().is_empty());
+/// ```
+pub struct SignedExtensions<C: Config> {
+    extensions: Vec<(String, Option<SharedExtension<C>>)>,
+}
+
+type SharedExtension<C> = Arc<dyn SignedExtension<C>>;
+
+impl<C: Config> SignedExtensions<C> {
+    /// Creates the signed extensions with the given identifiers, in the given
+    /// order.
+    pub fn new<I, S>(identifiers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        SignedExtensions {
+            extensions: identifiers
+                .into_iter()
+                .map(|identifier| {
+                    let identifier = identifier.as_ref();
+                    let ext = BuiltinExtension::from_identifier(identifier)
+                        .map(|ext| Arc::new(ext) as SharedExtension<C>);
+
+                    (identifier.to_string(), ext)
+                })
+                .collect(),
+        }
+    }
+    /// Creates the signed extensions declared by the metadata. Supports
+    /// metadata V11 and later. Requires the `"metadata"` feature.
+    #[cfg(feature = "metadata")]
+    pub fn from_metadata(metadata: &MetadataVersion) -> Result<Self> {
+        let extensions = match metadata {
+            MetadataVersion::V11(metadata) => &metadata.extrinsics.signed_extensions,
+            MetadataVersion::V12(metadata) => &metadata.extrinsics.signed_extensions,
+            MetadataVersion::V13(metadata) => &metadata.extrinsics.signed_extensions,
+            MetadataVersion::V14(metadata) => {
+                return Ok(Self::new(
+                    metadata
+                        .extrinsic
+                        .signed_extensions
+                        .iter()
+                        .map(|ext| &ext.identifier),
+                ))
+            }
+            _ => return Err(gekko_metadata::Error::InvalidMetadataVersion.into()),
+        };
+
+        Ok(Self::new(extensions))
+    }
+    /// Sets the implementation of the extension with the given identifier,
+    /// replacing the built-in implementation, if any. Has no effect if the
+    /// chain has no such extension.
+    pub fn with<E: SignedExtension<C> + 'static>(mut self, identifier: &str, extension: E) -> Self {
+        let extension: SharedExtension<C> = Arc::new(extension);
+        self.extensions
+            .iter_mut()
+            .filter(|(id, _)| id == identifier)
+            .for_each(|(_, ext)| *ext = Some(Arc::clone(&extension)));
+
+        self
+    }
+    /// The identifiers of all extensions, in order.
+    pub fn identifiers(&self) -> impl Iterator<Item = &str> {
+        self.extensions.iter().map(|(id, _)| id.as_str())
+    }
+    /// The identifiers of the extensions without an implementation.
+    pub fn unknown(&self) -> Vec<&str> {
+        self.extensions
+            .iter()
+            .filter(|(_, ext)| ext.is_none())
+            .map(|(id, _)| id.as_str())
+            .collect()
+    }
+    /// Encodes the data of all extensions which is part of the transaction and
+    /// the data which is only part of the signature payload, respectively.
+    /// Results in an error if any extension has no implementation.
+    pub fn encode(
+        &self,
+        params: &ExtrinsicParams<C>,
+    ) -> Result<(EncodedExtensions, EncodedExtensions)> {
+        let mut extra = vec![];
+        let mut additional = vec![];
+
+        for (identifier, ext) in &self.extensions {
+            let ext = ext
+                .as_ref()
+                .ok_or_else(|| Error::UnknownSignedExtension(identifier.clone()))?;
+
+            ext.encode_extra(params, &mut extra);
+            ext.encode_additional_signed(params, &mut additional);
+        }
+
+        Ok((EncodedExtensions(extra), EncodedExtensions(additional)))
+    }
+}
+
+impl<C: Config> Clone for SignedExtensions<C> {
+    fn clone(&self) -> Self {
+        SignedExtensions {
+            extensions: self.extensions.clone(),
+        }
+    }
+}
+
+impl<C: Config> fmt::Debug for SignedExtensions<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.identifiers()).finish()
+    }
+}
+
+/// The encoded data of signed extensions, see [`SignedExtensions::encode`].
+/// Encoded as is, without a length prefix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct EncodedExtensions(
+    #[cfg_attr(feature = "serde", serde(with = "gekko_metadata::serde_hex"))] pub Vec<u8>,
+);
+
+impl Encode for EncodedExtensions {
+    fn size_hint(&self) -> usize {
+        self.0.len()
+    }
+    fn encode_to<T: Output + ?Sized>(&self, dest: &mut T) {
+        dest.write(&self.0)
+    }
+}
@@ -8,15 +8,35 @@
 //! [`SignedTransactionBuilder`] type.
 
 // Re-export the latest version.
-pub use v4::{PolkadotSignedExtrinsic, SignedTransactionBuilder, Transaction};
+pub use batch::BatchBuilder;
+#[cfg(feature = "metadata")]
+pub use describe::{decode_and_describe, TransactionSummary};
+pub use extensions::SignedExtensions;
+pub use proxy::{ProxyCall, ProxyType};
+pub use signer::ExternalSigner;
+pub use v4::{
+    DynamicSignedExtrinsic, PolkadotSignedExtrinsic, RuntimeVersionInfo, SignedExtrinsic,
+    SignedTransactionBuilder, SigningPayload, Transaction,
+};
 
+// Batches of calls.
+pub mod batch;
+// Human-readable summaries of transactions.
+#[cfg(feature = "metadata")]
+pub mod describe;
+// Signed extensions assembled from the metadata.
+pub mod extensions;
+// Calls dispatched on behalf of another account.
+pub mod proxy;
+// Signers whose keys are held externally.
+pub mod signer;
+// Version 3 of the transaction format, used by historical chains.
+pub mod v3;
 // Version 4 of the transaction format.
 pub mod v4;
+// Version 5 of the transaction format.
+pub mod v5;
 
-/// TODO.
-pub mod v5 {}
-/// TODO.
-pub mod v3 {}
 /// TODO.
 pub mod v2 {}
 /// TODO.
@@ -0,0 +1,198 @@
+//! Calls dispatched on behalf of another account via the `Proxy` module.
+//!
+//! [`ProxyCall`] wraps a call of any type into `Proxy::proxy`, which a proxy
+//! account signs and submits in the name of the real account. The indices of
+//! the proxy call are resolved from the generated interfaces of a runtime,
+//! from the metadata or set explicitly.
+//!
+//! # Example
+//!
+//! ```
+//! # #[cfg(feature = "runtime-polkadot")]
+//! # {
+//! use gekko::common::*;
+//! use gekko::runtime::polkadot::{self, SpecVersion};
+//! use gekko::runtime::polkadot::extrinsics::balances::TransferKeepAlive;
+//! use gekko::transaction::proxy::{ProxyCall, ProxyType};
+//!
+//! let transfer = TransferKeepAlive {
+//!     dest: MultiAddress::from(AccountId::new([1; 32])),
+//!     value: BalanceBuilder::new(Currency::Polkadot).balance(1),
+//! };
+//!
+//! // The transfer is dispatched from the real account, the transaction is
+//! // signed by its proxy.
+//! let real = AccountId::new([2; 32]);
+//! let call = ProxyCall::wrap(real, Some(ProxyType::Any), transfer)
+//!     .build_for_runtime(&SpecVersion::latest().compiled_runtime())
+//!     .unwrap();
+//!
+//! // The call can be passed to the `SignedTransactionBuilder`.
+//! assert_eq!(call.module_id(), Some(29));
+//! # }
+//! ```
+
+use crate::common::OpaqueCall;
+use crate::runtime::CompiledRuntime;
+use crate::{Error, Result};
+#[cfg(feature = "metadata")]
+use gekko_metadata::ModuleMetadataExt;
+use parity_scale_codec::{Decode, Encode};
+use sp_core::crypto::AccountId32;
+
+/// The type of a proxy, which restricts the calls the proxy may dispatch.
+/// Encoded with the indices of the Polkadot and Kusama runtimes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub enum ProxyType {
+    /// Any call.
+    #[codec(index = 0)]
+    Any,
+    /// Any call which does not transfer funds.
+    #[codec(index = 1)]
+    NonTransfer,
+    /// Calls of the governance modules.
+    #[codec(index = 2)]
+    Governance,
+    /// Calls of the staking modules.
+    #[codec(index = 3)]
+    Staking,
+    /// `Identity::provide_judgement`.
+    #[codec(index = 5)]
+    IdentityJudgement,
+    /// `Proxy::reject_announcement`.
+    #[codec(index = 6)]
+    CancelProxy,
+    /// Calls of the auction and crowdloan modules. Kusama only.
+    #[codec(index = 7)]
+    Auction,
+}
+
+/// A call dispatched on behalf of the real account by one of its proxies. See
+/// the [module documentation](self).
+///
+/// The type of the proxy is generic for runtimes with other proxy types than
+/// [`ProxyType`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyCall<P = ProxyType> {
+    real: AccountId32,
+    force_proxy_type: Option<P>,
+    call: OpaqueCall,
+}
+
+impl<P: Encode> ProxyCall<P> {
+    /// Wraps the call, which is dispatched on behalf of the `real` account.
+    /// If `force_proxy_type` is set, only a proxy of this type is used. The
+    /// call must be encoded including its module and dispatch Id, like the
+    /// interfaces in the [runtime](crate::runtime) module.
+    pub fn wrap<R: Into<AccountId32>, C: Encode>(
+        real: R,
+        force_proxy_type: Option<P>,
+        call: C,
+    ) -> Self {
+        ProxyCall {
+            real: real.into(),
+            force_proxy_type,
+            call: OpaqueCall(call.encode()),
+        }
+    }
+    /// Encodes the proxy call with the given module and dispatch Id of
+    /// `Proxy::proxy`.
+    pub fn build_with_indices(self, module_id: u8, dispatch_id: u8) -> OpaqueCall {
+        let mut encoded = vec![module_id, dispatch_id];
+        // The real account is a plain account Id, not a lookup source like
+        // `MultiAddress`.
+        self.real.encode_to(&mut encoded);
+        self.force_proxy_type.encode_to(&mut encoded);
+        // The call is encoded as is, without a length prefix.
+        encoded.extend(self.call.0);
+
+        OpaqueCall(encoded)
+    }
+    /// Encodes the proxy call with the indices of the runtime, as generated
+    /// from its metadata. See [`SpecVersion::compiled_runtime`].
+    ///
+    /// [`SpecVersion::compiled_runtime`]: crate::runtime::polkadot::SpecVersion::compiled_runtime
+    pub fn build_for_runtime(self, runtime: &CompiledRuntime) -> Result<OpaqueCall> {
+        let (module_id, dispatch_id) = runtime
+            .find_call_indices("Proxy", "proxy")
+            .ok_or_else(unknown_call)?;
+
+        Ok(self.build_with_indices(module_id, dispatch_id))
+    }
+    /// Encodes the proxy call with the indices of the metadata. Requires the
+    /// `"metadata"` feature.
+    #[cfg(feature = "metadata")]
+    pub fn build_with_metadata<M: ModuleMetadataExt>(self, metadata: &M) -> Result<OpaqueCall> {
+        let info = metadata
+            .find_module_extrinsic("Proxy", "proxy")
+            .ok_or_else(unknown_call)?;
+
+        let (module_id, dispatch_id) = (info.module_id as u8, info.dispatch_id as u8);
+        Ok(self.build_with_indices(module_id, dispatch_id))
+    }
+}
+
+fn unknown_call() -> Error {
+    Error::UnknownCall {
+        module: "Proxy".to_string(),
+        call: "proxy".to_string(),
+    }
+}
+
+#[cfg(all(test, feature = "runtime-kusama"))]
+mod tests {
+    use super::*;
+    use crate::common::{AccountId, BalanceBuilder, Currency};
+    use crate::runtime::kusama::{self, SpecVersion};
+    use kusama::extrinsics::balances::TransferKeepAlive;
+
+    #[test]
+    fn encode_proxy_call() {
+        let transfer = TransferKeepAlive {
+            dest: AccountId::new([1; 32]),
+            value: BalanceBuilder::new(Currency::Kusama).balance(1),
+        };
+
+        let call = ProxyCall::wrap(AccountId::new([2; 32]), Some(ProxyType::Auction), &transfer)
+            .build_for_runtime(&SpecVersion::latest().compiled_runtime())
+            .unwrap();
+
+        let (module_id, dispatch_id) = SpecVersion::latest()
+            .compiled_runtime()
+            .find_call_indices("Proxy", "proxy")
+            .unwrap();
+
+        let mut expected = vec![module_id, dispatch_id];
+        expected.extend_from_slice(&[2; 32]);
+        expected.extend_from_slice(&[1, 7]);
+        expected.extend(transfer.encode());
+        assert_eq!(call.0, expected);
+
+        #[cfg(feature = "metadata")]
+        {
+            let metadata = gekko_metadata::parse_hex_metadata(
+                include_str!("../../dumps/metadata_kusama_9080.hex").trim(),
+            )
+            .unwrap()
+            .into_latest()
+            .unwrap();
+
+            let wrapped =
+                ProxyCall::wrap(AccountId::new([2; 32]), Some(ProxyType::Auction), &transfer)
+                    .build_with_metadata(&metadata)
+                    .unwrap();
+            assert_eq!(wrapped, call);
+        }
+    }
+
+    #[test]
+    fn encode_without_proxy_type() {
+        let call = ProxyCall::<ProxyType>::wrap(AccountId::new([2; 32]), None, [4u8, 1])
+            .build_with_indices(30, 0);
+
+        let mut expected = vec![30, 0];
+        expected.extend_from_slice(&[2; 32]);
+        expected.extend_from_slice(&[0, 4, 1]);
+        assert_eq!(call.0, expected);
+    }
+}
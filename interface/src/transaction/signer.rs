@@ -0,0 +1,80 @@
+//! Signers whose keys are held outside of the process, such as hardware
+//! wallets or air-gapped devices.
+//!
+//! The [`SignedTransactionBuilder`](super::SignedTransactionBuilder) passes the
+//! signature payload to the [`ExternalSigner`] and verifies the returned
+//! signature against the account of the signer, see
+//! [`signer_external`](super::SignedTransactionBuilder::signer_external).
+//!
+//! # Example
+//!
+//! ```
+//! use gekko::common::*;
+//! use gekko::common::sp_core::crypto::Pair;
+//! use gekko::transaction::*;
+//! use gekko::transaction::signer::CallbackSigner;
+//!
+//! // The key never enters the builder, e.g. because it's held by a device.
+//! let (device, _) = KeyPairBuilder::<Sr25519>::generate();
+//! let account = AccountId::from(device.public());
+//!
+//! let signer = CallbackSigner::new(account, move |payload: &[u8]| {
+//!     Ok(MultiSignature::from(device.sign(payload)))
+//! });
+//!
+//! let transaction: PolkadotSignedExtrinsic<_> = SignedTransactionBuilder::new()
+//!     .signer_external(signer)
+//!     .call(OpaqueCall(vec![0, 1]))
+//!     .nonce(0)
+//!     .tip_base_unit(0)
+//!     .network(Network::Polkadot)
+//!     .spec_version(9050)
+//!     .transaction_version(7)
+//!     .build()
+//!     .unwrap();
+//!
+//! assert!(transaction.signature.is_some());
+//! ```
+
+use crate::common::{AccountId, MultiSignature};
+use crate::Result;
+
+/// A signer whose key is held externally. Signing may block, e.g. until the
+/// user confirms the transaction on a device.
+pub trait ExternalSigner {
+    /// The account of the key, which becomes the address of the transaction.
+    fn account_id(&self) -> AccountId;
+    /// Signs the exact payload the runtime verifies: the SCALE encoded call,
+    /// signed extensions and additional signed data, hashed with the
+    /// [`Hasher`](crate::common::Hasher) of the chain if exceeding 256 bytes.
+    /// ECDSA signatures must be created over the hash of the payload, like
+    /// the runtime does.
+    ///
+    /// Failures of the signer can be returned as
+    /// [`Error::ExternalSigner`](crate::Error::ExternalSigner).
+    fn sign_payload(&self, payload: &[u8]) -> Result<MultiSignature>;
+}
+
+/// An [`ExternalSigner`] which passes the payload to a callback.
+#[derive(Debug, Clone)]
+pub struct CallbackSigner<F> {
+    account: AccountId,
+    callback: F,
+}
+
+impl<F: Fn(&[u8]) -> Result<MultiSignature>> CallbackSigner<F> {
+    /// Creates a signer of the account, which signs payloads with the
+    /// callback.
+    pub fn new(account: AccountId, callback: F) -> Self {
+        CallbackSigner { account, callback }
+    }
+}
+
+impl<F: Fn(&[u8]) -> Result<MultiSignature>> ExternalSigner for CallbackSigner<F> {
+    fn account_id(&self) -> AccountId {
+        self.account
+    }
+    fn sign_payload(&self, payload: &[u8]) -> Result<MultiSignature> {
+        (self.callback)(payload)
+    }
+}
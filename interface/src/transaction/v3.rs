@@ -0,0 +1,370 @@
+//! Version 3 of the extrinsic format, as used by historical chains. Unlike
+//! version 4, the signature payload does not contain the `transaction_version`
+//! of the runtime, since the `CheckTxVersion` extension did not exist yet.
+//!
+//! # Example
+//!
+//! ```
+//! use gekko::common::*;
+//! use gekko::transaction::v3::*;
+//! use parity_scale_codec::Encode;
+//!
+//! let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+//!
+//! let transaction = SignedTransactionBuilder::new()
+//!     .signer(keypair)
+//!     .call(OpaqueCall(vec![0, 1]))
+//!     .nonce(0)
+//!     .tip_base_unit(0)
+//!     .network(Network::Kusama)
+//!     .spec_version(1020)
+//!     .build()
+//!     .unwrap();
+//!
+//! // The signed bit and the version.
+//! assert_eq!(transaction.encode()[2], 0b1000_0011);
+//! ```
+
+use super::v4::{self, read_bytes, SignaturePayload, Signer};
+use crate::common::{
+    Balance, Blake2_256, Config, Mortality, MultiAddress, MultiKeyPair, MultiSignature, Network,
+    PolkadotConfig,
+};
+use crate::{Error, Result};
+use parity_scale_codec::{Compact, Decode, Encode, Error as ScaleError, Input, Output};
+use std::convert::TryFrom;
+
+pub use super::v4::Payload;
+
+/// The version of the extrinsic format.
+pub const TX_VERSION: u32 = 3;
+
+/// A transaction of the version 3 format that can contain a signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transaction<Address, Call, Signature, ExtraSignaturePayload> {
+    pub signature: Option<(Address, Signature, ExtraSignaturePayload)>,
+    pub call: Call,
+}
+
+impl<Call> Transaction<(), Call, (), ()> {
+    pub fn new_unsigned(call: Call) -> Self {
+        Self {
+            signature: None,
+            call,
+        }
+    }
+}
+
+impl<Address, Call, Signature, ExtraSignaturePayload> Encode
+    for Transaction<Address, Call, Signature, ExtraSignaturePayload>
+where
+    Address: Encode,
+    Signature: Encode,
+    Call: Encode,
+    ExtraSignaturePayload: Encode,
+{
+    fn size_hint(&self) -> usize {
+        // Length prefix and version Id.
+        5 + 1 + self.signature.size_hint() + self.call.size_hint()
+    }
+    fn encode_to<T: Output + ?Sized>(&self, dest: &mut T) {
+        let mut enc: Vec<u8> = Vec::with_capacity(self.size_hint());
+
+        // The first bit implies whether the transaction is signed, the
+        // remaining 7 bits represent the TX_VERSION.
+        match &self.signature {
+            Some(sig) => {
+                enc.push(0b1000_0000 | TX_VERSION as u8);
+                sig.encode_to(&mut enc);
+            }
+            None => enc.push(TX_VERSION as u8),
+        }
+
+        self.call.encode_to(&mut enc);
+
+        // Prefix the length.
+        enc.encode_to(dest);
+    }
+}
+
+impl<Address, Call, Signature, ExtraSignaturePayload> Decode
+    for Transaction<Address, Call, Signature, ExtraSignaturePayload>
+where
+    Address: Decode,
+    Signature: Decode,
+    Call: Decode,
+    ExtraSignaturePayload: Decode,
+{
+    fn decode<I: Input>(input: &mut I) -> std::result::Result<Self, ScaleError> {
+        // See the version 4 implementation on why the transaction is read
+        // first.
+        let len = Compact::<u32>::decode(input)?.0 as usize;
+        if input
+            .remaining_len()?
+            .is_some_and(|remaining| remaining < len)
+        {
+            return Err("Length prefix of transaction exceeds the input".into());
+        }
+
+        let encoded = read_bytes(input, len)?;
+        let input = &mut encoded.as_slice();
+
+        let sig = match input.read_byte()? {
+            0b1000_0011 => Some(Decode::decode(input)?),
+            0b0000_0011 => None,
+            _ => return Err("Invalid transaction version".into()),
+        };
+
+        let call = Decode::decode(input)?;
+        if !input.is_empty() {
+            return Err("Invalid length prefix of transaction".into());
+        }
+
+        Ok(Self {
+            signature: sig,
+            call,
+        })
+    }
+}
+
+impl<Call: Encode, Extra: Encode> Transaction<MultiAddress, Call, MultiSignature, Extra> {
+    /// Verifies the signature of the transaction against the address of the
+    /// signer, see [`v4::Transaction::verify_signature`]. The version 3
+    /// signature payload has no transaction version.
+    pub fn verify_signature(&self, genesis: [u8; 32], spec_version: u32, birth: [u8; 32]) -> bool {
+        let (account, sig, payload) = match &self.signature {
+            Some((MultiAddress::Id(account), sig, payload)) => (account, sig, payload),
+            _ => return false,
+        };
+
+        let extra = ExtraSignaturePayload {
+            spec_version,
+            genesis,
+            birth,
+        };
+
+        SignaturePayload::<_, _, _, Blake2_256>::new(&self.call, payload, extra)
+            .using_encoded(|payload| sig.verify(payload, &account.clone().into()))
+    }
+}
+
+/// A signed version 3 transaction of the chain with the given [`Config`].
+pub type SignedExtrinsic<C, Call> = Transaction<
+    <C as Config>::Address,
+    Call,
+    <C as Config>::Signature,
+    Payload<<C as Config>::Index, <C as Config>::Balance, <C as Config>::Era>,
+>;
+
+/// The signed extensions which are only part of the signature payload.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "Hash: AsRef<[u8]>",
+        deserialize = "Hash: TryFrom<Vec<u8>>"
+    ))
+)]
+pub struct ExtraSignaturePayload<Hash = [u8; 32]> {
+    pub spec_version: u32,
+    #[cfg_attr(feature = "serde", serde(with = "gekko_metadata::serde_hex"))]
+    pub genesis: Hash,
+    /// The block hash from where the period of mortality begins. If the
+    /// transaction is immortal, it's the genesis hash.
+    #[cfg_attr(feature = "serde", serde(with = "gekko_metadata::serde_hex"))]
+    pub birth: Hash,
+}
+
+/// Builder type for creating signed version 3 transactions, see
+/// [`v4::SignedTransactionBuilder`]. The primitive types are taken from the
+/// [`Config`], while its signed extensions are ignored in favor of [`Payload`]
+/// and [`ExtraSignaturePayload`].
+#[derive(Clone)]
+pub struct SignedTransactionBuilder<Call, C: Config = PolkadotConfig> {
+    signer: Option<MultiKeyPair>,
+    call: Option<Call>,
+    nonce: Option<C::Index>,
+    tip: Option<u128>,
+    network: Option<Network>,
+    mortality: Mortality,
+    spec_version: Option<u32>,
+}
+
+impl<Call, C: Config> Default for SignedTransactionBuilder<Call, C> {
+    fn default() -> Self {
+        Self {
+            signer: None,
+            call: None,
+            nonce: None,
+            tip: None,
+            network: None,
+            mortality: Mortality::Immortal,
+            spec_version: None,
+        }
+    }
+}
+
+impl<Call: Encode> SignedTransactionBuilder<Call> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<Call: Encode, C: Config> SignedTransactionBuilder<Call, C> {
+    pub fn signer<T: Into<MultiKeyPair>>(self, signer: T) -> Self {
+        Self {
+            signer: Some(signer.into()),
+            ..self
+        }
+    }
+    pub fn call(self, call: Call) -> Self {
+        Self {
+            call: Some(call),
+            ..self
+        }
+    }
+    pub fn nonce(self, nonce: C::Index) -> Self {
+        Self {
+            nonce: Some(nonce),
+            ..self
+        }
+    }
+    pub fn tip(self, tip: Balance) -> Self {
+        Self {
+            tip: Some(tip.as_base_unit()),
+            ..self
+        }
+    }
+    pub fn tip_base_unit(self, tip: C::Balance) -> Self {
+        Self {
+            tip: Some(tip.into()),
+            ..self
+        }
+    }
+    pub fn network(self, network: Network) -> Self {
+        Self {
+            network: Some(network),
+            ..self
+        }
+    }
+    /// Set the mortality of the transaction. Immortal by default.
+    pub fn mortality(self, mortality: Mortality) -> Self {
+        Self { mortality, ..self }
+    }
+    /// Set the `spec_version` of the runtime. Always required, since the
+    /// latest known versions do not apply to historical runtimes.
+    pub fn spec_version(self, version: u32) -> Self {
+        Self {
+            spec_version: Some(version),
+            ..self
+        }
+    }
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), fields(nonce = ?self.nonce), err)
+    )]
+    pub fn build(self) -> Result<SignedExtrinsic<C, Call>> {
+        let signer = self.signer.ok_or(Error::BuilderMissingField("signer"))?;
+        let call = self.call.ok_or(Error::BuilderMissingField("call"))?;
+        let nonce = self.nonce.ok_or(Error::BuilderMissingField("nonce"))?;
+        let tip = self.tip.ok_or(Error::BuilderMissingField("tip"))?;
+        let tip = C::Balance::try_from(tip).map_err(|_| Error::BalanceOverflow(tip))?;
+        let network = self.network.ok_or(Error::BuilderMissingField("network"))?;
+        let spec_version = self
+            .spec_version
+            .ok_or(Error::BuilderMissingField("spec_version"))?;
+
+        // Set mortality starting period.
+        let birth = match self.mortality {
+            Mortality::Immortal => network.genesis(),
+            Mortality::Mortal(_, _, birth) => {
+                birth.ok_or(Error::BuilderMissingField("no birth block in Mortality"))?
+            }
+        };
+
+        let payload = Payload {
+            mortality: self.mortality.into(),
+            nonce,
+            tip,
+        };
+        let extra = ExtraSignaturePayload::<C::Hash> {
+            spec_version,
+            genesis: network.genesis().into(),
+            birth: birth.into(),
+        };
+
+        let transaction = v4::sign::<_, _, _, C>(Signer::KeyPair(signer), call, payload, extra)?;
+        Ok(Transaction {
+            signature: transaction.signature,
+            call: transaction.call,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::*;
+
+    #[test]
+    fn signed_transaction_encode_decode() {
+        let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+
+        let transaction = SignedTransactionBuilder::new()
+            .signer(keypair)
+            .call(OpaqueCall(vec![4, 3, 1, 2]))
+            .nonce(1)
+            .tip_base_unit(5)
+            .network(Network::Custom([1; 32]))
+            .spec_version(1020)
+            .build()
+            .unwrap();
+
+        let encoded = transaction.encode();
+        assert_eq!(encoded[2], 0b1000_0011);
+
+        let decoded: SignedExtrinsic<PolkadotConfig, OpaqueCall> =
+            Decode::decode(&mut encoded.as_slice()).unwrap();
+        assert_eq!(decoded, transaction);
+
+        assert!(decoded.verify_signature([1; 32], 1020, [1; 32]));
+        assert!(!decoded.verify_signature([1; 32], 1021, [1; 32]));
+
+        // Not a version 4 transaction.
+        assert!(
+            v4::PolkadotSignedExtrinsic::<OpaqueCall>::decode(&mut encoded.as_slice()).is_err()
+        );
+    }
+
+    #[test]
+    fn unsigned_transaction_encode_decode() {
+        let transaction = Transaction::new_unsigned(OpaqueCall(vec![0, 1]));
+
+        let encoded = transaction.encode();
+        assert_eq!(encoded, vec![12, 3, 0, 1]);
+        assert_eq!(
+            Transaction::decode(&mut encoded.as_slice()).unwrap(),
+            transaction
+        );
+    }
+
+    #[test]
+    fn builder_requires_spec_version() {
+        let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+
+        let res = SignedTransactionBuilder::new()
+            .signer(keypair)
+            .call(OpaqueCall(vec![0, 1]))
+            .nonce(0)
+            .tip_base_unit(0)
+            .network(Network::Polkadot)
+            .build();
+
+        assert!(matches!(
+            res,
+            Err(Error::BuilderMissingField("spec_version"))
+        ));
+    }
+}
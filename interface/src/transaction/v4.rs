@@ -1,14 +1,33 @@
-use crate::common::{AccountId, Balance, Mortality, MultiKeyPair, MultiSignature, Network};
-use crate::runtime::{kusama, polkadot};
-use crate::{blake2b, Error, Result};
-use parity_scale_codec::{Decode, Encode, Error as ScaleError, Input};
+use crate::common::{
+    AccountId, Balance, Blake2_256, Config, Ecdsa, Hasher, Mortality, MultiAddress, MultiKeyPair,
+    MultiSignature, Network, OpaqueCall, PolkadotConfig,
+};
+#[cfg(feature = "runtime-kusama")]
+use crate::runtime::kusama;
+#[cfg(feature = "runtime-polkadot")]
+use crate::runtime::polkadot;
+use crate::transaction::extensions::{EncodedExtensions, SignedExtensions};
+use crate::transaction::ExternalSigner;
+use crate::{Error, Result};
+#[cfg(feature = "metadata")]
+use gekko_metadata::{
+    dynamic::ExtrinsicDecoder, scale::TypeRegistry, MetadataExt, MetadataVersion,
+};
+use parity_scale_codec::{Compact, Decode, Encode, Error as ScaleError, Input, Output};
 use sp_core::crypto::Pair;
+use std::convert::TryFrom;
+use std::marker::PhantomData;
+use std::sync::Arc;
 
+/// The version of the extrinsic format. Not to be confused with the
+/// `transaction_version` of a runtime, see
+/// [`SignedTransactionBuilder::transaction_version`].
 pub const TX_VERSION: u32 = 4;
 
 /// A transaction that can contain a signature. Referred to as
 /// "UncheckedExtrinsic" in Substrate vocabulary.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Transaction<Address, Call, Signature, ExtraSignaturePayload> {
     pub signature: Option<(Address, Signature, ExtraSignaturePayload)>,
     pub call: Call,
@@ -31,8 +50,12 @@ where
     Call: Encode,
     ExtraSignaturePayload: Encode,
 {
-    fn using_encoded<R, F: FnOnce(&[u8]) -> R>(&self, f: F) -> R {
-        let mut enc: Vec<u8> = Vec::with_capacity(std::mem::size_of::<Self>());
+    fn size_hint(&self) -> usize {
+        // Length prefix and version Id.
+        5 + 1 + self.signature.size_hint() + self.call.size_hint()
+    }
+    fn encode_to<T: Output + ?Sized>(&self, dest: &mut T) {
+        let mut enc: Vec<u8> = Vec::with_capacity(self.size_hint());
 
         // Add version Id.
         match &self.signature {
@@ -50,7 +73,9 @@ where
         }
 
         self.call.encode_to(&mut enc);
-        f(&enc.encode())
+
+        // Prefix the length.
+        enc.encode_to(dest);
     }
 }
 
@@ -63,8 +88,20 @@ where
     ExtraSignaturePayload: Decode,
 {
     fn decode<I: Input>(input: &mut I) -> std::result::Result<Self, ScaleError> {
-        // Throw away that compact integer which indicates the array length.
-        let _: Vec<()> = Decode::decode(input)?;
+        // Read the length prefixed transaction first, so decoding the
+        // individual fields never reads beyond the transaction. This is
+        // required for types which consume the remaining input, such as
+        // `OpaqueCall`.
+        let len = Compact::<u32>::decode(input)?.0 as usize;
+        if input
+            .remaining_len()?
+            .is_some_and(|remaining| remaining < len)
+        {
+            return Err("Length prefix of transaction exceeds the input".into());
+        }
+
+        let encoded = read_bytes(input, len)?;
+        let input = &mut encoded.as_slice();
 
         // Determine transaction version, handle signed/unsigned variant.
         // See the `Encode` implementation on why those values are used.
@@ -74,20 +111,125 @@ where
             _ => return Err("Invalid transaction version".into()),
         };
 
+        let call = Decode::decode(input)?;
+        if !input.is_empty() {
+            return Err("Invalid length prefix of transaction".into());
+        }
+
         Ok(Self {
             signature: sig,
-            call: Decode::decode(input)?,
+            call,
         })
     }
 }
 
-pub type PolkadotSignedExtrinsic<Call> = Transaction<AccountId, Call, MultiSignature, Payload>;
+impl<Address, Call, Signature, ExtraSignaturePayload>
+    Transaction<Address, Call, Signature, ExtraSignaturePayload>
+where
+    Address: Decode,
+    Signature: Decode,
+    Call: Decode,
+    ExtraSignaturePayload: Decode,
+{
+    /// Decodes the length prefixed transaction, like [`Decode::decode`], but
+    /// rejects any bytes following the transaction.
+    pub fn decode_all(data: &[u8]) -> std::result::Result<Self, ScaleError> {
+        let input = &mut &*data;
+        let transaction = Self::decode(input)?;
+        if !input.is_empty() {
+            return Err("Trailing bytes after transaction".into());
+        }
+
+        Ok(transaction)
+    }
+}
+
+/// Reads the given number of bytes. The length is not trusted for allocating
+/// memory, since the length of the input might be unknown.
+pub(super) fn read_bytes<I: Input>(
+    input: &mut I,
+    len: usize,
+) -> std::result::Result<Vec<u8>, ScaleError> {
+    const CHUNK_LEN: usize = 16 * 1024;
+
+    let mut data = Vec::with_capacity(len.min(CHUNK_LEN));
+    while data.len() < len {
+        let start = data.len();
+        data.resize(len.min(start + CHUNK_LEN), 0);
+        input.read(&mut data[start..])?;
+    }
+
+    Ok(data)
+}
+
+impl<Address, Signature, Extra> Transaction<Address, OpaqueCall, Signature, Extra> {
+    /// Creates the signed transaction from the payload and the signature,
+    /// which was created offline, see
+    /// [`SignedTransactionBuilder::build_unsigned_payload`]. The signature is
+    /// not verified, see [`SigningPayload::verify`].
+    pub fn attach_signature<H>(
+        address: Address,
+        signature: Signature,
+        payload: SigningPayload<Extra, H>,
+    ) -> Self {
+        Transaction {
+            signature: Some((address, signature, payload.extra)),
+            call: OpaqueCall(payload.call),
+        }
+    }
+}
+
+impl<Call: Encode, Extra: Encode> Transaction<MultiAddress, Call, MultiSignature, Extra> {
+    /// Verifies the signature of the transaction against the address of the
+    /// signer, by reconstructing the signature payload from the call, the
+    /// signed extensions and the given parameters. This allows validating
+    /// extrinsics which were created by third parties or offline.
+    ///
+    /// Returns `false` if the transaction is unsigned or signed by an address
+    /// other than [`MultiAddress::Id`]. Like Substrate, payloads exceeding 256
+    /// bytes are hashed with [`Blake2_256`].
+    pub fn verify_signature(
+        &self,
+        genesis: [u8; 32],
+        spec_version: u32,
+        tx_version: u32,
+        birth: [u8; 32],
+    ) -> bool {
+        let (account, sig, payload) = match &self.signature {
+            Some((MultiAddress::Id(account), sig, payload)) => (account, sig, payload),
+            _ => return false,
+        };
+
+        let extra = ExtraSignaturePayload {
+            spec_version,
+            tx_version,
+            genesis,
+            birth,
+        };
+
+        SignaturePayload::<_, _, _, Blake2_256>::new(&self.call, payload, extra)
+            .using_encoded(|payload| sig.verify(payload, &account.clone().into()))
+    }
+}
+
+/// A signed transaction of the chain with the given [`Config`].
+pub type SignedExtrinsic<C, Call> =
+    Transaction<<C as Config>::Address, Call, <C as Config>::Signature, <C as Config>::Extra>;
+
+pub type PolkadotSignedExtrinsic<Call> = SignedExtrinsic<PolkadotConfig, Call>;
+
+/// A signed transaction of the chain with the given [`Config`], whose signed
+/// extensions were encoded by [`SignedExtensions`].
+pub type DynamicSignedExtrinsic<C, Call> =
+    Transaction<<C as Config>::Address, Call, <C as Config>::Signature, EncodedExtensions>;
 
 /// Builder type for creating signed transactions.
 ///
 /// # Example
 ///
 /// ```
+/// # #[cfg(feature = "runtime-polkadot")]
+/// # {
 /// use gekko::common::*;
 /// use gekko::transaction::*;
 /// use gekko::runtime::polkadot::extrinsics::balances::TransferKeepAlive;
@@ -103,45 +245,70 @@ pub type PolkadotSignedExtrinsic<Call> = Transaction<AccountId, Call, MultiSigna
 ///
 /// // Send 50 DOT to the destination.
 /// let call = TransferKeepAlive {
-///     dest: destination,
+///     dest: MultiAddress::from(destination),
 ///     value: currency.balance(50),
 /// };
 ///
-/// // Transaction fee.
-/// let payment = currency.balance_as_metric(Metric::Milli, 10).unwrap();
+/// // Tip for the block author, in addition to the transaction fee.
+/// let tip = currency.balance_as_metric(Metric::Milli, 10).unwrap();
 ///
 /// // Build the final transaction.
 /// let transaction: PolkadotSignedExtrinsic<_> = SignedTransactionBuilder::new()
 ///     .signer(keypair)
 ///     .call(call)
 ///     .nonce(0)
-///     .payment(payment)
+///     .tip(tip)
 ///     .network(Network::Polkadot)
 ///     .spec_version(9050)
 ///     .build()
 ///     .unwrap();
+/// # }
 /// ```
+///
+/// The builder uses the primitive types of [`PolkadotConfig`], unless another
+/// [`Config`] is specified with `SignedTransactionBuilder::<_, C>::default()`.
 #[derive(Clone)]
-pub struct SignedTransactionBuilder<Call> {
-    signer: Option<MultiKeyPair>,
+pub struct SignedTransactionBuilder<Call, C: Config = PolkadotConfig> {
+    signer: Option<Signer>,
     call: Option<Call>,
-    nonce: Option<u32>,
-    payment: Option<u128>,
+    nonce: Option<C::Index>,
+    tip: Option<u128>,
+    asset_id: Option<u32>,
     network: Option<Network>,
     mortality: Mortality,
     spec_version: Option<u32>,
+    transaction_version: Option<u32>,
+    metadata_hash: Option<[u8; 32]>,
+    /// The module and call indices of the runtime, see
+    /// [`from_metadata`](Self::from_metadata).
+    #[cfg(feature = "metadata")]
+    call_indices: Option<Vec<(u8, u8)>>,
+}
+
+/// The key of the [`SignedTransactionBuilder`].
+// Only a single signer is held per builder.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone)]
+pub(super) enum Signer {
+    KeyPair(MultiKeyPair),
+    External(Arc<dyn ExternalSigner + Send + Sync>),
 }
 
-impl<Call> Default for SignedTransactionBuilder<Call> {
+impl<Call, C: Config> Default for SignedTransactionBuilder<Call, C> {
     fn default() -> Self {
         Self {
             signer: None,
             call: None,
             nonce: None,
-            payment: None,
+            tip: None,
+            asset_id: None,
             network: None,
             mortality: Mortality::Immortal,
             spec_version: None,
+            transaction_version: None,
+            metadata_hash: None,
+            #[cfg(feature = "metadata")]
+            call_indices: None,
         }
     }
 }
@@ -150,9 +317,21 @@ impl<Call: Encode> SignedTransactionBuilder<Call> {
     pub fn new() -> Self {
         Default::default()
     }
+}
+
+impl<Call: Encode, C: Config> SignedTransactionBuilder<Call, C> {
     pub fn signer<T: Into<MultiKeyPair>>(self, signer: T) -> Self {
         Self {
-            signer: Some(signer.into()),
+            signer: Some(Signer::KeyPair(signer.into())),
+            ..self
+        }
+    }
+    /// Set a signer whose key is held externally, such as a hardware wallet,
+    /// instead of a key pair. See the [`signer`](crate::transaction::signer)
+    /// module.
+    pub fn signer_external<S: ExternalSigner + Send + Sync + 'static>(self, signer: S) -> Self {
+        Self {
+            signer: Some(Signer::External(Arc::new(signer))),
             ..self
         }
     }
@@ -167,17 +346,46 @@ impl<Call: Encode> SignedTransactionBuilder<Call> {
     }
     /// Set the nonce of the transaction. You must track and increment the nonce
     /// of the corresponding signer manually, retrieved from the blockchain.
-    /// Keep pending transactions in mind.
-    pub fn nonce(self, nonce: u32) -> Self {
+    /// Keep pending transactions in mind. With the `"rpc"` feature, the nonce
+    /// can be fetched with `rpc::account_next_index`, which includes pending
+    /// transactions, or `rpc::account::account_nonce`.
+    pub fn nonce(self, nonce: C::Index) -> Self {
         Self {
             nonce: Some(nonce),
             ..self
         }
     }
-    // TODO: Rename to "fee"
+    /// Set the tip of the transaction, which is paid to the block author in
+    /// addition to the transaction fee in order to prioritize the transaction.
+    pub fn tip(self, tip: Balance) -> Self {
+        Self {
+            tip: Some(tip.as_base_unit()),
+            ..self
+        }
+    }
+    /// Like [`tip`](Self::tip), but takes the value in the base unit of the
+    /// runtime, such as "Planck" on Polkadot. Unlike [`Balance`], this allows
+    /// a tip of zero.
+    pub fn tip_base_unit(self, tip: C::Balance) -> Self {
+        Self {
+            tip: Some(tip.into()),
+            ..self
+        }
+    }
+    #[deprecated(note = "the payment is the tip, use `tip` instead")]
     pub fn payment(self, payment: Balance) -> Self {
+        self.tip(payment)
+    }
+    #[deprecated(note = "the payment is the tip, use `tip_base_unit` instead")]
+    pub fn payment_base_unit(self, payment: C::Balance) -> Self {
+        self.tip_base_unit(payment)
+    }
+    /// Set the asset with which the fee and the tip are paid, for chains with
+    /// the `ChargeAssetTxPayment` extension such as Statemint. See
+    /// [`AssetPayload`]. By default, both are paid with the native currency.
+    pub fn asset_id(self, asset_id: u32) -> Self {
         Self {
-            payment: Some(payment.as_base_unit()),
+            asset_id: Some(asset_id),
             ..self
         }
     }
@@ -190,38 +398,190 @@ impl<Call: Encode> SignedTransactionBuilder<Call> {
     }
     /// Set the mortality of the transaction. Immortal by default.
     pub fn mortality(self, mortality: Mortality) -> Self {
-        Self {
-            mortality: mortality,
-            ..self
-        }
+        Self { mortality, ..self }
     }
     /// Set the `spec_version` of the runtime. For Kusama and Polkadot,
     /// the builder uses the **latest** known versions by default:
-    /// [kusama::LATEST_SPEC_VERSION] and [polkadot::LATEST_SPEC_VERSION],
-    /// respectively.
+    /// `kusama::SPEC_VERSION` and `polkadot::SPEC_VERSION`, respectively.
+    /// Those defaults are only available if the corresponding
+    /// `runtime-kusama` or `runtime-polkadot` feature is enabled.
     ///
-    /// For any other [Network], calling this function is required.
+    /// For any other [Network], calling this function is required, unless
+    /// the network has a registered [`ChainSpec`](crate::common::ChainSpec)
+    /// with a default version.
     pub fn spec_version(self, version: u32) -> Self {
         Self {
             spec_version: Some(version),
             ..self
         }
     }
-    pub fn build(self) -> Result<PolkadotSignedExtrinsic<Call>> {
-        let signer = self.signer.ok_or(Error::BuilderMissingField("signer"))?;
+    /// Set the `transaction_version` of the runtime. Just like with
+    /// [`spec_version`](Self::spec_version), the builder uses
+    /// `kusama::TRANSACTION_VERSION` and `polkadot::TRANSACTION_VERSION` by
+    /// default.
+    ///
+    /// For any other [Network], calling this function is required.
+    pub fn transaction_version(self, version: u32) -> Self {
+        Self {
+            transaction_version: Some(version),
+            ..self
+        }
+    }
+    /// Enable the `CheckMetadataHash` extension with the hash of the metadata
+    /// of the runtime, which is added to the signature payload. Only encoded
+    /// by signed extensions which support it, such as
+    /// [`MetadataHashPayload`](super::v5::MetadataHashPayload). Disabled by
+    /// default. The hash is computed by `gekko_metadata::merkle::metadata_hash`.
+    pub fn metadata_hash(self, hash: [u8; 32]) -> Self {
+        Self {
+            metadata_hash: Some(hash),
+            ..self
+        }
+    }
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), fields(nonce = ?self.nonce), err)
+    )]
+    pub fn build(self) -> Result<SignedExtrinsic<C, Call>> {
+        let (signer, call, params) = self.prepare()?;
+
+        let payload = C::Extra::new(&params);
+        let extra = C::AdditionalSigned::new(&params);
+
+        sign::<_, _, _, C>(signer, call, payload, extra)
+    }
+    /// Like [`build`](Self::build), but encodes the given signed extensions
+    /// instead of [`Config::Extra`] and [`Config::AdditionalSigned`]. This
+    /// allows transactions for chains whose extensions are only known from
+    /// their metadata, see [`SignedExtensions::from_metadata`].
+    ///
+    /// The resulting transaction can be encoded, but not decoded, since the
+    /// length of the extensions is unknown without the metadata.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), fields(nonce = ?self.nonce), err)
+    )]
+    pub fn build_with_extensions(
+        self,
+        extensions: &SignedExtensions<C>,
+    ) -> Result<DynamicSignedExtrinsic<C, Call>> {
+        let (signer, call, params) = self.prepare()?;
+        let (payload, extra) = extensions.encode(&params)?;
+
+        sign::<_, _, _, C>(signer, call, payload, extra)
+    }
+    /// Creates the payload of the transaction without signing it, so it can
+    /// be signed on another machine, such as a cold wallet. No signer needs to
+    /// be set. The signature is attached with
+    /// [`Transaction::attach_signature`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gekko::common::*;
+    /// use gekko::common::sp_core::crypto::Pair;
+    /// use gekko::transaction::*;
+    ///
+    /// let payload = SignedTransactionBuilder::new()
+    ///     .call(OpaqueCall(vec![0, 1]))
+    ///     .nonce(0)
+    ///     .tip_base_unit(0)
+    ///     .network(Network::Polkadot)
+    ///     .spec_version(9050)
+    ///     .transaction_version(7)
+    ///     .build_unsigned_payload()
+    ///     .unwrap();
+    ///
+    /// // On the offline machine.
+    /// let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+    /// let sig = MultiSignature::from(keypair.sign(&payload.to_signable_bytes()));
+    ///
+    /// // Back online.
+    /// let account = AccountId::from(keypair.public());
+    /// assert!(payload.verify(&sig, &account));
+    ///
+    /// let transaction: PolkadotSignedExtrinsic<_> =
+    ///     Transaction::attach_signature(account.into(), sig, payload);
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), fields(nonce = ?self.nonce), err)
+    )]
+    pub fn build_unsigned_payload(self) -> Result<SigningPayload<C::Extra, C::Hasher>> {
+        let (call, params) = self.prepare_unsigned()?;
+
+        Ok(SigningPayload::new(
+            call.encode(),
+            C::Extra::new(&params),
+            C::AdditionalSigned::new(&params).encode(),
+        ))
+    }
+    /// Like [`build_unsigned_payload`](Self::build_unsigned_payload), but
+    /// encodes the given signed extensions, see
+    /// [`build_with_extensions`](Self::build_with_extensions).
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), fields(nonce = ?self.nonce), err)
+    )]
+    pub fn build_unsigned_payload_with_extensions(
+        self,
+        extensions: &SignedExtensions<C>,
+    ) -> Result<SigningPayload<EncodedExtensions, C::Hasher>> {
+        let (call, params) = self.prepare_unsigned()?;
+        let (payload, extra) = extensions.encode(&params)?;
+
+        Ok(SigningPayload::new(call.encode(), payload, extra.encode()))
+    }
+    /// Validates the fields of the builder and creates the parameters from
+    /// which the signed extensions are created.
+    fn prepare(mut self) -> Result<(Signer, Call, ExtrinsicParams<C>)> {
+        let signer = self
+            .signer
+            .take()
+            .ok_or(Error::BuilderMissingField("signer"))?;
+        let (call, params) = self.prepare_unsigned()?;
+
+        Ok((signer, call, params))
+    }
+    /// Like [`prepare`](Self::prepare), but without requiring a signer.
+    pub(super) fn prepare_unsigned(self) -> Result<(Call, ExtrinsicParams<C>)> {
         let call = self.call.ok_or(Error::BuilderMissingField("call"))?;
+        #[cfg(feature = "metadata")]
+        if let Some(indices) = &self.call_indices {
+            call.using_encoded(|encoded| validate_call_index(indices, encoded))?;
+        }
         let nonce = self.nonce.ok_or(Error::BuilderMissingField("nonce"))?;
-        let payment = self.payment.ok_or(Error::BuilderMissingField("payment"))?;
+        let tip = self.tip.ok_or(Error::BuilderMissingField("tip"))?;
+        let tip = C::Balance::try_from(tip).map_err(|_| Error::BalanceOverflow(tip))?;
         let network = self.network.ok_or(Error::BuilderMissingField("network"))?;
 
-        // Determine spec_version.
-        let spec_version = match network {
-            Network::Kusama => self.spec_version.unwrap_or(kusama::SPEC_VERSION),
-            Network::Polkadot => self.spec_version.unwrap_or(polkadot::SPEC_VERSION),
-            // `spec_version` must be provided for any other network.
-            _ => self
-                .spec_version
-                .ok_or(Error::BuilderMissingField("spec_version"))?,
+        // Determine spec_version and transaction_version.
+        let (spec_version, transaction_version) = match network {
+            #[cfg(feature = "runtime-kusama")]
+            Network::Kusama => (
+                self.spec_version.unwrap_or(kusama::SPEC_VERSION),
+                self.transaction_version
+                    .unwrap_or(kusama::TRANSACTION_VERSION),
+            ),
+            #[cfg(feature = "runtime-polkadot")]
+            Network::Polkadot => (
+                self.spec_version.unwrap_or(polkadot::SPEC_VERSION),
+                self.transaction_version
+                    .unwrap_or(polkadot::TRANSACTION_VERSION),
+            ),
+            // Both versions must be provided for any other network, unless
+            // registered with its chain specification.
+            _ => {
+                let spec = network.chain_spec();
+                (
+                    self.spec_version
+                        .or_else(|| spec.as_ref()?.spec_version)
+                        .ok_or(Error::BuilderMissingField("spec_version"))?,
+                    self.transaction_version
+                        .or_else(|| spec.as_ref()?.transaction_version)
+                        .ok_or(Error::BuilderMissingField("transaction_version"))?,
+                )
+            }
         };
 
         // Set mortality starting period.
@@ -232,73 +592,469 @@ impl<Call: Encode> SignedTransactionBuilder<Call> {
             }
         };
 
-        // Prepare transaction payload.
-        let payload = Payload {
-            mortality: self.mortality,
-            nonce: nonce,
-            payment: payment,
+        // Prepare the parameters of the signed extensions.
+        let params = ExtrinsicParams::<C> {
+            era: self.mortality.into(),
+            nonce,
+            tip,
+            asset_id: self.asset_id,
+            spec_version,
+            transaction_version,
+            genesis: network.genesis().into(),
+            birth: birth.into(),
+            metadata_hash: self.metadata_hash,
         };
 
-        let extra = ExtraSignaturePayload {
-            spec_version: spec_version,
-            tx_version: TX_VERSION,
-            genesis: network.genesis(),
-            birth: birth,
-        };
+        Ok((call, params))
+    }
+}
+
+/// Signs the call together with the signed extensions and creates the final
+/// transaction.
+pub(super) fn sign<Call, Extra, AdditionalSigned, C>(
+    signer: Signer,
+    call: Call,
+    payload: Extra,
+    extra: AdditionalSigned,
+) -> Result<Transaction<C::Address, Call, C::Signature, Extra>>
+where
+    Call: Encode,
+    Extra: Encode,
+    AdditionalSigned: Encode,
+    C: Config,
+{
+    // Create the full signature payload.
+    let sig_payload = SignaturePayload::<_, _, _, C::Hasher>::new(call, payload, extra);
+
+    // Create signature.
+    let (addr, sig) = match signer {
+        Signer::KeyPair(signer) => {
+            let sig = sig_payload.using_encoded(|payload| match &signer {
+                MultiKeyPair::Ed25519(pair) => MultiSignature::from(pair.sign(payload)),
+                MultiKeyPair::Sr25519(pair) => pair.sign(payload).into(),
+                MultiKeyPair::Ecdsa(pair) => sign_prehashed(pair, &C::Hasher::hash(payload)).into(),
+            });
+
+            (signer.into(), sig)
+        }
+        // The signature is not trusted, since a device might hold another key
+        // than expected.
+        Signer::External(signer) => {
+            let account = signer.account_id();
+            let sig = sig_payload.using_encoded(|payload| {
+                let sig = signer.sign_payload(payload)?;
+                if verify_payload::<C::Hasher>(&sig, payload, &account) {
+                    Ok(sig)
+                } else {
+                    Err(Error::InvalidSignature(account))
+                }
+            })?;
+
+            (account.into(), sig)
+        }
+    };
 
-        // Create the full signature payload.
-        let sig_payload = SignaturePayload::new(call, payload, extra);
+    // Prepare all entries for the final extrinsic.
+    let (call, payload, _) = sig_payload.deconstruct();
 
-        // Create signature.
-        let sig = sig_payload.using_encoded(|payload| match &signer {
-            MultiKeyPair::Ed25519(pair) => pair.sign(payload).into(),
-            MultiKeyPair::Sr25519(pair) => pair.sign(payload).into(),
-            MultiKeyPair::Ecdsa(pair) => pair.sign(payload).into(),
+    Ok(Transaction {
+        signature: Some((addr, sig.into(), payload)),
+        call,
+    })
+}
+
+/// Verifies the signature of the (possibly hashed) signature payload. Unlike
+/// [`MultiSignature::verify`], ECDSA signatures are verified over the hash of
+/// the given [`Hasher`].
+fn verify_payload<H: Hasher>(sig: &MultiSignature, payload: &[u8], account: &AccountId) -> bool {
+    use secp256k1::recovery::{RecoverableSignature, RecoveryId};
+    use secp256k1::{Message, Secp256k1};
+
+    let sig = match sig {
+        MultiSignature::Ecdsa(sig) => sig,
+        _ => return sig.verify(payload, account),
+    };
+
+    let raw: &[u8] = sig.as_ref();
+    let public = RecoveryId::from_i32(raw[64] as i32)
+        .and_then(|id| RecoverableSignature::from_compact(&raw[..64], id))
+        .and_then(|sig| {
+            // The hash is always a valid message.
+            let message = Message::from_slice(&H::hash(payload)).unwrap();
+            Secp256k1::verification_only().recover(&message, &sig)
         });
 
-        // Prepare all entries for the final extrinsic.
-        let addr = signer.into();
-        let (call, payload, _) = sig_payload.deconstruct();
+    match public {
+        Ok(public) => crate::blake2b(public.serialize()) == account.to_bytes(),
+        Err(_) => false,
+    }
+}
 
-        Ok(Transaction {
-            signature: Some((addr, sig, payload)),
-            call: call,
+#[cfg(feature = "metadata")]
+impl<Call: Encode, C: Config> SignedTransactionBuilder<Call, C> {
+    /// Creates a builder for the runtime described by the metadata and its
+    /// version record, instead of the latest known versions. Sets the
+    /// `spec_version` and `transaction_version`, and verifies on build that
+    /// the module and call index of the call exist in the runtime. The version
+    /// record can be read from the metadata itself with
+    /// [`RuntimeVersionInfo::from_metadata`]. Requires the `"metadata"`
+    /// feature.
+    ///
+    /// Fails if the runtime uses another version of the extrinsic format than
+    /// [`TX_VERSION`].
+    pub fn from_metadata(metadata: &MetadataVersion, version: &RuntimeVersionInfo) -> Result<Self> {
+        if let Some(tx_version) = metadata.extrinsic_version() {
+            if tx_version as u32 != TX_VERSION {
+                return Err(gekko_metadata::Error::UnsupportedExtrinsicVersion(tx_version).into());
+            }
+        }
+
+        Ok(Self {
+            spec_version: Some(version.spec_version),
+            transaction_version: Some(version.transaction_version),
+            call_indices: Some(call_indices(metadata.as_inner()?)),
+            ..Default::default()
+        })
+    }
+    /// Verifies the call against the metadata of the runtime, before it is
+    /// signed. The module and call index must exist and, for metadata V13 and
+    /// V14, the encoded arguments must match the argument types of the call,
+    /// neither missing nor exceeding any. Older metadata versions only carry
+    /// type names, so only the index is checked. This catches interfaces
+    /// generated from outdated metadata. Requires the `"metadata"` feature.
+    ///
+    /// The call must be set before calling this function.
+    pub fn validate_against(self, metadata: &MetadataVersion) -> Result<Self> {
+        let call = self
+            .call
+            .as_ref()
+            .ok_or(Error::BuilderMissingField("call"))?;
+
+        validate_call(metadata, &call.encode())?;
+        Ok(self)
+    }
+    /// Verifies that the signed extensions of the runtime match the signed
+    /// extensions encoded by the [`Config`], ignoring extensions which encode
+    /// no data. A mismatch, such as an extension added by a runtime upgrade,
+    /// would otherwise result in an invalid signature. Supports metadata V11
+    /// to V14, reading the identifiers of the V14 extrinsic metadata for the
+    /// latter. Requires the `"metadata"` feature.
+    pub fn validate_signed_extensions(self, metadata: &MetadataVersion) -> Result<Self> {
+        let extensions = SignedExtensions::<C>::from_metadata(metadata)?;
+        let expected = extensions_with_data::<C, _>(extensions.identifiers());
+        let encoded = extensions_with_data::<C, _>(C::SIGNED_EXTENSIONS.iter().copied());
+
+        if expected != encoded {
+            return Err(Error::SignedExtensionMismatch { expected, encoded });
+        }
+
+        Ok(self)
+    }
+}
+
+/// Verifies that the module and call index of the encoded call, its first two
+/// bytes, exist in the runtime.
+#[cfg(feature = "metadata")]
+fn validate_call_index(indices: &[(u8, u8)], encoded: &[u8]) -> Result<()> {
+    let (module_index, call_index) = call_index(encoded);
+
+    if !indices.contains(&(module_index, call_index)) {
+        return Err(gekko_metadata::Error::UnknownCall {
+            module_index,
+            call_index,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// The module and call index of the encoded call, its first two bytes.
+#[cfg(feature = "metadata")]
+fn call_index(encoded: &[u8]) -> (u8, u8) {
+    (
+        encoded.first().copied().unwrap_or_default(),
+        encoded.get(1).copied().unwrap_or_default(),
+    )
+}
+
+/// The module and call indices of all calls of the runtime.
+#[cfg(feature = "metadata")]
+fn call_indices(metadata: &dyn MetadataExt) -> Vec<(u8, u8)> {
+    metadata
+        .modules_extrinsics()
+        .iter()
+        .map(|info| (info.module_id as u8, info.dispatch_id as u8))
+        .collect()
+}
+
+/// Verifies that the module and call index of the encoded call exist in the
+/// runtime and, for metadata V13 and V14, that the arguments decode to exactly
+/// the remaining bytes.
+#[cfg(feature = "metadata")]
+fn validate_call(metadata: &MetadataVersion, encoded: &[u8]) -> Result<()> {
+    let mut registry = TypeRegistry::default();
+    if let MetadataVersion::V13(metadata) = metadata {
+        registry.register_calls(metadata)?;
+    }
+
+    let decoder = ExtrinsicDecoder::new(metadata, &registry).ok();
+    let metadata = metadata.as_inner()?;
+
+    validate_call_index(&call_indices(metadata), encoded)?;
+
+    let (module_index, call_index) = call_index(encoded);
+    let info = metadata
+        .find_extrinsic_by_index(module_index, call_index)
+        .ok_or(gekko_metadata::Error::UnknownCall {
+            module_index,
+            call_index,
+        })?;
+
+    let decoder = match decoder {
+        Some(decoder) => decoder,
+        None => return Ok(()),
+    };
+
+    let input = &mut &*encoded;
+    let reason = match decoder.decode_call(input) {
+        Ok(_) if input.is_empty() => return Ok(()),
+        Ok(_) => format!("{} trailing bytes after the last argument", input.len()),
+        Err(err) => match std::error::Error::source(&err) {
+            Some(source) => format!("{}: {}", err, source),
+            None => err.to_string(),
+        },
+    };
+
+    Err(Error::CallMismatch {
+        module: info.module_name.to_string(),
+        call: info.extrinsic_name.to_string(),
+        reason,
+    })
+}
+
+/// Filters the signed extensions which encode data, see
+/// [`Config::EMPTY_SIGNED_EXTENSIONS`].
+#[cfg(feature = "metadata")]
+fn extensions_with_data<'a, C: Config, I: Iterator<Item = &'a str>>(extensions: I) -> Vec<String> {
+    extensions
+        .filter(|ext| !C::EMPTY_SIGNED_EXTENSIONS.contains(ext))
+        .map(String::from)
+        .collect()
+}
+
+/// The versions of a runtime, as recorded alongside its metadata. Deserializes
+/// from the response of `state_getRuntimeVersion`, ignoring the other fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct RuntimeVersionInfo {
+    pub spec_name: String,
+    pub spec_version: u32,
+    pub transaction_version: u32,
+}
+
+#[cfg(feature = "metadata")]
+impl RuntimeVersionInfo {
+    /// Reads the versions from the `Version` constant of the `System` module,
+    /// which runtimes declare since metadata V12. Requires the `"metadata"`
+    /// feature.
+    pub fn from_metadata(metadata: &MetadataVersion) -> Result<Self> {
+        /// The leading fields of `sp_version::RuntimeVersion`. Later runtimes
+        /// append a `state_version`, which is ignored.
+        #[derive(Decode)]
+        struct EncodedRuntimeVersion {
+            spec_name: String,
+            _impl_name: String,
+            _authoring_version: u32,
+            spec_version: u32,
+            _impl_version: u32,
+            _apis: Vec<([u8; 8], u32)>,
+            transaction_version: u32,
+        }
+
+        let info = metadata
+            .as_inner()?
+            .find_constant("System", "Version")
+            .ok_or_else(|| gekko_metadata::Error::UnknownConstant {
+                module: "System".to_string(),
+                constant: "Version".to_string(),
+            })?;
+        let version = EncodedRuntimeVersion::decode(&mut &*info.value).map_err(|source| {
+            gekko_metadata::Error::DecodeType {
+                ty: info.ty.to_string(),
+                source,
+            }
+        })?;
+
+        Ok(RuntimeVersionInfo {
+            spec_name: version.spec_name,
+            spec_version: version.spec_version,
+            transaction_version: version.transaction_version,
         })
     }
 }
 
+#[cfg(feature = "rpc")]
+impl From<crate::rpc::RuntimeVersion> for RuntimeVersionInfo {
+    fn from(val: crate::rpc::RuntimeVersion) -> Self {
+        RuntimeVersionInfo {
+            spec_name: val.spec_name,
+            spec_version: val.spec_version,
+            transaction_version: val.transaction_version,
+        }
+    }
+}
+
+/// The parameters of a transaction from which its signed extensions are
+/// created, see [`SignedExtra`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtrinsicParams<C: Config> {
+    pub era: C::Era,
+    pub nonce: C::Index,
+    pub tip: C::Balance,
+    /// The asset with which the fee and the tip are paid, if not the native
+    /// currency. Only encoded by [`AssetPayload`].
+    pub asset_id: Option<u32>,
+    pub spec_version: u32,
+    pub transaction_version: u32,
+    pub genesis: C::Hash,
+    /// The block hash from where the period of mortality begins. If the
+    /// transaction is immortal, it's the genesis hash.
+    pub birth: C::Hash,
+    /// The hash of the metadata, if the `CheckMetadataHash` extension is
+    /// enabled.
+    pub metadata_hash: Option<[u8; 32]>,
+}
+
+/// Signed extensions, which are created by the
+/// [`SignedTransactionBuilder`] from the parameters of the transaction. See
+/// [`Config::Extra`] and [`Config::AdditionalSigned`].
+pub trait SignedExtra<C: Config>: Encode {
+    fn new(params: &ExtrinsicParams<C>) -> Self;
+}
+
+/// The signed extensions which are part of the transaction. The types of the
+/// nonce, the tip and the mortality correspond to [`Config::Index`],
+/// [`Config::Balance`] and [`Config::Era`], respectively.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Payload<Index = u32, Balance = u128, Era = Mortality> {
+    pub mortality: Era,
+    #[codec(compact)]
+    pub nonce: Index,
+    /// The tip of the `ChargeTransactionPayment` extension.
+    #[codec(compact)]
+    pub tip: Balance,
+}
+
+/// Like [`Payload`], but for chains with the `ChargeAssetTxPayment` extension
+/// instead of `ChargeTransactionPayment`, such as Statemint and Statemine. The
+/// fee and the tip are paid with the given asset, or with the native currency
+/// if there is none. See [`SignedTransactionBuilder::asset_id`].
 #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
-pub struct Payload {
-    pub mortality: Mortality,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AssetPayload<Index = u32, Balance = u128, Era = Mortality> {
+    pub mortality: Era,
     #[codec(compact)]
-    pub nonce: u32,
+    pub nonce: Index,
     #[codec(compact)]
-    pub payment: u128,
+    pub tip: Balance,
+    pub asset_id: Option<u32>,
 }
 
+/// The signed extensions which are only part of the signature payload. The
+/// type of the hashes corresponds to [`Config::Hash`].
 #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
-pub struct ExtraSignaturePayload {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "Hash: AsRef<[u8]>",
+        deserialize = "Hash: TryFrom<Vec<u8>>"
+    ))
+)]
+pub struct ExtraSignaturePayload<Hash = [u8; 32]> {
     pub spec_version: u32,
     pub tx_version: u32,
-    pub genesis: [u8; 32],
+    #[cfg_attr(feature = "serde", serde(with = "gekko_metadata::serde_hex"))]
+    pub genesis: Hash,
     /// The block hash from where the period of mortality begins. If the
     /// transaction is immortal, it's the genesis hash. See [Mortality] for more
     /// information.
-    pub birth: [u8; 32],
+    #[cfg_attr(feature = "serde", serde(with = "gekko_metadata::serde_hex"))]
+    pub birth: Hash,
+}
+
+impl<C: Config> SignedExtra<C> for Payload<C::Index, C::Balance, C::Era> {
+    fn new(params: &ExtrinsicParams<C>) -> Self {
+        Payload {
+            mortality: params.era.clone(),
+            nonce: params.nonce,
+            tip: params.tip,
+        }
+    }
+}
+
+impl<C: Config> SignedExtra<C> for AssetPayload<C::Index, C::Balance, C::Era> {
+    fn new(params: &ExtrinsicParams<C>) -> Self {
+        AssetPayload {
+            mortality: params.era.clone(),
+            nonce: params.nonce,
+            tip: params.tip,
+            asset_id: params.asset_id,
+        }
+    }
+}
+
+impl<C: Config> SignedExtra<C> for ExtraSignaturePayload<C::Hash> {
+    fn new(params: &ExtrinsicParams<C>) -> Self {
+        ExtraSignaturePayload {
+            spec_version: params.spec_version,
+            tx_version: params.transaction_version,
+            genesis: params.genesis,
+            birth: params.birth,
+        }
+    }
 }
 
-pub struct SignaturePayload<Call, Payload, ExtraSignaturePayload> {
+/// Signs the hash of a message with ECDSA. Unlike [`Pair::sign`], which
+/// always hashes the message with Blake2, this allows signatures of chains with
+/// another [`Hasher`].
+fn sign_prehashed(pair: &Ecdsa, hash: &[u8; 32]) -> sp_core::ecdsa::Signature {
+    use secp256k1::{Message, Secp256k1, SecretKey};
+
+    // The seed is a valid secret key and the hash a valid message.
+    let secret = SecretKey::from_slice(&pair.seed()).unwrap();
+    let message = Message::from_slice(hash).unwrap();
+    let (recovery_id, sig) = Secp256k1::signing_only()
+        .sign_recoverable(&message, &secret)
+        .serialize_compact();
+
+    let mut raw = [0; 65];
+    raw[..64].copy_from_slice(&sig);
+    raw[64] = recovery_id.to_i32() as u8;
+    sp_core::ecdsa::Signature::from_raw(raw)
+}
+
+/// The payload which is signed. Payloads exceeding 256 bytes are hashed with
+/// the given [`Hasher`] before signing.
+pub struct SignaturePayload<Call, Payload, ExtraSignaturePayload, H = Blake2_256> {
     pub call: Call,
     pub payload: Payload,
     pub extra: ExtraSignaturePayload,
+    _hasher: PhantomData<H>,
 }
 
-impl<Call, Payload, ExtraSignaturePayload> SignaturePayload<Call, Payload, ExtraSignaturePayload> {
-    fn new(call: Call, payload: Payload, extra: ExtraSignaturePayload) -> Self {
+impl<Call, Payload, ExtraSignaturePayload, H>
+    SignaturePayload<Call, Payload, ExtraSignaturePayload, H>
+{
+    pub(super) fn new(call: Call, payload: Payload, extra: ExtraSignaturePayload) -> Self {
         SignaturePayload {
-            call: call,
-            payload: payload,
-            extra: extra,
+            call,
+            payload,
+            extra,
+            _hasher: PhantomData,
         }
     }
     fn deconstruct(self) -> (Call, Payload, ExtraSignaturePayload) {
@@ -306,17 +1062,18 @@ impl<Call, Payload, ExtraSignaturePayload> SignaturePayload<Call, Payload, Extra
     }
 }
 
-impl<Call, Payload, ExtraSignaturePayload> Encode
-    for SignaturePayload<Call, Payload, ExtraSignaturePayload>
+impl<Call, Payload, ExtraSignaturePayload, H> Encode
+    for SignaturePayload<Call, Payload, ExtraSignaturePayload, H>
 where
     Call: Encode,
     Payload: Encode,
     ExtraSignaturePayload: Encode,
+    H: Hasher,
 {
     fn using_encoded<R, F: FnOnce(&[u8]) -> R>(&self, f: F) -> R {
         (&self.call, &self.payload, &self.extra).using_encoded(|payload| {
             if payload.len() > 256 {
-                f(&blake2b(&payload))
+                f(&H::hash(payload))
             } else {
                 f(payload)
             }
@@ -324,11 +1081,64 @@ where
     }
 }
 
+/// The payload of a transaction which is signed offline, created by
+/// [`SignedTransactionBuilder::build_unsigned_payload`]. Payloads exceeding
+/// 256 bytes are hashed with the given [`Hasher`] before signing.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SigningPayload<Extra, H = Blake2_256> {
+    /// The SCALE encoded call.
+    #[cfg_attr(feature = "serde", serde(with = "gekko_metadata::serde_hex"))]
+    pub call: Vec<u8>,
+    /// The signed extensions which are part of the transaction.
+    pub extra: Extra,
+    /// The SCALE encoded signed extensions which are only part of the
+    /// signature payload, such as [`ExtraSignaturePayload`].
+    #[cfg_attr(feature = "serde", serde(with = "gekko_metadata::serde_hex"))]
+    pub additional: Vec<u8>,
+    #[codec(skip)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _hasher: PhantomData<H>,
+}
+
+impl<Extra, H> SigningPayload<Extra, H> {
+    pub fn new(call: Vec<u8>, extra: Extra, additional: Vec<u8>) -> Self {
+        SigningPayload {
+            call,
+            extra,
+            additional,
+            _hasher: PhantomData,
+        }
+    }
+}
+
+impl<Extra: Encode, H: Hasher> SigningPayload<Extra, H> {
+    /// The exact bytes which must be signed, hashed if exceeding 256 bytes.
+    /// Like the runtime, ECDSA signatures must be created over the hash of
+    /// the bytes.
+    pub fn to_signable_bytes(&self) -> Vec<u8> {
+        let mut payload = self.call.clone();
+        self.extra.encode_to(&mut payload);
+        payload.extend_from_slice(&self.additional);
+
+        if payload.len() > 256 {
+            H::hash(&payload).to_vec()
+        } else {
+            payload
+        }
+    }
+    /// Verifies the signature of the payload against the account of the
+    /// signer.
+    pub fn verify(&self, signature: &MultiSignature, account: &AccountId) -> bool {
+        verify_payload::<H>(signature, &self.to_signable_bytes(), account)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::common::*;
-    use std::env;
+    use crate::transaction::extensions::SignedExtension;
 
     #[derive(Debug, Eq, PartialEq, Encode, Decode)]
     struct SomeExtrinsic {
@@ -353,6 +1163,127 @@ mod tests {
         assert_eq!(transaction, decoded);
     }
 
+    #[test]
+    fn transaction_decode_respects_length_prefix() {
+        let first = Transaction::new_unsigned(OpaqueCall(vec![4, 3, 1, 2]));
+        let second = Transaction::new_unsigned(OpaqueCall(vec![0, 1]));
+
+        // `OpaqueCall` must not consume the following transaction.
+        let encoded = [first.encode(), second.encode()].concat();
+        let input = &mut encoded.as_slice();
+
+        let decoded: Transaction<(), OpaqueCall, (), ()> = Decode::decode(input).unwrap();
+        assert_eq!(decoded, first);
+        let decoded: Transaction<(), OpaqueCall, (), ()> = Decode::decode(input).unwrap();
+        assert_eq!(decoded, second);
+
+        // The length prefix exceeds the remaining input.
+        let mut encoded = first.encode();
+        encoded[0] += 4;
+        assert!(Transaction::<(), OpaqueCall, (), ()>::decode(&mut encoded.as_slice()).is_err());
+
+        // The length prefix is shorter than the transaction, so the call is
+        // truncated.
+        let mut encoded = Transaction::new_unsigned(3u32).encode();
+        encoded[0] -= 4;
+        assert!(Transaction::<(), u32, (), ()>::decode(&mut encoded.as_slice()).is_err());
+    }
+
+    #[test]
+    fn transaction_decode_all() {
+        let transaction = Transaction::new_unsigned(OpaqueCall(vec![4, 3, 1, 2]));
+        let encoded = transaction.encode();
+
+        assert_eq!(Transaction::decode_all(&encoded).unwrap(), transaction);
+
+        let trailing = [encoded.as_slice(), &[0]].concat();
+        assert!(Transaction::<(), OpaqueCall, (), ()>::decode(&mut trailing.as_slice()).is_ok());
+        assert!(Transaction::<(), OpaqueCall, (), ()>::decode_all(&trailing).is_err());
+        assert!(Transaction::<(), OpaqueCall, (), ()>::decode_all(&encoded[..3]).is_err());
+    }
+
+    #[test]
+    fn transaction_decode_large_length_prefix() {
+        // The input of a reader has no known length, so the declared length
+        // must not be allocated up front.
+        struct Reader<'a>(&'a [u8]);
+
+        impl Input for Reader<'_> {
+            fn remaining_len(&mut self) -> std::result::Result<Option<usize>, ScaleError> {
+                Ok(None)
+            }
+            fn read(&mut self, into: &mut [u8]) -> std::result::Result<(), ScaleError> {
+                self.0.read(into)
+            }
+        }
+
+        let mut encoded = Compact(u32::MAX).encode();
+        encoded.extend_from_slice(&[4, 0, 1]);
+        assert!(Transaction::<(), OpaqueCall, (), ()>::decode(&mut Reader(&encoded)).is_err());
+
+        let transaction = Transaction::new_unsigned(OpaqueCall(vec![1; 40_000]));
+        let encoded = transaction.encode();
+        assert_eq!(
+            Transaction::decode(&mut Reader(&encoded)).unwrap(),
+            transaction
+        );
+    }
+
+    #[test]
+    fn versions_of_registered_chain() {
+        // The registry is shared by all tests, so the genesis hash must be
+        // unique.
+        let genesis = crate::blake2b("versions_of_registered_chain");
+
+        let (keypair, _) = KeyPairBuilder::<Ed25519>::generate();
+        let builder = SignedTransactionBuilder::new()
+            .signer(keypair)
+            .call(OpaqueCall(vec![4, 3, 1, 2]))
+            .nonce(1)
+            .tip_base_unit(0)
+            .network(Network::Custom(genesis));
+
+        assert!(matches!(
+            builder.clone().build(),
+            Err(Error::BuilderMissingField("spec_version"))
+        ));
+
+        ChainSpec::new(genesis, 42, 12, "UNIT")
+            .versions(5, 6)
+            .register();
+
+        let transaction = builder.build();
+        ChainSpec::unregister(&genesis);
+        assert!(transaction
+            .unwrap()
+            .verify_signature(genesis, 5, 6, genesis));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn signed_transaction_serde() {
+        let (keypair, _) = KeyPairBuilder::<Ed25519>::generate();
+
+        let transaction = SignedTransactionBuilder::new()
+            .signer(keypair)
+            .call(OpaqueCall(vec![4, 3, 1, 2]))
+            .nonce(1)
+            .tip_base_unit(10)
+            .network(Network::Custom([1; 32]))
+            .mortality(Mortality::Mortal(64, 18, Some([2; 32])))
+            .spec_version(1)
+            .transaction_version(1)
+            .build()
+            .unwrap();
+
+        let json = serde_json::to_value(&transaction).unwrap();
+        assert_eq!(json["call"], "0x04030102");
+        assert_eq!(json["signature"][2]["tip"], 10);
+
+        let decoded: PolkadotSignedExtrinsic<OpaqueCall> = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, transaction);
+    }
+
     #[test]
     fn signed_transaction_encode_decode() {
         let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
@@ -363,8 +1294,7 @@ mod tests {
             c: vec![20, 30, 40],
         };
 
-        // Transaction fee.
-        let payment = BalanceBuilder::new(Currency::Westend)
+        let tip = BalanceBuilder::new(Currency::Westend)
             .balance_as_metric(Metric::Milli, 500)
             .unwrap();
 
@@ -372,7 +1302,7 @@ mod tests {
             .signer(keypair)
             .call(call)
             .nonce(0)
-            .payment(payment)
+            .tip(tip)
             .network(Network::Polkadot)
             .build()
             .unwrap();
@@ -383,15 +1313,462 @@ mod tests {
         assert_eq!(transaction, decoded);
     }
 
+    #[test]
+    fn signed_transaction_custom_config() {
+        use sp_core::crypto::AccountId32;
+
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        struct U64Chain;
+
+        impl Config for U64Chain {
+            type Balance = u64;
+            type Index = u64;
+            type Hash = [u8; 32];
+            type Address = AccountId32;
+            type Signature = MultiSignature;
+            type Era = Mortality;
+            type Extra = Payload<u64, u64>;
+            type AdditionalSigned = ExtraSignaturePayload;
+            type Hasher = Blake2_256;
+        }
+
+        let (keypair, _) = KeyPairBuilder::<Ed25519>::generate();
+        let builder = SignedTransactionBuilder::<_, U64Chain>::default()
+            .signer(keypair.clone())
+            .call(OpaqueCall(vec![4, 3]))
+            .nonce(u64::MAX)
+            .network(Network::Custom([1; 32]))
+            .spec_version(1)
+            .transaction_version(1);
+
+        let transaction = builder.clone().tip_base_unit(10).build().unwrap();
+
+        let (address, _, payload) = transaction.signature.as_ref().unwrap();
+        assert_eq!(address, &AccountId32::from(keypair.public()));
+        assert_eq!(payload.nonce, u64::MAX);
+        assert_eq!(payload.tip, 10);
+
+        let encoded = transaction.encode();
+        let decoded: SignedExtrinsic<U64Chain, OpaqueCall> =
+            Decode::decode(&mut encoded.as_ref()).unwrap();
+        assert_eq!(decoded, transaction);
+
+        // The tip does not fit into the balance type of the chain.
+        let tip = BalanceBuilder::new(Currency::Custom(u64::MAX as u128)).balance(2);
+        assert!(matches!(
+            builder.tip(tip).build(),
+            Err(Error::BalanceOverflow(_))
+        ));
+    }
+
+    #[test]
+    fn signed_transaction_custom_extra() {
+        // A chain which pays fees with the asset of the given Id, if any.
+        #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+        struct AssetPayload {
+            payload: Payload,
+            asset_id: Option<u32>,
+        }
+
+        impl SignedExtra<AssetChain> for AssetPayload {
+            fn new(params: &ExtrinsicParams<AssetChain>) -> Self {
+                AssetPayload {
+                    payload: SignedExtra::<AssetChain>::new(params),
+                    asset_id: None,
+                }
+            }
+        }
+
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        struct AssetChain;
+
+        impl Config for AssetChain {
+            type Balance = u128;
+            type Index = u32;
+            type Hash = [u8; 32];
+            type Address = MultiAddress;
+            type Signature = MultiSignature;
+            type Era = Mortality;
+            type Extra = AssetPayload;
+            type AdditionalSigned = ExtraSignaturePayload;
+            type Hasher = Blake2_256;
+        }
+
+        let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+        let transaction = SignedTransactionBuilder::<_, AssetChain>::default()
+            .signer(keypair)
+            .call(OpaqueCall(vec![4, 3]))
+            .nonce(5)
+            .tip_base_unit(10)
+            .network(Network::Custom([1; 32]))
+            .spec_version(1)
+            .transaction_version(1)
+            .build()
+            .unwrap();
+
+        let (_, _, extra) = transaction.signature.as_ref().unwrap();
+        assert_eq!(extra.payload.nonce, 5);
+        assert_eq!(extra.asset_id, None);
+
+        let encoded = transaction.encode();
+        let decoded: SignedExtrinsic<AssetChain, OpaqueCall> =
+            Decode::decode(&mut encoded.as_ref()).unwrap();
+        assert_eq!(decoded, transaction);
+    }
+
+    #[test]
+    fn signed_transaction_with_extensions() {
+        let (keypair, _) = KeyPairBuilder::<Ed25519>::generate();
+        let builder = SignedTransactionBuilder::new()
+            .signer(keypair)
+            .call(OpaqueCall(vec![4, 3, 1, 2]))
+            .nonce(3)
+            .tip_base_unit(10)
+            .network(Network::Custom([1; 32]))
+            .mortality(Mortality::Mortal(64, 18, Some([2; 32])))
+            .spec_version(1)
+            .transaction_version(1);
+
+        // The extensions of the Polkadot config result in the same transaction.
+        let extensions = SignedExtensions::new(PolkadotConfig::SIGNED_EXTENSIONS);
+        let transaction = builder.clone().build().unwrap();
+        let dynamic = builder.clone().build_with_extensions(&extensions).unwrap();
+        assert_eq!(dynamic.encode(), transaction.encode());
+
+        // Fees are paid in the native asset, i.e. the asset Id is `None`.
+        let extensions =
+            SignedExtensions::new(["CheckNonce", "ChargeAssetTxPayment", "CheckTxVersion"]);
+        let dynamic = builder.clone().build_with_extensions(&extensions).unwrap();
+        let (_, _, extra) = dynamic.signature.as_ref().unwrap();
+        assert_eq!(extra.0, vec![3 << 2, 10 << 2, 0]);
+
+        // Unknown extensions must be provided.
+        struct CheckFlag;
+
+        impl SignedExtension<PolkadotConfig> for CheckFlag {
+            fn encode_extra(&self, _params: &ExtrinsicParams<PolkadotConfig>, dest: &mut Vec<u8>) {
+                dest.push(1);
+            }
+        }
+
+        let extensions = SignedExtensions::new(["CheckNonce", "CheckFlag"]);
+        assert_eq!(extensions.unknown(), vec!["CheckFlag"]);
+        assert!(matches!(
+            builder.clone().build_with_extensions(&extensions),
+            Err(Error::UnknownSignedExtension(ref id)) if id == "CheckFlag"
+        ));
+
+        let extensions = extensions.with("CheckFlag", CheckFlag);
+        let dynamic = builder.build_with_extensions(&extensions).unwrap();
+        let (_, _, extra) = dynamic.signature.as_ref().unwrap();
+        assert_eq!(extra.0, vec![3 << 2, 1]);
+    }
+
+    #[test]
+    fn signed_transaction_asset_payment() {
+        let (keypair, _) = KeyPairBuilder::<Ed25519>::generate();
+        let builder = SignedTransactionBuilder::<_, StatemintConfig>::default()
+            .signer(keypair)
+            .call(OpaqueCall(vec![4, 3]))
+            .nonce(5)
+            .tip_base_unit(10)
+            .network(Network::Custom([1; 32]))
+            .spec_version(1)
+            .transaction_version(1);
+
+        // Paid with the native currency by default.
+        let transaction = builder.clone().build().unwrap();
+        let (_, _, extra) = transaction.signature.as_ref().unwrap();
+        assert_eq!(extra.asset_id, None);
+
+        let transaction = builder.clone().asset_id(1984).build().unwrap();
+        let (_, _, extra) = transaction.signature.as_ref().unwrap();
+        assert_eq!(extra.tip, 10);
+        assert_eq!(extra.asset_id, Some(1984));
+
+        let encoded = transaction.encode();
+        let decoded: SignedExtrinsic<StatemintConfig, OpaqueCall> =
+            Decode::decode(&mut encoded.as_ref()).unwrap();
+        assert_eq!(decoded, transaction);
+
+        // The same transaction, assembled from the extension identifiers.
+        let extensions = SignedExtensions::new(StatemintConfig::SIGNED_EXTENSIONS);
+        let dynamic = builder
+            .asset_id(1984)
+            .build_with_extensions(&extensions)
+            .unwrap();
+        assert_eq!(dynamic.encode(), encoded);
+    }
+
+    #[test]
+    fn verify_transaction_signature() {
+        let sign = |keypair: MultiKeyPair, call: OpaqueCall| {
+            SignedTransactionBuilder::new()
+                .signer(keypair)
+                .call(call)
+                .nonce(1)
+                .tip_base_unit(10)
+                .network(Network::Custom([1; 32]))
+                .mortality(Mortality::Mortal(64, 18, Some([2; 32])))
+                .spec_version(9080)
+                .transaction_version(5)
+                .build()
+                .unwrap()
+        };
+
+        let keypairs: Vec<MultiKeyPair> = vec![
+            KeyPairBuilder::<Ed25519>::generate().0.into(),
+            KeyPairBuilder::<Sr25519>::generate().0.into(),
+            KeyPairBuilder::<Ecdsa>::generate().0.into(),
+        ];
+
+        for keypair in keypairs {
+            // The payload of the latter exceeds 256 bytes and is hashed.
+            for call in [OpaqueCall(vec![4, 3]), OpaqueCall(vec![1; 300])] {
+                let transaction = sign(keypair.clone(), call);
+                let encoded = transaction.encode();
+                let decoded: PolkadotSignedExtrinsic<OpaqueCall> =
+                    Decode::decode(&mut encoded.as_slice()).unwrap();
+
+                assert!(decoded.verify_signature([1; 32], 9080, 5, [2; 32]));
+                assert!(!decoded.verify_signature([1; 32], 9081, 5, [2; 32]));
+                assert!(!decoded.verify_signature([1; 32], 9080, 5, [1; 32]));
+
+                // Signed by another account.
+                let mut forged = decoded.clone();
+                forged.signature.as_mut().unwrap().0 = MultiAddress::Id([3; 32].into());
+                assert!(!forged.verify_signature([1; 32], 9080, 5, [2; 32]));
+            }
+        }
+    }
+
+    #[test]
+    fn ecdsa_sign_prehashed() {
+        let (keypair, _) = KeyPairBuilder::<Ecdsa>::generate();
+        let sig = sign_prehashed(&keypair, &Blake2_256::hash(b"message"));
+
+        assert!(Ecdsa::verify(&sig, b"message", &keypair.public()));
+    }
+
+    #[test]
+    fn signed_transaction_keccak_hasher() {
+        use secp256k1::recovery::{RecoverableSignature, RecoveryId};
+        use secp256k1::{Message, Secp256k1};
+
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        struct KeccakChain;
+
+        impl Config for KeccakChain {
+            type Balance = u128;
+            type Index = u32;
+            type Hash = [u8; 32];
+            type Address = AccountId;
+            type Signature = MultiSignature;
+            type Era = Mortality;
+            type Extra = Payload;
+            type AdditionalSigned = ExtraSignaturePayload;
+            type Hasher = Keccak256;
+        }
+
+        let (keypair, _) = KeyPairBuilder::<Ecdsa>::generate();
+        // Exceeds 256 bytes, so the payload is hashed before signing.
+        let call = OpaqueCall(vec![1; 300]);
+
+        let transaction = SignedTransactionBuilder::<_, KeccakChain>::default()
+            .signer(keypair.clone())
+            .call(call.clone())
+            .nonce(0)
+            .tip_base_unit(0)
+            .network(Network::Custom([1; 32]))
+            .spec_version(1)
+            .transaction_version(1)
+            .build()
+            .unwrap();
+
+        let (_, sig, payload) = transaction.signature.unwrap();
+        let sig = match sig {
+            MultiSignature::Ecdsa(sig) => sig,
+            _ => panic!("expected ECDSA signature"),
+        };
+
+        let extra = ExtraSignaturePayload {
+            spec_version: 1,
+            tx_version: 1,
+            genesis: [1u8; 32],
+            birth: [1u8; 32],
+        };
+        let hash = Keccak256::hash(&Keccak256::hash(&(call, payload, extra).encode()));
+
+        let raw: &[u8] = sig.as_ref();
+        let sig = RecoverableSignature::from_compact(
+            &raw[..64],
+            RecoveryId::from_i32(raw[64] as i32).unwrap(),
+        )
+        .unwrap();
+        let public = Secp256k1::verification_only()
+            .recover(&Message::from_slice(&hash).unwrap(), &sig)
+            .unwrap();
+
+        assert_eq!(public.serialize(), keypair.public().0);
+    }
+
+    #[test]
+    fn external_signer() {
+        use crate::transaction::signer::CallbackSigner;
+
+        let (device, _) = KeyPairBuilder::<Sr25519>::generate();
+        let account = AccountId::from(device.public());
+        let builder = SignedTransactionBuilder::new()
+            // Exceeds 256 bytes, so the payload is hashed before signing.
+            .call(OpaqueCall(vec![1; 300]))
+            .nonce(0)
+            .tip_base_unit(0)
+            .network(Network::Custom([1; 32]))
+            .spec_version(9080)
+            .transaction_version(5);
+
+        let transaction: PolkadotSignedExtrinsic<_> = builder
+            .clone()
+            .signer_external(CallbackSigner::new(account, move |payload: &[u8]| {
+                assert_eq!(payload.len(), 32);
+                Ok(MultiSignature::from(device.sign(payload)))
+            }))
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            transaction.signature.as_ref().unwrap().0,
+            MultiAddress::from(account)
+        );
+        assert!(transaction.verify_signature([1; 32], 9080, 5, [1; 32]));
+
+        // The signer holds another key than announced.
+        let (other, _) = KeyPairBuilder::<Sr25519>::generate();
+        let res = builder
+            .clone()
+            .signer_external(CallbackSigner::new(account, move |payload: &[u8]| {
+                Ok(MultiSignature::from(other.sign(payload)))
+            }))
+            .build();
+        assert!(matches!(res, Err(Error::InvalidSignature(acc)) if acc == account));
+
+        let res = builder
+            .signer_external(CallbackSigner::new(account, |_: &[u8]| {
+                Err(Error::ExternalSigner("rejected by user".into()))
+            }))
+            .build();
+        assert!(matches!(res, Err(Error::ExternalSigner(_))));
+    }
+
+    #[test]
+    fn external_signer_ecdsa_keccak_hasher() {
+        use crate::transaction::signer::CallbackSigner;
+
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        struct KeccakChain;
+
+        impl Config for KeccakChain {
+            type Balance = u128;
+            type Index = u32;
+            type Hash = [u8; 32];
+            type Address = AccountId;
+            type Signature = MultiSignature;
+            type Era = Mortality;
+            type Extra = Payload;
+            type AdditionalSigned = ExtraSignaturePayload;
+            type Hasher = Keccak256;
+        }
+
+        let (device, _) = KeyPairBuilder::<Ecdsa>::generate();
+        let account = AccountId::from(device.public());
+        let builder = SignedTransactionBuilder::<_, KeccakChain>::default()
+            .call(OpaqueCall(vec![1, 2, 3]))
+            .nonce(0)
+            .tip_base_unit(0)
+            .network(Network::Custom([1; 32]))
+            .spec_version(1)
+            .transaction_version(1);
+
+        let keccak = device.clone();
+        let transaction = builder
+            .clone()
+            .signer_external(CallbackSigner::new(account, move |payload: &[u8]| {
+                Ok(sign_prehashed(&keccak, &Keccak256::hash(payload)).into())
+            }))
+            .build()
+            .unwrap();
+        assert_eq!(transaction.signature.unwrap().0, account);
+
+        // Signed over the Blake2 hash instead of the Keccak hash of the chain.
+        let res = builder
+            .signer_external(CallbackSigner::new(account, move |payload: &[u8]| {
+                Ok(MultiSignature::from(device.sign(payload)))
+            }))
+            .build();
+        assert!(matches!(res, Err(Error::InvalidSignature(_))));
+    }
+
+    #[test]
+    fn offline_signing_payload() {
+        let (keypair, _) = KeyPairBuilder::<Ed25519>::generate();
+        let account = AccountId::from(keypair.public());
+        let builder = SignedTransactionBuilder::new()
+            .call(OpaqueCall(vec![1; 300]))
+            .nonce(3)
+            .tip_base_unit(5)
+            .network(Network::Custom([1; 32]))
+            .spec_version(9080)
+            .transaction_version(5);
+
+        let payload = builder.clone().build_unsigned_payload().unwrap();
+        let decoded = SigningPayload::decode(&mut payload.encode().as_slice()).unwrap();
+        assert_eq!(payload, decoded);
+
+        let signable = payload.to_signable_bytes();
+        assert_eq!(signable.len(), 32);
+        let sig = MultiSignature::from(keypair.sign(&signable));
+        assert!(payload.verify(&sig, &account));
+        assert!(!payload.verify(&sig, &AccountId::new([1; 32])));
+
+        let offline: PolkadotSignedExtrinsic<_> =
+            Transaction::attach_signature(account.into(), sig, payload);
+        assert!(offline.verify_signature([1; 32], 9080, 5, [1; 32]));
+
+        // Ed25519 signatures are deterministic.
+        let online = builder.signer(keypair).build().unwrap();
+        assert_eq!(offline.encode(), online.encode());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_signing_payload() {
+        let payload = SignedTransactionBuilder::new()
+            .call(OpaqueCall(vec![0, 1]))
+            .nonce(0)
+            .tip_base_unit(0)
+            .network(Network::Custom([1; 32]))
+            .spec_version(9080)
+            .transaction_version(5)
+            .build_unsigned_payload()
+            .unwrap();
+
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["call"], "0x0001");
+        assert_eq!(
+            serde_json::from_value::<SigningPayload<Payload>>(json).unwrap(),
+            payload
+        );
+    }
+
     #[test]
     #[ignore]
+    #[cfg(feature = "runtime-kusama")]
     fn westend_create_signed_extrinsic() {
         use crate::runtime::kusama::extrinsics::balances::TransferKeepAlive;
+        use std::env;
 
         let mut seed = [0; 32];
-        seed.copy_from_slice(
-            &mut hex::decode(env::var("WESTEND_SEED").unwrap().as_bytes()).unwrap(),
-        );
+        seed.copy_from_slice(&hex::decode(env::var("WESTEND_SEED").unwrap().as_bytes()).unwrap());
 
         let keypair = KeyPairBuilder::<Sr25519>::from_seed(&seed);
         let currency = BalanceBuilder::new(Currency::Westend);
@@ -404,24 +1781,348 @@ mod tests {
             value: currency.balance(1),
         };
 
-        println!("CALL >> 0x{}", hex::encode(&call.encode()));
+        println!("CALL >> 0x{}", hex::encode(call.encode()));
 
-        // Transaction fee.
-        let payment = currency.balance_as_metric(Metric::Milli, 500).unwrap();
+        let tip = currency.balance_as_metric(Metric::Milli, 500).unwrap();
 
         let transaction: PolkadotSignedExtrinsic<_> = SignedTransactionBuilder::new()
             .signer(keypair)
             .call(call)
             .nonce(0)
-            .payment(payment)
+            .tip(tip)
             .network(Network::Westend)
             .spec_version(9080)
+            .transaction_version(5)
             .build()
             .unwrap();
 
         println!(
             "SIGNED TRANSACTION >> 0x{}",
-            hex::encode(&transaction.encode())
+            hex::encode(transaction.encode())
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "metadata", feature = "runtime-kusama"))]
+    fn validate_call_against_metadata() {
+        use crate::runtime::kusama::extrinsics::balances::TransferKeepAlive;
+
+        let metadata = gekko_metadata::parse_hex_metadata(include_str!(
+            "../../dumps/metadata_kusama_9080.hex"
+        ))
+        .unwrap();
+
+        let builder = |call: OpaqueCall| {
+            SignedTransactionBuilder::new()
+                .call(call)
+                .validate_against(&metadata)
+                .map(|_| ())
+        };
+
+        let transfer = TransferKeepAlive {
+            dest: AccountId::new([1; 32]),
+            value: BalanceBuilder::new(Currency::Kusama).balance(1),
+        }
+        .encode();
+
+        assert!(builder(OpaqueCall(transfer.clone())).is_ok());
+
+        // The `value` argument is missing.
+        let err = builder(OpaqueCall(transfer[..35].to_vec())).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::CallMismatch { ref module, ref call, .. }
+                if module == "Balances" && call == "transfer_keep_alive"
+        ));
+
+        // An additional argument.
+        let err = builder(OpaqueCall([transfer.clone(), vec![0]].concat())).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::CallMismatch { ref module, ref call, ref reason }
+                if module == "Balances"
+                    && call == "transfer_keep_alive"
+                    && reason.contains("trailing")
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "metadata")]
+    fn validate_call_index_against_metadata() {
+        let metadata = gekko_metadata::parse_hex_metadata(include_str!(
+            "../../dumps/metadata_kusama_9080.hex"
+        ))
+        .unwrap();
+
+        let builder = |call: OpaqueCall| {
+            SignedTransactionBuilder::new()
+                .call(call)
+                .validate_against(&metadata)
+                .map(|_| ())
+        };
+
+        // `Balances::transfer_keep_alive`, without any arguments.
+        let err = builder(OpaqueCall(vec![4, 3])).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::CallMismatch { ref module, ref call, .. }
+                if module == "Balances" && call == "transfer_keep_alive"
+        ));
+
+        let err = builder(OpaqueCall(vec![4, 200])).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Metadata(gekko_metadata::Error::UnknownCall {
+                module_index: 4,
+                call_index: 200
+            })
+        ));
+
+        let err = builder(OpaqueCall(vec![200, 0])).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Metadata(gekko_metadata::Error::UnknownCall {
+                module_index: 200,
+                call_index: 0
+            })
+        ));
+
+        let res = SignedTransactionBuilder::<OpaqueCall>::new().validate_against(&metadata);
+        assert!(matches!(res, Err(Error::BuilderMissingField("call"))));
+    }
+
+    #[test]
+    #[cfg(feature = "metadata")]
+    fn validate_call_against_metadata_v14() {
+        let metadata = gekko_metadata::parse_hex_metadata(
+            include_str!("../../dumps/metadata_kusama_9111.hex").trim(),
+        )
+        .unwrap();
+
+        let builder = |call: Vec<u8>| {
+            SignedTransactionBuilder::new()
+                .call(OpaqueCall(call))
+                .validate_against(&metadata)
+                .map(|_| ())
+        };
+
+        // `Balances::transfer`, to `MultiAddress::Id` with a compact value.
+        let transfer = [vec![4, 0, 0], vec![1; 32], Compact(1_000u128).encode()].concat();
+        assert!(builder(transfer.clone()).is_ok());
+
+        // The `value` argument is missing.
+        let err = builder(transfer[..35].to_vec()).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::CallMismatch { ref module, ref call, .. }
+                if module == "Balances" && call == "transfer"
+        ));
+
+        // An additional argument.
+        let err = builder([transfer, vec![0]].concat()).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::CallMismatch { ref module, ref call, ref reason }
+                if module == "Balances" && call == "transfer" && reason.contains("trailing")
+        ));
+
+        let err = builder(vec![4, 200]).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Metadata(gekko_metadata::Error::UnknownCall {
+                module_index: 4,
+                call_index: 200
+            })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "metadata")]
+    fn builder_from_metadata() {
+        let mut metadata = gekko_metadata::parse_hex_metadata(include_str!(
+            "../../dumps/metadata_kusama_9080.hex"
+        ))
+        .unwrap();
+        let version = RuntimeVersionInfo {
+            spec_name: "kusama".to_string(),
+            spec_version: 9080,
+            transaction_version: 5,
+        };
+
+        let builder = |metadata: &MetadataVersion, call: OpaqueCall| {
+            let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+
+            SignedTransactionBuilder::<_, PolkadotConfig>::from_metadata(metadata, &version)?
+                .signer(keypair)
+                .call(call)
+                .nonce(0)
+                .tip_base_unit(0)
+                .network(Network::Custom([1; 32]))
+                .build()
+        };
+
+        // `Balances::transfer_keep_alive`, the arguments are not verified.
+        let transaction = builder(&metadata, OpaqueCall(vec![4, 3])).unwrap();
+        assert!(transaction.verify_signature([1; 32], 9080, 5, [1; 32]));
+
+        let err = builder(&metadata, OpaqueCall(vec![4, 200])).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Metadata(gekko_metadata::Error::UnknownCall {
+                module_index: 4,
+                call_index: 200
+            })
+        ));
+
+        if let MetadataVersion::V13(data) = &mut metadata {
+            data.extrinsics.version = 5;
+        }
+        let err = builder(&metadata, OpaqueCall(vec![4, 3])).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Metadata(gekko_metadata::Error::UnsupportedExtrinsicVersion(5))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "metadata")]
+    fn builder_from_metadata_v14() {
+        let metadata = gekko_metadata::parse_hex_metadata(
+            include_str!("../../dumps/metadata_polkadot_1001002.hex").trim(),
+        )
+        .unwrap();
+
+        let version = RuntimeVersionInfo::from_metadata(&metadata).unwrap();
+        assert_eq!(version.spec_name, "polkadot");
+        assert_eq!(version.spec_version, 1_001_002);
+        assert_eq!(version.transaction_version, 25);
+
+        let builder = |call: OpaqueCall| {
+            let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+
+            SignedTransactionBuilder::<_, PolkadotConfig>::from_metadata(&metadata, &version)?
+                .signer(keypair)
+                .call(call)
+                .nonce(0)
+                .tip_base_unit(0)
+                .network(Network::Custom([1; 32]))
+                .build()
+        };
+
+        // `Balances::transfer_keep_alive`, the arguments are not verified.
+        let transaction = builder(OpaqueCall(vec![5, 3])).unwrap();
+        assert!(transaction.verify_signature([1; 32], 1_001_002, 25, [1; 32]));
+
+        let err = builder(OpaqueCall(vec![5, 200])).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Metadata(gekko_metadata::Error::UnknownCall {
+                module_index: 5,
+                call_index: 200
+            })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "metadata")]
+    fn runtime_version_info_from_metadata() {
+        let parse = |hex: &str| gekko_metadata::parse_hex_metadata(hex.trim()).unwrap();
+
+        let version = RuntimeVersionInfo::from_metadata(&parse(include_str!(
+            "../../dumps/metadata_kusama_9111.hex"
+        )))
+        .unwrap();
+        assert_eq!(
+            version,
+            RuntimeVersionInfo {
+                spec_name: "kusama".to_string(),
+                spec_version: 9111,
+                transaction_version: 7,
+            }
         );
+
+        let version = RuntimeVersionInfo::from_metadata(&parse(include_str!(
+            "../../dumps/metadata_kusama_9080.hex"
+        )))
+        .unwrap();
+        assert_eq!(version.spec_version, 9080);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn deserialize_runtime_version_info() {
+        let info: RuntimeVersionInfo = serde_json::from_str(
+            r#"{"specName":"polkadot","implName":"parity-polkadot","authoringVersion":0,
+                "specVersion":9050,"implVersion":0,"transactionVersion":7,"apis":[]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(info.spec_version, 9050);
+        assert_eq!(info.transaction_version, 7);
+    }
+
+    #[test]
+    #[cfg(feature = "metadata")]
+    fn validate_signed_extensions_against_metadata() {
+        let parse = |hex: &str| gekko_metadata::parse_hex_metadata(hex.trim()).unwrap();
+
+        let builder = || SignedTransactionBuilder::<OpaqueCall>::new();
+
+        let kusama = parse(include_str!("../../dumps/metadata_kusama_9080.hex"));
+        assert!(builder().validate_signed_extensions(&kusama).is_ok());
+
+        // `PrevalidateAttests` encodes no data.
+        let polkadot = parse(include_str!("../../dumps/metadata_polkadot_9050.hex"));
+        assert!(builder().validate_signed_extensions(&polkadot).is_ok());
+
+        // The extensions of the metadata are all known.
+        let extensions = SignedExtensions::<PolkadotConfig>::from_metadata(&kusama).unwrap();
+        assert!(extensions.unknown().is_empty());
+        assert!(extensions
+            .identifiers()
+            .eq(PolkadotConfig::SIGNED_EXTENSIONS.iter().copied()));
+
+        let mut kusama = kusama.into_latest().unwrap();
+        kusama
+            .extrinsics
+            .signed_extensions
+            .push("CheckMetadataHash".to_string());
+        let upgraded = MetadataVersion::V13(kusama);
+
+        let err = builder()
+            .validate_signed_extensions(&upgraded)
+            .map(|_| ())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::SignedExtensionMismatch { ref expected, ref encoded }
+                if expected.len() == 7 && encoded.len() == 6
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "metadata")]
+    fn validate_signed_extensions_against_metadata_v14() {
+        let parse = |hex: &str| gekko_metadata::parse_hex_metadata(hex.trim()).unwrap();
+
+        let builder = || SignedTransactionBuilder::<OpaqueCall>::new();
+
+        let kusama = parse(include_str!("../../dumps/metadata_kusama_9111.hex"));
+        assert!(builder().validate_signed_extensions(&kusama).is_ok());
+
+        // `CheckNonZeroSender` and `PrevalidateAttests` encode no data.
+        let polkadot = parse(include_str!("../../dumps/metadata_polkadot_1001002.hex"));
+        assert!(builder().validate_signed_extensions(&polkadot).is_ok());
+
+        // The runtime predates `CheckMetadataHash`.
+        let err = SignedTransactionBuilder::<OpaqueCall, MetadataHashConfig>::default()
+            .validate_signed_extensions(&polkadot)
+            .map(|_| ())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::SignedExtensionMismatch { ref expected, ref encoded }
+                if expected.len() == 6 && encoded.last().map(String::as_str) == Some("CheckMetadataHash")
+        ));
     }
 }
@@ -0,0 +1,460 @@
+//! Version 5 of the extrinsic format, as introduced by newer runtimes, together
+//! with the `CheckMetadataHash` signed extension.
+//!
+//! Version 5 distinguishes bare extrinsics, such as inherents, from general
+//! transactions, which carry signed extensions without a signature. Signed
+//! transactions are still encoded in the version 4 format. See [`Preamble`].
+//!
+//! The `CheckMetadataHash` extension adds the hash of the metadata to the
+//! signature payload, which allows offline signers to verify the decoded call
+//! against the metadata. It's encoded by [`MetadataHashPayload`] and
+//! [`MetadataHashExtra`], as used by [`MetadataHashConfig`].
+//!
+//! # Example
+//!
+//! ```
+//! use gekko::common::*;
+//! use gekko::transaction::v5::*;
+//!
+//! let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+//!
+//! let transaction = SignedTransactionBuilder::new()
+//!     .signer(keypair)
+//!     .call(OpaqueCall(vec![0, 1]))
+//!     .nonce(0)
+//!     .tip_base_unit(0)
+//!     .network(Network::Polkadot)
+//!     .spec_version(9050)
+//!     .transaction_version(7)
+//!     .metadata_hash([1; 32])
+//!     .build()
+//!     .unwrap();
+//!
+//! let (_, _, extra) = transaction.preamble.signature().unwrap();
+//! assert_eq!(extra.mode, 1);
+//! ```
+
+use super::extensions::{EncodedExtensions, SignedExtensions};
+use super::v4::{self, ExtrinsicParams, SignedExtra};
+use super::ExternalSigner;
+use crate::common::{Balance, Config, MetadataHashConfig, Mortality, MultiKeyPair, Network};
+use crate::Result;
+use parity_scale_codec::{Compact, Decode, Encode, Error as ScaleError, Input, Output};
+
+/// The version of the extrinsic format.
+pub const TX_VERSION: u32 = 5;
+
+/// The version of the signed extensions of general transactions.
+pub const EXTENSION_VERSION: u8 = 0;
+
+/// The version byte of bare extrinsics.
+const BARE: u8 = TX_VERSION as u8;
+/// The version byte of signed transactions, which remain in the version 4
+/// format.
+const SIGNED: u8 = 0b1000_0000 | v4::TX_VERSION as u8;
+/// The version byte of general transactions.
+const GENERAL: u8 = 0b0100_0000 | TX_VERSION as u8;
+
+/// The part of the extrinsic preceding the call, determined by the version
+/// byte.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Preamble<Address, Signature, Extra> {
+    /// An extrinsic without signature and signed extensions, such as an
+    /// inherent.
+    Bare,
+    /// A signed transaction, encoded in the version 4 format.
+    Signed(Address, Signature, Extra),
+    /// A transaction with signed extensions of the given version, but
+    /// without a signature.
+    General(u8, Extra),
+}
+
+impl<Address, Signature, Extra> Preamble<Address, Signature, Extra> {
+    /// The address, the signature and the signed extensions, if signed.
+    pub fn signature(&self) -> Option<(&Address, &Signature, &Extra)> {
+        match self {
+            Preamble::Signed(address, sig, extra) => Some((address, sig, extra)),
+            _ => None,
+        }
+    }
+}
+
+/// A transaction in the version 5 format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transaction<Address, Call, Signature, Extra> {
+    pub preamble: Preamble<Address, Signature, Extra>,
+    pub call: Call,
+}
+
+impl<Call> Transaction<(), Call, (), ()> {
+    pub fn new_bare(call: Call) -> Self {
+        Self {
+            preamble: Preamble::Bare,
+            call,
+        }
+    }
+}
+
+impl<Address, Call, Signature, Extra> From<v4::Transaction<Address, Call, Signature, Extra>>
+    for Transaction<Address, Call, Signature, Extra>
+{
+    /// Converts signed transactions into [`Preamble::Signed`] and unsigned
+    /// transactions into [`Preamble::Bare`].
+    fn from(val: v4::Transaction<Address, Call, Signature, Extra>) -> Self {
+        Transaction {
+            preamble: match val.signature {
+                Some((address, sig, extra)) => Preamble::Signed(address, sig, extra),
+                None => Preamble::Bare,
+            },
+            call: val.call,
+        }
+    }
+}
+
+impl<Address, Call, Signature, Extra> Encode for Transaction<Address, Call, Signature, Extra>
+where
+    Address: Encode,
+    Signature: Encode,
+    Call: Encode,
+    Extra: Encode,
+{
+    fn encode_to<T: Output + ?Sized>(&self, dest: &mut T) {
+        let mut enc: Vec<u8> = vec![];
+
+        match &self.preamble {
+            Preamble::Bare => enc.push(BARE),
+            Preamble::Signed(address, sig, extra) => {
+                enc.push(SIGNED);
+                (address, sig, extra).encode_to(&mut enc);
+            }
+            Preamble::General(version, extra) => {
+                enc.push(GENERAL);
+                (version, extra).encode_to(&mut enc);
+            }
+        }
+
+        self.call.encode_to(&mut enc);
+
+        // Prefix the length.
+        enc.encode_to(dest);
+    }
+}
+
+impl<Address, Call, Signature, Extra> Decode for Transaction<Address, Call, Signature, Extra>
+where
+    Address: Decode,
+    Signature: Decode,
+    Call: Decode,
+    Extra: Decode,
+{
+    fn decode<I: Input>(input: &mut I) -> std::result::Result<Self, ScaleError> {
+        // See the version 4 implementation on why the transaction is read
+        // first.
+        let len = Compact::<u32>::decode(input)?.0 as usize;
+        if input
+            .remaining_len()?
+            .is_some_and(|remaining| remaining < len)
+        {
+            return Err("Length prefix of transaction exceeds the input".into());
+        }
+
+        let encoded = v4::read_bytes(input, len)?;
+        let input = &mut encoded.as_slice();
+
+        let preamble = match input.read_byte()? {
+            BARE => Preamble::Bare,
+            SIGNED => Preamble::Signed(
+                Decode::decode(input)?,
+                Decode::decode(input)?,
+                Decode::decode(input)?,
+            ),
+            GENERAL => Preamble::General(Decode::decode(input)?, Decode::decode(input)?),
+            _ => return Err("Invalid transaction version".into()),
+        };
+
+        let call = Decode::decode(input)?;
+        if !input.is_empty() {
+            return Err("Invalid length prefix of transaction".into());
+        }
+
+        Ok(Self { preamble, call })
+    }
+}
+
+/// A transaction of the chain with the given [`Config`].
+pub type SignedExtrinsic<C, Call> =
+    Transaction<<C as Config>::Address, Call, <C as Config>::Signature, <C as Config>::Extra>;
+
+/// A transaction of the chain with the given [`Config`], whose signed
+/// extensions were encoded by [`SignedExtensions`].
+pub type DynamicSignedExtrinsic<C, Call> =
+    Transaction<<C as Config>::Address, Call, <C as Config>::Signature, EncodedExtensions>;
+
+/// Like [`v4::Payload`], followed by the mode of the `CheckMetadataHash`
+/// extension.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetadataHashPayload<Index = u32, Balance = u128, Era = Mortality> {
+    pub mortality: Era,
+    #[codec(compact)]
+    pub nonce: Index,
+    #[codec(compact)]
+    pub tip: Balance,
+    /// Whether the metadata hash is part of the signature payload, `1` if
+    /// enabled and `0` otherwise.
+    pub mode: u8,
+}
+
+/// Like [`v4::ExtraSignaturePayload`], followed by the metadata hash of the
+/// `CheckMetadataHash` extension, or `None` if disabled.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "Hash: AsRef<[u8]>",
+        deserialize = "Hash: std::convert::TryFrom<Vec<u8>>"
+    ))
+)]
+pub struct MetadataHashExtra<Hash = [u8; 32]> {
+    pub spec_version: u32,
+    pub tx_version: u32,
+    #[cfg_attr(feature = "serde", serde(with = "gekko_metadata::serde_hex"))]
+    pub genesis: Hash,
+    #[cfg_attr(feature = "serde", serde(with = "gekko_metadata::serde_hex"))]
+    pub birth: Hash,
+    #[cfg_attr(feature = "serde", serde(with = "gekko_metadata::serde_hex::option"))]
+    pub metadata_hash: Option<[u8; 32]>,
+}
+
+impl<C: Config> SignedExtra<C> for MetadataHashPayload<C::Index, C::Balance, C::Era> {
+    fn new(params: &ExtrinsicParams<C>) -> Self {
+        MetadataHashPayload {
+            mortality: params.era.clone(),
+            nonce: params.nonce,
+            tip: params.tip,
+            mode: params.metadata_hash.is_some() as u8,
+        }
+    }
+}
+
+impl<C: Config> SignedExtra<C> for MetadataHashExtra<C::Hash> {
+    fn new(params: &ExtrinsicParams<C>) -> Self {
+        MetadataHashExtra {
+            spec_version: params.spec_version,
+            tx_version: params.transaction_version,
+            genesis: params.genesis,
+            birth: params.birth,
+            metadata_hash: params.metadata_hash,
+        }
+    }
+}
+
+/// Builder type for creating version 5 transactions. Wraps the
+/// [`v4::SignedTransactionBuilder`], see its documentation for the individual
+/// fields. Uses the primitive types of [`MetadataHashConfig`] by default.
+#[derive(Clone)]
+pub struct SignedTransactionBuilder<Call, C: Config = MetadataHashConfig>(
+    v4::SignedTransactionBuilder<Call, C>,
+);
+
+impl<Call, C: Config> Default for SignedTransactionBuilder<Call, C> {
+    fn default() -> Self {
+        Self(Default::default())
+    }
+}
+
+impl<Call: Encode> SignedTransactionBuilder<Call> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<Call, C: Config> From<v4::SignedTransactionBuilder<Call, C>>
+    for SignedTransactionBuilder<Call, C>
+{
+    fn from(val: v4::SignedTransactionBuilder<Call, C>) -> Self {
+        Self(val)
+    }
+}
+
+impl<Call: Encode, C: Config> SignedTransactionBuilder<Call, C> {
+    pub fn signer<T: Into<MultiKeyPair>>(self, signer: T) -> Self {
+        Self(self.0.signer(signer))
+    }
+    pub fn signer_external<S: ExternalSigner + Send + Sync + 'static>(self, signer: S) -> Self {
+        Self(self.0.signer_external(signer))
+    }
+    pub fn call(self, call: Call) -> Self {
+        Self(self.0.call(call))
+    }
+    pub fn nonce(self, nonce: C::Index) -> Self {
+        Self(self.0.nonce(nonce))
+    }
+    pub fn tip(self, tip: Balance) -> Self {
+        Self(self.0.tip(tip))
+    }
+    pub fn tip_base_unit(self, tip: C::Balance) -> Self {
+        Self(self.0.tip_base_unit(tip))
+    }
+    pub fn asset_id(self, asset_id: u32) -> Self {
+        Self(self.0.asset_id(asset_id))
+    }
+    pub fn network(self, network: Network) -> Self {
+        Self(self.0.network(network))
+    }
+    pub fn mortality(self, mortality: Mortality) -> Self {
+        Self(self.0.mortality(mortality))
+    }
+    pub fn spec_version(self, version: u32) -> Self {
+        Self(self.0.spec_version(version))
+    }
+    pub fn transaction_version(self, version: u32) -> Self {
+        Self(self.0.transaction_version(version))
+    }
+    /// Enable the `CheckMetadataHash` extension with the given hash, see
+    /// [`v4::SignedTransactionBuilder::metadata_hash`].
+    pub fn metadata_hash(self, hash: [u8; 32]) -> Self {
+        Self(self.0.metadata_hash(hash))
+    }
+    /// Builds the signed transaction, see
+    /// [`v4::SignedTransactionBuilder::build`].
+    pub fn build(self) -> Result<SignedExtrinsic<C, Call>> {
+        self.0.build().map(Into::into)
+    }
+    /// Builds the signed transaction with the given signed extensions, see
+    /// [`v4::SignedTransactionBuilder::build_with_extensions`].
+    pub fn build_with_extensions(
+        self,
+        extensions: &SignedExtensions<C>,
+    ) -> Result<DynamicSignedExtrinsic<C, Call>> {
+        self.0.build_with_extensions(extensions).map(Into::into)
+    }
+    /// Builds a general transaction, which contains the signed extensions of
+    /// [`Config::Extra`] but no signature. No signer needs to be set.
+    pub fn build_general(self) -> Result<Transaction<(), Call, (), C::Extra>> {
+        let (call, params) = self.0.prepare_unsigned()?;
+
+        Ok(Transaction {
+            preamble: Preamble::General(EXTENSION_VERSION, C::Extra::new(&params)),
+            call,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::*;
+    use crate::transaction::extensions::SignedExtensions;
+
+    fn builder() -> SignedTransactionBuilder<OpaqueCall> {
+        let keypair = KeyPairBuilder::<Ed25519>::from_seed(&[1; 32]);
+
+        SignedTransactionBuilder::new()
+            .signer(keypair)
+            .call(OpaqueCall(vec![4, 3, 1, 2]))
+            .nonce(3)
+            .tip_base_unit(10)
+            .network(Network::Custom([1; 32]))
+            .spec_version(1)
+            .transaction_version(2)
+    }
+
+    #[test]
+    fn signed_transaction_metadata_hash() {
+        let transaction = builder().metadata_hash([5; 32]).build().unwrap();
+
+        let (address, sig, extra) = transaction.preamble.signature().unwrap();
+        assert_eq!(extra.mode, 1);
+
+        // The metadata hash is part of the signature payload.
+        let mut payload = vec![4, 3, 1, 2];
+        extra.encode_to(&mut payload);
+        MetadataHashExtra::<[u8; 32]> {
+            spec_version: 1,
+            tx_version: 2,
+            genesis: [1; 32],
+            birth: [1; 32],
+            metadata_hash: Some([5; 32]),
+        }
+        .encode_to(&mut payload);
+
+        let account = match address {
+            MultiAddress::Id(account) => AccountId::from(account.clone()),
+            _ => panic!("expected account Id"),
+        };
+        assert!(sig.verify(&payload, &account));
+
+        // Signed transactions remain in the version 4 format.
+        let encoded = transaction.encode();
+        assert_eq!(encoded[2], 0b1000_0100);
+        let decoded: SignedExtrinsic<MetadataHashConfig, OpaqueCall> =
+            Decode::decode(&mut encoded.as_slice()).unwrap();
+        assert_eq!(decoded, transaction);
+
+        let decoded: v4::SignedExtrinsic<MetadataHashConfig, OpaqueCall> =
+            Decode::decode(&mut encoded.as_slice()).unwrap();
+        assert_eq!(Transaction::from(decoded), transaction);
+    }
+
+    #[test]
+    fn signed_transaction_metadata_hash_disabled() {
+        let transaction = builder().build().unwrap();
+        let (_, _, extra) = transaction.preamble.signature().unwrap();
+        assert_eq!(extra.mode, 0);
+
+        // The extensions of the config result in the same transaction.
+        let extensions = SignedExtensions::new(MetadataHashConfig::SIGNED_EXTENSIONS);
+        for builder in [builder(), builder().metadata_hash([5; 32])] {
+            let transaction = builder.clone().build().unwrap();
+            let dynamic = builder.build_with_extensions(&extensions).unwrap();
+            assert_eq!(dynamic.encode(), transaction.encode());
+        }
+    }
+
+    #[test]
+    fn bare_and_general_transactions() {
+        let bare = Transaction::new_bare(OpaqueCall(vec![0, 1]));
+        let encoded = bare.encode();
+        assert_eq!(encoded, vec![12, 0b0000_0101, 0, 1]);
+        assert_eq!(Transaction::decode(&mut encoded.as_slice()).unwrap(), bare);
+
+        // No signer required.
+        let general = SignedTransactionBuilder::<_, MetadataHashConfig>::default()
+            .call(OpaqueCall(vec![0, 1]))
+            .nonce(3)
+            .tip_base_unit(10)
+            .network(Network::Custom([1; 32]))
+            .spec_version(1)
+            .transaction_version(2)
+            .build_general()
+            .unwrap();
+
+        let encoded = general.encode();
+        assert_eq!(
+            encoded,
+            vec![
+                32,
+                0b0100_0101,
+                EXTENSION_VERSION,
+                0,
+                3 << 2,
+                10 << 2,
+                0,
+                0,
+                1
+            ]
+        );
+        assert_eq!(
+            Transaction::decode(&mut encoded.as_slice()).unwrap(),
+            general
+        );
+
+        // Version 4 transactions are not bare.
+        let unsigned = v4::Transaction::new_unsigned(OpaqueCall(vec![0, 1])).encode();
+        assert!(Transaction::<(), OpaqueCall, (), ()>::decode(&mut unsigned.as_slice()).is_err());
+    }
+}
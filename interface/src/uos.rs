@@ -0,0 +1,260 @@
+//! The Universal Offline Signatures (UOS) format of Polkadot Vault, formerly
+//! Parity Signer, for signing transactions on an air-gapped device. Requires
+//! the `"uos"` feature.
+//!
+//! The [`SigningPayload`] of a transaction is encoded with
+//! [`transaction_payload`] and split into QR code [`frames`], which are
+//! scanned by the device. The device displays the signature as a QR code,
+//! which is decoded with [`decode_signature`]. This module only handles the
+//! bytes of the QR codes, rendering and scanning is left to the caller.
+//!
+//! # Example
+//!
+//! ```
+//! use gekko::common::*;
+//! use gekko::common::sp_core::crypto::Pair;
+//! use gekko::transaction::*;
+//! use gekko::uos;
+//!
+//! let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+//!
+//! let payload = SignedTransactionBuilder::new()
+//!     .call(OpaqueCall(vec![0, 1]))
+//!     .nonce(0)
+//!     .tip_base_unit(0)
+//!     .network(Network::Polkadot)
+//!     .build_unsigned_payload()
+//!     .unwrap();
+//!
+//! let data = uos::transaction_payload(&keypair.public().into(), &payload, Network::Polkadot);
+//! let frames = uos::frames(&data, 1024).unwrap();
+//! assert_eq!(frames.len(), 1);
+//!
+//! // Scanned from the device, as HEX encoded text.
+//! let sig = MultiSignature::from(keypair.sign(&payload.to_signable_bytes()));
+//! let scanned = hex::encode(parity_scale_codec::Encode::encode(&sig));
+//!
+//! assert_eq!(uos::decode_signature(scanned.as_bytes()).unwrap(), sig);
+//! ```
+
+use crate::common::{Blake2_256, MultiSignature, Network};
+use crate::transaction::SigningPayload;
+use parity_scale_codec::{Decode, Encode};
+use sp_core::{ecdsa, ed25519, sr25519};
+use std::convert::TryFrom;
+
+/// The prefix of payloads for Substrate chains.
+const SUBSTRATE: u8 = 0x53;
+/// The payload type of transactions, both mortal and immortal.
+const TRANSACTION: u8 = 0x02;
+/// The header of multipart frames, followed by the frame count and index.
+const MULTIPART: u8 = 0x00;
+/// The length of the multipart header.
+const MULTIPART_LEN: usize = 5;
+
+/// Errors that can occur when encoding or decoding UOS payloads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum UosError {
+    /// The frame size cannot hold the multipart header and any data.
+    #[error("frame size of {0} bytes is too small")]
+    InvalidFrameSize(usize),
+    /// The payload requires more than `u16::MAX` frames.
+    #[error("payload exceeds the maximum number of frames")]
+    TooManyFrames,
+    /// The scanned data is neither a HEX encoded nor a raw signature.
+    #[error("invalid signature")]
+    InvalidSignature,
+}
+
+/// The public key of the signer, which selects the key on the device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MultiSigner {
+    Ed25519(ed25519::Public),
+    Sr25519(sr25519::Public),
+    Ecdsa(ecdsa::Public),
+}
+
+impl MultiSigner {
+    /// The identifier of the cryptographic algorithm.
+    fn crypto(&self) -> u8 {
+        match self {
+            MultiSigner::Ed25519(_) => 0x00,
+            MultiSigner::Sr25519(_) => 0x01,
+            MultiSigner::Ecdsa(_) => 0x02,
+        }
+    }
+    fn as_bytes(&self) -> &[u8] {
+        match self {
+            MultiSigner::Ed25519(public) => public.as_ref(),
+            MultiSigner::Sr25519(public) => public.as_ref(),
+            MultiSigner::Ecdsa(public) => public.as_ref(),
+        }
+    }
+}
+
+impl From<ed25519::Public> for MultiSigner {
+    fn from(val: ed25519::Public) -> Self {
+        MultiSigner::Ed25519(val)
+    }
+}
+
+impl From<sr25519::Public> for MultiSigner {
+    fn from(val: sr25519::Public) -> Self {
+        MultiSigner::Sr25519(val)
+    }
+}
+
+impl From<ecdsa::Public> for MultiSigner {
+    fn from(val: ecdsa::Public) -> Self {
+        MultiSigner::Ecdsa(val)
+    }
+}
+
+/// Encodes the payload of the transaction to be signed by the given signer.
+/// The device only signs transactions of networks whose metadata it knows,
+/// identified by the genesis hash of the network.
+///
+/// The payload consists of the prelude `0x53 <crypto> 0x02`, the public key,
+/// the length prefixed call, the signed extensions and the genesis hash.
+pub fn transaction_payload<Extra: Encode>(
+    signer: &MultiSigner,
+    payload: &SigningPayload<Extra, Blake2_256>,
+    network: Network,
+) -> Vec<u8> {
+    let mut data = vec![SUBSTRATE, signer.crypto(), TRANSACTION];
+    data.extend_from_slice(signer.as_bytes());
+    payload.call.encode_to(&mut data);
+    payload.extra.encode_to(&mut data);
+    data.extend_from_slice(&payload.additional);
+    data.extend_from_slice(&network.genesis());
+    data
+}
+
+/// Splits the data into multipart frames of at most `frame_size` bytes, each
+/// displayed as a binary QR code in order. Each frame starts with `0x00`,
+/// followed by the frame count and the frame index as big-endian `u16`.
+pub fn frames(data: &[u8], frame_size: usize) -> Result<Vec<Vec<u8>>, UosError> {
+    if frame_size <= MULTIPART_LEN {
+        return Err(UosError::InvalidFrameSize(frame_size));
+    }
+
+    let chunks: Vec<&[u8]> = data.chunks(frame_size - MULTIPART_LEN).collect();
+    let count = u16::try_from(chunks.len()).map_err(|_| UosError::TooManyFrames)?;
+
+    Ok(chunks
+        .into_iter()
+        .zip(0u16..)
+        .map(|(chunk, index)| {
+            let mut frame = Vec::with_capacity(MULTIPART_LEN + chunk.len());
+            frame.push(MULTIPART);
+            frame.extend_from_slice(&count.to_be_bytes());
+            frame.extend_from_slice(&index.to_be_bytes());
+            frame.extend_from_slice(chunk);
+            frame
+        })
+        .collect())
+}
+
+/// Decodes the signature scanned from the device. The device displays the
+/// SCALE encoded [`MultiSignature`] as HEX encoded text, optionally prefixed
+/// with `0x`, but the raw bytes are accepted, too.
+pub fn decode_signature(data: &[u8]) -> Result<MultiSignature, UosError> {
+    let text = data.strip_prefix(b"0x").unwrap_or(data);
+    let raw = if !text.is_empty() && text.iter().all(u8::is_ascii_hexdigit) {
+        hex::decode(text).map_err(|_| UosError::InvalidSignature)?
+    } else {
+        data.to_vec()
+    };
+
+    let input = &mut raw.as_slice();
+    match MultiSignature::decode(input) {
+        Ok(sig) if input.is_empty() => Ok(sig),
+        _ => Err(UosError::InvalidSignature),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{KeyPairBuilder, OpaqueCall, Sr25519};
+    use crate::transaction::SignedTransactionBuilder;
+    use sp_core::crypto::Pair;
+
+    #[test]
+    fn encode_transaction_payload() {
+        let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+        let payload = SignedTransactionBuilder::new()
+            .call(OpaqueCall(vec![4, 3, 1, 2]))
+            .nonce(1)
+            .tip_base_unit(0)
+            .network(Network::Custom([1; 32]))
+            .spec_version(9080)
+            .transaction_version(5)
+            .build_unsigned_payload()
+            .unwrap();
+
+        let data =
+            transaction_payload(&keypair.public().into(), &payload, Network::Custom([1; 32]));
+
+        let mut expected = vec![0x53, 0x01, 0x02];
+        expected.extend_from_slice(&keypair.public().0);
+        // Length prefixed call.
+        expected.extend_from_slice(&[16, 4, 3, 1, 2]);
+        // Immortal, nonce and tip.
+        expected.extend_from_slice(&[0, 4, 0]);
+        expected.extend_from_slice(&9080u32.to_le_bytes());
+        expected.extend_from_slice(&5u32.to_le_bytes());
+        expected.extend_from_slice(&[1; 64]);
+        expected.extend_from_slice(&[1; 32]);
+
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn multipart_frames() {
+        let data: Vec<u8> = (0..10).collect();
+
+        let frames = frames(&data, 9).unwrap();
+        assert_eq!(
+            frames,
+            vec![
+                vec![0, 0, 3, 0, 0, 0, 1, 2, 3],
+                vec![0, 0, 3, 0, 1, 4, 5, 6, 7],
+                vec![0, 0, 3, 0, 2, 8, 9],
+            ]
+        );
+
+        assert_eq!(super::frames(&data, 5), Err(UosError::InvalidFrameSize(5)));
+        assert_eq!(
+            super::frames(&vec![0; 70_000], 6),
+            Err(UosError::TooManyFrames)
+        );
+    }
+
+    #[test]
+    fn decode_signatures() {
+        let (keypair, _) = KeyPairBuilder::<Sr25519>::generate();
+        let sig = MultiSignature::from(keypair.sign(b"payload"));
+        let encoded = sig.encode();
+
+        assert_eq!(decode_signature(&encoded), Ok(sig.clone()));
+        assert_eq!(
+            decode_signature(hex::encode(&encoded).as_bytes()),
+            Ok(sig.clone())
+        );
+        assert_eq!(
+            decode_signature(format!("0x{}", hex::encode(&encoded)).as_bytes()),
+            Ok(sig)
+        );
+
+        assert_eq!(
+            decode_signature(&encoded[..40]),
+            Err(UosError::InvalidSignature)
+        );
+        assert_eq!(
+            decode_signature(&[encoded.clone(), vec![0]].concat()),
+            Err(UosError::InvalidSignature)
+        );
+        assert_eq!(decode_signature(b"0x"), Err(UosError::InvalidSignature));
+    }
+}
@@ -0,0 +1,40 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use gekko_metadata::{decode_hex, parse_hex_metadata, parse_raw_metadata, ModuleMetadataExt};
+
+const POLKADOT_METADATA: &str = include_str!("../../interface/dumps/metadata_polkadot_9050.hex");
+
+fn raw_metadata() -> Vec<u8> {
+    decode_hex(POLKADOT_METADATA.trim()).unwrap()
+}
+
+fn parse(c: &mut Criterion) {
+    let raw = raw_metadata();
+
+    c.bench_function("parse_hex_metadata", |b| {
+        b.iter(|| parse_hex_metadata(black_box(POLKADOT_METADATA.trim())).unwrap())
+    });
+    c.bench_function("parse_raw_metadata", |b| {
+        b.iter(|| parse_raw_metadata(black_box(&raw)).unwrap())
+    });
+}
+
+fn extrinsics(c: &mut Criterion) {
+    let data = parse_raw_metadata(raw_metadata())
+        .unwrap()
+        .into_latest()
+        .unwrap();
+
+    c.bench_function("modules_extrinsics", |b| {
+        b.iter(|| black_box(&data).modules_extrinsics())
+    });
+    c.bench_function("find_module_extrinsic", |b| {
+        b.iter(|| {
+            black_box(&data)
+                .find_module_extrinsic("Balances", "transfer_keep_alive")
+                .unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, parse, extrinsics);
+criterion_main!(benches);
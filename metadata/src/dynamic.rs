@@ -0,0 +1,1238 @@
+//! Decoding of extrinsics into dynamic [`Value`]s and encoding of calls from
+//! dynamic values, driven by the metadata at runtime instead of generated Rust
+//! types.
+//!
+//! The call index of an extrinsic is resolved to the module and call of the
+//! metadata, and each argument is decoded according to its type. The envelope
+//! of the extrinsic is split by the same parser as
+//! [`decode_extrinsic`](crate::extrinsics::decode_extrinsic), whose
+//! [`CallRecord`]s can be decoded with
+//! [`ExtrinsicDecoder::decode_record`]. Conversely, a [`DynamicCall`] is
+//! encoded by looking up the call index and the order and types of the
+//! arguments. Metadata up to V13 only contains the names of types, whose
+//! definitions are provided by a [`TypeRegistry`], while metadata V14 embeds
+//! the definitions in its [`PortableRegistry`].
+//!
+//! Values of the [`PortableRegistry`] are represented like the values of the
+//! [`TypeRegistry`], see [`Value`]. Structs with named fields are
+//! [`Composite`](Value::Composite)s, tuple structs with a single field are
+//! represented by the value of the field and tuple structs with multiple fields
+//! are [`Sequence`](Value::Sequence)s.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use gekko_metadata::dynamic::ExtrinsicDecoder;
+//! use gekko_metadata::scale::TypeRegistry;
+//! use gekko_metadata::*;
+//!
+//! let content = std::fs::read_to_string("metadata_kusama_9080.hex").unwrap();
+//! let metadata = parse_hex_metadata(content).unwrap().into_latest().unwrap();
+//! let registry = TypeRegistry::default();
+//!
+//! // A length-prefixed extrinsic, as contained in the body of a block.
+//! let raw = std::fs::read("extrinsic.bin").unwrap();
+//!
+//! let extrinsic = ExtrinsicDecoder::v13(&metadata, &registry)
+//!     .decode_extrinsic(&raw)
+//!     .unwrap();
+//!
+//! if extrinsic.call.is("Balances", "transfer_keep_alive") {
+//!     let value = extrinsic.call.arg("value").and_then(|value| value.as_u128());
+//!     println!("transferred {:?}", value);
+//! }
+//! ```
+
+use crate::extrinsics::{
+    split_body, split_extrinsic, CallArg, CallRecord, ExtrinsicLayout, RegistryLayout,
+    SignatureRecord,
+};
+use crate::scale::json::same_name;
+use crate::scale::{TypeRegistry, Value};
+use crate::version::v14::{
+    Field, PalletMetadata, PortableRegistry, TypeDef, TypeDefPrimitive, TypeId, Variant,
+};
+use crate::version::{MetadataV13, MetadataV14};
+use crate::{Error, MetadataVersion, Result};
+use parity_scale_codec::{Compact, Decode, Encode};
+use std::convert::TryFrom;
+
+/// The maximum nesting of types, protecting against cyclic definitions.
+const MAX_DEPTH: usize = 128;
+
+/// An extrinsic whose call arguments and signature are decoded into dynamic
+/// values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecodedExtrinsic {
+    /// The signature, or `None` if the extrinsic is unsigned, such as
+    /// inherents.
+    pub signature: Option<DecodedSignature>,
+    /// The dispatched call.
+    pub call: DecodedCall,
+}
+
+impl DecodedExtrinsic {
+    /// Whether the extrinsic is signed.
+    pub fn is_signed(&self) -> bool {
+        self.signature.is_some()
+    }
+}
+
+/// The decoded signature of a signed extrinsic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecodedSignature {
+    /// The address of the signer, usually a `MultiAddress`.
+    pub address: Value,
+    /// The signature, usually a `MultiSignature`.
+    pub signature: Value,
+    /// The extra data of the signed extensions, such as the era and the nonce,
+    /// together with the name of the signed extension.
+    pub extra: Vec<(String, Value)>,
+}
+
+/// A call of a module, with its decoded arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecodedCall {
+    /// The index of the module, as declared by the runtime.
+    pub module_index: u8,
+    /// The index of the call within the module.
+    pub call_index: u8,
+    /// The name of the module.
+    pub module_name: String,
+    /// The name of the call.
+    pub call_name: String,
+    /// The names and values of the arguments, in order.
+    pub args: Vec<(String, Value)>,
+}
+
+impl DecodedCall {
+    /// Whether this is the call of the given module and name.
+    pub fn is(&self, module: &str, call: &str) -> bool {
+        self.module_name == module && self.call_name == call
+    }
+    /// Returns the value of the argument with the given name, if it exists.
+    pub fn arg(&self, name: &str) -> Option<&Value> {
+        self.args
+            .iter()
+            .find(|(arg, _)| arg == name)
+            .map(|(_, value)| value)
+    }
+    /// Converts the call into a variant of the module, containing a variant of
+    /// the call, containing a composite of the arguments. See
+    /// [`Value::call`].
+    pub fn into_value(self) -> Value {
+        Value::call(self.module_name, self.call_name, self.args)
+    }
+}
+
+/// A call which is built by the name of the module and the call, with the
+/// arguments as dynamic values. The order and types of the arguments are looked
+/// up in the metadata when encoding the call.
+///
+/// # Example
+///
+/// ```no_run
+/// use gekko_metadata::dynamic::{DynamicCall, ExtrinsicDecoder};
+/// use gekko_metadata::scale::{TypeRegistry, Value};
+/// use gekko_metadata::*;
+///
+/// let content = std::fs::read_to_string("metadata_kusama_9080.hex").unwrap();
+/// let metadata = parse_hex_metadata(content).unwrap().into_latest().unwrap();
+/// let registry = TypeRegistry::default();
+///
+/// let call = DynamicCall::new("Balances", "transfer_keep_alive")
+///     .arg("dest", Value::variant("Id", Value::Bytes(vec![1; 32])))
+///     .arg("value", 10u128)
+///     .encode(&ExtrinsicDecoder::v13(&metadata, &registry))
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DynamicCall {
+    /// The name of the module.
+    pub module_name: String,
+    /// The name of the call.
+    pub call_name: String,
+    /// The names and values of the arguments, in any order.
+    pub args: Vec<(String, Value)>,
+}
+
+impl DynamicCall {
+    /// Creates a call of the given module without any arguments. Names are
+    /// matched regardless of whether they are camelCase or snake_case.
+    pub fn new<M: Into<String>, C: Into<String>>(module: M, call: C) -> Self {
+        DynamicCall {
+            module_name: module.into(),
+            call_name: call.into(),
+            args: vec![],
+        }
+    }
+    /// Adds the argument with the given name.
+    pub fn arg<N: Into<String>, V: Into<Value>>(mut self, name: N, value: V) -> Self {
+        self.args.push((name.into(), value.into()));
+        self
+    }
+    /// Encodes the call with the metadata of the decoder, i.e. the module and
+    /// call index followed by the encoded arguments. The result can be passed
+    /// to transaction builders as an opaque call.
+    pub fn encode(&self, decoder: &ExtrinsicDecoder<'_>) -> Result<Vec<u8>> {
+        let unknown = || Error::UnknownCallName {
+            module: self.module_name.clone(),
+            call: self.call_name.clone(),
+        };
+
+        let mut out = vec![];
+        match decoder.source {
+            Source::V13 { metadata, registry } => {
+                let module = metadata
+                    .modules
+                    .iter()
+                    .find(|module| same_name(&module.name, &self.module_name))
+                    .ok_or_else(unknown)?;
+
+                let (call_index, call) = module
+                    .calls
+                    .iter()
+                    .flatten()
+                    .enumerate()
+                    .find(|(_, call)| same_name(&call.name, &self.call_name))
+                    .ok_or_else(unknown)?;
+
+                let names: Vec<&str> = call.arguments.iter().map(|arg| arg.name.as_str()).collect();
+                let values = self.ordered_args(&names)?;
+
+                out.extend([module.index, call_index as u8]);
+                for (arg, value) in call.arguments.iter().zip(values) {
+                    out.extend(registry.encode_value(&arg.ty, value)?);
+                }
+            }
+            Source::V14(metadata) => {
+                let pallet = metadata
+                    .pallets
+                    .iter()
+                    .find(|pallet| same_name(&pallet.name, &self.module_name))
+                    .ok_or_else(unknown)?;
+
+                let call = pallet
+                    .calls
+                    .map(|ty| metadata.types.variants(ty))
+                    .unwrap_or_default()
+                    .iter()
+                    .find(|call| same_name(&call.name, &self.call_name))
+                    .ok_or_else(unknown)?;
+
+                let names: Vec<String> = call
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .map(|(position, field)| {
+                        field.name.clone().unwrap_or_else(|| position.to_string())
+                    })
+                    .collect();
+                let names: Vec<&str> = names.iter().map(String::as_str).collect();
+                let values = self.ordered_args(&names)?;
+
+                out.extend([pallet.index, call.index]);
+                for (field, value) in call.fields.iter().zip(values) {
+                    out.extend(metadata.types.encode_value(field.ty, value)?);
+                }
+            }
+        }
+
+        Ok(out)
+    }
+    /// The values of the arguments in the order of the given names.
+    fn ordered_args(&self, names: &[&str]) -> Result<Vec<&Value>> {
+        let invalid = |reason| Error::InvalidValue {
+            ty: format!("{}::{}", self.module_name, self.call_name),
+            reason,
+        };
+
+        if let Some((name, _)) = self
+            .args
+            .iter()
+            .find(|(name, _)| !names.iter().any(|other| same_name(other, name)))
+        {
+            return Err(invalid(format!("unknown argument {}", name)));
+        }
+
+        names
+            .iter()
+            .map(|name| {
+                self.args
+                    .iter()
+                    .find(|(other, _)| same_name(name, other))
+                    .map(|(_, value)| value)
+                    .ok_or_else(|| invalid(format!("missing argument {}", name)))
+            })
+            .collect()
+    }
+}
+
+impl From<DecodedCall> for DynamicCall {
+    fn from(call: DecodedCall) -> Self {
+        DynamicCall {
+            module_name: call.module_name,
+            call_name: call.call_name,
+            args: call.args,
+        }
+    }
+}
+
+/// Decodes extrinsics and calls based on the metadata. See the
+/// [module documentation](self).
+#[derive(Debug, Clone, Copy)]
+pub struct ExtrinsicDecoder<'a> {
+    source: Source<'a>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Source<'a> {
+    V13 {
+        metadata: &'a MetadataV13,
+        registry: &'a TypeRegistry,
+    },
+    V14(&'a MetadataV14),
+}
+
+impl<'a> ExtrinsicDecoder<'a> {
+    /// Creates a decoder for metadata V13, resolving the type names of the
+    /// metadata with the definitions of the registry.
+    pub fn v13(metadata: &'a MetadataV13, registry: &'a TypeRegistry) -> Self {
+        ExtrinsicDecoder {
+            source: Source::V13 { metadata, registry },
+        }
+    }
+    /// Creates a decoder for metadata V14, based on its type registry.
+    pub fn v14(metadata: &'a MetadataV14) -> Self {
+        ExtrinsicDecoder {
+            source: Source::V14(metadata),
+        }
+    }
+    /// Creates a decoder for either metadata V13 or V14. The registry is only
+    /// used for V13. Results in an error for any other version.
+    pub fn new(metadata: &'a MetadataVersion, registry: &'a TypeRegistry) -> Result<Self> {
+        match metadata {
+            MetadataVersion::V13(metadata) => Ok(Self::v13(metadata, registry)),
+            MetadataVersion::V14(metadata) => Ok(Self::v14(metadata)),
+            _ => Err(Error::InvalidMetadataVersion),
+        }
+    }
+    /// Decodes a length-prefixed extrinsic, as contained in the extrinsics of a
+    /// block.
+    pub fn decode_extrinsic(&self, data: &[u8]) -> Result<DecodedExtrinsic> {
+        let record = split_extrinsic(self, data)?;
+
+        Ok(DecodedExtrinsic {
+            signature: record
+                .signature
+                .as_ref()
+                .map(|signature| self.decode_signature(signature))
+                .transpose()?,
+            call: self.decode_record(&record.call)?,
+        })
+    }
+    /// Decodes the extrinsics of a SCALE encoded block body, i.e. the
+    /// length-prefixed extrinsics preceded by their compact encoded count. The
+    /// extrinsics are decoded lazily, one at a time.
+    pub fn decode_extrinsics<'b>(
+        &'b self,
+        body: &'b [u8],
+    ) -> Result<impl Iterator<Item = Result<DecodedExtrinsic>> + 'b> {
+        Ok(split_body(body)?.map(move |extrinsic| self.decode_extrinsic(extrinsic)))
+    }
+    /// Decodes a call, advancing the input past it.
+    pub fn decode_call(&self, input: &mut &[u8]) -> Result<DecodedCall> {
+        let call = self.split_call(input)?;
+        self.decode_record(&call)
+    }
+    /// Decodes the arguments of a call whose arguments are still SCALE
+    /// encoded, such as the calls of [`decode_extrinsic`](crate::extrinsics::decode_extrinsic).
+    /// Each argument must be decoded completely.
+    pub fn decode_record(&self, call: &CallRecord) -> Result<DecodedCall> {
+        let args = match self.source {
+            Source::V13 { registry, .. } => call
+                .args
+                .iter()
+                .map(|arg| {
+                    let value = decode_exact(&arg.ty, &arg.value, |input| {
+                        registry.decode_value(&arg.ty, input)
+                    })?;
+
+                    Ok((arg.name.clone(), value))
+                })
+                .collect::<Result<_>>()?,
+            Source::V14(metadata) => {
+                let (_, variant) = v14_call(metadata, call.module_index, call.call_index)?;
+                if variant.fields.len() != call.args.len() {
+                    return Err(Error::InvalidValue {
+                        ty: format!("{}::{}", call.module_name, call.call_name),
+                        reason: format!("expected {} arguments", variant.fields.len()),
+                    });
+                }
+
+                variant
+                    .fields
+                    .iter()
+                    .zip(&call.args)
+                    .map(|(field, arg)| {
+                        let value = decode_exact(&arg.ty, &arg.value, |input| {
+                            metadata.types.decode_value(field.ty, input)
+                        })?;
+
+                        Ok((arg.name.clone(), value))
+                    })
+                    .collect::<Result<_>>()?
+            }
+        };
+
+        Ok(DecodedCall {
+            module_index: call.module_index,
+            call_index: call.call_index,
+            module_name: call.module_name.clone(),
+            call_name: call.call_name.clone(),
+            args,
+        })
+    }
+    fn decode_signature(&self, signature: &SignatureRecord) -> Result<DecodedSignature> {
+        match self.source {
+            Source::V13 { metadata, registry } => {
+                let decode = |ty: &str, bytes: &[u8]| {
+                    decode_exact(ty, bytes, |input| registry.decode_value(ty, input))
+                };
+
+                Ok(DecodedSignature {
+                    address: decode("Address", &signature.address)?,
+                    signature: decode("ExtrinsicSignature", &signature.signature)?,
+                    extra: metadata
+                        .extrinsics
+                        .signed_extensions
+                        .iter()
+                        .zip(&signature.extra)
+                        .map(|(ext, bytes)| Ok((ext.clone(), decode(ext, bytes)?)))
+                        .collect::<Result<_>>()?,
+                })
+            }
+            Source::V14(metadata) => {
+                let types = &metadata.types;
+                let decode = |id: TypeId, bytes: &[u8]| {
+                    decode_exact(&types.type_name(id), bytes, |input| {
+                        types.decode_value(id, input)
+                    })
+                };
+
+                Ok(DecodedSignature {
+                    address: decode(v14_param(metadata, "Address")?, &signature.address)?,
+                    signature: decode(v14_param(metadata, "Signature")?, &signature.signature)?,
+                    extra: metadata
+                        .extrinsic
+                        .signed_extensions
+                        .iter()
+                        .zip(&signature.extra)
+                        .map(|(ext, bytes)| Ok((ext.identifier.clone(), decode(ext.ty, bytes)?)))
+                        .collect::<Result<_>>()?,
+                })
+            }
+        }
+    }
+}
+
+impl ExtrinsicLayout for ExtrinsicDecoder<'_> {
+    fn split_signature(&self, input: &mut &[u8]) -> Result<SignatureRecord> {
+        match self.source {
+            Source::V13 { metadata, registry } => {
+                RegistryLayout { metadata, registry }.split_signature(input)
+            }
+            Source::V14(metadata) => {
+                let types = &metadata.types;
+
+                Ok(SignatureRecord {
+                    address: split(types, v14_param(metadata, "Address")?, input)?,
+                    signature: split(types, v14_param(metadata, "Signature")?, input)?,
+                    extra: metadata
+                        .extrinsic
+                        .signed_extensions
+                        .iter()
+                        .map(|ext| split(types, ext.ty, input))
+                        .collect::<Result<_>>()?,
+                })
+            }
+        }
+    }
+    fn split_call(&self, input: &mut &[u8]) -> Result<CallRecord> {
+        let metadata = match self.source {
+            Source::V13 { metadata, registry } => {
+                return RegistryLayout { metadata, registry }.split_call(input)
+            }
+            Source::V14(metadata) => metadata,
+        };
+
+        let [module_index, call_index] =
+            <[u8; 2]>::decode(input).map_err(|source| Error::DecodeType {
+                ty: "Call".to_string(),
+                source,
+            })?;
+
+        let (pallet, call) = v14_call(metadata, module_index, call_index)?;
+        let types = &metadata.types;
+
+        let mut args = Vec::with_capacity(call.fields.len());
+        for (position, field) in call.fields.iter().enumerate() {
+            args.push(CallArg {
+                name: field.name.clone().unwrap_or_else(|| position.to_string()),
+                ty: types.type_name(field.ty),
+                value: split(types, field.ty, input)?,
+            });
+        }
+
+        Ok(CallRecord {
+            module_index,
+            call_index,
+            module_name: pallet.name.clone(),
+            call_name: call.name.clone(),
+            args,
+        })
+    }
+}
+
+/// The pallet and the call variant of metadata V14 with the given indices.
+fn v14_call(
+    metadata: &MetadataV14,
+    module_index: u8,
+    call_index: u8,
+) -> Result<(&PalletMetadata, &Variant)> {
+    let unknown = || Error::UnknownCall {
+        module_index,
+        call_index,
+    };
+
+    let pallet = metadata
+        .pallets
+        .iter()
+        .find(|pallet| pallet.index == module_index)
+        .ok_or_else(unknown)?;
+
+    let call = pallet
+        .calls
+        .map(|ty| metadata.types.variants(ty))
+        .unwrap_or_default()
+        .iter()
+        .find(|call| call.index == call_index)
+        .ok_or_else(unknown)?;
+
+    Ok((pallet, call))
+}
+
+/// The type of the named generic parameter of the extrinsic type of metadata
+/// V14, such as `Address` or `Signature`.
+fn v14_param(metadata: &MetadataV14, name: &str) -> Result<TypeId> {
+    let types = &metadata.types;
+    types
+        .resolve(metadata.extrinsic.ty)
+        .and_then(|ty| ty.type_params.iter().find(|param| param.name == name))
+        .and_then(|param| param.ty)
+        .ok_or_else(|| Error::InvalidTypeDefinition(types.type_name(metadata.extrinsic.ty)))
+}
+
+/// Advances the input past the value of the type with the given Id, returning
+/// its encoded bytes.
+fn split(types: &PortableRegistry, id: TypeId, input: &mut &[u8]) -> Result<Vec<u8>> {
+    let start = *input;
+    types.decode_value(id, input)?;
+    Ok(start[..start.len() - input.len()].to_vec())
+}
+
+/// Decodes the encoded value, which must be consumed completely.
+fn decode_exact<F>(ty: &str, bytes: &[u8], decode: F) -> Result<Value>
+where
+    F: FnOnce(&mut &[u8]) -> Result<Value>,
+{
+    let input = &mut &*bytes;
+    let value = decode(input)?;
+    if !input.is_empty() {
+        return Err(Error::DecodeType {
+            ty: ty.to_string(),
+            source: "Trailing bytes after value".into(),
+        });
+    }
+
+    Ok(value)
+}
+
+impl PortableRegistry {
+    /// Decodes a value of the type with the given Id, advancing the input past
+    /// it.
+    pub fn decode_value(&self, id: TypeId, input: &mut &[u8]) -> Result<Value> {
+        self.decode_value_inner(id, input, 0)
+    }
+    fn decode_value_inner(&self, id: TypeId, input: &mut &[u8], depth: usize) -> Result<Value> {
+        if depth > MAX_DEPTH {
+            return Err(Error::InvalidTypeDefinition(self.type_name(id)));
+        }
+
+        let depth = depth + 1;
+        let ty = self
+            .resolve(id)
+            .ok_or_else(|| Error::UnknownType(self.type_name(id)))?;
+
+        match &ty.type_def {
+            TypeDef::Composite { fields } => self.decode_fields(fields, input, depth),
+            TypeDef::Variant { variants } => {
+                let index = self.decode::<u8>(id, input)?;
+                let variant = variants
+                    .iter()
+                    .find(|variant| variant.index == index)
+                    .ok_or_else(|| self.decode_err(id, "Invalid enum variant index"))?;
+
+                Ok(Value::variant(
+                    variant.name.clone(),
+                    self.decode_fields(&variant.fields, input, depth)?,
+                ))
+            }
+            TypeDef::Sequence { type_param } => {
+                let len = self.decode::<Compact<u32>>(id, input)?.0 as usize;
+                self.decode_elements(id, *type_param, len, input, depth)
+            }
+            TypeDef::Array { len, type_param } => {
+                self.decode_elements(id, *type_param, *len as usize, input, depth)
+            }
+            TypeDef::Tuple { fields } if fields.is_empty() => Ok(Value::Unit),
+            TypeDef::Tuple { fields } => fields
+                .iter()
+                .map(|field| self.decode_value_inner(*field, input, depth))
+                .collect::<Result<_>>()
+                .map(Value::Sequence),
+            TypeDef::Primitive(primitive) => self.decode_primitive(id, *primitive, input),
+            TypeDef::Compact { type_param } => {
+                let value = match self.compact_primitive(*type_param, depth) {
+                    Some(TypeDefPrimitive::U8) => self.decode::<Compact<u8>>(id, input)?.0.into(),
+                    Some(TypeDefPrimitive::U16) => self.decode::<Compact<u16>>(id, input)?.0.into(),
+                    Some(TypeDefPrimitive::U32) => self.decode::<Compact<u32>>(id, input)?.0.into(),
+                    Some(TypeDefPrimitive::U64) => self.decode::<Compact<u64>>(id, input)?.0.into(),
+                    Some(TypeDefPrimitive::U128) => {
+                        self.decode::<Compact<u128>>(id, input)?.0.into()
+                    }
+                    _ => return Err(Error::InvalidTypeDefinition(self.type_name(id))),
+                };
+
+                Ok(value)
+            }
+            TypeDef::BitSequence {
+                bit_store_type,
+                bit_order_type,
+            } => {
+                let store = self.bit_store(id, *bit_store_type, *bit_order_type)?;
+                let bits = self.decode::<Compact<u32>>(id, input)?.0 as usize;
+                let bytes = self.take(id, input, store.len(bits))?;
+
+                Ok(Value::Sequence(
+                    (0..bits)
+                        .map(|bit| {
+                            let (byte, mask) = store.position(bit);
+                            Value::Bool(bytes[byte] & mask != 0)
+                        })
+                        .collect(),
+                ))
+            }
+        }
+    }
+    fn decode_fields(&self, fields: &[Field], input: &mut &[u8], depth: usize) -> Result<Value> {
+        match fields {
+            [] => Ok(Value::Unit),
+            [field] if field.name.is_none() => self.decode_value_inner(field.ty, input, depth),
+            _ if fields.iter().all(|field| field.name.is_some()) => fields
+                .iter()
+                .map(|field| {
+                    Ok((
+                        field.name.clone().unwrap_or_default(),
+                        self.decode_value_inner(field.ty, input, depth)?,
+                    ))
+                })
+                .collect::<Result<_>>()
+                .map(Value::Composite),
+            _ => fields
+                .iter()
+                .map(|field| self.decode_value_inner(field.ty, input, depth))
+                .collect::<Result<_>>()
+                .map(Value::Sequence),
+        }
+    }
+    fn decode_elements(
+        &self,
+        id: TypeId,
+        elem: TypeId,
+        len: usize,
+        input: &mut &[u8],
+        depth: usize,
+    ) -> Result<Value> {
+        if let Some(TypeDef::Primitive(TypeDefPrimitive::U8)) =
+            self.resolve(elem).map(|ty| &ty.type_def)
+        {
+            return self
+                .take(id, input, len)
+                .map(|bytes| Value::Bytes(bytes.to_vec()));
+        }
+
+        (0..len)
+            .map(|_| self.decode_value_inner(elem, input, depth))
+            .collect::<Result<_>>()
+            .map(Value::Sequence)
+    }
+    fn decode_primitive(
+        &self,
+        id: TypeId,
+        primitive: TypeDefPrimitive,
+        input: &mut &[u8],
+    ) -> Result<Value> {
+        use TypeDefPrimitive::*;
+
+        match primitive {
+            Bool => self.decode::<bool>(id, input).map(Value::Bool),
+            Char => char::from_u32(self.decode::<u32>(id, input)?)
+                .map(|c| Value::Text(c.to_string()))
+                .ok_or_else(|| self.decode_err(id, "Invalid character")),
+            Str => String::from_utf8(self.decode::<Vec<u8>>(id, input)?)
+                .map(Value::Text)
+                .map_err(|_| self.decode_err(id, "Invalid UTF-8 string")),
+            U8 => self.decode::<u8>(id, input).map(Value::from),
+            U16 => self.decode::<u16>(id, input).map(Value::from),
+            U32 => self.decode::<u32>(id, input).map(Value::from),
+            U64 => self.decode::<u64>(id, input).map(Value::from),
+            U128 => self.decode::<u128>(id, input).map(Value::from),
+            I8 => self.decode::<i8>(id, input).map(Value::from),
+            I16 => self.decode::<i16>(id, input).map(Value::from),
+            I32 => self.decode::<i32>(id, input).map(Value::from),
+            I64 => self.decode::<i64>(id, input).map(Value::from),
+            I128 => self.decode::<i128>(id, input).map(Value::from),
+            // Exceeds the range of the integer values.
+            U256 | I256 => self
+                .take(id, input, 32)
+                .map(|bytes| Value::Bytes(bytes.to_vec())),
+        }
+    }
+    /// Encodes the value as the type with the given Id.
+    pub fn encode_value(&self, id: TypeId, value: &Value) -> Result<Vec<u8>> {
+        let mut out = vec![];
+        self.encode_value_inner(id, value, &mut out, 0)?;
+        Ok(out)
+    }
+    fn encode_value_inner(
+        &self,
+        id: TypeId,
+        value: &Value,
+        out: &mut Vec<u8>,
+        depth: usize,
+    ) -> Result<()> {
+        if depth > MAX_DEPTH {
+            return Err(Error::InvalidTypeDefinition(self.type_name(id)));
+        }
+
+        let depth = depth + 1;
+        let ty = self
+            .resolve(id)
+            .ok_or_else(|| Error::UnknownType(self.type_name(id)))?;
+
+        match &ty.type_def {
+            TypeDef::Composite { fields } => self.encode_fields(id, fields, value, out, depth)?,
+            TypeDef::Variant { variants } => {
+                let (name, data) = value
+                    .as_variant()
+                    .ok_or_else(|| self.invalid_value(id, "expected a variant"))?;
+                let variant = variants
+                    .iter()
+                    .find(|variant| same_name(&variant.name, name))
+                    .ok_or_else(|| self.invalid_value(id, format!("unknown variant {}", name)))?;
+
+                out.push(variant.index);
+                self.encode_fields(id, &variant.fields, data, out, depth)?;
+            }
+            TypeDef::Sequence { type_param } => {
+                if let (true, Value::Bytes(bytes)) = (self.is_byte(*type_param), value) {
+                    bytes.encode_to(out);
+                    return Ok(());
+                }
+
+                let values = self.expect_sequence(id, value, None)?;
+                Compact(values.len() as u32).encode_to(out);
+                values.iter().try_for_each(|value| {
+                    self.encode_value_inner(*type_param, value, out, depth)
+                })?;
+            }
+            TypeDef::Array { len, type_param } => {
+                if let (true, Value::Bytes(bytes)) = (self.is_byte(*type_param), value) {
+                    if bytes.len() != *len as usize {
+                        return Err(self.invalid_value(id, format!("expected {} bytes", len)));
+                    }
+
+                    out.extend(bytes);
+                    return Ok(());
+                }
+
+                self.expect_sequence(id, value, Some(*len as usize))?
+                    .iter()
+                    .try_for_each(|value| {
+                        self.encode_value_inner(*type_param, value, out, depth)
+                    })?;
+            }
+            TypeDef::Tuple { fields } if fields.is_empty() => {}
+            TypeDef::Tuple { fields } => fields
+                .iter()
+                .zip(self.expect_sequence(id, value, Some(fields.len()))?)
+                .try_for_each(|(field, value)| {
+                    self.encode_value_inner(*field, value, out, depth)
+                })?,
+            TypeDef::Primitive(primitive) => self.encode_primitive(id, *primitive, value, out)?,
+            TypeDef::Compact { type_param } => match self.compact_primitive(*type_param, depth) {
+                Some(TypeDefPrimitive::U8) => Compact(self.uint::<u8>(id, value)?).encode_to(out),
+                Some(TypeDefPrimitive::U16) => Compact(self.uint::<u16>(id, value)?).encode_to(out),
+                Some(TypeDefPrimitive::U32) => Compact(self.uint::<u32>(id, value)?).encode_to(out),
+                Some(TypeDefPrimitive::U64) => Compact(self.uint::<u64>(id, value)?).encode_to(out),
+                Some(TypeDefPrimitive::U128) => {
+                    Compact(self.uint::<u128>(id, value)?).encode_to(out)
+                }
+                _ => return Err(Error::InvalidTypeDefinition(self.type_name(id))),
+            },
+            TypeDef::BitSequence {
+                bit_store_type,
+                bit_order_type,
+            } => {
+                let store = self.bit_store(id, *bit_store_type, *bit_order_type)?;
+                let bits = self
+                    .expect_sequence(id, value, None)?
+                    .iter()
+                    .map(|bit| {
+                        bit.as_bool().ok_or_else(|| {
+                            self.invalid_value(id, "expected a sequence of booleans")
+                        })
+                    })
+                    .collect::<Result<Vec<bool>>>()?;
+
+                let mut bytes = vec![0u8; store.len(bits.len())];
+                for (bit, _) in bits.iter().enumerate().filter(|(_, set)| **set) {
+                    let (byte, mask) = store.position(bit);
+                    bytes[byte] |= mask;
+                }
+
+                Compact(bits.len() as u32).encode_to(out);
+                out.extend(bytes);
+            }
+        }
+
+        Ok(())
+    }
+    /// Encodes the fields of a struct or an enum variant, the inverse of
+    /// `decode_fields`. Named fields can also be passed in order.
+    fn encode_fields(
+        &self,
+        id: TypeId,
+        fields: &[Field],
+        value: &Value,
+        out: &mut Vec<u8>,
+        depth: usize,
+    ) -> Result<()> {
+        match (fields, value) {
+            ([], _) => Ok(()),
+            ([field], _) if field.name.is_none() => {
+                self.encode_value_inner(field.ty, value, out, depth)
+            }
+            (_, Value::Composite(values)) => fields.iter().try_for_each(|field| {
+                let name = field.name.as_deref().unwrap_or_default();
+                let value = values
+                    .iter()
+                    .find(|(key, _)| same_name(name, key))
+                    .map(|(_, value)| value)
+                    .ok_or_else(|| self.invalid_value(id, format!("missing field {}", name)))?;
+
+                self.encode_value_inner(field.ty, value, out, depth)
+            }),
+            _ => fields
+                .iter()
+                .zip(self.expect_sequence(id, value, Some(fields.len()))?)
+                .try_for_each(|(field, value)| {
+                    self.encode_value_inner(field.ty, value, out, depth)
+                }),
+        }
+    }
+    fn encode_primitive(
+        &self,
+        id: TypeId,
+        primitive: TypeDefPrimitive,
+        value: &Value,
+        out: &mut Vec<u8>,
+    ) -> Result<()> {
+        use TypeDefPrimitive::*;
+
+        match primitive {
+            Bool => value
+                .as_bool()
+                .ok_or_else(|| self.invalid_value(id, "expected a boolean"))?
+                .encode_to(out),
+            Char => {
+                let mut chars = value.as_str().unwrap_or_default().chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => (c as u32).encode_to(out),
+                    _ => return Err(self.invalid_value(id, "expected a single character")),
+                }
+            }
+            Str => value
+                .as_str()
+                .ok_or_else(|| self.invalid_value(id, "expected text"))?
+                .encode_to(out),
+            U8 => self.uint::<u8>(id, value)?.encode_to(out),
+            U16 => self.uint::<u16>(id, value)?.encode_to(out),
+            U32 => self.uint::<u32>(id, value)?.encode_to(out),
+            U64 => self.uint::<u64>(id, value)?.encode_to(out),
+            U128 => self.uint::<u128>(id, value)?.encode_to(out),
+            I8 => self.int::<i8>(id, value)?.encode_to(out),
+            I16 => self.int::<i16>(id, value)?.encode_to(out),
+            I32 => self.int::<i32>(id, value)?.encode_to(out),
+            I64 => self.int::<i64>(id, value)?.encode_to(out),
+            I128 => self.int::<i128>(id, value)?.encode_to(out),
+            U256 | I256 => match value.as_bytes() {
+                Some(bytes) if bytes.len() == 32 => out.extend(bytes),
+                _ => return Err(self.invalid_value(id, "expected 32 bytes")),
+            },
+        }
+
+        Ok(())
+    }
+    fn is_byte(&self, id: TypeId) -> bool {
+        matches!(
+            self.resolve(id).map(|ty| &ty.type_def),
+            Some(TypeDef::Primitive(TypeDefPrimitive::U8))
+        )
+    }
+    /// The layout of a bit sequence with the given store and order types.
+    fn bit_store(&self, id: TypeId, store: TypeId, order: TypeId) -> Result<BitStore> {
+        let word_bits = match self.resolve(store).map(|ty| &ty.type_def) {
+            Some(TypeDef::Primitive(TypeDefPrimitive::U8)) => 8,
+            Some(TypeDef::Primitive(TypeDefPrimitive::U16)) => 16,
+            Some(TypeDef::Primitive(TypeDefPrimitive::U32)) => 32,
+            Some(TypeDef::Primitive(TypeDefPrimitive::U64)) => 64,
+            _ => return Err(Error::InvalidTypeDefinition(self.type_name(id))),
+        };
+
+        Ok(BitStore {
+            word_bits,
+            msb_first: self
+                .resolve(order)
+                .and_then(|ty| ty.path.last())
+                .map(|name| name == "Msb0")
+                .unwrap_or(false),
+        })
+    }
+    /// The unsigned integer which is compact encoded, unwrapping tuple structs
+    /// with a single field such as `Perbill(u32)`.
+    fn compact_primitive(&self, id: TypeId, depth: usize) -> Option<TypeDefPrimitive> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+
+        match &self.resolve(id)?.type_def {
+            TypeDef::Primitive(primitive) => Some(*primitive),
+            TypeDef::Composite { fields } if fields.len() == 1 => {
+                self.compact_primitive(fields[0].ty, depth + 1)
+            }
+            _ => None,
+        }
+    }
+    fn decode<T: Decode>(&self, id: TypeId, input: &mut &[u8]) -> Result<T> {
+        T::decode(input).map_err(|source| Error::DecodeType {
+            ty: self.type_name(id),
+            source,
+        })
+    }
+    fn take<'b>(&self, id: TypeId, input: &mut &'b [u8], len: usize) -> Result<&'b [u8]> {
+        if input.len() < len {
+            return Err(self.decode_err(id, "Not enough data to decode value"));
+        }
+
+        let (bytes, rest) = input.split_at(len);
+        *input = rest;
+        Ok(bytes)
+    }
+    fn decode_err(&self, id: TypeId, reason: &'static str) -> Error {
+        Error::DecodeType {
+            ty: self.type_name(id),
+            source: reason.into(),
+        }
+    }
+    fn invalid_value<T: Into<String>>(&self, id: TypeId, reason: T) -> Error {
+        Error::InvalidValue {
+            ty: self.type_name(id),
+            reason: reason.into(),
+        }
+    }
+    fn uint<T: TryFrom<u128>>(&self, id: TypeId, value: &Value) -> Result<T> {
+        let n = value
+            .as_u128()
+            .ok_or_else(|| self.invalid_value(id, "expected an unsigned integer"))?;
+
+        T::try_from(n).map_err(|_| self.invalid_value(id, "integer out of range"))
+    }
+    fn int<T: TryFrom<i128>>(&self, id: TypeId, value: &Value) -> Result<T> {
+        let n = value
+            .as_i128()
+            .ok_or_else(|| self.invalid_value(id, "expected an integer"))?;
+
+        T::try_from(n).map_err(|_| self.invalid_value(id, "integer out of range"))
+    }
+    fn expect_sequence<'b>(
+        &self,
+        id: TypeId,
+        value: &'b Value,
+        len: Option<usize>,
+    ) -> Result<&'b [Value]> {
+        let values = value
+            .as_sequence()
+            .ok_or_else(|| self.invalid_value(id, "expected a sequence"))?;
+
+        match len {
+            Some(len) if values.len() != len => {
+                Err(self.invalid_value(id, format!("expected {} elements", len)))
+            }
+            _ => Ok(values),
+        }
+    }
+}
+
+/// The layout of a `BitVec`, whose bits are stored in words of the store type.
+/// Each word is encoded in little endian.
+struct BitStore {
+    word_bits: usize,
+    /// Whether the most significant bit of a word is the first bit.
+    msb_first: bool,
+}
+
+impl BitStore {
+    /// The number of bytes of the words which store the bits.
+    fn len(&self, bits: usize) -> usize {
+        bits.div_ceil(self.word_bits) * self.word_bits / 8
+    }
+    /// The byte and mask of the bit with the given index.
+    fn position(&self, index: usize) -> (usize, u8) {
+        let (word, offset) = (index / self.word_bits, index % self.word_bits);
+        let offset = if self.msb_first {
+            self.word_bits - 1 - offset
+        } else {
+            offset
+        };
+
+        (word * self.word_bits / 8 + offset / 8, 1 << (offset % 8))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::v14::{ExtrinsicMetadata, PortableType, Type};
+
+    /// Metadata V14 with a single `Balances` pallet at index 10, whose
+    /// `transfer` call has index 3.
+    fn metadata() -> MetadataV14 {
+        let ty = |path: &[&str], type_def| Type {
+            path: path.iter().map(|s| s.to_string()).collect(),
+            type_params: vec![],
+            type_def,
+            docs: vec![],
+        };
+        let field = |name: Option<&str>, id| Field {
+            name: name.map(String::from),
+            ty: TypeId(id),
+            type_name: None,
+            docs: vec![],
+        };
+
+        let types = vec![
+            ty(&[], TypeDef::Primitive(TypeDefPrimitive::U8)),
+            ty(
+                &[],
+                TypeDef::Array {
+                    len: 32,
+                    type_param: TypeId(0),
+                },
+            ),
+            ty(
+                &["sp_core", "crypto", "AccountId32"],
+                TypeDef::Composite {
+                    fields: vec![field(None, 1)],
+                },
+            ),
+            ty(&[], TypeDef::Primitive(TypeDefPrimitive::U128)),
+            ty(
+                &[],
+                TypeDef::Compact {
+                    type_param: TypeId(3),
+                },
+            ),
+            ty(
+                &["pallet_balances", "pallet", "Call"],
+                TypeDef::Variant {
+                    variants: vec![Variant {
+                        name: "transfer".to_string(),
+                        fields: vec![field(Some("dest"), 2), field(Some("value"), 4)],
+                        index: 3,
+                        docs: vec![],
+                    }],
+                },
+            ),
+        ];
+
+        MetadataV14 {
+            types: PortableRegistry {
+                types: types
+                    .into_iter()
+                    .enumerate()
+                    .map(|(id, ty)| PortableType { id: id as u32, ty })
+                    .collect(),
+            },
+            pallets: vec![PalletMetadata {
+                name: "Balances".to_string(),
+                storage: None,
+                calls: Some(TypeId(5)),
+                event: None,
+                constants: vec![],
+                error: None,
+                index: 10,
+            }],
+            extrinsic: ExtrinsicMetadata {
+                ty: TypeId(0),
+                version: 4,
+                signed_extensions: vec![],
+            },
+            ty: TypeId(0),
+        }
+    }
+
+    /// `Balances::transfer` of 1000 to the account `[7; 32]`.
+    fn transfer() -> Vec<u8> {
+        [&[10u8, 3][..], &[7; 32], &Compact(1_000u128).encode()].concat()
+    }
+
+    /// A length-prefixed, unsigned extrinsic of the given version.
+    fn unsigned(version: u8, call: &[u8]) -> Vec<u8> {
+        let mut extrinsic = Compact(call.len() as u32 + 1).encode();
+        extrinsic.push(version);
+        extrinsic.extend(call);
+        extrinsic
+    }
+
+    #[test]
+    fn decode_extrinsic_v14() {
+        let metadata = metadata();
+        let decoder = ExtrinsicDecoder::v14(&metadata);
+
+        let extrinsic = decoder.decode_extrinsic(&unsigned(4, &transfer())).unwrap();
+
+        assert!(!extrinsic.is_signed());
+        assert!(extrinsic.call.is("Balances", "transfer"));
+        assert_eq!(
+            extrinsic.call.args,
+            vec![
+                ("dest".to_string(), Value::Bytes(vec![7; 32])),
+                ("value".to_string(), Value::UInt(1_000)),
+            ]
+        );
+
+        // Encoding the decoded call results in the same bytes.
+        let encoded = DynamicCall::from(extrinsic.call).encode(&decoder).unwrap();
+        assert_eq!(encoded, transfer());
+    }
+
+    #[test]
+    fn decode_truncated_extrinsic() {
+        let metadata = metadata();
+        let decoder = ExtrinsicDecoder::v14(&metadata);
+
+        // The length prefix exceeds the data.
+        let mut extrinsic = unsigned(4, &transfer());
+        extrinsic.pop();
+        assert!(matches!(
+            decoder.decode_extrinsic(&extrinsic),
+            Err(Error::DecodeType { .. })
+        ));
+
+        // The call ends within the account of the first argument.
+        let call = &transfer()[..20];
+        assert!(matches!(
+            decoder.decode_extrinsic(&unsigned(4, call)),
+            Err(Error::DecodeType { .. })
+        ));
+        assert!(matches!(
+            decoder.decode_call(&mut &[10u8][..]),
+            Err(Error::DecodeType { .. })
+        ));
+    }
+
+    #[test]
+    fn decode_unknown_call() {
+        let metadata = metadata();
+        let decoder = ExtrinsicDecoder::v14(&metadata);
+
+        let mut call = transfer();
+        call[1] = 4;
+        assert!(matches!(
+            decoder.decode_call(&mut call.as_slice()),
+            Err(Error::UnknownCall {
+                module_index: 10,
+                call_index: 4
+            })
+        ));
+
+        call[0] = 11;
+        call[1] = 3;
+        assert!(matches!(
+            decoder.decode_extrinsic(&unsigned(4, &call)),
+            Err(Error::UnknownCall {
+                module_index: 11,
+                call_index: 3
+            })
+        ));
+
+        assert!(matches!(
+            DynamicCall::new("Balances", "unknown").encode(&decoder),
+            Err(Error::UnknownCallName { .. })
+        ));
+    }
+
+    #[test]
+    fn decode_missing_argument() {
+        let metadata = metadata();
+        let decoder = ExtrinsicDecoder::v14(&metadata);
+
+        // The call lacks the `value` argument.
+        let call = &transfer()[..34];
+        assert!(matches!(
+            decoder.decode_extrinsic(&unsigned(4, call)),
+            Err(Error::DecodeType { .. })
+        ));
+
+        let err = DynamicCall::new("Balances", "transfer")
+            .arg("dest", Value::Bytes(vec![7; 32]))
+            .encode(&decoder)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InvalidValue { ref reason, .. } if reason == "missing argument value"
+        ));
+    }
+
+    #[test]
+    fn decode_unsupported_extrinsic_version() {
+        let metadata = metadata();
+        let decoder = ExtrinsicDecoder::v14(&metadata);
+
+        assert!(matches!(
+            decoder.decode_extrinsic(&unsigned(3, &transfer())),
+            Err(Error::UnsupportedExtrinsicVersion(3))
+        ));
+        // Signed extrinsics of another version.
+        assert!(matches!(
+            decoder.decode_extrinsic(&unsigned(0b1000_0101, &transfer())),
+            Err(Error::UnsupportedExtrinsicVersion(5))
+        ));
+    }
+}
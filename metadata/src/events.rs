@@ -0,0 +1,123 @@
+//! Decoding of the events of a block, as stored in `System::Events`.
+
+use crate::scale::TypeRegistry;
+use crate::version::MetadataV13;
+use crate::{Error, Result};
+use parity_scale_codec::{Compact, Decode, DecodeAll};
+
+/// The phase of the block in which an event was emitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Phase {
+    /// The event was emitted while applying the extrinsic at the given index.
+    ApplyExtrinsic(u32),
+    /// The event was emitted during the finalization of the block.
+    Finalization,
+    /// The event was emitted during the initialization of the block.
+    Initialization,
+}
+
+/// An individual event of a block, with its arguments still SCALE encoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EventRecord {
+    /// The phase in which the event was emitted.
+    pub phase: Phase,
+    /// The index of the module, as declared by the runtime.
+    pub module_index: u8,
+    /// The index of the event within the module.
+    pub event_index: u8,
+    /// The name of the module.
+    pub module_name: String,
+    /// The name of the event.
+    pub event_name: String,
+    /// The SCALE encoded arguments, one for each argument type of the event.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::vec"))]
+    pub args: Vec<Vec<u8>>,
+    /// The topics of the event.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::vec"))]
+    pub topics: Vec<[u8; 32]>,
+}
+
+impl EventRecord {
+    /// Whether this is the event of the given module and name.
+    pub fn is(&self, module: &str, event: &str) -> bool {
+        self.module_name == module && self.event_name == event
+    }
+    /// Decodes all arguments of the event into a single type, such as a tuple
+    /// or a struct with the same field order. All bytes must be consumed.
+    pub fn decode_args<T: Decode>(&self) -> Result<T> {
+        T::decode_all(&self.args.concat()).map_err(|source| Error::DecodeType {
+            ty: format!("{}::{}", self.module_name, self.event_name),
+            source,
+        })
+    }
+}
+
+/// Decodes the value of `System::Events`. The arguments of each event are
+/// split according to the type descriptions of the metadata, using the type
+/// definitions of the registry.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(len = data.len()), err)
+)]
+pub fn decode_events(
+    metadata: &MetadataV13,
+    registry: &TypeRegistry,
+    data: &[u8],
+) -> Result<Vec<EventRecord>> {
+    let input = &mut &*data;
+    let decode_err = |source| Error::DecodeType {
+        ty: "EventRecord".to_string(),
+        source,
+    };
+
+    let count = Compact::<u32>::decode(input).map_err(decode_err)?.0;
+    // Every record requires at least four bytes.
+    let mut records = Vec::with_capacity((count as usize).min(data.len() / 4));
+
+    for _ in 0..count {
+        let phase = Phase::decode(input).map_err(decode_err)?;
+        let [module_index, event_index] = <[u8; 2]>::decode(input).map_err(decode_err)?;
+
+        let module = metadata
+            .modules
+            .iter()
+            .find(|module| module.index == module_index)
+            .ok_or(Error::UnknownEvent {
+                module_index,
+                event_index,
+            })?;
+
+        let event = module
+            .events
+            .iter()
+            .flatten()
+            .nth(event_index as usize)
+            .ok_or(Error::UnknownEvent {
+                module_index,
+                event_index,
+            })?;
+
+        let mut args = Vec::with_capacity(event.arguments.len());
+        for ty in &event.arguments {
+            let start = *input;
+            registry.skip(ty, input)?;
+            args.push(start[..start.len() - input.len()].to_vec());
+        }
+
+        let topics = Vec::<[u8; 32]>::decode(input).map_err(decode_err)?;
+
+        records.push(EventRecord {
+            phase,
+            module_index,
+            event_index,
+            module_name: module.name.clone(),
+            event_name: event.name.clone(),
+            args,
+            topics,
+        });
+    }
+
+    Ok(records)
+}
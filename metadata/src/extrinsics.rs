@@ -0,0 +1,668 @@
+//! Decoding of the extrinsics of a block, as returned by `chain_getBlock`.
+
+use crate::scale::json::{call_args, call_json, call_names, camel_case, same_name, unknown_call};
+use crate::scale::{Type, TypeRegistry};
+use crate::version::MetadataV13;
+use crate::{Error, Result};
+use parity_scale_codec::{Compact, Decode, DecodeAll};
+use serde_json::{Map, Value};
+
+/// The only supported extrinsic format version.
+const EXTRINSIC_VERSION: u8 = 4;
+
+/// An individual extrinsic of a block, with its arguments still SCALE encoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExtrinsicRecord {
+    /// The signature, or `None` if the extrinsic is unsigned, such as
+    /// inherents.
+    pub signature: Option<SignatureRecord>,
+    /// The dispatched call.
+    pub call: CallRecord,
+}
+
+impl ExtrinsicRecord {
+    /// Whether the extrinsic is signed.
+    pub fn is_signed(&self) -> bool {
+        self.signature.is_some()
+    }
+}
+
+/// The signature of a signed extrinsic, with its parts still SCALE encoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SignatureRecord {
+    /// The encoded address of the signer, usually a `MultiAddress`.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
+    pub address: Vec<u8>,
+    /// The encoded signature, usually a `MultiSignature`.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
+    pub signature: Vec<u8>,
+    /// The encoded extra data of the signed extensions, such as the era and
+    /// the nonce, in the order of the signed extensions of the metadata.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::vec"))]
+    pub extra: Vec<Vec<u8>>,
+}
+
+/// A call of a module, with its arguments still SCALE encoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CallRecord {
+    /// The index of the module, as declared by the runtime.
+    pub module_index: u8,
+    /// The index of the call within the module.
+    pub call_index: u8,
+    /// The name of the module.
+    pub module_name: String,
+    /// The name of the call.
+    pub call_name: String,
+    /// The arguments of the call, in order.
+    pub args: Vec<CallArg>,
+}
+
+impl CallRecord {
+    /// Whether this is the call of the given module and name.
+    pub fn is(&self, module: &str, call: &str) -> bool {
+        self.module_name == module && self.call_name == call
+    }
+    /// Returns the argument with the given name, if it exists.
+    pub fn arg(&self, name: &str) -> Option<&CallArg> {
+        self.args.iter().find(|arg| arg.name == name)
+    }
+    /// Encodes the call, i.e. the module and call index followed by the
+    /// encoded arguments.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoded = vec![self.module_index, self.call_index];
+        for arg in &self.args {
+            encoded.extend(&arg.value);
+        }
+
+        encoded
+    }
+    /// Converts the call into the JSON representation used by polkadot-js,
+    /// e.g. `{ "section": "balances", "method": "transferKeepAlive", "args": {
+    /// "dest": { "id": "0x..." }, "value": 1000 } }`. The arguments are decoded
+    /// with the type definitions of the registry, see
+    /// [`scale::json`](crate::scale::json) for the format.
+    pub fn to_polkadot_js(&self, registry: &TypeRegistry) -> Result<Value> {
+        let mut args = Map::new();
+        for arg in &self.args {
+            let input = &mut arg.value.as_slice();
+            let value = registry.decode_json(&arg.ty, input)?;
+            if !input.is_empty() {
+                return Err(Error::DecodeType {
+                    ty: arg.ty.clone(),
+                    source: "Trailing bytes after argument".into(),
+                });
+            }
+
+            args.insert(camel_case(&arg.name), value);
+        }
+
+        Ok(call_json(&self.module_name, &self.call_name, args))
+    }
+    /// Creates the call from the JSON representation used by polkadot-js, the
+    /// inverse of [`to_polkadot_js`](Self::to_polkadot_js). Names are matched
+    /// regardless of whether they are camelCase or snake_case, and the
+    /// arguments can be either an object or an array in argument order.
+    pub fn from_polkadot_js(
+        metadata: &MetadataV13,
+        registry: &TypeRegistry,
+        json: &Value,
+    ) -> Result<Self> {
+        let (section, method) = call_names(&Type::named("Call"), json)?;
+
+        let module = metadata
+            .modules
+            .iter()
+            .find(|module| same_name(&module.name, section))
+            .ok_or_else(|| unknown_call(section, method))?;
+
+        let (call_index, call) = module
+            .calls
+            .iter()
+            .flatten()
+            .enumerate()
+            .find(|(_, call)| same_name(&call.name, method))
+            .ok_or_else(|| unknown_call(section, method))?;
+
+        let json_args = call_args(json);
+        let mut args = Vec::with_capacity(call.arguments.len());
+        for (index, arg) in call.arguments.iter().enumerate() {
+            let value = match json_args {
+                Value::Array(values) => values.get(index),
+                Value::Object(object) => object
+                    .iter()
+                    .find(|(key, _)| same_name(&arg.name, key))
+                    .map(|(_, value)| value),
+                _ => None,
+            }
+            .ok_or_else(|| Error::InvalidValue {
+                ty: format!("{}::{}", module.name, call.name),
+                reason: format!("missing argument {}", arg.name),
+            })?;
+
+            args.push(CallArg {
+                name: arg.name.clone(),
+                ty: arg.ty.clone(),
+                value: registry.encode_json(&arg.ty, value)?,
+            });
+        }
+
+        Ok(CallRecord {
+            module_index: module.index,
+            call_index: call_index as u8,
+            module_name: module.name.clone(),
+            call_name: call.name.clone(),
+            args,
+        })
+    }
+}
+
+/// An individual argument of a call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CallArg {
+    /// The name of the argument.
+    pub name: String,
+    /// The type description of the argument, as provided by the metadata up
+    /// to V13, or the name of the type for metadata V14.
+    pub ty: String,
+    /// The SCALE encoded value.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
+    pub value: Vec<u8>,
+}
+
+impl CallArg {
+    /// Decodes the value of the argument. All bytes must be consumed.
+    pub fn decode<T: Decode>(&self) -> Result<T> {
+        T::decode_all(&self.value).map_err(|source| Error::DecodeType {
+            ty: self.ty.clone(),
+            source,
+        })
+    }
+}
+
+/// Decodes a length-prefixed extrinsic, as contained in the extrinsics of a
+/// block. The address, signature and signed extensions are split according to
+/// the `Address`, `ExtrinsicSignature` and signed extension types of the
+/// registry.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(len = data.len()), err)
+)]
+pub fn decode_extrinsic(
+    metadata: &MetadataV13,
+    registry: &TypeRegistry,
+    data: &[u8],
+) -> Result<ExtrinsicRecord> {
+    split_extrinsic(&RegistryLayout { metadata, registry }, data)
+}
+
+/// Splits the parts of an extrinsic according to their types, as described by
+/// the metadata.
+pub(crate) trait ExtrinsicLayout {
+    /// Splits the address, the signature and the extra data of the signed
+    /// extensions, advancing the input past them.
+    fn split_signature(&self, input: &mut &[u8]) -> Result<SignatureRecord>;
+    /// Splits the arguments of a call, advancing the input past it.
+    fn split_call(&self, input: &mut &[u8]) -> Result<CallRecord>;
+}
+
+/// The layout of extrinsics of metadata V13, whose types are defined by the
+/// registry.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RegistryLayout<'a> {
+    pub metadata: &'a MetadataV13,
+    pub registry: &'a TypeRegistry,
+}
+
+impl ExtrinsicLayout for RegistryLayout<'_> {
+    fn split_signature(&self, input: &mut &[u8]) -> Result<SignatureRecord> {
+        Ok(SignatureRecord {
+            address: split(self.registry, "Address", input)?,
+            signature: split(self.registry, "ExtrinsicSignature", input)?,
+            extra: self
+                .metadata
+                .extrinsics
+                .signed_extensions
+                .iter()
+                .map(|ext| split(self.registry, ext, input))
+                .collect::<Result<_>>()?,
+        })
+    }
+    fn split_call(&self, input: &mut &[u8]) -> Result<CallRecord> {
+        decode_call(self.metadata, self.registry, input)
+    }
+}
+
+/// Splits a length-prefixed extrinsic into its signature, if signed, and its
+/// call.
+pub(crate) fn split_extrinsic<L: ExtrinsicLayout>(
+    layout: &L,
+    data: &[u8],
+) -> Result<ExtrinsicRecord> {
+    let (signed, mut input) = decode_header(data)?;
+    let input = &mut input;
+
+    let signature = if signed {
+        Some(layout.split_signature(input)?)
+    } else {
+        None
+    };
+
+    let call = layout.split_call(input)?;
+    if !input.is_empty() {
+        return Err(trailing_bytes());
+    }
+
+    Ok(ExtrinsicRecord { signature, call })
+}
+
+/// Checks the length prefix and the version of an extrinsic, returning whether
+/// the extrinsic is signed and the input following the version.
+fn decode_header(data: &[u8]) -> Result<(bool, &[u8])> {
+    let input = &mut &*data;
+    let decode_err = |source| Error::DecodeType {
+        ty: "Extrinsic".to_string(),
+        source,
+    };
+
+    let len = Compact::<u32>::decode(input).map_err(decode_err)?.0 as usize;
+    if input.len() != len {
+        return Err(decode_err("Invalid length prefix of extrinsic".into()));
+    }
+
+    let version = u8::decode(input).map_err(decode_err)?;
+    if version & 0b0111_1111 != EXTRINSIC_VERSION {
+        return Err(Error::UnsupportedExtrinsicVersion(version & 0b0111_1111));
+    }
+
+    // The most significant bit indicates whether the extrinsic is signed.
+    Ok((version & 0b1000_0000 != 0, input))
+}
+
+fn trailing_bytes() -> Error {
+    Error::DecodeType {
+        ty: "Extrinsic".to_string(),
+        source: "Trailing bytes after extrinsic".into(),
+    }
+}
+
+/// An iterator which decodes extrinsics one at a time, such as the hex decoded
+/// `extrinsics` array of `chain_getBlock`. Each item must be a length-prefixed
+/// extrinsic, see [`decode_extrinsic`].
+///
+/// Only the extrinsic currently being decoded is held in memory, which keeps
+/// the memory usage low when processing large blocks or long ranges of blocks.
+pub struct ExtrinsicIter<'a, I> {
+    metadata: &'a MetadataV13,
+    registry: &'a TypeRegistry,
+    extrinsics: I,
+}
+
+impl<'a, I> ExtrinsicIter<'a, I> {
+    /// Creates the iterator over the individual length-prefixed extrinsics.
+    pub fn new<T>(metadata: &'a MetadataV13, registry: &'a TypeRegistry, extrinsics: T) -> Self
+    where
+        T: IntoIterator<IntoIter = I>,
+    {
+        ExtrinsicIter {
+            metadata,
+            registry,
+            extrinsics: extrinsics.into_iter(),
+        }
+    }
+}
+
+impl<'a, I> Iterator for ExtrinsicIter<'a, I>
+where
+    I: Iterator,
+    I::Item: AsRef<[u8]>,
+{
+    type Item = Result<ExtrinsicRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let extrinsic = self.extrinsics.next()?;
+        Some(decode_extrinsic(
+            self.metadata,
+            self.registry,
+            extrinsic.as_ref(),
+        ))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.extrinsics.size_hint()
+    }
+}
+
+/// Splits a SCALE encoded block body into its length-prefixed extrinsics,
+/// without decoding them.
+///
+/// Bytes which cannot be split, such as an invalid length prefix or trailing
+/// bytes after the last extrinsic, are returned as a final item, which fails
+/// to decode as an extrinsic.
+#[derive(Debug, Clone)]
+pub struct BodySplitter<'a> {
+    remaining: usize,
+    input: &'a [u8],
+}
+
+impl<'a> Iterator for BodySplitter<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.input.is_empty() && self.remaining == 0 {
+            return None;
+        }
+
+        let rest = &mut &*self.input;
+        let end = match Compact::<u32>::decode(rest) {
+            Ok(len) if self.remaining > 0 && rest.len() >= len.0 as usize => {
+                self.remaining -= 1;
+                self.input.len() - rest.len() + len.0 as usize
+            }
+            _ => {
+                self.remaining = 0;
+                self.input.len()
+            }
+        };
+
+        let (extrinsic, input) = self.input.split_at(end);
+        self.input = input;
+        Some(extrinsic)
+    }
+}
+
+/// Decodes the extrinsics of a SCALE encoded block body, i.e. the
+/// length-prefixed extrinsics preceded by their compact encoded count. The
+/// extrinsics are decoded lazily, one at a time.
+pub fn decode_extrinsics<'a>(
+    metadata: &'a MetadataV13,
+    registry: &'a TypeRegistry,
+    body: &'a [u8],
+) -> Result<ExtrinsicIter<'a, BodySplitter<'a>>> {
+    Ok(ExtrinsicIter::new(metadata, registry, split_body(body)?))
+}
+
+/// Splits a SCALE encoded block body into its length-prefixed extrinsics.
+pub(crate) fn split_body(body: &[u8]) -> Result<BodySplitter<'_>> {
+    let mut input = body;
+    let count = Compact::<u32>::decode(&mut input).map_err(|source| Error::DecodeType {
+        ty: "Vec<Extrinsic>".to_string(),
+        source,
+    })?;
+
+    Ok(BodySplitter {
+        remaining: count.0 as usize,
+        input,
+    })
+}
+
+/// Decodes a call, advancing the input past it. The arguments are split
+/// according to the type descriptions of the metadata, using the type
+/// definitions of the registry.
+pub fn decode_call(
+    metadata: &MetadataV13,
+    registry: &TypeRegistry,
+    input: &mut &[u8],
+) -> Result<CallRecord> {
+    let [module_index, call_index] =
+        <[u8; 2]>::decode(input).map_err(|source| Error::DecodeType {
+            ty: "Call".to_string(),
+            source,
+        })?;
+
+    let unknown = || Error::UnknownCall {
+        module_index,
+        call_index,
+    };
+
+    let module = metadata
+        .modules
+        .iter()
+        .find(|module| module.index == module_index)
+        .ok_or_else(unknown)?;
+
+    let call = module
+        .calls
+        .iter()
+        .flatten()
+        .nth(call_index as usize)
+        .ok_or_else(unknown)?;
+
+    let mut args = Vec::with_capacity(call.arguments.len());
+    for arg in &call.arguments {
+        args.push(CallArg {
+            name: arg.name.clone(),
+            ty: arg.ty.clone(),
+            value: split(registry, &arg.ty, input)?,
+        });
+    }
+
+    Ok(CallRecord {
+        module_index,
+        call_index,
+        module_name: module.name.clone(),
+        call_name: call.name.clone(),
+        args,
+    })
+}
+
+/// Advances the input past the value of the described type, returning its
+/// encoded bytes.
+fn split(registry: &TypeRegistry, ty: &str, input: &mut &[u8]) -> Result<Vec<u8>> {
+    let start = *input;
+    registry.skip(ty, input)?;
+    Ok(start[..start.len() - input.len()].to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dynamic::ExtrinsicDecoder;
+    use crate::parse_hex_metadata;
+    use parity_scale_codec::Encode;
+    use serde_json::json;
+
+    fn metadata() -> (MetadataV13, TypeRegistry) {
+        let metadata = parse_hex_metadata(
+            include_str!("../../interface/dumps/metadata_kusama_9080.hex").trim(),
+        )
+        .unwrap()
+        .into_latest()
+        .unwrap();
+
+        let mut registry = TypeRegistry::default();
+        registry.register_calls(&metadata).unwrap();
+
+        (metadata, registry)
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        format!("0x{}", hex::encode(bytes))
+    }
+
+    /// A signed extrinsic of `Sr25519` signer `[1; 32]` with a mortal era, a
+    /// nonce of 7 and no tip.
+    fn signed(call: &[u8]) -> Vec<u8> {
+        let mut extrinsic = vec![0x84, 0];
+        extrinsic.extend([1; 32]);
+        extrinsic.push(1);
+        extrinsic.extend([2; 64]);
+        extrinsic.extend([0xb5, 0x03, 28, 0]);
+        extrinsic.extend(call);
+        extrinsic.encode()
+    }
+
+    /// Decodes the extrinsic and converts its call to the JSON of
+    /// polkadot-js, both from the record and from the dynamic value, and
+    /// back to the same encoding.
+    fn round_trip(metadata: &MetadataV13, registry: &TypeRegistry, raw: &[u8], expected: Value) {
+        let record = decode_extrinsic(metadata, registry, raw).unwrap();
+        let json = record.call.to_polkadot_js(registry).unwrap();
+        assert_eq!(json, expected);
+
+        let call = CallRecord::from_polkadot_js(metadata, registry, &json).unwrap();
+        assert_eq!(call, record.call);
+        assert!(raw.ends_with(&call.encode()));
+
+        let decoded = ExtrinsicDecoder::v13(metadata, registry)
+            .decode_extrinsic(raw)
+            .unwrap();
+        assert_eq!(decoded.is_signed(), record.is_signed());
+
+        let value = decoded.call.into_value();
+        assert_eq!(registry.value_to_json("Call", &value).unwrap(), expected);
+        assert_eq!(registry.json_to_value("Call", &expected).unwrap(), value);
+        assert_eq!(
+            registry.encode_json("Call", &expected).unwrap(),
+            call.encode()
+        );
+    }
+
+    #[test]
+    fn transfer_keep_alive_to_polkadot_js() {
+        let (metadata, registry) = metadata();
+
+        let call = [
+            vec![4, 3, 0],
+            vec![3; 32],
+            Compact(1_000_000_000_000u128).encode(),
+        ]
+        .concat();
+        let raw = signed(&call);
+
+        let record = decode_extrinsic(&metadata, &registry, &raw).unwrap();
+        assert!(record.call.is("Balances", "transfer_keep_alive"));
+        assert_eq!(record.signature.unwrap().extra.len(), 7);
+
+        round_trip(
+            &metadata,
+            &registry,
+            &raw,
+            json!({
+                "section": "balances",
+                "method": "transferKeepAlive",
+                "args": {
+                    "dest": { "id": hex(&[3; 32]) },
+                    "value": 1_000_000_000_000u64
+                }
+            }),
+        );
+    }
+
+    #[test]
+    fn timestamp_to_polkadot_js() {
+        let (metadata, registry) = metadata();
+
+        // An unsigned inherent.
+        let raw = [vec![4, 2, 0], Compact(1_630_000_000_000u64).encode()]
+            .concat()
+            .encode();
+
+        round_trip(
+            &metadata,
+            &registry,
+            &raw,
+            json!({
+                "section": "timestamp",
+                "method": "set",
+                "args": { "now": 1_630_000_000_000u64 }
+            }),
+        );
+    }
+
+    #[test]
+    fn batch_to_polkadot_js() {
+        let (metadata, registry) = metadata();
+
+        let bond = [
+            vec![6, 0, 0],
+            vec![5; 32],
+            Compact(10_000_000_000_000u128).encode(),
+            vec![0],
+        ]
+        .concat();
+        let remark = [vec![0, 1], b"hello".to_vec().encode()].concat();
+        let call = [vec![24, 0, 8], bond, remark].concat();
+        let raw = signed(&call);
+
+        round_trip(
+            &metadata,
+            &registry,
+            &raw,
+            json!({
+                "section": "utility",
+                "method": "batch",
+                "args": {
+                    "calls": [
+                        {
+                            "section": "staking",
+                            "method": "bond",
+                            "args": {
+                                "controller": { "id": hex(&[5; 32]) },
+                                "value": 10_000_000_000_000u64,
+                                "payee": { "staked": null }
+                            }
+                        },
+                        {
+                            "section": "system",
+                            "method": "remark",
+                            "args": { "remark": hex(b"hello") }
+                        }
+                    ]
+                }
+            }),
+        );
+
+        // Arguments can also be given in order and with snake_case names.
+        let call = CallRecord::from_polkadot_js(
+            &metadata,
+            &registry,
+            &json!({
+                "section": "system",
+                "method": "remark_with_event",
+                "args": [hex(b"hello")]
+            }),
+        )
+        .unwrap();
+        assert!(call.is("System", "remark_with_event"));
+    }
+
+    #[test]
+    fn decode_body_with_both_decoders() {
+        let (metadata, registry) = metadata();
+
+        let timestamp = [vec![4, 2, 0], Compact(1_630_000_000_000u64).encode()].concat();
+        let transfer = [vec![4, 3, 0], vec![3; 32], Compact(1u128).encode()].concat();
+        let body = [
+            Compact(2u32).encode(),
+            timestamp.encode(),
+            signed(&transfer),
+        ]
+        .concat();
+
+        let records = decode_extrinsics(&metadata, &registry, &body)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let decoded = ExtrinsicDecoder::v13(&metadata, &registry)
+            .decode_extrinsics(&body)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(decoded.len(), 2);
+        for (record, decoded) in records.iter().zip(&decoded) {
+            assert_eq!(record.is_signed(), decoded.is_signed());
+            assert_eq!(record.call.module_name, decoded.call.module_name);
+            assert_eq!(record.call.call_name, decoded.call.call_name);
+            assert_eq!(record.call.args.len(), decoded.call.args.len());
+        }
+        assert_eq!(
+            decoded[1].call.arg("value"),
+            Some(&crate::scale::Value::UInt(1))
+        );
+    }
+}
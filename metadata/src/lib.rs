@@ -8,7 +8,7 @@
 //!
 //! // Parse runtime metadata
 //! let content = std::fs::read_to_string("metadata_kusama_9080.hex").unwrap();
-//! let data = parse_hex_metadata(content).unwrap().into_inner();
+//! let data = parse_hex_metadata(content).unwrap().into_inner().unwrap();
 //!
 //! // Get information about the extrinsic.
 //! let extr = data
@@ -24,29 +24,51 @@
 //!         ("value", "Compact<T::Balance>"),
 //!     ]
 //! );
+//!
+//! // Get information about the storage entry.
+//! let entry = data.find_module_storage("System", "Account").unwrap();
+//! assert_eq!(entry.value_type(), "AccountInfo<T::Index, T::AccountData>");
 //! ```
 
 // INFO: The earliest metadata versions are available in the substrate repo at
 // commit: a31c01b398d958ccf0a24d8c1c11fb073df66212
 
-#[macro_use]
-extern crate serde;
 #[macro_use]
 extern crate parity_scale_codec;
 
+use self::version::v13::{StorageEntryModifier, StorageEntryType, StorageHasher};
 use self::version::*;
-use parity_scale_codec::{Decode, Error as ScaleError};
-use serde_json::Error as SerdeJsonError;
+use parity_scale_codec::{Decode, DecodeAll, Encode, Error as ScaleError};
+use serde_json::{Error as SerdeJsonError, Value};
+use std::borrow::Cow;
 
 type Result<T> = std::result::Result<T, Error>;
 
+pub mod dynamic;
+pub mod events;
+pub mod extrinsics;
+pub mod merkle;
+pub mod scale;
+#[cfg(feature = "serde")]
+pub mod serde_hex;
+pub mod shared;
 pub mod version;
 
-/// Parameters and other information about an individual extrinsic.
+pub use self::shared::SharedMetadata;
+
+/// Parameters and other information about an individual extrinsic. Can only be
+/// serialized, since it borrows from the metadata.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ExtrinsicInfo<'a> {
-    /// The module Id. This is required when encoding the final extrinsic.
+    /// The module Id, which is the index of the module as declared in the
+    /// runtime. This is required when encoding the final extrinsic.
     pub module_id: usize,
+    /// The position of the module in the metadata. Equals the module Id only if
+    /// no module was removed from the runtime, since metadata V12 declares the
+    /// index of each module explicitly. Prefer [`ExtrinsicInfo::module_id`]
+    /// for encoding extrinsics.
+    pub module_position: usize,
     /// The dispatch Id. This is required when encoding the final extrinsic.
     pub dispatch_id: usize,
     /// The name of the module.
@@ -69,20 +91,355 @@ pub trait ModuleMetadataExt {
         method: &str,
         extrinsic: &str,
     ) -> Option<ExtrinsicInfo<'a>>;
+    /// Finds the extrinsic by the first two bytes of an encoded call, the
+    /// index of the module as declared in the runtime and the index of the
+    /// call within the module.
+    fn find_extrinsic_by_index<'a>(
+        &'a self,
+        module_index: u8,
+        dispatch_index: u8,
+    ) -> Option<ExtrinsicInfo<'a>>;
+}
+
+/// Information about an individual event.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EventInfo<'a> {
+    /// The index of the module, which is the first byte of an encoded event.
+    pub module_index: u8,
+    /// The index of the event within the module, which is the second byte of
+    /// an encoded event.
+    pub event_index: u8,
+    /// The name of the module.
+    pub module_name: &'a str,
+    /// The name of the event.
+    pub event_name: &'a str,
+    /// The type descriptions of the arguments, in encoding order.
+    pub args: Vec<&'a str>,
+    /// Documentation of the event, as provided by the Substrate metadata.
+    pub documentation: Vec<&'a str>,
+}
+
+/// An interface to retrieve information about events on any Substrate metadata
+/// version.
+pub trait EventMetadataExt {
+    fn modules_events<'a>(&'a self) -> Vec<EventInfo<'a>>;
+    fn find_module_event<'a>(&'a self, module: &str, event: &str) -> Option<EventInfo<'a>> {
+        self.modules_events()
+            .into_iter()
+            .find(|info| info.module_name == module && info.event_name == event)
+    }
+    /// Finds the event by the first two bytes of an encoded event.
+    fn find_event_by_index<'a>(
+        &'a self,
+        module_index: u8,
+        event_index: u8,
+    ) -> Option<EventInfo<'a>> {
+        self.modules_events()
+            .into_iter()
+            .find(|info| info.module_index == module_index && info.event_index == event_index)
+    }
+}
+
+/// Information about an individual error of a module.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ErrorInfo<'a> {
+    /// The index of the module, as reported by a failed dispatch.
+    pub module_index: u8,
+    /// The index of the error within the module, as reported by a failed
+    /// dispatch.
+    pub error_index: u8,
+    /// The name of the module.
+    pub module_name: &'a str,
+    /// The name of the error.
+    pub error_name: &'a str,
+    /// Documentation of the error, as provided by the Substrate metadata.
+    pub documentation: Vec<&'a str>,
+}
+
+/// An interface to retrieve information about the errors of the modules on any
+/// Substrate metadata version, e.g. to translate a failed dispatch into a
+/// human-readable message.
+pub trait ErrorBuilderExt {
+    fn modules_errors<'a>(&'a self) -> Vec<ErrorInfo<'a>>;
+    /// Finds the error by the module and error index of a failed dispatch.
+    fn find_error<'a>(&'a self, module_index: u8, error_index: u8) -> Option<ErrorInfo<'a>> {
+        self.modules_errors()
+            .into_iter()
+            .find(|info| info.module_index == module_index && info.error_index == error_index)
+    }
+}
+
+/// Information about an individual storage entry.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StorageInfo<'a> {
+    /// The name of the module.
+    pub module_name: &'a str,
+    /// The storage prefix of the module, usually the module name.
+    pub prefix: &'a str,
+    /// The name of the storage entry.
+    pub entry_name: &'a str,
+    /// Whether the entry returns the default value if it is not set.
+    pub modifier: &'a StorageEntryModifier,
+    /// The hashers and types of the keys and value. Borrowed from metadata up
+    /// to V13, while the type names are resolved from the type registry for
+    /// later versions.
+    pub ty: Cow<'a, StorageEntryType>,
+    /// The SCALE encoded default value.
+    pub default: &'a [u8],
+    /// Documentation of the storage entry, as provided by the Substrate
+    /// metadata.
+    pub documentation: Vec<&'a str>,
+}
+
+impl<'a> StorageInfo<'a> {
+    /// The hashers of the keys, in order. Empty for plain storage values.
+    pub fn hashers(&self) -> Vec<&StorageHasher> {
+        match &*self.ty {
+            StorageEntryType::Plain(_) => vec![],
+            StorageEntryType::Map { hasher, .. } => vec![hasher],
+            StorageEntryType::DoubleMap {
+                hasher,
+                key2_hasher,
+                ..
+            } => vec![hasher, key2_hasher],
+            StorageEntryType::NMap { hashers, .. } => hashers.iter().collect(),
+        }
+    }
+    /// The type description of the value.
+    pub fn value_type(&self) -> &str {
+        match &*self.ty {
+            StorageEntryType::Plain(value)
+            | StorageEntryType::Map { value, .. }
+            | StorageEntryType::DoubleMap { value, .. }
+            | StorageEntryType::NMap { value, .. } => value,
+        }
+    }
+    /// The prefix of all keys of the entry, i.e. the TwoX-128 hashes of the
+    /// module prefix and the entry name.
+    pub fn storage_prefix(&self) -> Vec<u8> {
+        let mut prefix = StorageHasher::Twox128.hash(self.prefix.as_bytes());
+        prefix.extend(StorageHasher::Twox128.hash(self.entry_name.as_bytes()));
+        prefix
+    }
+    /// Computes the storage key from the SCALE encoded keys. The number of
+    /// keys must match the number of [hashers](Self::hashers).
+    pub fn storage_key(&self, keys: &[&[u8]]) -> Result<Vec<u8>> {
+        let hashers = self.hashers();
+        if hashers.len() != keys.len() {
+            return Err(Error::InvalidStorageKeyCount {
+                expected: hashers.len(),
+                got: keys.len(),
+            });
+        }
+
+        let mut storage_key = self.storage_prefix();
+        for (hasher, key) in hashers.iter().zip(keys) {
+            storage_key.extend(hasher.hash(key));
+        }
+
+        Ok(storage_key)
+    }
+    /// Creates a builder of the storage key, which encodes the keys of the
+    /// entry. Keys of maps are added with [`StorageKeyBuilder::arg`].
+    pub fn key(&self) -> StorageKeyBuilder<'_, 'a> {
+        StorageKeyBuilder {
+            info: self,
+            keys: vec![],
+        }
+    }
+    /// Computes the storage key from keys of the same type, such as the single
+    /// key of a map. See [`key`](Self::key) for keys of different types.
+    pub fn key_for<K: Encode>(&self, keys: &[K]) -> Result<Vec<u8>> {
+        keys.iter()
+            .fold(self.key(), |builder, key| builder.arg(key))
+            .build()
+    }
+}
+
+/// Builds the storage key of an entry from its keys, which are SCALE encoded
+/// and hashed with the corresponding hasher. Created with
+/// [`StorageInfo::key`].
+///
+/// # Example
+///
+/// ```no_run
+/// use gekko_metadata::*;
+///
+/// let content = std::fs::read_to_string("metadata_kusama_9080.hex").unwrap();
+/// let data = parse_hex_metadata(content).unwrap().into_inner().unwrap();
+///
+/// // Staking::ErasStakers(EraIndex, AccountId)
+/// let entry = data.find_module_storage("Staking", "ErasStakers").unwrap();
+/// let key = entry.key().arg(&2000u32).arg(&[1u8; 32]).build_hex().unwrap();
+///
+/// assert!(key.starts_with("0x5f3e4907f716ac89b6347d15ececedca"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct StorageKeyBuilder<'b, 'a> {
+    info: &'b StorageInfo<'a>,
+    keys: Vec<Vec<u8>>,
+}
+
+impl<'b, 'a> StorageKeyBuilder<'b, 'a> {
+    /// Adds the next key, in the order of the [hashers](StorageInfo::hashers).
+    pub fn arg<K: Encode>(mut self, key: &K) -> Self {
+        self.keys.push(key.encode());
+        self
+    }
+    /// Computes the storage key. The number of keys must match the number of
+    /// [hashers](StorageInfo::hashers).
+    pub fn build(&self) -> Result<Vec<u8>> {
+        let keys: Vec<&[u8]> = self.keys.iter().map(Vec::as_slice).collect();
+        self.info.storage_key(&keys)
+    }
+    /// Computes the `0x`-prefixed HEX representation of the storage key, as
+    /// expected by `state_getStorage`.
+    pub fn build_hex(&self) -> Result<String> {
+        self.build().map(|key| format!("0x{}", hex::encode(key)))
+    }
+}
+
+/// An interface to retrieve information about storage entries on any Substrate
+/// metadata version.
+pub trait StorageMetadataExt {
+    fn modules_storage<'a>(&'a self) -> Vec<StorageInfo<'a>>;
+    fn find_module_storage<'a>(&'a self, module: &str, entry: &str) -> Option<StorageInfo<'a>>;
+}
+
+/// Information about an individual constant of a module.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ConstantInfo<'a> {
+    /// The name of the module.
+    pub module_name: &'a str,
+    /// The name of the constant.
+    pub constant_name: &'a str,
+    /// The type description of the value. Borrowed from metadata up to V13,
+    /// while the type name is resolved from the type registry for later
+    /// versions.
+    pub ty: Cow<'a, str>,
+    /// The SCALE encoded value.
+    pub value: &'a [u8],
+    /// Documentation of the constant, as provided by the Substrate metadata.
+    pub documentation: Vec<&'a str>,
+}
+
+impl<'a> ConstantInfo<'a> {
+    /// Decodes the value of the constant. All bytes must be consumed.
+    pub fn decode<T: Decode>(&self) -> Result<T> {
+        T::decode_all(self.value).map_err(|source| Error::DecodeType {
+            ty: self.ty.to_string(),
+            source,
+        })
+    }
+}
+
+/// An interface to retrieve the constants of the modules on any Substrate
+/// metadata version.
+///
+/// # Example
+///
+/// ```no_run
+/// use gekko_metadata::*;
+///
+/// let content = std::fs::read_to_string("metadata_kusama_9080.hex").unwrap();
+/// let data = parse_hex_metadata(content).unwrap().into_inner().unwrap();
+///
+/// let deposit: u128 = data
+///     .find_constant("Balances", "ExistentialDeposit")
+///     .unwrap()
+///     .decode()
+///     .unwrap();
+/// ```
+pub trait ConstantsBuilderExt {
+    fn constants<'a>(&'a self) -> Vec<ConstantInfo<'a>>;
+    fn find_constant<'a>(&'a self, module: &str, name: &str) -> Option<ConstantInfo<'a>> {
+        self.constants()
+            .into_iter()
+            .find(|info| info.module_name == module && info.constant_name == name)
+    }
+    /// Decodes the value of the constant `name` of the given module.
+    fn decode_constant<T: Decode>(&self, module: &str, name: &str) -> Result<T>
+    where
+        Self: Sized,
+    {
+        self.find_constant(module, name)
+            .ok_or_else(|| Error::UnknownConstant {
+                module: module.to_string(),
+                constant: name.to_string(),
+            })?
+            .decode()
+    }
+}
+
+/// An interface to retrieve information about extrinsics, storage entries,
+/// events, constants and errors on any Substrate metadata version, as returned by
+/// [`MetadataVersion::into_inner`]. Implemented for all types which implement
+/// the individual interfaces.
+pub trait MetadataExt:
+    ModuleMetadataExt + StorageMetadataExt + EventMetadataExt + ConstantsBuilderExt + ErrorBuilderExt
+{
+}
+
+impl<T> MetadataExt for T where
+    T: ModuleMetadataExt
+        + StorageMetadataExt
+        + EventMetadataExt
+        + ConstantsBuilderExt
+        + ErrorBuilderExt
+{
 }
 
 /// Errors that can occur when parsing Substrate metadata.
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
 pub enum Error {
-    ParseJsonRpcMetadata(SerdeJsonError),
-    ParseHexMetadata(hex::FromHexError),
-    ParseRawMetadata(ScaleError),
+    #[error("failed to parse JSON-RPC metadata response")]
+    ParseJsonRpcMetadata(#[from] SerdeJsonError),
+    #[error("missing result in JSON-RPC metadata response")]
+    MissingJsonRpcResult,
+    #[error("failed to decode HEX encoded metadata")]
+    ParseHexMetadata(#[from] hex::FromHexError),
+    #[error("failed to decode raw metadata")]
+    ParseRawMetadata(#[from] ScaleError),
+    #[error("unsupported metadata version, expected the latest version")]
     InvalidMetadataVersion,
+    #[error("expected {expected} storage keys, got {got}")]
+    InvalidStorageKeyCount { expected: usize, got: usize },
+    #[error("failed to parse type definitions")]
+    ParseTypeDefinitions(#[source] SerdeJsonError),
+    #[error("invalid type description: {0}")]
+    InvalidTypeString(String),
+    #[error("invalid definition of type {0}")]
+    InvalidTypeDefinition(String),
+    #[error("no definition of type {0}")]
+    UnknownType(String),
+    #[error("failed to decode value of type {ty}")]
+    DecodeType {
+        ty: String,
+        #[source]
+        source: ScaleError,
+    },
+    #[error("no event with index {event_index} in module with index {module_index}")]
+    UnknownEvent { module_index: u8, event_index: u8 },
+    #[error("unsupported extrinsic version {0}, expected version 4")]
+    UnsupportedExtrinsicVersion(u8),
+    #[error("no call with index {call_index} in module with index {module_index}")]
+    UnknownCall { module_index: u8, call_index: u8 },
+    #[error("no constant {constant} in module {module}")]
+    UnknownConstant { module: String, constant: String },
+    #[error("no call {call} in module {module}")]
+    UnknownCallName { module: String, call: String },
+    #[error("invalid value of type {ty}: {reason}")]
+    InvalidValue { ty: String, reason: String },
 }
 
 /// Helper type when dealing with the Json RPC response returned by
 /// Substrates `state_getMetadata`.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct JsonRpcResponse {
     pub jsonrpc: String,
     pub result: String,
@@ -92,15 +449,29 @@ pub struct JsonRpcResponse {
 /// `state_getMetadata`.
 ///
 /// Must fit the [`JsonRpcResponse`] structure.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(json), err)
+)]
 pub fn parse_jsonrpc_metadata<T: AsRef<[u8]>>(json: T) -> Result<MetadataVersion> {
-    let resp = serde_json::from_slice::<JsonRpcResponse>(json.as_ref())
-        .map_err(|err| Error::ParseJsonRpcMetadata(err))?;
+    let resp = serde_json::from_slice::<Value>(json.as_ref())?;
+    let result = resp["result"].as_str().ok_or(Error::MissingJsonRpcResult)?;
 
-    parse_hex_metadata(resp.result.as_bytes())
+    parse_hex_metadata(result.as_bytes())
+}
+
+/// Decodes a HEX string, with or without the `0x` prefix.
+pub fn decode_hex<T: AsRef<str>>(hex: T) -> std::result::Result<Vec<u8>, hex::FromHexError> {
+    let hex = hex.as_ref();
+    hex::decode(hex.strip_prefix("0x").unwrap_or(hex))
 }
 
 /// Convenience function for parsing the metadata from a HEX representation, as
 /// returned by `state_getMetadata`.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(hex), err)
+)]
 pub fn parse_hex_metadata<T: AsRef<[u8]>>(hex: T) -> Result<MetadataVersion> {
     let hex = hex.as_ref();
 
@@ -111,10 +482,19 @@ pub fn parse_hex_metadata<T: AsRef<[u8]>>(hex: T) -> Result<MetadataVersion> {
         hex
     };
 
-    parse_raw_metadata(hex::decode(slice).map_err(|err| Error::ParseHexMetadata(err))?)
+    // Decoding into a preallocated buffer is considerably faster than
+    // `hex::decode`.
+    let mut raw = vec![0; slice.len() / 2];
+    hex::decode_to_slice(slice, &mut raw).map_err(Error::ParseHexMetadata)?;
+
+    parse_raw_metadata(raw)
 }
 
 /// Parse the raw Substrate metadata.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(raw), fields(len = raw.as_ref().len()), err)
+)]
 pub fn parse_raw_metadata<T: AsRef<[u8]>>(raw: T) -> Result<MetadataVersion> {
     let raw = raw.as_ref();
 
@@ -129,11 +509,20 @@ pub fn parse_raw_metadata<T: AsRef<[u8]>>(raw: T) -> Result<MetadataVersion> {
         raw
     };
 
-    MetadataVersion::decode(&mut slice).map_err(|err| Error::ParseRawMetadata(err))
+    MetadataVersion::decode(&mut slice).map_err(Error::ParseRawMetadata)
 }
 
 /// Identifier of all the available Substrate metadata versions.
+///
+/// With the `"serde"` feature, the version is (de)serialized as an object
+/// containing the name of the variant in the `"version"` field and the inner
+/// metadata structure, if any, in the `"metadata"` field, e.g.
+/// `{"version":"V13","metadata":{"modules":[...],"extrinsic":{...}}}`. Byte
+/// arrays such as default values of storage entries are represented as
+/// `0x`-prefixed HEX strings.
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "version", content = "metadata"))]
 pub enum MetadataVersion {
     V0,
     V1,
@@ -144,22 +533,33 @@ pub enum MetadataVersion {
     V6,
     V7,
     V8,
-    V9,
-    V10,
-    V11,
-    V12,
+    V9(MetadataV9),
+    V10(MetadataV10),
+    V11(MetadataV11),
+    V12(MetadataV12),
     V13(MetadataV13),
+    V14(MetadataV14),
 }
 
 impl MetadataVersion {
     /// Consumes the object and returns the inner metadata structure, expecting
-    /// the latest version. Results in an error if the version is not the latest.
+    /// V13, the latest version described by type names. The extrinsic, event
+    /// and storage decoders of this crate are based on it. Results in an error
+    /// for any other version.
     pub fn into_latest(self) -> Result<MetadataV13> {
         match self {
             MetadataVersion::V13(data) => Ok(data),
             _ => Err(Error::InvalidMetadataVersion),
         }
     }
+    /// Consumes the object and returns the inner metadata structure, expecting
+    /// V14. Results in an error for any other version.
+    pub fn into_v14(self) -> Result<MetadataV14> {
+        match self {
+            MetadataVersion::V14(data) => Ok(data),
+            _ => Err(Error::InvalidMetadataVersion),
+        }
+    }
     /// Returns the version number as an integer.
     pub fn version_number(&self) -> usize {
         use MetadataVersion::*;
@@ -174,17 +574,278 @@ impl MetadataVersion {
             V6 => 6,
             V7 => 7,
             V8 => 8,
-            V9 => 9,
-            V10 => 10,
-            V11 => 11,
-            V12 => 12,
+            V9(_) => 9,
+            V10(_) => 10,
+            V11(_) => 11,
+            V12(_) => 12,
             V13(_) => 13,
+            V14(_) => 14,
         }
     }
-    pub fn into_inner(self) -> impl ModuleMetadataExt {
+    /// Consumes the object and returns the inner metadata structure as a
+    /// version-independent [`MetadataExt`] implementation, so extrinsics and
+    /// storage entries can be looked up regardless of the version. Results in
+    /// an error if the version is not supported, i.e. older than V9.
+    pub fn into_inner(self) -> Result<Box<dyn MetadataExt>> {
         match self {
-            MetadataVersion::V13(m) => m,
-            _ => panic!(),
+            MetadataVersion::V9(m) => Ok(Box::new(m)),
+            MetadataVersion::V10(m) => Ok(Box::new(m)),
+            MetadataVersion::V11(m) => Ok(Box::new(m)),
+            MetadataVersion::V12(m) => Ok(Box::new(m)),
+            MetadataVersion::V13(m) => Ok(Box::new(m)),
+            MetadataVersion::V14(m) => Ok(Box::new(m)),
+            _ => Err(Error::InvalidMetadataVersion),
         }
     }
+    /// Returns the inner metadata structure as a version-independent
+    /// [`MetadataExt`] implementation, see [`MetadataVersion::into_inner`].
+    pub fn as_inner(&self) -> Result<&dyn MetadataExt> {
+        match self {
+            MetadataVersion::V9(m) => Ok(m),
+            MetadataVersion::V10(m) => Ok(m),
+            MetadataVersion::V11(m) => Ok(m),
+            MetadataVersion::V12(m) => Ok(m),
+            MetadataVersion::V13(m) => Ok(m),
+            MetadataVersion::V14(m) => Ok(m),
+            _ => Err(Error::InvalidMetadataVersion),
+        }
+    }
+    /// Returns the version of the extrinsic format used by the runtime, which
+    /// is declared since metadata V11.
+    pub fn extrinsic_version(&self) -> Option<u8> {
+        match self {
+            MetadataVersion::V11(m) => Some(m.extrinsics.version),
+            MetadataVersion::V12(m) => Some(m.extrinsics.version),
+            MetadataVersion::V13(m) => Some(m.extrinsics.version),
+            MetadataVersion::V14(m) => Some(m.extrinsic.version),
+            _ => None,
+        }
+    }
+    /// Consumes the object and returns a smaller copy of the metadata. Drops
+    /// all documentation strings unless `keep_docs` is set, including the
+    /// documentation of the types of metadata V14. Since V12, where modules
+    /// declare their index, empty storage, call and event sections are replaced
+    /// with `None`. Lookups by module and entry names as well as the module and
+    /// dispatch Ids are unaffected.
+    ///
+    /// Results in an error for versions prior to V9, which are not parsed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gekko_metadata::*;
+    ///
+    /// let content = std::fs::read_to_string("metadata_kusama_9080.hex").unwrap();
+    /// let metadata = parse_hex_metadata(content).unwrap();
+    ///
+    /// let pruned = metadata.clone().pruned(false).unwrap();
+    /// assert!(pruned.to_raw().len() < metadata.to_raw().len());
+    ///
+    /// // The pruned metadata can be embedded and parsed as usual.
+    /// let parsed = parse_hex_metadata(pruned.to_hex()).unwrap();
+    /// assert_eq!(parsed, pruned);
+    /// ```
+    pub fn pruned(self, keep_docs: bool) -> Result<MetadataVersion> {
+        Ok(match self {
+            MetadataVersion::V9(data) => MetadataVersion::V9(data.pruned(keep_docs)),
+            MetadataVersion::V10(data) => MetadataVersion::V10(data.pruned(keep_docs)),
+            MetadataVersion::V11(data) => MetadataVersion::V11(data.pruned(keep_docs)),
+            MetadataVersion::V12(data) => MetadataVersion::V12(data.pruned(keep_docs)),
+            MetadataVersion::V13(data) => MetadataVersion::V13(data.pruned(keep_docs)),
+            MetadataVersion::V14(data) => MetadataVersion::V14(data.pruned(keep_docs)),
+            _ => return Err(Error::InvalidMetadataVersion),
+        })
+    }
+    /// Encodes the metadata into its raw representation, including the magic
+    /// number. The result can be parsed with [`parse_raw_metadata`].
+    pub fn to_raw(&self) -> Vec<u8> {
+        let mut raw = b"meta".to_vec();
+        self.encode_to(&mut raw);
+        raw
+    }
+    /// Encodes the metadata into its HEX representation, as returned by
+    /// `state_getMetadata`. The result can be parsed with
+    /// [`parse_hex_metadata`].
+    pub fn to_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.to_raw()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DUMP: &str = include_str!("../../interface/dumps/metadata_kusama_9080.hex");
+
+    fn metadata() -> MetadataV13 {
+        parse_hex_metadata(DUMP.trim())
+            .unwrap()
+            .into_latest()
+            .unwrap()
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        format!("0x{}", hex::encode(bytes))
+    }
+
+    #[test]
+    fn storage_key_of_plain_value() {
+        let metadata = metadata();
+        let entry = metadata.find_module_storage("Timestamp", "Now").unwrap();
+
+        let expected = "0xf0c365c3cf59d671eb72da0e7a4113c49f1f0515f462cdcf84e0f1d6045dfcbb";
+        assert_eq!(entry.key().build_hex().unwrap(), expected);
+        assert_eq!(hex(&entry.key_for::<u32>(&[]).unwrap()), expected);
+    }
+
+    #[test]
+    fn storage_key_of_map() {
+        let metadata = metadata();
+
+        // System::BlockHash(0), the key of the genesis hash.
+        let entry = metadata.find_module_storage("System", "BlockHash").unwrap();
+        assert_eq!(
+            entry.key().arg(&0u32).build_hex().unwrap(),
+            "0x26aa394eea5630e07c48ae0c9558cef7a44704b568d21667356a5a050c118746b4def25cfda6ef3a00000000"
+        );
+
+        // System::Account, hashed with Blake2_128Concat.
+        let entry = metadata.find_module_storage("System", "Account").unwrap();
+        let key = entry.key_for(&[[1u8; 32]]).unwrap();
+        assert_eq!(
+            hex(&key[..32]),
+            "0x26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da9"
+        );
+        assert_eq!(key[32..48], StorageHasher::Blake2_128.hash(&[1; 32])[..]);
+        assert_eq!(key[48..], [1; 32]);
+    }
+
+    #[test]
+    fn storage_key_of_double_map() {
+        let metadata = metadata();
+        let entry = metadata
+            .find_module_storage("Staking", "ErasStakers")
+            .unwrap();
+
+        let key = entry.key().arg(&2000u32).arg(&[1u8; 32]).build().unwrap();
+        assert_eq!(
+            hex(&key[..32]),
+            "0x5f3e4907f716ac89b6347d15ececedca8bde0a0ea8864605e3b68ed9cb2da01b"
+        );
+
+        // Both keys are hashed with Twox64Concat.
+        let era = 2000u32.encode();
+        assert_eq!(key[32..40], StorageHasher::Twox64Concat.hash(&era)[..8]);
+        assert_eq!(key[40..44], era[..]);
+        assert_eq!(key[44..52], StorageHasher::Twox64Concat.hash(&[1; 32])[..8]);
+        assert_eq!(key[52..], [1; 32]);
+        assert_eq!(key.len(), 32 + 12 + 40);
+    }
+
+    #[test]
+    fn storage_key_count_mismatch() {
+        let metadata = metadata();
+        let entry = metadata
+            .find_module_storage("Staking", "ErasStakers")
+            .unwrap();
+
+        match entry.key().arg(&2000u32).build() {
+            Err(Error::InvalidStorageKeyCount { expected, got }) => {
+                assert_eq!((expected, got), (2, 1))
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn storage_key_of_n_map() {
+        let ty = StorageEntryType::NMap {
+            keys: "(u32, AccountId, u8)".to_string(),
+            hashers: vec![
+                StorageHasher::Twox64Concat,
+                StorageHasher::Blake2_128Concat,
+                StorageHasher::Identity,
+            ],
+            value: "u128".to_string(),
+        };
+        let entry = StorageInfo {
+            module_name: "Assets",
+            prefix: "Assets",
+            entry_name: "Approvals",
+            modifier: &StorageEntryModifier::Optional,
+            ty: Cow::Owned(ty),
+            default: &[0],
+            documentation: vec![],
+        };
+
+        let key = entry
+            .key()
+            .arg(&7u32)
+            .arg(&[1u8; 32])
+            .arg(&9u8)
+            .build()
+            .unwrap();
+        let expected = [
+            StorageHasher::Twox128.hash(b"Assets"),
+            StorageHasher::Twox128.hash(b"Approvals"),
+            StorageHasher::Twox64Concat.hash(&7u32.encode()),
+            StorageHasher::Blake2_128Concat.hash(&[1; 32]),
+            vec![9],
+        ]
+        .concat();
+        assert_eq!(key, expected);
+        assert!(entry.key_for(&[7u32]).is_err());
+    }
+
+    #[test]
+    fn pruned_every_version() {
+        let dumps = [
+            include_str!("../../interface/dumps/metadata_kusama_v9.hex"),
+            include_str!("../../interface/dumps/metadata_kusama_v10.hex"),
+            include_str!("../../interface/dumps/metadata_kusama_v11.hex"),
+            include_str!("../../interface/dumps/metadata_kusama_v12.hex"),
+            DUMP,
+            include_str!("../../interface/dumps/metadata_kusama_9111.hex"),
+        ];
+
+        // The module, dispatch Ids and names of all extrinsics.
+        fn extrinsics(data: &dyn MetadataExt) -> Vec<(usize, usize, String, String)> {
+            data.modules_extrinsics()
+                .into_iter()
+                .map(|info| {
+                    (
+                        info.module_id,
+                        info.dispatch_id,
+                        info.module_name.to_string(),
+                        info.extrinsic_name.to_string(),
+                    )
+                })
+                .collect()
+        }
+
+        for dump in dumps.iter() {
+            let metadata = parse_hex_metadata(dump.trim()).unwrap();
+            let pruned = metadata.clone().pruned(false).unwrap();
+            assert_eq!(pruned.version_number(), metadata.version_number());
+            assert!(pruned.to_raw().len() < metadata.to_raw().len());
+            assert_eq!(parse_hex_metadata(pruned.to_hex()).unwrap(), pruned);
+
+            let (data, pruned) = (metadata.as_inner().unwrap(), pruned.as_inner().unwrap());
+            assert_eq!(extrinsics(pruned), extrinsics(data));
+            assert!(pruned
+                .modules_extrinsics()
+                .iter()
+                .all(|info| info.documentation.is_empty()));
+            assert_eq!(pruned.modules_events().len(), data.modules_events().len());
+            assert_eq!(pruned.modules_storage().len(), data.modules_storage().len());
+        }
+    }
+
+    #[test]
+    fn pruned_unparsed_version() {
+        assert!(matches!(
+            MetadataVersion::V8.pruned(false),
+            Err(Error::InvalidMetadataVersion)
+        ));
+    }
 }
@@ -0,0 +1,622 @@
+//! The metadata hash as specified by RFC-78, "Merkleized Metadata", which is
+//! included in the signature payload by the `CheckMetadataHash` signed
+//! extension. It commits to the types required to decode transactions, so
+//! offline signers such as Ledger devices can verify the decoded call against
+//! a proof of the relevant types, instead of the full metadata.
+//!
+//! The types reachable from the extrinsic are collected from the type
+//! registry, with primitives and compacts inlined as [`TypeRef`]s. Each type,
+//! and each variant of an enum, is a leaf of a binary merkle tree. The root of
+//! the tree, the hash of the [`ExtrinsicMetadata`] and the [`ExtraInfo`] of
+//! the chain make up the [`MetadataDigest`], whose hash is the metadata hash.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use gekko_metadata::*;
+//! use gekko_metadata::merkle::*;
+//!
+//! let content = std::fs::read_to_string("dumps/metadata_polkadot_1001002.hex").unwrap();
+//! let data = parse_hex_metadata(content).unwrap().into_v14().unwrap();
+//!
+//! let extra = ExtraInfo::from_metadata(&data, 10, "DOT").unwrap();
+//! let hash = MetadataDigest::new(&data, extra).unwrap().hash();
+//! ```
+
+use crate::version::v14::{self, MetadataV14, PortableRegistry, TypeDefPrimitive, TypeId};
+use crate::{ConstantsBuilderExt, Error, MetadataVersion, Result};
+use parity_scale_codec::{Decode, Encode};
+use std::collections::{BTreeMap, VecDeque};
+
+/// A BLAKE3 hash.
+pub type Hash = [u8; 32];
+
+/// A reference to a type, with primitives and compacts inlined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TypeRef {
+    Bool,
+    Char,
+    Str,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    I256,
+    CompactU8,
+    CompactU16,
+    CompactU32,
+    CompactU64,
+    CompactU128,
+    CompactU256,
+    /// Empty composites, tuples and enums, which encode no data.
+    Void,
+    /// A type of the merkle tree with the given Id.
+    PerId(#[codec(compact)] u32),
+}
+
+impl From<TypeDefPrimitive> for TypeRef {
+    fn from(val: TypeDefPrimitive) -> Self {
+        use TypeDefPrimitive::*;
+
+        match val {
+            Bool => TypeRef::Bool,
+            Char => TypeRef::Char,
+            Str => TypeRef::Str,
+            U8 => TypeRef::U8,
+            U16 => TypeRef::U16,
+            U32 => TypeRef::U32,
+            U64 => TypeRef::U64,
+            U128 => TypeRef::U128,
+            U256 => TypeRef::U256,
+            I8 => TypeRef::I8,
+            I16 => TypeRef::I16,
+            I32 => TypeRef::I32,
+            I64 => TypeRef::I64,
+            I128 => TypeRef::I128,
+            I256 => TypeRef::I256,
+        }
+    }
+}
+
+/// A leaf of the merkle tree. Enums are split into one leaf per variant, all
+/// sharing the Id of the enum.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Type {
+    pub path: Vec<String>,
+    pub type_def: TypeDef,
+    #[codec(compact)]
+    pub type_id: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TypeDef {
+    Composite(Vec<Field>),
+    Enumeration(EnumerationVariant),
+    Sequence(TypeRef),
+    Array(Array),
+    Tuple(Vec<TypeRef>),
+    BitSequence(BitSequence),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Field {
+    pub name: Option<String>,
+    pub ty: TypeRef,
+    pub type_name: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EnumerationVariant {
+    pub name: String,
+    pub fields: Vec<Field>,
+    #[codec(compact)]
+    pub index: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Array {
+    pub len: u32,
+    pub type_param: TypeRef,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BitSequence {
+    /// The size of the store type, such as `1` for `u8`.
+    pub num_bytes: u8,
+    /// Whether the bit order is `Lsb0`.
+    pub least_significant_bit_first: bool,
+}
+
+/// The types of the extrinsic, required to decode transactions.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExtrinsicMetadata {
+    pub version: u8,
+    pub address_ty: TypeRef,
+    pub call_ty: TypeRef,
+    pub signature_ty: TypeRef,
+    pub signed_extensions: Vec<SignedExtensionMetadata>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SignedExtensionMetadata {
+    pub identifier: String,
+    pub included_in_extrinsic: TypeRef,
+    pub included_in_signed_data: TypeRef,
+}
+
+/// Information about the chain which is not part of the type registry.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExtraInfo {
+    pub spec_version: u32,
+    pub spec_name: String,
+    /// The SS58 prefix of addresses.
+    pub base58_prefix: u16,
+    /// The decimals of the native token.
+    pub decimals: u8,
+    /// The symbol of the native token, such as `"DOT"`.
+    pub token_symbol: String,
+}
+
+/// The prefix of the `RuntimeVersion`, as encoded in the `Version` constant
+/// of the `System` pallet.
+#[derive(Decode)]
+struct RuntimeVersionPrefix {
+    spec_name: String,
+    _impl_name: String,
+    _authoring_version: u32,
+    spec_version: u32,
+}
+
+impl ExtraInfo {
+    /// Reads the spec name, the spec version and the SS58 prefix from the
+    /// constants of the `System` pallet. The token is not part of the
+    /// metadata.
+    pub fn from_metadata(metadata: &MetadataV14, decimals: u8, token_symbol: &str) -> Result<Self> {
+        let version =
+            metadata
+                .find_constant("System", "Version")
+                .ok_or_else(|| Error::UnknownConstant {
+                    module: "System".to_string(),
+                    constant: "Version".to_string(),
+                })?;
+
+        // The version contains further fields, depending on the runtime.
+        let prefix = RuntimeVersionPrefix::decode(&mut &*version.value).map_err(|source| {
+            Error::DecodeType {
+                ty: version.ty.to_string(),
+                source,
+            }
+        })?;
+
+        Ok(ExtraInfo {
+            spec_version: prefix.spec_version,
+            spec_name: prefix.spec_name,
+            base58_prefix: metadata.decode_constant("System", "SS58Prefix")?,
+            decimals,
+            token_symbol: token_symbol.to_string(),
+        })
+    }
+}
+
+/// The digest of the metadata, whose hash is the metadata hash.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MetadataDigest {
+    Disabled,
+    V1 {
+        type_information_tree_root: Hash,
+        extrinsic_metadata_hash: Hash,
+        spec_version: u32,
+        spec_name: String,
+        base58_prefix: u16,
+        decimals: u8,
+        token_symbol: String,
+    },
+}
+
+impl MetadataDigest {
+    /// Creates the digest of the types required to decode transactions.
+    pub fn new(metadata: &MetadataV14, extra: ExtraInfo) -> Result<Self> {
+        let (types, extrinsic) = merkleize(metadata)?;
+
+        Ok(MetadataDigest::V1 {
+            type_information_tree_root: root(types.iter().map(|ty| blake3(&ty.encode()))),
+            extrinsic_metadata_hash: blake3(&extrinsic.encode()),
+            spec_version: extra.spec_version,
+            spec_name: extra.spec_name,
+            base58_prefix: extra.base58_prefix,
+            decimals: extra.decimals,
+            token_symbol: extra.token_symbol,
+        })
+    }
+    /// The metadata hash, as used by the `CheckMetadataHash` extension.
+    pub fn hash(&self) -> Hash {
+        blake3(&self.encode())
+    }
+}
+
+/// Computes the metadata hash, which requires metadata V14 or later.
+pub fn metadata_hash(metadata: &MetadataVersion, extra: ExtraInfo) -> Result<Hash> {
+    match metadata {
+        MetadataVersion::V14(metadata) => Ok(MetadataDigest::new(metadata, extra)?.hash()),
+        _ => Err(Error::InvalidMetadataVersion),
+    }
+}
+
+/// Collects the leaves of the merkle tree, ordered by Id and variant index,
+/// and the extrinsic metadata referencing them.
+pub fn merkleize(metadata: &MetadataV14) -> Result<(Vec<Type>, ExtrinsicMetadata)> {
+    let registry = &metadata.types;
+
+    let extrinsic = resolve(registry, metadata.extrinsic.ty)?;
+    let param = |name: &str| {
+        extrinsic
+            .type_params
+            .iter()
+            .find(|param| param.name == name)
+            .and_then(|param| param.ty)
+            .ok_or_else(|| Error::InvalidTypeDefinition(registry.type_name(metadata.extrinsic.ty)))
+    };
+    let (address, call, signature) = (param("Address")?, param("Call")?, param("Signature")?);
+
+    let mut roots = vec![address, call, signature];
+    for ext in &metadata.extrinsic.signed_extensions {
+        roots.push(ext.ty);
+        roots.push(ext.additional_signed);
+    }
+
+    // Collect the types referenced by Id. The Ids of the tree are assigned in
+    // the order of the Ids of the registry.
+    let mut collected = BTreeMap::new();
+    while let Some(id) = roots.pop() {
+        if collected.contains_key(&id.0) || inline_ref(registry, id)?.is_some() {
+            continue;
+        }
+
+        let ty = resolve(registry, id)?;
+        collected.insert(id.0, ty);
+
+        match &ty.type_def {
+            v14::TypeDef::Composite { fields } => roots.extend(fields.iter().map(|f| f.ty)),
+            v14::TypeDef::Variant { variants } => roots.extend(
+                variants
+                    .iter()
+                    .flat_map(|variant| variant.fields.iter().map(|f| f.ty)),
+            ),
+            v14::TypeDef::Sequence { type_param } | v14::TypeDef::Array { type_param, .. } => {
+                roots.push(*type_param)
+            }
+            v14::TypeDef::Tuple { fields } => roots.extend(fields),
+            v14::TypeDef::Primitive(_)
+            | v14::TypeDef::Compact { .. }
+            | v14::TypeDef::BitSequence { .. } => {}
+        }
+    }
+
+    let ids: BTreeMap<u32, u32> = collected
+        .keys()
+        .zip(0..)
+        .map(|(id, new)| (*id, new))
+        .collect();
+    let type_ref = |id: TypeId| -> Result<TypeRef> {
+        match inline_ref(registry, id)? {
+            Some(ty) => Ok(ty),
+            None => Ok(TypeRef::PerId(ids[&id.0])),
+        }
+    };
+    let fields = |fields: &[v14::Field]| -> Result<Vec<Field>> {
+        fields
+            .iter()
+            .map(|field| {
+                Ok(Field {
+                    name: field.name.clone(),
+                    ty: type_ref(field.ty)?,
+                    type_name: field.type_name.clone(),
+                })
+            })
+            .collect()
+    };
+
+    let mut types = vec![];
+    for (id, ty) in &collected {
+        let type_id = ids[id];
+        let leaf = |type_def| Type {
+            path: ty.path.clone(),
+            type_def,
+            type_id,
+        };
+
+        match &ty.type_def {
+            v14::TypeDef::Composite { fields: f } => {
+                types.push(leaf(TypeDef::Composite(fields(f)?)));
+            }
+            v14::TypeDef::Variant { variants } => {
+                let mut variants: Vec<&v14::Variant> = variants.iter().collect();
+                variants.sort_by_key(|variant| variant.index);
+
+                for variant in variants {
+                    types.push(leaf(TypeDef::Enumeration(EnumerationVariant {
+                        name: variant.name.clone(),
+                        fields: fields(&variant.fields)?,
+                        index: variant.index as u32,
+                    })));
+                }
+            }
+            v14::TypeDef::Sequence { type_param } => {
+                types.push(leaf(TypeDef::Sequence(type_ref(*type_param)?)));
+            }
+            v14::TypeDef::Array { len, type_param } => {
+                types.push(leaf(TypeDef::Array(Array {
+                    len: *len,
+                    type_param: type_ref(*type_param)?,
+                })));
+            }
+            v14::TypeDef::Tuple { fields } => {
+                types.push(leaf(TypeDef::Tuple(
+                    fields
+                        .iter()
+                        .map(|field| type_ref(*field))
+                        .collect::<Result<_>>()?,
+                )));
+            }
+            v14::TypeDef::BitSequence {
+                bit_store_type,
+                bit_order_type,
+            } => {
+                let num_bytes = match &resolve(registry, *bit_store_type)?.type_def {
+                    v14::TypeDef::Primitive(TypeDefPrimitive::U8) => 1,
+                    v14::TypeDef::Primitive(TypeDefPrimitive::U16) => 2,
+                    v14::TypeDef::Primitive(TypeDefPrimitive::U32) => 4,
+                    v14::TypeDef::Primitive(TypeDefPrimitive::U64) => 8,
+                    _ => {
+                        return Err(Error::InvalidTypeDefinition(
+                            registry.type_name(*bit_store_type),
+                        ))
+                    }
+                };
+                let order = resolve(registry, *bit_order_type)?;
+
+                types.push(leaf(TypeDef::BitSequence(BitSequence {
+                    num_bytes,
+                    least_significant_bit_first: order.path.last().map(String::as_str)
+                        == Some("Lsb0"),
+                })));
+            }
+            // Inlined.
+            v14::TypeDef::Primitive(_) | v14::TypeDef::Compact { .. } => {}
+        }
+    }
+
+    let extrinsic = ExtrinsicMetadata {
+        version: metadata.extrinsic.version,
+        address_ty: type_ref(address)?,
+        call_ty: type_ref(call)?,
+        signature_ty: type_ref(signature)?,
+        signed_extensions: metadata
+            .extrinsic
+            .signed_extensions
+            .iter()
+            .map(|ext| {
+                Ok(SignedExtensionMetadata {
+                    identifier: ext.identifier.clone(),
+                    included_in_extrinsic: type_ref(ext.ty)?,
+                    included_in_signed_data: type_ref(ext.additional_signed)?,
+                })
+            })
+            .collect::<Result<_>>()?,
+    };
+
+    Ok((types, extrinsic))
+}
+
+fn resolve(registry: &PortableRegistry, id: TypeId) -> Result<&v14::Type> {
+    registry
+        .resolve(id)
+        .ok_or_else(|| Error::UnknownType(registry.type_name(id)))
+}
+
+/// Returns the inlined reference of primitives, compacts and empty types, or
+/// `None` if the type is referenced by its Id.
+fn inline_ref(registry: &PortableRegistry, id: TypeId) -> Result<Option<TypeRef>> {
+    Ok(match &resolve(registry, id)?.type_def {
+        v14::TypeDef::Primitive(primitive) => Some((*primitive).into()),
+        v14::TypeDef::Compact { type_param } => Some(compact_ref(registry, *type_param)?),
+        v14::TypeDef::Composite { fields } if fields.is_empty() => Some(TypeRef::Void),
+        v14::TypeDef::Tuple { fields } if fields.is_empty() => Some(TypeRef::Void),
+        v14::TypeDef::Variant { variants } if variants.is_empty() => Some(TypeRef::Void),
+        _ => None,
+    })
+}
+
+/// Returns the reference of a compact, which wraps an unsigned integer or a
+/// composite with a single field of such, such as `Perbill`.
+fn compact_ref(registry: &PortableRegistry, id: TypeId) -> Result<TypeRef> {
+    match &resolve(registry, id)?.type_def {
+        v14::TypeDef::Primitive(TypeDefPrimitive::U8) => Ok(TypeRef::CompactU8),
+        v14::TypeDef::Primitive(TypeDefPrimitive::U16) => Ok(TypeRef::CompactU16),
+        v14::TypeDef::Primitive(TypeDefPrimitive::U32) => Ok(TypeRef::CompactU32),
+        v14::TypeDef::Primitive(TypeDefPrimitive::U64) => Ok(TypeRef::CompactU64),
+        v14::TypeDef::Primitive(TypeDefPrimitive::U128) => Ok(TypeRef::CompactU128),
+        v14::TypeDef::Primitive(TypeDefPrimitive::U256) => Ok(TypeRef::CompactU256),
+        v14::TypeDef::Composite { fields } if fields.len() == 1 => {
+            compact_ref(registry, fields[0].ty)
+        }
+        v14::TypeDef::Composite { fields } if fields.is_empty() => Ok(TypeRef::Void),
+        v14::TypeDef::Tuple { fields } if fields.is_empty() => Ok(TypeRef::Void),
+        _ => Err(Error::InvalidTypeDefinition(registry.type_name(id))),
+    }
+}
+
+/// Computes the root of the merkle tree. The last two nodes are repeatedly
+/// replaced by their parent at the front, until only the root remains.
+fn root<I: IntoIterator<Item = Hash>>(leaves: I) -> Hash {
+    let mut nodes: VecDeque<Hash> = leaves.into_iter().collect();
+
+    while nodes.len() > 1 {
+        let right = nodes.pop_back().unwrap();
+        let left = nodes.pop_back().unwrap();
+
+        let mut parent = blake3::Hasher::new();
+        parent.update(&left);
+        parent.update(&right);
+        nodes.push_front(*parent.finalize().as_bytes());
+    }
+
+    nodes.pop_front().unwrap_or_default()
+}
+
+fn blake3(data: &[u8]) -> Hash {
+    *blake3::hash(data).as_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode_hex, parse_hex_metadata};
+
+    // Polkadot 1001002, from the fixtures of the RFC-78 reference
+    // implementation, the `merkleized-metadata` crate. Converted from V15 to
+    // V14, which leaves the type registry unchanged.
+    fn polkadot() -> MetadataV14 {
+        parse_hex_metadata(include_str!(
+            "../../interface/dumps/metadata_polkadot_1001002.hex"
+        ))
+        .unwrap()
+        .into_v14()
+        .unwrap()
+    }
+
+    fn unhex(hex: &str) -> Hash {
+        let mut hash = [0; 32];
+        hash.copy_from_slice(&decode_hex(hex).unwrap());
+        hash
+    }
+
+    fn node(left: Hash, right: Hash) -> Hash {
+        blake3(&[left, right].concat())
+    }
+
+    #[test]
+    fn metadata_hash_polkadot() {
+        let data = polkadot();
+
+        // The extra information of the test vectors of the reference
+        // implementation.
+        let extra = ExtraInfo {
+            spec_version: 1,
+            spec_name: "nice".to_string(),
+            base58_prefix: 1,
+            decimals: 1,
+            token_symbol: "lol".to_string(),
+        };
+        assert_eq!(
+            MetadataDigest::new(&data, extra).unwrap().hash(),
+            unhex("0x72b3e70cb722edeb45a9380720ecad79b09b4113ab2dee5f5d974f170fb77a7e")
+        );
+
+        let extra = ExtraInfo::from_metadata(&data, 10, "DOT").unwrap();
+        assert_eq!(extra.spec_version, 1_001_002);
+        assert_eq!(extra.spec_name, "polkadot");
+        assert_eq!(extra.base58_prefix, 0);
+
+        let digest = MetadataDigest::new(&data, extra.clone()).unwrap();
+        match &digest {
+            MetadataDigest::V1 {
+                type_information_tree_root,
+                extrinsic_metadata_hash,
+                ..
+            } => {
+                assert_eq!(
+                    *type_information_tree_root,
+                    unhex("0x25489a11288e6da319cf7b3b0beee583a8bdb4f0b27aa2eea8c9464620824744")
+                );
+                assert_eq!(
+                    *extrinsic_metadata_hash,
+                    unhex("0x4fa3d014bb1625a188cfc0f596a551ecbc9ea00dbc292f4a3b17d4c1e331185e")
+                );
+            }
+            MetadataDigest::Disabled => panic!("expected digest V1"),
+        }
+
+        let hash = unhex("0x9faf53ae2ab33755e1f0fc7c046448262c7596bb896ba210f93b4a40a97fe844");
+        assert_eq!(digest.hash(), hash);
+        assert_eq!(
+            metadata_hash(&MetadataVersion::V14(data), extra).unwrap(),
+            hash
+        );
+    }
+
+    #[test]
+    fn metadata_hash_requires_v14() {
+        let data = parse_hex_metadata(include_str!(
+            "../../interface/dumps/metadata_kusama_9080.hex"
+        ))
+        .unwrap();
+        let extra = ExtraInfo {
+            spec_version: 9080,
+            spec_name: "kusama".to_string(),
+            base58_prefix: 2,
+            decimals: 12,
+            token_symbol: "KSM".to_string(),
+        };
+
+        assert!(matches!(
+            metadata_hash(&data, extra),
+            Err(Error::InvalidMetadataVersion)
+        ));
+    }
+
+    #[test]
+    fn digest_known_answer() {
+        let digest = MetadataDigest::V1 {
+            type_information_tree_root: [1; 32],
+            extrinsic_metadata_hash: [2; 32],
+            spec_version: 1_001_002,
+            spec_name: "polkadot".to_string(),
+            base58_prefix: 0,
+            decimals: 10,
+            token_symbol: "DOT".to_string(),
+        };
+
+        assert_eq!(
+            digest.hash(),
+            unhex("0x4deab1888f48e8941479959799156619c3eca0299e1501eefa9efeda77bdb865")
+        );
+        assert_eq!(
+            MetadataDigest::Disabled.hash(),
+            unhex("0x2d3adedff11b61f14c886e35afa036736dcd87a74d27b5c1510225d0f592e213")
+        );
+    }
+
+    #[test]
+    fn root_of_leaves() {
+        let [a, b, c, d, e] = [[1; 32], [2; 32], [3; 32], [4; 32], [5; 32]];
+
+        assert_eq!(root(vec![]), [0; 32]);
+        assert_eq!(root(vec![a]), a);
+        assert_eq!(root(vec![a, b]), node(a, b));
+        // The last two nodes are combined first, the parent moves to the front.
+        assert_eq!(root(vec![a, b, c]), node(node(b, c), a));
+        assert_eq!(
+            root(vec![a, b, c, d, e]),
+            node(node(node(d, e), a), node(b, c))
+        );
+    }
+}
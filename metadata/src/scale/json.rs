@@ -0,0 +1,793 @@
+//! Conversion between SCALE encoded values and their JSON representation, as
+//! used by polkadot-js.
+//!
+//! Values are decoded and encoded as a [`Value`], which is converted from and
+//! to JSON according to the type, see
+//! [`TypeRegistry::value_to_json`] and [`TypeRegistry::json_to_value`].
+//!
+//! * Integers are JSON numbers. Integers which exceed 2^53 - 1 are `0x`-prefixed,
+//!   big-endian HEX strings. Both, as well as decimal strings, are accepted
+//!   when encoding.
+//! * Byte sequences and arrays, such as `Bytes` or `AccountId`, are
+//!   `0x`-prefixed HEX strings, `Text` is a JSON string.
+//! * Structs are JSON objects and tuples are JSON arrays.
+//! * Enums without any data are the name of the variant, e.g. `"Staked"`.
+//!   Other enums are objects with the camelCase name of the variant as the
+//!   only key, e.g. `{ "id": "0x..." }`.
+//! * `Option` is either `null` or the value, `Result` is either `{ "ok": .. }`
+//!   or `{ "err": .. }`.
+//! * Maps are JSON arrays of key-value pairs.
+//! * Calls are objects with the camelCase names of the module and call, e.g.
+//!   `{ "section": "balances", "method": "transfer", "args": { .. } }`.
+//!
+//! # Example
+//!
+//! ```
+//! use gekko_metadata::scale::TypeRegistry;
+//! use serde_json::json;
+//!
+//! let registry = TypeRegistry::default();
+//! let value = json!({ "id": format!("0x{}", "01".repeat(32)) });
+//!
+//! let encoded = registry.encode_json("MultiAddress", &value).unwrap();
+//! assert_eq!(encoded, [&[0][..], &[1; 32]].concat());
+//!
+//! let decoded = registry.decode_json("LookupSource", &mut encoded.as_slice()).unwrap();
+//! assert_eq!(decoded, value);
+//! ```
+
+use super::{invalid_value, Shape, Type, TypeDef, TypeRegistry, Variant, MAX_DEPTH};
+use crate::scale::Value;
+use crate::{Error, Result};
+use serde_json::{Map, Value as Json};
+
+/// The largest integer which can be represented by a JSON number without loss
+/// of precision in JavaScript.
+const MAX_SAFE_INTEGER: u128 = (1 << 53) - 1;
+
+impl TypeRegistry {
+    /// Decodes the value of the described type into its JSON representation,
+    /// advancing the input past it. See the [module documentation](self) for
+    /// the format.
+    pub fn decode_json(&self, ty: &str, input: &mut &[u8]) -> Result<Json> {
+        self.decode_json_type(&ty.parse()?, input)
+    }
+    /// Decodes the value of the type into its JSON representation, advancing
+    /// the input past it.
+    pub fn decode_json_type(&self, ty: &Type, input: &mut &[u8]) -> Result<Json> {
+        let value = self.decode_value_type(ty, input)?;
+        self.value_to_json_inner(ty, &value, 0)
+    }
+    /// Encodes the JSON representation of a value of the described type. See
+    /// the [module documentation](self) for the format.
+    pub fn encode_json(&self, ty: &str, value: &Json) -> Result<Vec<u8>> {
+        self.encode_json_type(&ty.parse()?, value)
+    }
+    /// Encodes the JSON representation of a value of the type.
+    pub fn encode_json_type(&self, ty: &Type, value: &Json) -> Result<Vec<u8>> {
+        let value = self.json_to_value_inner(ty, value, 0)?;
+        self.encode_value_type(ty, &value)
+    }
+    /// Converts the value of the described type into its JSON representation,
+    /// without encoding it.
+    pub fn value_to_json(&self, ty: &str, value: &Value) -> Result<Json> {
+        self.value_to_json_inner(&ty.parse()?, value, 0)
+    }
+    /// Converts the JSON representation of a value of the described type into
+    /// the value, without encoding it.
+    pub fn json_to_value(&self, ty: &str, value: &Json) -> Result<Value> {
+        self.json_to_value_inner(&ty.parse()?, value, 0)
+    }
+    fn value_to_json_inner(&self, ty: &Type, value: &Value, depth: usize) -> Result<Json> {
+        if depth > MAX_DEPTH {
+            return Err(Error::InvalidTypeDefinition(ty.to_string()));
+        }
+
+        let depth = depth + 1;
+        let json = match (self.resolve(ty)?, value) {
+            (Shape::Bool, Value::Bool(value)) => Json::Bool(*value),
+            (Shape::UInt(width), Value::UInt(n)) => uint_json(*n, width),
+            (Shape::Compact, Value::UInt(n)) => uint_json(*n, 16),
+            (Shape::Int(_), Value::Int(n)) => int_json(*n),
+            (Shape::Unit, _) => Json::Null,
+            (Shape::Text, Value::Text(text)) => Json::String(text.clone()),
+            (Shape::Bytes, Value::Bytes(bytes))
+            | (Shape::ByteArray(_), Value::Bytes(bytes))
+            | (Shape::Era, Value::Bytes(bytes)) => hex_json(bytes),
+            // The bits, least significant bit first.
+            (Shape::BitVec, Value::Sequence(bits)) => {
+                let mut bytes = vec![0u8; bits.len().div_ceil(8)];
+                for (bit, _) in bits
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, set)| set.as_bool() == Some(true))
+                {
+                    bytes[bit / 8] |= 1 << (bit % 8);
+                }
+
+                hex_json(&bytes)
+            }
+            (Shape::Array(elem, _), Value::Sequence(values))
+            | (Shape::Sequence(elem), Value::Sequence(values)) => values
+                .iter()
+                .map(|value| self.value_to_json_inner(elem, value, depth))
+                .collect::<Result<_>>()
+                .map(Json::Array)?,
+            (Shape::Tuple(types), Value::Sequence(values)) if types.len() == values.len() => types
+                .iter()
+                .zip(values)
+                .map(|(ty, value)| self.value_to_json_inner(ty, value, depth))
+                .collect::<Result<_>>()
+                .map(Json::Array)?,
+            (Shape::Map(key, elem), Value::Sequence(pairs)) => pairs
+                .iter()
+                .map(|pair| match pair.as_sequence() {
+                    Some([k, v]) => Ok(Json::Array(vec![
+                        self.value_to_json_inner(key, k, depth)?,
+                        self.value_to_json_inner(elem, v, depth)?,
+                    ])),
+                    _ => Err(invalid_value(ty, "expected a key-value pair")),
+                })
+                .collect::<Result<_>>()
+                .map(Json::Array)?,
+            (Shape::Option(inner), Value::Variant(name, value)) => match name.as_str() {
+                "None" => Json::Null,
+                "Some" => self.value_to_json_inner(inner, value, depth)?,
+                _ => return Err(invalid_value(ty, "expected `None` or `Some`")),
+            },
+            (Shape::Result(ok, err), Value::Variant(name, value)) => {
+                let (key, inner) = match name.as_str() {
+                    "Ok" => ("ok", ok),
+                    "Err" => ("err", err),
+                    _ => return Err(invalid_value(ty, "expected `Ok` or `Err`")),
+                };
+
+                let mut object = Map::new();
+                object.insert(
+                    key.to_string(),
+                    self.value_to_json_inner(inner, value, depth)?,
+                );
+                Json::Object(object)
+            }
+            (Shape::Def("Call", TypeDef::Enum(modules)), value) => {
+                self.call_value_to_json(ty, modules, value, depth)?
+            }
+            (Shape::Def(_, def), value) => self.def_value_to_json(ty, def, value, depth)?,
+            _ => return Err(invalid_value(ty, format!("unexpected value {}", value))),
+        };
+
+        Ok(json)
+    }
+    fn def_value_to_json(
+        &self,
+        ty: &Type,
+        def: &TypeDef,
+        value: &Value,
+        depth: usize,
+    ) -> Result<Json> {
+        match (def, value) {
+            (TypeDef::Alias(alias), value) => self.value_to_json_inner(alias, value, depth),
+            (TypeDef::Struct(fields), value) => fields
+                .iter()
+                .map(|(name, field)| {
+                    let value = find_field(ty, value, name)?;
+                    Ok((name.clone(), self.value_to_json_inner(field, value, depth)?))
+                })
+                .collect::<Result<_>>()
+                .map(Json::Object),
+            (TypeDef::Enum(variants), Value::Variant(name, data)) => {
+                let variant = find_variant(ty, variants, name)?;
+                if variants.iter().all(|variant| variant.def.is_none()) {
+                    return Ok(Json::String(variant.name.clone()));
+                }
+
+                let value = match &variant.def {
+                    Some(def) => self.def_value_to_json(ty, def, data, depth)?,
+                    None => Json::Null,
+                };
+
+                let mut object = Map::new();
+                object.insert(camel_case(&variant.name), value);
+                Ok(Json::Object(object))
+            }
+            _ => Err(invalid_value(ty, format!("unexpected value {}", value))),
+        }
+    }
+    fn call_value_to_json(
+        &self,
+        ty: &Type,
+        modules: &[Variant],
+        value: &Value,
+        depth: usize,
+    ) -> Result<Json> {
+        let invalid = || invalid_value(ty, "expected a call");
+        let (module_name, call) = value.as_variant().ok_or_else(invalid)?;
+        let (call_name, args) = call.as_variant().ok_or_else(invalid)?;
+
+        let module = find_variant(ty, modules, module_name)?;
+        let call = match &module.def {
+            Some(TypeDef::Enum(calls)) => find_variant(ty, calls, call_name)?,
+            _ => return Err(unknown_call(module_name, call_name)),
+        };
+        let types = match &call.def {
+            Some(TypeDef::Struct(types)) => types.as_slice(),
+            _ => &[],
+        };
+
+        let args = types
+            .iter()
+            .map(|(name, arg)| {
+                let value = find_field(ty, args, name)?;
+                Ok((
+                    camel_case(name),
+                    self.value_to_json_inner(arg, value, depth)?,
+                ))
+            })
+            .collect::<Result<Map<String, Json>>>()?;
+
+        Ok(call_json(&module.name, &call.name, args))
+    }
+    fn json_to_value_inner(&self, ty: &Type, value: &Json, depth: usize) -> Result<Value> {
+        if depth > MAX_DEPTH {
+            return Err(Error::InvalidTypeDefinition(ty.to_string()));
+        }
+
+        let depth = depth + 1;
+        let value = match self.resolve(ty)? {
+            Shape::Bool => value
+                .as_bool()
+                .map(Value::Bool)
+                .ok_or_else(|| invalid_value(ty, "expected a boolean"))?,
+            Shape::UInt(_) | Shape::Compact => Value::UInt(uint(ty, value)?),
+            Shape::Int(_) => Value::Int(int(ty, value)?),
+            Shape::Unit => Value::Unit,
+            Shape::Text => value
+                .as_str()
+                .map(Value::from)
+                .ok_or_else(|| invalid_value(ty, "expected a string"))?,
+            Shape::Bytes | Shape::ByteArray(_) | Shape::Era => Value::Bytes(parse_hex(ty, value)?),
+            // Either the HEX encoded bytes, least significant bit first, or
+            // an array of booleans.
+            Shape::BitVec if value.is_string() => {
+                let bytes = parse_hex(ty, value)?;
+                Value::Sequence(
+                    (0..bytes.len() * 8)
+                        .map(|bit| Value::Bool(bytes[bit / 8] & (1 << (bit % 8)) != 0))
+                        .collect(),
+                )
+            }
+            Shape::BitVec => Value::Sequence(
+                expect_array(ty, value, None)?
+                    .iter()
+                    .map(|bit| {
+                        bit.as_bool()
+                            .map(Value::Bool)
+                            .ok_or_else(|| invalid_value(ty, "expected an array of booleans"))
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            Shape::Array(elem, len) => Value::Sequence(
+                expect_array(ty, value, Some(len))?
+                    .iter()
+                    .map(|value| self.json_to_value_inner(elem, value, depth))
+                    .collect::<Result<_>>()?,
+            ),
+            Shape::Sequence(elem) => Value::Sequence(
+                expect_array(ty, value, None)?
+                    .iter()
+                    .map(|value| self.json_to_value_inner(elem, value, depth))
+                    .collect::<Result<_>>()?,
+            ),
+            Shape::Tuple(types) => Value::Sequence(
+                types
+                    .iter()
+                    .zip(expect_array(ty, value, Some(types.len()))?)
+                    .map(|(ty, value)| self.json_to_value_inner(ty, value, depth))
+                    .collect::<Result<_>>()?,
+            ),
+            Shape::Map(key, elem) => {
+                let pairs: Vec<(Json, &Json)> = match value {
+                    Json::Object(object) => object
+                        .iter()
+                        .map(|(key, value)| (Json::String(key.clone()), value))
+                        .collect(),
+                    _ => expect_array(ty, value, None)?
+                        .iter()
+                        .map(|pair| {
+                            let pair = expect_array(ty, pair, Some(2))?;
+                            Ok((pair[0].clone(), &pair[1]))
+                        })
+                        .collect::<Result<_>>()?,
+                };
+
+                Value::Sequence(
+                    pairs
+                        .iter()
+                        .map(|(k, v)| {
+                            Ok(Value::Sequence(vec![
+                                self.json_to_value_inner(key, k, depth)?,
+                                self.json_to_value_inner(elem, v, depth)?,
+                            ]))
+                        })
+                        .collect::<Result<_>>()?,
+                )
+            }
+            Shape::Option(inner) => match value {
+                Json::Null => Value::unit_variant("None"),
+                _ => Value::variant("Some", self.json_to_value_inner(inner, value, depth)?),
+            },
+            Shape::Result(ok, err) => match single_entry(ty, value)? {
+                (key, value) if key.eq_ignore_ascii_case("ok") => {
+                    Value::variant("Ok", self.json_to_value_inner(ok, value, depth)?)
+                }
+                (key, value) if key.eq_ignore_ascii_case("err") => {
+                    Value::variant("Err", self.json_to_value_inner(err, value, depth)?)
+                }
+                _ => return Err(invalid_value(ty, "expected `ok` or `err`")),
+            },
+            Shape::Def("Call", TypeDef::Enum(modules)) => {
+                self.call_json_to_value(ty, modules, value, depth)?
+            }
+            Shape::Def(_, def) => self.def_json_to_value(ty, def, value, depth)?,
+        };
+
+        Ok(value)
+    }
+    fn def_json_to_value(
+        &self,
+        ty: &Type,
+        def: &TypeDef,
+        value: &Json,
+        depth: usize,
+    ) -> Result<Value> {
+        match def {
+            TypeDef::Alias(alias) => self.json_to_value_inner(alias, value, depth),
+            TypeDef::Struct(fields) => self.fields_json_to_value(ty, fields, value, depth),
+            TypeDef::Enum(variants) => {
+                let (name, data) = match value {
+                    Json::String(name) => (name.as_str(), &Json::Null),
+                    _ => single_entry(ty, value)?,
+                };
+
+                let variant = find_variant(ty, variants, name)?;
+                let data = match &variant.def {
+                    Some(def) => self.def_json_to_value(ty, def, data, depth)?,
+                    None => Value::Unit,
+                };
+
+                Ok(Value::variant(variant.name.clone(), data))
+            }
+        }
+    }
+    /// Converts the fields of a struct, given either as an object or as an
+    /// array in field order.
+    fn fields_json_to_value(
+        &self,
+        ty: &Type,
+        fields: &[(String, Type)],
+        value: &Json,
+        depth: usize,
+    ) -> Result<Value> {
+        if fields.is_empty() {
+            return Ok(Value::Composite(vec![]));
+        }
+
+        if let Json::Array(values) = value {
+            if values.len() != fields.len() {
+                return Err(invalid_value(
+                    ty,
+                    format!("expected {} fields", fields.len()),
+                ));
+            }
+
+            return fields
+                .iter()
+                .zip(values)
+                .map(|((name, field), value)| {
+                    Ok((name.clone(), self.json_to_value_inner(field, value, depth)?))
+                })
+                .collect::<Result<_>>()
+                .map(Value::Composite);
+        }
+
+        let object = value
+            .as_object()
+            .ok_or_else(|| invalid_value(ty, "expected an object"))?;
+
+        fields
+            .iter()
+            .map(|(name, field)| {
+                let value = object
+                    .iter()
+                    .find(|(key, _)| same_name(name, key))
+                    .map(|(_, value)| value)
+                    .ok_or_else(|| invalid_value(ty, format!("missing field {}", name)))?;
+
+                Ok((name.clone(), self.json_to_value_inner(field, value, depth)?))
+            })
+            .collect::<Result<_>>()
+            .map(Value::Composite)
+    }
+    fn call_json_to_value(
+        &self,
+        ty: &Type,
+        modules: &[Variant],
+        value: &Json,
+        depth: usize,
+    ) -> Result<Value> {
+        let (section, method) = call_names(ty, value)?;
+        let module = modules
+            .iter()
+            .find(|module| same_name(&module.name, section))
+            .ok_or_else(|| unknown_call(section, method))?;
+
+        let call = match &module.def {
+            Some(TypeDef::Enum(calls)) => calls
+                .iter()
+                .find(|call| same_name(&call.name, method))
+                .ok_or_else(|| unknown_call(section, method))?,
+            _ => return Err(unknown_call(section, method)),
+        };
+
+        let args = match &call.def {
+            Some(TypeDef::Struct(args)) => {
+                self.fields_json_to_value(ty, args, call_args(value), depth)?
+            }
+            _ => Value::Composite(vec![]),
+        };
+
+        Ok(Value::variant(
+            module.name.clone(),
+            Value::variant(call.name.clone(), args),
+        ))
+    }
+}
+
+/// Converts a name to camelCase, the way polkadot-js names modules, calls,
+/// arguments and enum variants, e.g. `transfer_keep_alive` to
+/// `transferKeepAlive` and `Balances` to `balances`.
+pub(crate) fn camel_case(name: &str) -> String {
+    let mut camel = String::with_capacity(name.len());
+    for (index, part) in name.split('_').filter(|part| !part.is_empty()).enumerate() {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            match index {
+                0 => camel.extend(first.to_lowercase()),
+                _ => camel.extend(first.to_uppercase()),
+            }
+            camel.extend(chars);
+        }
+    }
+
+    camel
+}
+
+/// Whether both names are equal after converting them to camelCase.
+pub(crate) fn same_name(name: &str, other: &str) -> bool {
+    name == other || camel_case(name) == camel_case(other)
+}
+
+/// Builds the polkadot-js representation of a call.
+pub(crate) fn call_json(module: &str, call: &str, args: Map<String, Json>) -> Json {
+    let mut object = Map::new();
+    object.insert("section".to_string(), Json::String(camel_case(module)));
+    object.insert("method".to_string(), Json::String(camel_case(call)));
+    object.insert("args".to_string(), Json::Object(args));
+    Json::Object(object)
+}
+
+/// The module and call names of the polkadot-js representation of a call.
+pub(crate) fn call_names<'a>(ty: &Type, value: &'a Json) -> Result<(&'a str, &'a str)> {
+    let name = |key: &str| {
+        value
+            .get(key)
+            .and_then(Json::as_str)
+            .ok_or_else(|| invalid_value(ty, format!("missing {}", key)))
+    };
+
+    Ok((name("section")?, name("method")?))
+}
+
+/// The arguments of the polkadot-js representation of a call, either an object
+/// or an array in argument order.
+pub(crate) fn call_args(value: &Json) -> &Json {
+    value.get("args").unwrap_or(&Json::Null)
+}
+
+pub(crate) fn unknown_call(module: &str, call: &str) -> Error {
+    Error::UnknownCallName {
+        module: module.to_string(),
+        call: call.to_string(),
+    }
+}
+
+fn find_variant<'a>(ty: &Type, variants: &'a [Variant], name: &str) -> Result<&'a Variant> {
+    variants
+        .iter()
+        .find(|variant| same_name(&variant.name, name))
+        .ok_or_else(|| invalid_value(ty, format!("unknown variant {}", name)))
+}
+
+/// The field of a composite, matched by name.
+fn find_field<'a>(ty: &Type, value: &'a Value, name: &str) -> Result<&'a Value> {
+    match value {
+        Value::Composite(values) => values
+            .iter()
+            .find(|(key, _)| same_name(name, key))
+            .map(|(_, value)| value),
+        _ => None,
+    }
+    .ok_or_else(|| invalid_value(ty, format!("missing field {}", name)))
+}
+
+fn hex_json(bytes: &[u8]) -> Json {
+    Json::String(format!("0x{}", hex::encode(bytes)))
+}
+
+/// Represents the unsigned integer as a JSON number or, if it is too large,
+/// as a big-endian HEX string of the given width.
+fn uint_json(n: u128, width: usize) -> Json {
+    if n <= MAX_SAFE_INTEGER {
+        return Json::from(n as u64);
+    }
+
+    hex_json(&n.to_be_bytes()[16 - width..])
+}
+
+/// Represents the signed integer as a JSON number or, if it is too large, as
+/// a decimal string.
+fn int_json(n: i128) -> Json {
+    if n.unsigned_abs() <= MAX_SAFE_INTEGER {
+        return Json::from(n as i64);
+    }
+
+    Json::String(n.to_string())
+}
+
+fn parse_hex(ty: &Type, value: &Json) -> Result<Vec<u8>> {
+    let hex_str = value
+        .as_str()
+        .ok_or_else(|| invalid_value(ty, "expected a HEX string"))?;
+
+    crate::decode_hex(hex_str).map_err(|_| invalid_value(ty, "invalid HEX string"))
+}
+
+/// Parses a JSON number, a decimal string or a big-endian HEX string.
+fn uint(ty: &Type, value: &Json) -> Result<u128> {
+    match value {
+        Json::Number(n) => n.as_u64().map(u128::from),
+        Json::String(s) => match s.strip_prefix("0x") {
+            Some(hex_str) => u128::from_str_radix(hex_str, 16).ok(),
+            None => s.parse().ok(),
+        },
+        _ => None,
+    }
+    .ok_or_else(|| invalid_value(ty, "expected an unsigned integer"))
+}
+
+/// Parses a JSON number or a decimal string.
+fn int(ty: &Type, value: &Json) -> Result<i128> {
+    match value {
+        Json::Number(n) => n.as_i64().map(i128::from),
+        Json::String(s) => s.parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| invalid_value(ty, "expected an integer"))
+}
+
+fn expect_array<'a>(ty: &Type, value: &'a Json, len: Option<usize>) -> Result<&'a Vec<Json>> {
+    let values = value
+        .as_array()
+        .ok_or_else(|| invalid_value(ty, "expected an array"))?;
+
+    match len {
+        Some(len) if values.len() != len => {
+            Err(invalid_value(ty, format!("expected {} elements", len)))
+        }
+        _ => Ok(values),
+    }
+}
+
+/// The only entry of an object, such as the variant of an enum.
+fn single_entry<'a>(ty: &Type, value: &'a Json) -> Result<(&'a str, &'a Json)> {
+    match value.as_object() {
+        Some(object) if object.len() == 1 => object
+            .iter()
+            .next()
+            .map(|(key, value)| (key.as_str(), value))
+            .ok_or_else(|| invalid_value(ty, "expected a single entry")),
+        _ => Err(invalid_value(ty, "expected an object with a single entry")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parity_scale_codec::{Compact, Encode};
+    use serde_json::json;
+
+    fn registry() -> TypeRegistry {
+        let mut registry = TypeRegistry::default();
+        registry
+            .register_json(
+                r#"{
+                    "Score": { "points": "Compact<u32>", "owner": "AccountId" },
+                    "Status": { "_enum": ["Free", "Reserved"] }
+                }"#,
+            )
+            .unwrap();
+
+        registry
+    }
+
+    /// Encodes the JSON, checks the encoding and decodes it again, both
+    /// directly and through the equivalent value.
+    fn round_trip(registry: &TypeRegistry, ty: &str, json: Json, encoded: &[u8]) {
+        assert_eq!(registry.encode_json(ty, &json).unwrap(), encoded, "{}", ty);
+
+        let input = &mut &*encoded;
+        assert_eq!(registry.decode_json(ty, input).unwrap(), json, "{}", ty);
+        assert!(input.is_empty());
+
+        let value = registry.decode_value(ty, &mut &*encoded).unwrap();
+        assert_eq!(registry.json_to_value(ty, &json).unwrap(), value, "{}", ty);
+        assert_eq!(registry.value_to_json(ty, &value).unwrap(), json, "{}", ty);
+    }
+
+    #[test]
+    fn round_trip_primitives() {
+        let registry = registry();
+
+        round_trip(&registry, "bool", json!(true), &[1]);
+        round_trip(&registry, "u32", json!(7), &[7, 0, 0, 0]);
+        round_trip(&registry, "i16", json!(-2), &(-2i16).encode());
+        round_trip(&registry, "Text", json!("gekko"), &"gekko".encode());
+        round_trip(&registry, "Bytes", json!("0x0102"), &[8, 1, 2]);
+        round_trip(&registry, "Null", json!(null), &[]);
+
+        // Integers beyond 2^53 - 1 are big-endian HEX strings of the width of
+        // the type, or decimal strings if signed.
+        round_trip(
+            &registry,
+            "u64",
+            json!("0xffffffffffffffff"),
+            &u64::MAX.encode(),
+        );
+        round_trip(
+            &registry,
+            "i64",
+            json!(i64::MIN.to_string()),
+            &i64::MIN.encode(),
+        );
+
+        // Decimal strings are accepted as well.
+        assert_eq!(
+            registry.encode_json("u128", &json!("1000")).unwrap(),
+            1000u128.encode()
+        );
+    }
+
+    #[test]
+    fn round_trip_compact() {
+        let registry = registry();
+
+        for n in [0u64, 63, 64, 1 << 14, 1 << 30, (1 << 53) - 1] {
+            round_trip(
+                &registry,
+                "Compact<Balance>",
+                json!(n),
+                &Compact(n).encode(),
+            );
+        }
+
+        round_trip(
+            &registry,
+            "Compact<Balance>",
+            json!(format!("0x{}", "ff".repeat(16))),
+            &Compact(u128::MAX).encode(),
+        );
+    }
+
+    #[test]
+    fn round_trip_composites() {
+        let registry = registry();
+        let encoded = [&[4][..], &[7; 32]].concat();
+
+        round_trip(
+            &registry,
+            "Score",
+            json!({ "points": 1, "owner": format!("0x{}", "07".repeat(32)) }),
+            &encoded,
+        );
+
+        // Fields can be given in order.
+        let fields = json!([1, format!("0x{}", "07".repeat(32))]);
+        assert_eq!(registry.encode_json("Score", &fields).unwrap(), encoded);
+    }
+
+    #[test]
+    fn round_trip_variants() {
+        let registry = registry();
+
+        // Enums without data are the name of the variant, others are objects
+        // with the camelCase name of the variant as the only key.
+        round_trip(&registry, "Status", json!("Reserved"), &[1]);
+        round_trip(
+            &registry,
+            "RewardDestination",
+            json!({ "staked": null }),
+            &[0],
+        );
+        round_trip(
+            &registry,
+            "MultiAddress",
+            json!({ "id": format!("0x{}", "01".repeat(32)) }),
+            &[&[0][..], &[1; 32]].concat(),
+        );
+        round_trip(&registry, "Option<u32>", json!(null), &[0]);
+        round_trip(&registry, "Option<u32>", json!(1), &[1, 1, 0, 0, 0]);
+        round_trip(&registry, "Option<bool>", json!(false), &[2]);
+        round_trip(&registry, "Result<u8, Text>", json!({ "ok": 1 }), &[0, 1]);
+
+        let err = registry.encode_json("Status", &json!("Unknown"));
+        assert!(matches!(err, Err(Error::InvalidValue { .. })));
+    }
+
+    #[test]
+    fn round_trip_sequences() {
+        let registry = registry();
+
+        round_trip(
+            &registry,
+            "Vec<u32>",
+            json!([1, 2]),
+            &vec![1u32, 2].encode(),
+        );
+        round_trip(
+            &registry,
+            "(u8, Text)",
+            json!([1, "a"]),
+            &(1u8, "a").encode(),
+        );
+        round_trip(&registry, "[u16; 2]", json!([1, 2]), &[1, 0, 2, 0]);
+        round_trip(&registry, "[u8; 2]", json!("0x0102"), &[1, 2]);
+        round_trip(
+            &registry,
+            "BTreeMap<u32, bool>",
+            json!([[1, true]]),
+            &[4, 1, 0, 0, 0, 1],
+        );
+
+        let err = registry.encode_json("(u8, Text)", &json!([1]));
+        assert!(matches!(err, Err(Error::InvalidValue { .. })));
+    }
+
+    #[test]
+    fn round_trip_bit_sequences() {
+        let registry = registry();
+
+        // The bytes of the bits, least significant bit first.
+        round_trip(
+            &registry,
+            "BitVec",
+            json!("0x0502"),
+            &[64, 0b0000_0101, 0b0000_0010],
+        );
+
+        // Bits can also be given individually.
+        let encoded = registry.encode_json("BitVec", &json!([true, false, true]));
+        assert_eq!(encoded.unwrap(), [12, 0b0000_0101]);
+    }
+
+    #[test]
+    fn camel_case_names() {
+        assert_eq!(camel_case("transfer_keep_alive"), "transferKeepAlive");
+        assert_eq!(camel_case("Balances"), "balances");
+        assert_eq!(
+            camel_case("ElectionProviderMultiPhase"),
+            "electionProviderMultiPhase"
+        );
+        assert!(same_name("transfer_keep_alive", "transferKeepAlive"));
+        assert!(!same_name("transfer", "transferKeepAlive"));
+    }
+}
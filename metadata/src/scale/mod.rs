@@ -0,0 +1,504 @@
+//! Decoding of SCALE encoded values whose type is only known at runtime, as
+//! described by the type strings of the runtime metadata.
+//!
+//! Metadata up to V13 only contains the names of types, such as
+//! `Vec<T::AccountId>`, but not their definitions. The [`TypeRegistry`]
+//! provides those definitions, in the JSON format used by
+//! [polkadot-js](https://polkadot.js.org/docs/api/start/types.extend). The
+//! default registry contains the types of Polkadot and Kusama, which can be
+//! extended or overwritten by custom definitions.
+//!
+//! # Example
+//!
+//! ```
+//! use gekko_metadata::scale::TypeRegistry;
+//!
+//! let mut registry = TypeRegistry::default();
+//! registry
+//!     .register_json(r#"{ "Score": { "points": "Compact<u32>", "owner": "AccountId" } }"#)
+//!     .unwrap();
+//!
+//! let mut encoded: &[u8] = &[0x04; 34];
+//! registry.skip("Vec<Score>", &mut encoded).unwrap();
+//!
+//! assert!(encoded.is_empty());
+//! ```
+
+use crate::version::MetadataV13;
+use crate::{Error, Result};
+use parity_scale_codec::{Compact, Decode};
+use serde_json::Value as Json;
+use std::collections::HashMap;
+
+pub mod json;
+mod ty;
+mod value;
+
+pub use ty::Type;
+pub use value::Value;
+
+/// The type definitions of Polkadot and Kusama, in the polkadot-js format.
+const DEFAULT_TYPES: &str = include_str!("types.json");
+
+/// The maximum nesting of types, protecting against cyclic definitions.
+const MAX_DEPTH: usize = 128;
+
+/// The definition of a named type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TypeDef {
+    /// The type is an alias for another type.
+    Alias(Type),
+    /// A struct with named fields, in encoding order.
+    Struct(Vec<(String, Type)>),
+    /// An enum with its variants.
+    Enum(Vec<Variant>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Variant {
+    /// The index of the variant, which is the first byte of the encoded value.
+    index: u8,
+    name: String,
+    /// The definition of the data, or `None` if the variant has no data.
+    def: Option<TypeDef>,
+}
+
+/// A registry of type definitions, used to decode values of types described by
+/// the runtime metadata.
+///
+/// Generic parameters of registered types are ignored, so `BalanceOf<T>` and
+/// `BalanceOf<T, I>` both resolve to the definition of `BalanceOf`. Primitive
+/// types (`u8` to `u128`, `i8` to `i128`, `bool`), `Vec`, `Option`, `Result`,
+/// `Compact`, `Box`, `BTreeMap`, `BTreeSet`, `PhantomData`, `BitVec`, `Era`,
+/// tuples and arrays are always available. The `Call` type must be registered with
+/// [`register_calls`](Self::register_calls).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeRegistry {
+    types: HashMap<String, TypeDef>,
+}
+
+impl Default for TypeRegistry {
+    /// Creates a registry with the types of Polkadot and Kusama.
+    fn default() -> Self {
+        let mut registry = TypeRegistry::new();
+        registry
+            .register_json(DEFAULT_TYPES)
+            .expect("bundled type definitions are valid");
+
+        registry
+    }
+}
+
+impl TypeRegistry {
+    /// Creates an empty registry, which only supports the built-in types.
+    pub fn new() -> Self {
+        TypeRegistry {
+            types: HashMap::new(),
+        }
+    }
+    /// Registers all type definitions of the JSON object, in the format used
+    /// by polkadot-js. Existing definitions are overwritten.
+    ///
+    /// * `"Balance": "u128"` - An alias for another type.
+    /// * `"Timepoint": { "height": "BlockNumber", "index": "u32" }` - A struct.
+    /// * `"Status": { "_enum": ["Free", "Reserved"] }` - An enum without data.
+    /// * `"Outcome": { "_enum": { "Complete": "Weight", "Error": "XcmError" } }`
+    ///   \- An enum with data, where `"Null"` indicates no data.
+    pub fn register_json(&mut self, json: &str) -> Result<()> {
+        let definitions = match serde_json::from_str(json).map_err(Error::ParseTypeDefinitions)? {
+            Json::Object(definitions) => definitions,
+            _ => return Err(Error::InvalidTypeDefinition("<root>".to_string())),
+        };
+
+        for (name, definition) in &definitions {
+            self.register(name, definition)?;
+        }
+
+        Ok(())
+    }
+    /// Registers the definition of a single type. See
+    /// [`register_json`](Self::register_json) for the supported format.
+    pub fn register(&mut self, name: &str, definition: &Json) -> Result<()> {
+        let def = parse_definition(definition)
+            .ok_or_else(|| Error::InvalidTypeDefinition(name.to_string()))??;
+
+        self.types.insert(name.to_string(), def);
+        Ok(())
+    }
+    /// Registers the `Call` type of the runtime, which is required to decode
+    /// arguments containing calls, such as those of `Utility::batch`. The
+    /// definition is specific to the metadata, overwriting any previously
+    /// registered calls.
+    pub fn register_calls(&mut self, metadata: &MetadataV13) -> Result<()> {
+        let mut modules = vec![];
+        for module in &metadata.modules {
+            let calls = match &module.calls {
+                Some(calls) => calls,
+                None => continue,
+            };
+
+            let mut variants = Vec::with_capacity(calls.len());
+            for (index, call) in calls.iter().enumerate() {
+                let args = call
+                    .arguments
+                    .iter()
+                    .map(|arg| Ok((arg.name.clone(), arg.ty.parse()?)))
+                    .collect::<Result<Vec<(String, Type)>>>()?;
+
+                variants.push(Variant::new(index, &call.name, Some(TypeDef::Struct(args))));
+            }
+
+            modules.push(Variant {
+                index: module.index,
+                name: module.name.clone(),
+                def: Some(TypeDef::Enum(variants)),
+            });
+        }
+
+        self.types
+            .insert("Call".to_string(), TypeDef::Enum(modules));
+        Ok(())
+    }
+    /// Whether a definition for the named type exists.
+    pub fn contains(&self, name: &str) -> bool {
+        self.types.contains_key(name)
+    }
+    /// Advances the input past the encoded value of the described type,
+    /// without decoding it.
+    pub fn skip(&self, ty: &str, input: &mut &[u8]) -> Result<()> {
+        self.skip_type(&ty.parse()?, input)
+    }
+    /// Advances the input past the encoded value of the type, without
+    /// decoding it.
+    pub fn skip_type(&self, ty: &Type, input: &mut &[u8]) -> Result<()> {
+        self.skip_inner(ty, input, 0)
+    }
+    fn skip_inner(&self, ty: &Type, input: &mut &[u8], depth: usize) -> Result<()> {
+        if depth > MAX_DEPTH {
+            return Err(Error::InvalidTypeDefinition(ty.to_string()));
+        }
+
+        let depth = depth + 1;
+        match self.resolve(ty)? {
+            Shape::Bool => advance(ty, input, 1),
+            Shape::UInt(width) | Shape::Int(width) => advance(ty, input, width),
+            Shape::Unit => Ok(()),
+            Shape::Bytes | Shape::Text => {
+                let len = decode_len(ty, input)?;
+                advance(ty, input, len)
+            }
+            // The length prefix is the number of bits, not bytes.
+            Shape::BitVec => {
+                let bits = decode_len(ty, input)?;
+                advance(ty, input, bits.div_ceil(8))
+            }
+            // Immortal eras are encoded as a single zero byte.
+            Shape::Era => match input.first() {
+                Some(0) => advance(ty, input, 1),
+                _ => advance(ty, input, 2),
+            },
+            Shape::Compact => {
+                let first = *input.first().ok_or_else(|| not_enough_data(ty))?;
+                let len = match first & 0b11 {
+                    0b00 => 1,
+                    0b01 => 2,
+                    0b10 => 4,
+                    _ => (first >> 2) as usize + 5,
+                };
+
+                advance(ty, input, len)
+            }
+            Shape::ByteArray(len) => advance(ty, input, len),
+            Shape::Array(elem, len) => {
+                (0..len).try_for_each(|_| self.skip_inner(elem, input, depth))
+            }
+            Shape::Tuple(types) => types
+                .iter()
+                .try_for_each(|ty| self.skip_inner(ty, input, depth)),
+            Shape::Sequence(elem) => {
+                let len = decode_len(ty, input)?;
+                (0..len).try_for_each(|_| self.skip_inner(elem, input, depth))
+            }
+            Shape::Map(key, value) => {
+                let len = decode_len(ty, input)?;
+                (0..len).try_for_each(|_| {
+                    self.skip_inner(key, input, depth)?;
+                    self.skip_inner(value, input, depth)
+                })
+            }
+            // `Option<bool>` is encoded as a single byte.
+            Shape::Option(inner) if is_bool(inner) => match decode_byte(ty, input)? {
+                0..=2 => Ok(()),
+                _ => Err(invalid_variant(ty)),
+            },
+            Shape::Option(inner) => match decode_byte(ty, input)? {
+                0 => Ok(()),
+                1 => self.skip_inner(inner, input, depth),
+                _ => Err(invalid_variant(ty)),
+            },
+            Shape::Result(ok, err) => match decode_byte(ty, input)? {
+                0 => self.skip_inner(ok, input, depth),
+                1 => self.skip_inner(err, input, depth),
+                _ => Err(invalid_variant(ty)),
+            },
+            Shape::Def(_, def) => self.skip_def(ty, def, input, depth),
+        }
+    }
+    fn skip_def(&self, ty: &Type, def: &TypeDef, input: &mut &[u8], depth: usize) -> Result<()> {
+        match def {
+            TypeDef::Alias(alias) => self.skip_inner(alias, input, depth),
+            TypeDef::Struct(fields) => fields
+                .iter()
+                .try_for_each(|(_, field)| self.skip_inner(field, input, depth)),
+            TypeDef::Enum(variants) => {
+                let index = decode_byte(ty, input)?;
+                match variants.iter().find(|variant| variant.index == index) {
+                    Some(Variant { def: Some(def), .. }) => self.skip_def(ty, def, input, depth),
+                    Some(Variant { def: None, .. }) => Ok(()),
+                    None => Err(invalid_variant(ty)),
+                }
+            }
+        }
+    }
+    /// Resolves the type to its shape, which is how the skipping, decoding
+    /// and encoding of a value is determined. Built-in types are resolved by
+    /// name, pointer types such as `Box<T>` resolve to the shape of `T`, and
+    /// any other type to its registered definition.
+    fn resolve<'a>(&'a self, ty: &'a Type) -> Result<Shape<'a>> {
+        let (name, params) = match ty {
+            Type::Named { name, params } => (name.as_str(), params.as_slice()),
+            Type::Tuple(types) if types.is_empty() => return Ok(Shape::Unit),
+            Type::Tuple(types) => return Ok(Shape::Tuple(types)),
+            Type::Array { ty, len } if is_byte(ty) => return Ok(Shape::ByteArray(*len)),
+            Type::Array { ty, len } => return Ok(Shape::Array(ty, *len)),
+        };
+
+        let param = |index: usize| {
+            params
+                .get(index)
+                .ok_or_else(|| Error::InvalidTypeString(ty.to_string()))
+        };
+
+        let shape = match name {
+            "bool" => Shape::Bool,
+            "u8" => Shape::UInt(1),
+            "u16" => Shape::UInt(2),
+            "u32" => Shape::UInt(4),
+            "u64" => Shape::UInt(8),
+            "u128" => Shape::UInt(16),
+            "i8" => Shape::Int(1),
+            "i16" => Shape::Int(2),
+            "i32" => Shape::Int(4),
+            "i64" => Shape::Int(8),
+            "i128" => Shape::Int(16),
+            "Null" | "PhantomData" => Shape::Unit,
+            "Bytes" => Shape::Bytes,
+            "Text" | "String" | "str" => Shape::Text,
+            "BitVec" => Shape::BitVec,
+            "Era" => Shape::Era,
+            "Compact" => Shape::Compact,
+            "Vec" | "VecDeque" | "BTreeSet" => match param(0)? {
+                elem if is_byte(elem) => Shape::Bytes,
+                elem => Shape::Sequence(elem),
+            },
+            "BTreeMap" | "HashMap" => Shape::Map(param(0)?, param(1)?),
+            "Option" => Shape::Option(param(0)?),
+            "Result" => Shape::Result(param(0)?, param(1)?),
+            "Box" | "Rc" | "Arc" | "Cow" => return self.resolve(param(0)?),
+            _ => {
+                let def = self
+                    .types
+                    .get(name)
+                    .ok_or_else(|| Error::UnknownType(name.to_string()))?;
+
+                Shape::Def(name, def)
+            }
+        };
+
+        Ok(shape)
+    }
+}
+
+/// How a type is encoded, see [`TypeRegistry::resolve`].
+#[derive(Debug, Clone, Copy)]
+enum Shape<'a> {
+    Bool,
+    /// An unsigned integer of the given width in bytes.
+    UInt(usize),
+    /// A signed integer of the given width in bytes.
+    Int(usize),
+    /// The unit type, which encodes no data.
+    Unit,
+    /// A length-prefixed byte sequence, such as `Bytes` or `Vec<u8>`.
+    Bytes,
+    Text,
+    BitVec,
+    Era,
+    /// A compact encoded unsigned integer.
+    Compact,
+    /// A fixed-size byte array, such as `[u8; 32]`.
+    ByteArray(usize),
+    Array(&'a Type, usize),
+    Tuple(&'a [Type]),
+    /// A length-prefixed sequence of the element type.
+    Sequence(&'a Type),
+    Map(&'a Type, &'a Type),
+    Option(&'a Type),
+    Result(&'a Type, &'a Type),
+    /// A registered type with its name and definition.
+    Def(&'a str, &'a TypeDef),
+}
+
+impl Variant {
+    fn new(index: usize, name: &str, def: Option<TypeDef>) -> Self {
+        Variant {
+            index: index as u8,
+            name: name.to_string(),
+            def,
+        }
+    }
+}
+
+/// Parses a type definition in the polkadot-js format. Returns `None` if the
+/// format is not supported.
+fn parse_definition(definition: &Json) -> Option<Result<TypeDef>> {
+    let object = match definition {
+        Json::String(alias) => return Some(alias.parse().map(TypeDef::Alias)),
+        Json::Object(object) => object,
+        _ => return None,
+    };
+
+    if let Some(variants) = object.get("_enum") {
+        return match variants {
+            Json::Array(variants) => variants
+                .iter()
+                .enumerate()
+                .map(|(index, variant)| {
+                    variant
+                        .as_str()
+                        .map(|name| Ok(Variant::new(index, name, None)))
+                })
+                .collect::<Option<Result<_>>>()
+                .map(|variants| variants.map(TypeDef::Enum)),
+            Json::Object(variants) => variants
+                .iter()
+                .enumerate()
+                .map(|(index, (name, def))| {
+                    if def.as_str() == Some("Null") {
+                        return Some(Ok(Variant::new(index, name, None)));
+                    }
+
+                    parse_definition(def)
+                        .map(|def| def.map(|def| Variant::new(index, name, Some(def))))
+                })
+                .collect::<Option<Result<_>>>()
+                .map(|variants| variants.map(TypeDef::Enum)),
+            _ => None,
+        };
+    }
+
+    if let Some(set) = object.get("_set") {
+        let bits = set.get("_bitLength").and_then(Json::as_u64).unwrap_or(8);
+        return Some(Ok(TypeDef::Alias(Type::named(format!("u{}", bits)))));
+    }
+
+    object
+        .iter()
+        // Keys such as `_alias` are options, not fields.
+        .filter(|(name, _)| !name.starts_with('_'))
+        .map(|(name, field)| {
+            field
+                .as_str()
+                .map(|field| field.parse().map(|field| (name.clone(), field)))
+        })
+        .collect::<Option<Result<_>>>()
+        .map(|fields| fields.map(TypeDef::Struct))
+}
+
+fn is_byte(ty: &Type) -> bool {
+    matches!(ty, Type::Named { name, .. } if name == "u8")
+}
+
+fn is_bool(ty: &Type) -> bool {
+    matches!(ty, Type::Named { name, .. } if name == "bool")
+}
+
+fn advance(ty: &Type, input: &mut &[u8], len: usize) -> Result<()> {
+    if input.len() < len {
+        return Err(not_enough_data(ty));
+    }
+
+    *input = &input[len..];
+    Ok(())
+}
+
+fn take(ty: &Type, input: &mut &[u8], len: usize) -> Result<Vec<u8>> {
+    if input.len() < len {
+        return Err(not_enough_data(ty));
+    }
+
+    let (bytes, rest) = input.split_at(len);
+    *input = rest;
+    Ok(bytes.to_vec())
+}
+
+fn decode<T: Decode>(ty: &Type, input: &mut &[u8]) -> Result<T> {
+    T::decode(input).map_err(|source| Error::DecodeType {
+        ty: ty.to_string(),
+        source,
+    })
+}
+
+/// Decodes a little-endian unsigned integer of the given width in bytes.
+fn decode_uint(ty: &Type, input: &mut &[u8], width: usize) -> Result<u128> {
+    let mut buf = [0; 16];
+    buf[..width].copy_from_slice(&take(ty, input, width)?);
+    Ok(u128::from_le_bytes(buf))
+}
+
+/// Decodes a little-endian signed integer of the given width in bytes.
+fn decode_int(ty: &Type, input: &mut &[u8], width: usize) -> Result<i128> {
+    let bytes = take(ty, input, width)?;
+    let mut buf = match bytes[width - 1] & 0x80 {
+        0 => [0; 16],
+        _ => [0xff; 16],
+    };
+    buf[..width].copy_from_slice(&bytes);
+    Ok(i128::from_le_bytes(buf))
+}
+
+fn decode_byte(ty: &Type, input: &mut &[u8]) -> Result<u8> {
+    let byte = *input.first().ok_or_else(|| not_enough_data(ty))?;
+    *input = &input[1..];
+    Ok(byte)
+}
+
+fn decode_len(ty: &Type, input: &mut &[u8]) -> Result<usize> {
+    Compact::<u32>::decode(input)
+        .map(|len| len.0 as usize)
+        .map_err(|source| Error::DecodeType {
+            ty: ty.to_string(),
+            source,
+        })
+}
+
+fn not_enough_data(ty: &Type) -> Error {
+    Error::DecodeType {
+        ty: ty.to_string(),
+        source: "Not enough data to decode value".into(),
+    }
+}
+
+fn invalid_variant(ty: &Type) -> Error {
+    Error::DecodeType {
+        ty: ty.to_string(),
+        source: "Invalid enum variant index".into(),
+    }
+}
+
+fn invalid_value<T: Into<String>>(ty: &Type, reason: T) -> Error {
+    Error::InvalidValue {
+        ty: ty.to_string(),
+        reason: reason.into(),
+    }
+}
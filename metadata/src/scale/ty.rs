@@ -0,0 +1,254 @@
+use crate::{Error, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// A parsed type description of the runtime metadata, such as
+/// `Vec<(T::AccountId, BalanceOf<T>)>`.
+///
+/// Paths are reduced to their last segment, so `T::AccountId` and
+/// `sp_std::marker::PhantomData` are represented as `AccountId` and
+/// `PhantomData`, respectively. Qualified paths like `<T as Config>::Call` are
+/// reduced to the associated type, except for `<T::Lookup as
+/// StaticLookup>::Source`, which is represented as `LookupSource`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Type {
+    /// A named type with its generic parameters, if any.
+    Named { name: String, params: Vec<Type> },
+    /// A tuple of types. The empty tuple is the unit type.
+    Tuple(Vec<Type>),
+    /// A fixed-size array, e.g. `[u8; 32]`.
+    Array { ty: Box<Type>, len: usize },
+}
+
+impl Type {
+    /// Creates a named type without generic parameters.
+    pub fn named<T: Into<String>>(name: T) -> Self {
+        Type::Named {
+            name: name.into(),
+            params: vec![],
+        }
+    }
+}
+
+impl FromStr for Type {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            pos: 0,
+            input: s,
+        };
+
+        let ty = parser.parse_type()?;
+        if parser.pos != parser.tokens.len() {
+            return Err(parser.error());
+        }
+
+        Ok(ty)
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Named { name, params } => {
+                write!(f, "{}", name)?;
+                if !params.is_empty() {
+                    write!(f, "<{}>", join(params))?;
+                }
+                Ok(())
+            }
+            Type::Tuple(types) => write!(f, "({})", join(types)),
+            Type::Array { ty, len } => write!(f, "[{}; {}]", ty, len),
+        }
+    }
+}
+
+fn join(types: &[Type]) -> String {
+    types
+        .iter()
+        .map(|ty| ty.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Punct(char),
+    PathSep,
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut ident = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    ident.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            // Lifetimes, such as in `&'static [u8]`, are irrelevant.
+            '\'' => {
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    chars.next();
+                }
+            }
+            ':' if chars.peek() == Some(&':') => {
+                chars.next();
+                tokens.push(Token::PathSep);
+            }
+            '<' | '>' | '(' | ')' | '[' | ']' | ';' | ',' | '&' => tokens.push(Token::Punct(c)),
+            _ => return Err(Error::InvalidTypeString(s.to_string())),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    input: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self) -> Error {
+        Error::InvalidTypeString(self.input.to_string())
+    }
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+    fn next(&mut self) -> Result<Token> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| self.error())?;
+        self.pos += 1;
+        Ok(token)
+    }
+    fn eat(&mut self, punct: char) -> bool {
+        if self.peek() == Some(&Token::Punct(punct)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+    fn expect(&mut self, punct: char) -> Result<()> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+    fn ident(&mut self) -> Result<String> {
+        match self.next()? {
+            Token::Ident(ident) => Ok(ident),
+            _ => Err(self.error()),
+        }
+    }
+    fn parse_type(&mut self) -> Result<Type> {
+        // References encode like the referenced type.
+        while self.eat('&') {}
+
+        if self.eat('(') {
+            let mut types = self.parse_list(')')?;
+            // A parenthesized type is not a tuple.
+            return Ok(if types.len() == 1 {
+                types.remove(0)
+            } else {
+                Type::Tuple(types)
+            });
+        }
+
+        if self.eat('[') {
+            let ty = self.parse_type()?;
+            // Slices encode like vectors.
+            if self.eat(']') {
+                return Ok(Type::Named {
+                    name: "Vec".to_string(),
+                    params: vec![ty],
+                });
+            }
+
+            self.expect(';')?;
+            let len = self.ident()?.parse().map_err(|_| self.error())?;
+            self.expect(']')?;
+
+            return Ok(Type::Array {
+                ty: Box::new(ty),
+                len,
+            });
+        }
+
+        if self.eat('<') {
+            let ty = self.parse_type()?;
+            if self.ident()? != "as" {
+                return Err(self.error());
+            }
+            self.parse_type()?;
+            self.expect('>')?;
+
+            let assoc = self.parse_path()?;
+            return Ok(match (ty, assoc) {
+                (Type::Named { name, .. }, Type::Named { name: assoc, .. })
+                    if name == "Lookup" && assoc == "Source" =>
+                {
+                    Type::named("LookupSource")
+                }
+                (_, assoc) => assoc,
+            });
+        }
+
+        self.parse_path()
+    }
+    /// Parses a path, such as `sp_std::marker::PhantomData<T>`, reducing it to
+    /// the last segment. Must be preceded by `::` if the path is the
+    /// associated type of a qualified path.
+    fn parse_path(&mut self) -> Result<Type> {
+        if self.peek() == Some(&Token::PathSep) {
+            self.pos += 1;
+        }
+
+        let mut name = self.ident()?;
+        while self.peek() == Some(&Token::PathSep) {
+            self.pos += 1;
+            name = self.ident()?;
+        }
+
+        let params = if self.eat('<') {
+            self.parse_list('>')?
+        } else {
+            vec![]
+        };
+
+        Ok(Type::Named { name, params })
+    }
+    fn parse_list(&mut self, close: char) -> Result<Vec<Type>> {
+        let mut types = vec![];
+        while !self.eat(close) {
+            types.push(self.parse_type()?);
+            if !self.eat(',') {
+                self.expect(close)?;
+                break;
+            }
+        }
+
+        Ok(types)
+    }
+}
@@ -0,0 +1,824 @@
+//! A dynamic representation of SCALE encoded values, for working with values
+//! whose type is only known at runtime.
+
+use super::json::same_name;
+use super::{
+    decode, decode_byte, decode_int, decode_len, decode_uint, invalid_value, invalid_variant,
+    is_bool, take, Shape, Type, TypeDef, TypeRegistry, MAX_DEPTH,
+};
+use crate::{Error, Result};
+use parity_scale_codec::{Compact, Encode};
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::fmt;
+
+/// A dynamically typed value, which mirrors the structure of its type without
+/// requiring generated Rust types:
+///
+/// * Unsigned and signed integers, including compact integers, are
+///   [`UInt`](Value::UInt) and [`Int`](Value::Int), respectively.
+/// * Byte sequences and arrays, such as `Bytes` or `AccountId`, are
+///   [`Bytes`](Value::Bytes), `Text` is [`Text`](Value::Text). Eras are the
+///   raw bytes of the encoded era.
+/// * Structs are [`Composite`](Value::Composite)s of the named fields.
+/// * Sequences, arrays, tuples and `BitVec`s (of [`Bool`](Value::Bool)s) are
+///   [`Sequence`](Value::Sequence)s. Maps are sequences of key-value pairs.
+/// * Enums, including `Option` and `Result`, are [`Variant`](Value::Variant)s
+///   with the data of the variant, or [`Unit`](Value::Unit) if it has none.
+/// * Calls are a variant of the module, containing a variant of the call,
+///   containing a composite of the arguments.
+///
+/// # Example
+///
+/// ```
+/// use gekko_metadata::scale::{TypeRegistry, Value};
+///
+/// let registry = TypeRegistry::default();
+/// let value = Value::variant("Id", Value::Bytes(vec![1; 32]));
+///
+/// let encoded = registry.encode_value("MultiAddress", &value).unwrap();
+/// assert_eq!(encoded, [&[0][..], &[1; 32]].concat());
+///
+/// let decoded = registry.decode_value("LookupSource", &mut encoded.as_slice()).unwrap();
+/// assert_eq!(decoded, value);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Value {
+    /// The unit type, or the absent data of an enum variant.
+    Unit,
+    Bool(bool),
+    UInt(u128),
+    Int(i128),
+    Text(String),
+    Bytes(Vec<u8>),
+    /// The elements of a sequence, array or tuple.
+    Sequence(Vec<Value>),
+    /// The named fields of a struct, in encoding order.
+    Composite(Vec<(String, Value)>),
+    /// The name of an enum variant and its data.
+    Variant(String, Box<Value>),
+}
+
+impl Value {
+    /// Creates an enum variant with the given data.
+    pub fn variant<T: Into<String>>(name: T, value: Value) -> Self {
+        Value::Variant(name.into(), Box::new(value))
+    }
+    /// Creates an enum variant without any data.
+    pub fn unit_variant<T: Into<String>>(name: T) -> Self {
+        Self::variant(name, Value::Unit)
+    }
+    /// Creates a struct from its named fields.
+    pub fn composite<I, T>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = (T, Value)>,
+        T: Into<String>,
+    {
+        Value::Composite(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.into(), value))
+                .collect(),
+        )
+    }
+    /// Creates a call of the module with the given arguments.
+    pub fn call<M, C, I, T>(module: M, call: C, args: I) -> Self
+    where
+        M: Into<String>,
+        C: Into<String>,
+        I: IntoIterator<Item = (T, Value)>,
+        T: Into<String>,
+    {
+        Self::variant(module, Self::variant(call, Self::composite(args)))
+    }
+    /// The value as an unsigned integer, if it is a non-negative integer.
+    pub fn as_u128(&self) -> Option<u128> {
+        match self {
+            Value::UInt(n) => Some(*n),
+            Value::Int(n) => u128::try_from(*n).ok(),
+            _ => None,
+        }
+    }
+    /// The value as a signed integer, if it is an integer in range.
+    pub fn as_i128(&self) -> Option<i128> {
+        match self {
+            Value::UInt(n) => i128::try_from(*n).ok(),
+            Value::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+    /// The value as a boolean, if it is one.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+    /// The value as a string, if it is text.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+    /// The value as bytes, if it is a byte sequence.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+    /// The elements, if the value is a sequence.
+    pub fn as_sequence(&self) -> Option<&[Value]> {
+        match self {
+            Value::Sequence(values) => Some(values),
+            _ => None,
+        }
+    }
+    /// The name and data, if the value is an enum variant.
+    pub fn as_variant(&self) -> Option<(&str, &Value)> {
+        match self {
+            Value::Variant(name, value) => Some((name, value)),
+            _ => None,
+        }
+    }
+    /// The field of a struct with the given name, if it exists.
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Composite(fields) => fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! impl_from {
+    ($($ty:ty => $variant:ident),*) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(value: $ty) -> Self {
+                    Value::$variant(value.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_from!(
+    bool => Bool,
+    u8 => UInt, u16 => UInt, u32 => UInt, u64 => UInt, u128 => UInt,
+    i8 => Int, i16 => Int, i32 => Int, i64 => Int, i128 => Int,
+    String => Text, &str => Text,
+    Vec<u8> => Bytes, &[u8] => Bytes
+);
+
+impl<const N: usize> From<[u8; N]> for Value {
+    fn from(value: [u8; N]) -> Self {
+        Value::Bytes(value.to_vec())
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => Value::variant("Some", value.into()),
+            None => Value::unit_variant("None"),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    /// Formats the value in a Rust-like syntax, e.g.
+    /// `Id(0x0101..)` or `{ dest: Id(0x0101..), value: 1000 }`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Unit => write!(f, "()"),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::UInt(n) => write!(f, "{}", n),
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Text(text) => write!(f, "{:?}", text),
+            Value::Bytes(bytes) => write!(f, "0x{}", hex::encode(bytes)),
+            Value::Sequence(values) => {
+                write!(f, "[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Value::Composite(fields) if fields.is_empty() => write!(f, "{{}}"),
+            Value::Composite(fields) => {
+                write!(f, "{{ ")?;
+                for (index, (name, value)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", name, value)?;
+                }
+                write!(f, " }}")
+            }
+            Value::Variant(name, value) => match value.as_ref() {
+                Value::Unit => write!(f, "{}", name),
+                Value::Composite(_) => write!(f, "{} {}", name, value),
+                value => write!(f, "{}({})", name, value),
+            },
+        }
+    }
+}
+
+impl TypeRegistry {
+    /// Decodes the value of the described type, advancing the input past it.
+    pub fn decode_value(&self, ty: &str, input: &mut &[u8]) -> Result<Value> {
+        self.decode_value_type(&ty.parse()?, input)
+    }
+    /// Decodes the value of the type, advancing the input past it.
+    pub fn decode_value_type(&self, ty: &Type, input: &mut &[u8]) -> Result<Value> {
+        self.decode_value_inner(ty, input, 0)
+    }
+    /// Encodes the value as the described type. Struct fields and enum
+    /// variants are matched by name, regardless of whether they are camelCase
+    /// or snake_case.
+    pub fn encode_value(&self, ty: &str, value: &Value) -> Result<Vec<u8>> {
+        self.encode_value_type(&ty.parse()?, value)
+    }
+    /// Encodes the value as the type.
+    pub fn encode_value_type(&self, ty: &Type, value: &Value) -> Result<Vec<u8>> {
+        let mut out = vec![];
+        self.encode_value_inner(ty, value, &mut out, 0)?;
+        Ok(out)
+    }
+    fn decode_value_inner(&self, ty: &Type, input: &mut &[u8], depth: usize) -> Result<Value> {
+        if depth > MAX_DEPTH {
+            return Err(Error::InvalidTypeDefinition(ty.to_string()));
+        }
+
+        let depth = depth + 1;
+        match self.resolve(ty)? {
+            Shape::Bool => match decode_byte(ty, input)? {
+                0 => Ok(Value::Bool(false)),
+                1 => Ok(Value::Bool(true)),
+                _ => Err(invalid_variant(ty)),
+            },
+            Shape::UInt(width) => decode_uint(ty, input, width).map(Value::UInt),
+            Shape::Int(width) => decode_int(ty, input, width).map(Value::Int),
+            Shape::Unit => Ok(Value::Unit),
+            Shape::Bytes => {
+                let len = decode_len(ty, input)?;
+                take(ty, input, len).map(Value::Bytes)
+            }
+            Shape::Text => {
+                let len = decode_len(ty, input)?;
+                String::from_utf8(take(ty, input, len)?)
+                    .map(Value::Text)
+                    .map_err(|_| Error::DecodeType {
+                        ty: ty.to_string(),
+                        source: "Invalid UTF-8 string".into(),
+                    })
+            }
+            // Bits are stored in the least significant bit first.
+            Shape::BitVec => {
+                let bits = decode_len(ty, input)?;
+                let bytes = take(ty, input, bits.div_ceil(8))?;
+
+                Ok(Value::Sequence(
+                    (0..bits)
+                        .map(|bit| Value::Bool(bytes[bit / 8] & (1 << (bit % 8)) != 0))
+                        .collect(),
+                ))
+            }
+            Shape::Era => {
+                let len = if input.first() == Some(&0) { 1 } else { 2 };
+                take(ty, input, len).map(Value::Bytes)
+            }
+            Shape::Compact => decode::<Compact<u128>>(ty, input).map(|n| Value::UInt(n.0)),
+            Shape::ByteArray(len) => take(ty, input, len).map(Value::Bytes),
+            Shape::Array(elem, len) => (0..len)
+                .map(|_| self.decode_value_inner(elem, input, depth))
+                .collect::<Result<_>>()
+                .map(Value::Sequence),
+            Shape::Tuple(types) => types
+                .iter()
+                .map(|ty| self.decode_value_inner(ty, input, depth))
+                .collect::<Result<_>>()
+                .map(Value::Sequence),
+            Shape::Sequence(elem) => {
+                let len = decode_len(ty, input)?;
+                (0..len)
+                    .map(|_| self.decode_value_inner(elem, input, depth))
+                    .collect::<Result<_>>()
+                    .map(Value::Sequence)
+            }
+            Shape::Map(key, value) => {
+                let len = decode_len(ty, input)?;
+                (0..len)
+                    .map(|_| {
+                        Ok(Value::Sequence(vec![
+                            self.decode_value_inner(key, input, depth)?,
+                            self.decode_value_inner(value, input, depth)?,
+                        ]))
+                    })
+                    .collect::<Result<_>>()
+                    .map(Value::Sequence)
+            }
+            // `Option<bool>` is encoded as a single byte.
+            Shape::Option(inner) if is_bool(inner) => match decode_byte(ty, input)? {
+                0 => Ok(Value::unit_variant("None")),
+                1 => Ok(Value::variant("Some", Value::Bool(true))),
+                2 => Ok(Value::variant("Some", Value::Bool(false))),
+                _ => Err(invalid_variant(ty)),
+            },
+            Shape::Option(inner) => match decode_byte(ty, input)? {
+                0 => Ok(Value::unit_variant("None")),
+                1 => Ok(Value::variant(
+                    "Some",
+                    self.decode_value_inner(inner, input, depth)?,
+                )),
+                _ => Err(invalid_variant(ty)),
+            },
+            Shape::Result(ok, err) => {
+                let (name, inner) = match decode_byte(ty, input)? {
+                    0 => ("Ok", ok),
+                    1 => ("Err", err),
+                    _ => return Err(invalid_variant(ty)),
+                };
+
+                Ok(Value::variant(
+                    name,
+                    self.decode_value_inner(inner, input, depth)?,
+                ))
+            }
+            Shape::Def(_, def) => self.decode_value_def(ty, def, input, depth),
+        }
+    }
+    fn decode_value_def(
+        &self,
+        ty: &Type,
+        def: &TypeDef,
+        input: &mut &[u8],
+        depth: usize,
+    ) -> Result<Value> {
+        match def {
+            TypeDef::Alias(alias) => self.decode_value_inner(alias, input, depth),
+            TypeDef::Struct(fields) => fields
+                .iter()
+                .map(|(name, field)| {
+                    Ok((name.clone(), self.decode_value_inner(field, input, depth)?))
+                })
+                .collect::<Result<_>>()
+                .map(Value::Composite),
+            TypeDef::Enum(variants) => {
+                let index = decode_byte(ty, input)?;
+                let variant = variants
+                    .iter()
+                    .find(|variant| variant.index == index)
+                    .ok_or_else(|| invalid_variant(ty))?;
+
+                let value = match &variant.def {
+                    Some(def) => self.decode_value_def(ty, def, input, depth)?,
+                    None => Value::Unit,
+                };
+
+                Ok(Value::variant(variant.name.clone(), value))
+            }
+        }
+    }
+    fn encode_value_inner(
+        &self,
+        ty: &Type,
+        value: &Value,
+        out: &mut Vec<u8>,
+        depth: usize,
+    ) -> Result<()> {
+        if depth > MAX_DEPTH {
+            return Err(Error::InvalidTypeDefinition(ty.to_string()));
+        }
+
+        let depth = depth + 1;
+        match self.resolve(ty)? {
+            Shape::Bool => value
+                .as_bool()
+                .ok_or_else(|| invalid_value(ty, "expected a boolean"))?
+                .encode_to(out),
+            Shape::UInt(width) => out.extend(&uint(ty, value, width)?.to_le_bytes()[..width]),
+            Shape::Int(width) => out.extend(&int(ty, value, width)?.to_le_bytes()[..width]),
+            Shape::Unit => {}
+            Shape::Bytes => bytes(ty, value)?.encode_to(out),
+            Shape::Text => value
+                .as_str()
+                .ok_or_else(|| invalid_value(ty, "expected text"))?
+                .encode_to(out),
+            Shape::BitVec => {
+                let bits = expect_sequence(ty, value, None)?
+                    .iter()
+                    .map(|bit| {
+                        bit.as_bool()
+                            .ok_or_else(|| invalid_value(ty, "expected a sequence of booleans"))
+                    })
+                    .collect::<Result<Vec<bool>>>()?;
+
+                let mut bytes = vec![0u8; bits.len().div_ceil(8)];
+                for (bit, _) in bits.iter().enumerate().filter(|(_, set)| **set) {
+                    bytes[bit / 8] |= 1 << (bit % 8);
+                }
+
+                Compact(bits.len() as u32).encode_to(out);
+                out.extend(bytes.iter());
+            }
+            Shape::Era => out.extend(bytes(ty, value)?.iter()),
+            Shape::Compact => Compact(uint(ty, value, 16)?).encode_to(out),
+            Shape::ByteArray(len) => {
+                let bytes = bytes(ty, value)?;
+                if bytes.len() != len {
+                    return Err(invalid_value(ty, format!("expected {} bytes", len)));
+                }
+
+                out.extend(bytes.iter());
+            }
+            Shape::Array(elem, len) => expect_sequence(ty, value, Some(len))?
+                .iter()
+                .try_for_each(|value| self.encode_value_inner(elem, value, out, depth))?,
+            Shape::Tuple(types) => {
+                let values = expect_sequence(ty, value, Some(types.len()))?;
+                types
+                    .iter()
+                    .zip(values)
+                    .try_for_each(|(ty, value)| self.encode_value_inner(ty, value, out, depth))?;
+            }
+            Shape::Sequence(elem) => {
+                let values = expect_sequence(ty, value, None)?;
+                Compact(values.len() as u32).encode_to(out);
+                values
+                    .iter()
+                    .try_for_each(|value| self.encode_value_inner(elem, value, out, depth))?;
+            }
+            Shape::Map(key, elem) => {
+                let pairs = expect_sequence(ty, value, None)?;
+
+                Compact(pairs.len() as u32).encode_to(out);
+                for pair in pairs {
+                    let pair = expect_sequence(ty, pair, Some(2))?;
+                    self.encode_value_inner(key, &pair[0], out, depth)?;
+                    self.encode_value_inner(elem, &pair[1], out, depth)?;
+                }
+            }
+            Shape::Option(inner) => match expect_variant(ty, value)? {
+                ("None", _) => out.push(0),
+                // `Option<bool>` is encoded as a single byte.
+                ("Some", Value::Bool(value)) if is_bool(inner) => {
+                    out.push(if *value { 1 } else { 2 })
+                }
+                ("Some", value) => {
+                    out.push(1);
+                    self.encode_value_inner(inner, value, out, depth)?;
+                }
+                _ => return Err(invalid_value(ty, "expected `None` or `Some`")),
+            },
+            Shape::Result(ok, err) => match expect_variant(ty, value)? {
+                ("Ok", value) => {
+                    out.push(0);
+                    self.encode_value_inner(ok, value, out, depth)?;
+                }
+                ("Err", value) => {
+                    out.push(1);
+                    self.encode_value_inner(err, value, out, depth)?;
+                }
+                _ => return Err(invalid_value(ty, "expected `Ok` or `Err`")),
+            },
+            Shape::Def(_, def) => self.encode_value_def(ty, def, value, out, depth)?,
+        }
+
+        Ok(())
+    }
+    fn encode_value_def(
+        &self,
+        ty: &Type,
+        def: &TypeDef,
+        value: &Value,
+        out: &mut Vec<u8>,
+        depth: usize,
+    ) -> Result<()> {
+        match def {
+            TypeDef::Alias(alias) => self.encode_value_inner(alias, value, out, depth),
+            TypeDef::Struct(fields) => {
+                if fields.is_empty() {
+                    return Ok(());
+                }
+
+                // Fields can also be passed in order.
+                if let Value::Sequence(values) = value {
+                    if values.len() != fields.len() {
+                        return Err(invalid_value(
+                            ty,
+                            format!("expected {} fields", fields.len()),
+                        ));
+                    }
+
+                    return fields
+                        .iter()
+                        .zip(values)
+                        .try_for_each(|((_, field), value)| {
+                            self.encode_value_inner(field, value, out, depth)
+                        });
+                }
+
+                let values = match value {
+                    Value::Composite(values) => values,
+                    _ => return Err(invalid_value(ty, "expected a composite")),
+                };
+
+                fields.iter().try_for_each(|(name, field)| {
+                    let value = values
+                        .iter()
+                        .find(|(key, _)| same_name(name, key))
+                        .map(|(_, value)| value)
+                        .ok_or_else(|| invalid_value(ty, format!("missing field {}", name)))?;
+
+                    self.encode_value_inner(field, value, out, depth)
+                })
+            }
+            TypeDef::Enum(variants) => {
+                let (name, data) = expect_variant(ty, value)?;
+                let variant = variants
+                    .iter()
+                    .find(|variant| same_name(&variant.name, name))
+                    .ok_or_else(|| invalid_value(ty, format!("unknown variant {}", name)))?;
+
+                out.push(variant.index);
+                match &variant.def {
+                    Some(def) => self.encode_value_def(ty, def, data, out, depth),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// The value as an unsigned integer of the given width in bytes.
+fn uint(ty: &Type, value: &Value, width: usize) -> Result<u128> {
+    let n = value
+        .as_u128()
+        .ok_or_else(|| invalid_value(ty, "expected an unsigned integer"))?;
+
+    if width < 16 && n >> (width * 8) != 0 {
+        return Err(invalid_value(ty, "integer out of range"));
+    }
+
+    Ok(n)
+}
+
+/// The value as a signed integer of the given width in bytes.
+fn int(ty: &Type, value: &Value, width: usize) -> Result<i128> {
+    let n = value
+        .as_i128()
+        .ok_or_else(|| invalid_value(ty, "expected an integer"))?;
+
+    if width < 16 {
+        let bound = 1 << (width * 8 - 1);
+        if !(-bound..bound).contains(&n) {
+            return Err(invalid_value(ty, "integer out of range"));
+        }
+    }
+
+    Ok(n)
+}
+
+/// The value as bytes, given either as bytes or as a sequence of integers.
+fn bytes<'a>(ty: &Type, value: &'a Value) -> Result<Cow<'a, [u8]>> {
+    match value {
+        Value::Bytes(bytes) => Ok(Cow::Borrowed(bytes)),
+        Value::Sequence(values) => values
+            .iter()
+            .map(|value| uint(ty, value, 1).map(|n| n as u8))
+            .collect::<Result<_>>()
+            .map(Cow::Owned),
+        _ => Err(invalid_value(ty, "expected bytes")),
+    }
+}
+
+fn expect_sequence<'a>(ty: &Type, value: &'a Value, len: Option<usize>) -> Result<&'a [Value]> {
+    let values = value
+        .as_sequence()
+        .ok_or_else(|| invalid_value(ty, "expected a sequence"))?;
+
+    match len {
+        Some(len) if values.len() != len => {
+            Err(invalid_value(ty, format!("expected {} elements", len)))
+        }
+        _ => Ok(values),
+    }
+}
+
+fn expect_variant<'a>(ty: &Type, value: &'a Value) -> Result<(&'a str, &'a Value)> {
+    value
+        .as_variant()
+        .ok_or_else(|| invalid_value(ty, "expected a variant"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> TypeRegistry {
+        let mut registry = TypeRegistry::default();
+        registry
+            .register_json(
+                r#"{
+                    "Score": { "points": "Compact<u32>", "owner": "AccountId" },
+                    "Status": { "_enum": ["Free", "Reserved"] }
+                }"#,
+            )
+            .unwrap();
+
+        registry
+    }
+
+    /// Encodes the value, checks the encoding and decodes it again.
+    fn round_trip(registry: &TypeRegistry, ty: &str, value: Value, encoded: &[u8]) {
+        assert_eq!(
+            registry.encode_value(ty, &value).unwrap(),
+            encoded,
+            "{}",
+            ty
+        );
+
+        let input = &mut &*encoded;
+        assert_eq!(registry.decode_value(ty, input).unwrap(), value, "{}", ty);
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn round_trip_primitives() {
+        let registry = registry();
+
+        round_trip(&registry, "bool", Value::Bool(true), &[1]);
+        round_trip(&registry, "u8", Value::UInt(255), &[255]);
+        round_trip(&registry, "u16", Value::UInt(0x0102), &[2, 1]);
+        round_trip(&registry, "u64", u64::MAX.into(), &[255; 8]);
+        round_trip(&registry, "u128", 1u128.into(), &1u128.encode());
+        round_trip(&registry, "i8", Value::Int(-1), &[255]);
+        round_trip(&registry, "i32", Value::Int(-2), &(-2i32).encode());
+        round_trip(&registry, "i64", i64::MIN.into(), &i64::MIN.encode());
+        round_trip(&registry, "i128", i128::MIN.into(), &i128::MIN.encode());
+        round_trip(&registry, "Text", "gekko".into(), &"gekko".encode());
+        round_trip(&registry, "Bytes", vec![1, 2].into(), &[8, 1, 2]);
+        round_trip(&registry, "()", Value::Unit, &[]);
+
+        // Integers must fit the width of the type.
+        assert!(registry.encode_value("u8", &Value::UInt(256)).is_err());
+        assert!(registry.encode_value("i8", &Value::Int(128)).is_err());
+        assert!(registry.encode_value("i8", &Value::Int(-129)).is_err());
+        assert!(registry.encode_value("u32", &Value::Int(-1)).is_err());
+    }
+
+    #[test]
+    fn round_trip_compact() {
+        let registry = registry();
+
+        for n in [0, 63, 64, 1 << 14, 1 << 30, u64::MAX as u128, u128::MAX] {
+            round_trip(
+                &registry,
+                "Compact<Balance>",
+                Value::UInt(n),
+                &Compact(n).encode(),
+            );
+        }
+    }
+
+    #[test]
+    fn round_trip_composites() {
+        let registry = registry();
+
+        let score = Value::composite(vec![
+            ("points", Value::UInt(1)),
+            ("owner", Value::Bytes(vec![7; 32])),
+        ]);
+        round_trip(&registry, "Score", score, &[&[4][..], &[7; 32]].concat());
+
+        // Fields are matched by name, regardless of their case and order, or
+        // by position.
+        let encoded = registry.encode_value(
+            "Score",
+            &Value::composite(vec![
+                ("owner", Value::Bytes(vec![7; 32])),
+                ("points", Value::UInt(1)),
+            ]),
+        );
+        assert_eq!(encoded.unwrap(), [&[4][..], &[7; 32]].concat());
+
+        let encoded = registry.encode_value(
+            "Score",
+            &Value::Sequence(vec![Value::UInt(1), Value::Bytes(vec![7; 32])]),
+        );
+        assert_eq!(encoded.unwrap(), [&[4][..], &[7; 32]].concat());
+
+        let err =
+            registry.encode_value("Score", &Value::composite(vec![("points", Value::UInt(1))]));
+        assert!(matches!(err, Err(Error::InvalidValue { .. })));
+    }
+
+    #[test]
+    fn round_trip_variants() {
+        let registry = registry();
+
+        round_trip(&registry, "Status", Value::unit_variant("Reserved"), &[1]);
+        round_trip(
+            &registry,
+            "MultiAddress",
+            Value::variant("Index", Value::UInt(1)),
+            &[1, 4],
+        );
+        round_trip(
+            &registry,
+            "RewardDestination",
+            Value::variant("Account", Value::Bytes(vec![1; 32])),
+            &[&[3][..], &[1; 32]].concat(),
+        );
+        round_trip(&registry, "Option<u32>", None::<u32>.into(), &[0]);
+        round_trip(
+            &registry,
+            "Option<u32>",
+            Some(1u32).into(),
+            &[1, 1, 0, 0, 0],
+        );
+        round_trip(&registry, "Option<bool>", Some(false).into(), &[2]);
+        round_trip(
+            &registry,
+            "Result<u8, Text>",
+            Value::variant("Err", "no".into()),
+            &[&[1][..], &"no".encode()].concat(),
+        );
+
+        let err = registry.encode_value("Status", &Value::unit_variant("Unknown"));
+        assert!(matches!(err, Err(Error::InvalidValue { .. })));
+        let err = registry.decode_value("Status", &mut &[2][..]);
+        assert!(matches!(err, Err(Error::DecodeType { .. })));
+    }
+
+    #[test]
+    fn round_trip_sequences() {
+        let registry = registry();
+
+        round_trip(
+            &registry,
+            "Vec<u32>",
+            Value::Sequence(vec![Value::UInt(1), Value::UInt(2)]),
+            &vec![1u32, 2].encode(),
+        );
+        round_trip(
+            &registry,
+            "(u8, Text)",
+            Value::Sequence(vec![Value::UInt(1), "a".into()]),
+            &(1u8, "a").encode(),
+        );
+        round_trip(
+            &registry,
+            "[u16; 2]",
+            Value::Sequence(vec![Value::UInt(1), Value::UInt(2)]),
+            &[1, 0, 2, 0],
+        );
+        round_trip(&registry, "[u8; 2]", vec![1, 2].into(), &[1, 2]);
+        round_trip(&registry, "Vec<u8>", vec![1, 2].into(), &[8, 1, 2]);
+        round_trip(
+            &registry,
+            "BTreeMap<u32, bool>",
+            Value::Sequence(vec![Value::Sequence(vec![Value::UInt(1), true.into()])]),
+            &[4, 1, 0, 0, 0, 1],
+        );
+
+        // Byte sequences can also be given as sequences of integers.
+        let encoded = registry.encode_value(
+            "Vec<u8>",
+            &Value::Sequence(vec![Value::UInt(1), Value::UInt(2)]),
+        );
+        assert_eq!(encoded.unwrap(), [8, 1, 2]);
+
+        let err = registry.encode_value("[u8; 2]", &vec![1].into());
+        assert!(matches!(err, Err(Error::InvalidValue { .. })));
+    }
+
+    #[test]
+    fn round_trip_bit_sequences() {
+        let registry = registry();
+
+        let bits = |bits: &[bool]| Value::Sequence(bits.iter().copied().map(Value::from).collect());
+
+        round_trip(&registry, "BitVec", bits(&[]), &[0]);
+        // Ten bits, least significant bit first.
+        round_trip(
+            &registry,
+            "BitVec",
+            bits(&[
+                true, false, true, false, false, false, false, false, false, true,
+            ]),
+            &[40, 0b0000_0101, 0b0000_0010],
+        );
+
+        let err = registry.encode_value("BitVec", &Value::Sequence(vec![Value::UInt(1)]));
+        assert!(matches!(err, Err(Error::InvalidValue { .. })));
+    }
+}
@@ -0,0 +1,83 @@
+//! (De)serializes byte arrays as `0x`-prefixed HEX strings, for use with
+//! `#[serde(with = "gekko_metadata::serde_hex")]`. Requires the `"serde"`
+//! feature.
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serializer};
+use std::convert::TryFrom;
+
+pub fn serialize<S: Serializer, T: AsRef<[u8]>>(
+    bytes: T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+}
+
+pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<Vec<u8>>,
+{
+    let bytes = crate::decode_hex(String::deserialize(deserializer)?).map_err(D::Error::custom)?;
+    T::try_from(bytes).map_err(|_| D::Error::custom("invalid length of HEX string"))
+}
+
+/// (De)serializes sequences of byte arrays as sequences of HEX strings.
+pub mod vec {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::convert::TryFrom;
+
+    #[derive(Serialize)]
+    struct Ser<'a>(#[serde(with = "super")] &'a [u8]);
+
+    #[derive(Deserialize)]
+    struct De(#[serde(with = "super")] Vec<u8>);
+
+    pub fn serialize<S: Serializer, T: AsRef<[u8]>>(
+        items: &[T],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(items.iter().map(|item| Ser(item.as_ref())))
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: TryFrom<Vec<u8>>,
+    {
+        Vec::<De>::deserialize(deserializer)?
+            .into_iter()
+            .map(|item| {
+                T::try_from(item.0)
+                    .map_err(|_| serde::de::Error::custom("invalid length of HEX string"))
+            })
+            .collect()
+    }
+}
+
+/// (De)serializes optional byte arrays as `0x`-prefixed HEX strings or `null`.
+pub mod option {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::convert::TryFrom;
+
+    pub fn serialize<S: Serializer, T: AsRef<[u8]>>(
+        bytes: &Option<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match bytes {
+            Some(bytes) => super::serialize(bytes, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: TryFrom<Vec<u8>>,
+    {
+        #[derive(Deserialize)]
+        struct Wrapper<T: TryFrom<Vec<u8>>>(#[serde(deserialize_with = "super::deserialize")] T);
+
+        Ok(Option::<Wrapper<T>>::deserialize(deserializer)?.map(|wrapper| wrapper.0))
+    }
+}
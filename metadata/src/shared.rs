@@ -0,0 +1,236 @@
+//! Metadata which is parsed lazily and shared across threads.
+
+use crate::version::MetadataV13;
+use crate::{parse_hex_metadata, parse_raw_metadata, Result};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+
+/// The unparsed metadata.
+enum Source {
+    Raw(Vec<u8>),
+    Hex(Vec<u8>),
+}
+
+struct LazyMetadata {
+    // Dropped once parsed.
+    source: Mutex<Option<Source>>,
+    parsed: OnceLock<Arc<MetadataV13>>,
+}
+
+impl LazyMetadata {
+    fn new(source: Source) -> Self {
+        LazyMetadata {
+            source: Mutex::new(Some(source)),
+            parsed: OnceLock::new(),
+        }
+    }
+    fn get(&self) -> Result<Arc<MetadataV13>> {
+        if let Some(metadata) = self.parsed.get() {
+            return Ok(Arc::clone(metadata));
+        }
+
+        // Only one thread parses the metadata, the others wait for it.
+        let mut source = self.source.lock().unwrap();
+        if let Some(metadata) = self.parsed.get() {
+            return Ok(Arc::clone(metadata));
+        }
+
+        let metadata = match source.as_ref() {
+            Some(Source::Raw(raw)) => parse_raw_metadata(raw)?,
+            Some(Source::Hex(hex)) => parse_hex_metadata(hex)?,
+            None => unreachable!("the source is only dropped once parsed"),
+        }
+        .into_latest()?;
+
+        let metadata = Arc::clone(self.parsed.get_or_init(|| Arc::new(metadata)));
+        *source = None;
+        Ok(metadata)
+    }
+}
+
+/// Metadata which is parsed once on first access and can be shared across
+/// threads. Cloning is cheap, all clones refer to the same metadata.
+///
+/// On a runtime upgrade, the metadata can be [replaced](Self::replace_raw)
+/// atomically for all clones. Metadata which was handed out before remains
+/// valid.
+///
+/// # Example
+///
+/// ```no_run
+/// use gekko_metadata::*;
+/// use std::thread;
+///
+/// let content = std::fs::read_to_string("metadata_kusama_9080.hex").unwrap();
+/// let shared = SharedMetadata::from_hex(content);
+///
+/// let handles: Vec<_> = (0..4)
+///     .map(|_| {
+///         let shared = shared.clone();
+///         thread::spawn(move || {
+///             // Parsed by the first thread only.
+///             let metadata = shared.get().unwrap();
+///             metadata
+///                 .find_module_extrinsic("Balances", "transfer_keep_alive")
+///                 .map(|info| (info.module_id, info.dispatch_id))
+///         })
+///     })
+///     .collect();
+///
+/// for handle in handles {
+///     assert_eq!(handle.join().unwrap(), Some((4, 3)));
+/// }
+/// ```
+#[derive(Clone)]
+pub struct SharedMetadata {
+    current: Arc<RwLock<Arc<LazyMetadata>>>,
+}
+
+impl SharedMetadata {
+    /// Creates the shared metadata from the raw metadata, as returned by
+    /// `state_getMetadata`. See [`parse_raw_metadata`].
+    pub fn from_raw<T: Into<Vec<u8>>>(raw: T) -> Self {
+        Self::from_source(Source::Raw(raw.into()))
+    }
+    /// Creates the shared metadata from the HEX representation of the
+    /// metadata. See [`parse_hex_metadata`].
+    pub fn from_hex<T: Into<Vec<u8>>>(hex: T) -> Self {
+        Self::from_source(Source::Hex(hex.into()))
+    }
+    fn from_source(source: Source) -> Self {
+        SharedMetadata {
+            current: Arc::new(RwLock::new(Arc::new(LazyMetadata::new(source)))),
+        }
+    }
+    /// The current metadata, parsing it on first access. Fails if the
+    /// metadata cannot be parsed or is not of the latest version.
+    pub fn get(&self) -> Result<Arc<MetadataV13>> {
+        let current = Arc::clone(&self.current.read().unwrap());
+        current.get()
+    }
+    /// Whether the current metadata was parsed already.
+    pub fn is_parsed(&self) -> bool {
+        self.current.read().unwrap().parsed.get().is_some()
+    }
+    /// Replaces the metadata of all clones with the given raw metadata, which
+    /// is parsed on the next access.
+    pub fn replace_raw<T: Into<Vec<u8>>>(&self, raw: T) {
+        self.replace_source(Source::Raw(raw.into()));
+    }
+    /// Replaces the metadata of all clones with the given HEX representation
+    /// of the metadata, which is parsed on the next access.
+    pub fn replace_hex<T: Into<Vec<u8>>>(&self, hex: T) {
+        self.replace_source(Source::Hex(hex.into()));
+    }
+    /// Replaces the metadata of all clones with the already parsed metadata.
+    pub fn replace(&self, metadata: MetadataV13) {
+        *self.current.write().unwrap() = Arc::new(LazyMetadata::from(metadata));
+    }
+    fn replace_source(&self, source: Source) {
+        *self.current.write().unwrap() = Arc::new(LazyMetadata::new(source));
+    }
+}
+
+impl From<MetadataV13> for LazyMetadata {
+    fn from(metadata: MetadataV13) -> Self {
+        LazyMetadata {
+            source: Mutex::new(None),
+            parsed: OnceLock::from(Arc::new(metadata)),
+        }
+    }
+}
+
+impl From<MetadataV13> for SharedMetadata {
+    fn from(metadata: MetadataV13) -> Self {
+        SharedMetadata {
+            current: Arc::new(RwLock::new(Arc::new(LazyMetadata::from(metadata)))),
+        }
+    }
+}
+
+impl std::fmt::Debug for SharedMetadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedMetadata")
+            .field("parsed", &self.is_parsed())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode_hex, ModuleMetadataExt};
+    use std::thread;
+
+    const DUMP: &str = include_str!("../../interface/dumps/metadata_kusama_9080.hex");
+
+    fn metadata() -> MetadataV13 {
+        parse_hex_metadata(DUMP.trim())
+            .unwrap()
+            .into_latest()
+            .unwrap()
+    }
+
+    #[test]
+    fn parse_once() {
+        let shared = SharedMetadata::from_hex(DUMP.trim());
+        assert!(!shared.is_parsed());
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let shared = shared.clone();
+                thread::spawn(move || shared.get().unwrap())
+            })
+            .collect();
+
+        let parsed: Vec<_> = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect();
+
+        assert!(shared.is_parsed());
+        assert!(parsed
+            .iter()
+            .all(|metadata| Arc::ptr_eq(metadata, &parsed[0])));
+        assert!(Arc::ptr_eq(&shared.get().unwrap(), &parsed[0]));
+    }
+
+    #[test]
+    fn replace_for_all_clones() {
+        let shared = SharedMetadata::from_hex(DUMP.trim());
+        let clone = shared.clone();
+
+        let before = shared.get().unwrap();
+        let mut upgraded = (*before).clone();
+        upgraded.modules.retain(|module| module.name != "Balances");
+
+        clone.replace(upgraded);
+        let after = shared.get().unwrap();
+        assert!(!Arc::ptr_eq(&before, &after));
+        assert!(after
+            .find_module_extrinsic("Balances", "transfer")
+            .is_none());
+
+        // Metadata handed out before the upgrade remains valid.
+        assert!(before
+            .find_module_extrinsic("Balances", "transfer")
+            .is_some());
+    }
+
+    #[test]
+    fn replace_with_unparsed_metadata() {
+        let raw = decode_hex(DUMP.trim()).unwrap();
+        let shared = SharedMetadata::from(metadata());
+        assert!(shared.is_parsed());
+
+        shared.replace_raw(raw);
+        assert!(!shared.is_parsed());
+        assert!(shared.get().is_ok());
+        assert!(shared.is_parsed());
+
+        // Parsing errors are reported on access, and on every further access.
+        shared.replace_hex("0xinvalid");
+        assert!(shared.get().is_err());
+        assert!(shared.get().is_err());
+        assert!(!shared.is_parsed());
+    }
+}
@@ -1,5 +1,15 @@
 //! Available versions of Substrates metadata format.
 
+pub mod v10;
+pub mod v11;
+pub mod v12;
 pub mod v13;
+pub mod v14;
+pub mod v9;
 
+pub use v10::MetadataV10;
+pub use v11::MetadataV11;
+pub use v12::MetadataV12;
 pub use v13::MetadataV13;
+pub use v14::MetadataV14;
+pub use v9::MetadataV9;
@@ -0,0 +1,118 @@
+//! Metadata V10. Equal to V11, except that it has no information about the
+//! extrinsic format and lacks the `Identity` storage hasher.
+
+use crate::{
+    ConstantInfo, ConstantsBuilderExt, ErrorBuilderExt, ErrorInfo, EventInfo, EventMetadataExt,
+    ExtrinsicInfo, ModuleMetadataExt, StorageInfo, StorageMetadataExt,
+};
+
+pub use super::v11::{
+    ErrorMetadata, EventMetadata, FunctionArgumentMetadata, FunctionMetadata,
+    ModuleConstantMetadata, ModuleMetadata, StorageEntryMetadata, StorageEntryModifier,
+    StorageEntryType, StorageHasher, StorageMetadata,
+};
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetadataV10 {
+    pub modules: Vec<ModuleMetadata>,
+}
+
+impl MetadataV10 {
+    /// Consumes the metadata and returns a smaller copy of it. See
+    /// [`MetadataVersion::pruned`](crate::MetadataVersion::pruned).
+    pub fn pruned(mut self, keep_docs: bool) -> Self {
+        if !keep_docs {
+            super::v11::strip_docs(&mut self.modules);
+        }
+        self
+    }
+}
+
+impl ModuleMetadataExt for MetadataV10 {
+    fn modules_extrinsics<'a>(&'a self) -> Vec<ExtrinsicInfo<'a>> {
+        super::v11::modules_extrinsics(&self.modules)
+    }
+    fn find_module_extrinsic<'a>(
+        &'a self,
+        method: &str,
+        extrinsic: &str,
+    ) -> Option<ExtrinsicInfo<'a>> {
+        super::v11::find_module_extrinsic(&self.modules, method, extrinsic)
+    }
+    fn find_extrinsic_by_index<'a>(
+        &'a self,
+        module_index: u8,
+        dispatch_index: u8,
+    ) -> Option<ExtrinsicInfo<'a>> {
+        super::v11::find_extrinsic_by_index(&self.modules, module_index, dispatch_index)
+    }
+}
+
+impl EventMetadataExt for MetadataV10 {
+    fn modules_events<'a>(&'a self) -> Vec<EventInfo<'a>> {
+        super::v11::modules_events(&self.modules)
+    }
+}
+
+impl ErrorBuilderExt for MetadataV10 {
+    fn modules_errors<'a>(&'a self) -> Vec<ErrorInfo<'a>> {
+        super::v11::modules_errors(&self.modules)
+    }
+}
+
+impl ConstantsBuilderExt for MetadataV10 {
+    fn constants<'a>(&'a self) -> Vec<ConstantInfo<'a>> {
+        super::v11::constants(&self.modules)
+    }
+}
+
+impl StorageMetadataExt for MetadataV10 {
+    fn modules_storage<'a>(&'a self) -> Vec<StorageInfo<'a>> {
+        super::v11::modules_storage(&self.modules)
+    }
+    fn find_module_storage<'a>(&'a self, module: &str, entry: &str) -> Option<StorageInfo<'a>> {
+        super::v11::find_module_storage(&self.modules, module, entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_hex_metadata, MetadataVersion};
+
+    // Kusama, from the test fixtures of the `frame-metadata` crate.
+    const DUMP: &str = include_str!("../../../interface/dumps/metadata_kusama_v10.hex");
+
+    #[test]
+    fn decode_kusama() {
+        let data = match parse_hex_metadata(DUMP).unwrap() {
+            MetadataVersion::V10(data) => data,
+            other => panic!("unexpected version {}", other.version_number()),
+        };
+
+        assert_eq!(data.modules_extrinsics().len(), 115);
+        assert_eq!(data.modules_events().len(), 92);
+        assert_eq!(data.modules_errors().len(), 103);
+
+        let transfer = data.find_module_extrinsic("Balances", "transfer").unwrap();
+        assert_eq!((transfer.module_id, transfer.dispatch_id), (4, 0));
+        assert_eq!(
+            transfer.args,
+            vec![
+                ("dest", "<T::Lookup as StaticLookup>::Source"),
+                ("value", "Compact<T::Balance>")
+            ]
+        );
+        assert_eq!(data.find_extrinsic_by_index(4, 0), Some(transfer));
+
+        assert_eq!(
+            data.decode_constant::<u128>("Balances", "ExistentialDeposit")
+                .unwrap(),
+            10_000_000_000
+        );
+
+        // Encodes back into the same dump.
+        assert_eq!(MetadataVersion::V10(data).to_hex(), DUMP);
+    }
+}
@@ -0,0 +1,287 @@
+//! Metadata V11. Unlike later versions, modules have no explicit index. The
+//! index of a module within the calls or events is its position among the
+//! modules which have calls or events, respectively.
+
+use super::v13::ModuleStorage;
+use crate::{
+    ConstantInfo, ConstantsBuilderExt, ErrorBuilderExt, ErrorInfo, EventInfo, EventMetadataExt,
+    ExtrinsicInfo, ModuleMetadataExt, StorageInfo, StorageMetadataExt,
+};
+
+pub use super::v13::{
+    ErrorMetadata, EventMetadata, ExtrinsicMetadata, FunctionArgumentMetadata, FunctionMetadata,
+    ModuleConstantMetadata, StorageEntryMetadata, StorageEntryModifier, StorageEntryType,
+    StorageHasher, StorageMetadata,
+};
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetadataV11 {
+    pub modules: Vec<ModuleMetadata>,
+    pub extrinsics: ExtrinsicMetadata,
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModuleMetadata {
+    pub name: String,
+    pub storage: Option<StorageMetadata>,
+    pub calls: Option<Vec<FunctionMetadata>>,
+    pub events: Option<Vec<EventMetadata>>,
+    pub constants: Vec<ModuleConstantMetadata>,
+    pub errors: Vec<ErrorMetadata>,
+}
+
+impl MetadataV11 {
+    /// Consumes the metadata and returns a smaller copy of it. See
+    /// [`MetadataVersion::pruned`](crate::MetadataVersion::pruned).
+    pub fn pruned(mut self, keep_docs: bool) -> Self {
+        if !keep_docs {
+            strip_docs(&mut self.modules);
+        }
+        self
+    }
+}
+
+impl ModuleMetadataExt for MetadataV11 {
+    fn modules_extrinsics<'a>(&'a self) -> Vec<ExtrinsicInfo<'a>> {
+        modules_extrinsics(&self.modules)
+    }
+    fn find_module_extrinsic<'a>(
+        &'a self,
+        method: &str,
+        extrinsic: &str,
+    ) -> Option<ExtrinsicInfo<'a>> {
+        find_module_extrinsic(&self.modules, method, extrinsic)
+    }
+    fn find_extrinsic_by_index<'a>(
+        &'a self,
+        module_index: u8,
+        dispatch_index: u8,
+    ) -> Option<ExtrinsicInfo<'a>> {
+        find_extrinsic_by_index(&self.modules, module_index, dispatch_index)
+    }
+}
+
+impl EventMetadataExt for MetadataV11 {
+    fn modules_events<'a>(&'a self) -> Vec<EventInfo<'a>> {
+        modules_events(&self.modules)
+    }
+}
+
+impl ErrorBuilderExt for MetadataV11 {
+    fn modules_errors<'a>(&'a self) -> Vec<ErrorInfo<'a>> {
+        modules_errors(&self.modules)
+    }
+}
+
+impl ConstantsBuilderExt for MetadataV11 {
+    fn constants<'a>(&'a self) -> Vec<ConstantInfo<'a>> {
+        constants(&self.modules)
+    }
+}
+
+impl StorageMetadataExt for MetadataV11 {
+    fn modules_storage<'a>(&'a self) -> Vec<StorageInfo<'a>> {
+        modules_storage(&self.modules)
+    }
+    fn find_module_storage<'a>(&'a self, module: &str, entry: &str) -> Option<StorageInfo<'a>> {
+        find_module_storage(&self.modules, module, entry)
+    }
+}
+
+/// Drops the documentation of the modules. Unlike in later versions, empty
+/// sections are kept, since removing them would shift the indices of the
+/// following modules.
+pub(super) fn strip_docs(modules: &mut [ModuleMetadata]) {
+    for module in modules {
+        for entry in module.storage.iter_mut().flat_map(|s| s.entries.iter_mut()) {
+            entry.documentation = vec![];
+        }
+        for call in module.calls.iter_mut().flatten() {
+            call.documentation = vec![];
+        }
+        for event in module.events.iter_mut().flatten() {
+            event.documentation = vec![];
+        }
+        for constant in &mut module.constants {
+            constant.documentation = vec![];
+        }
+        for error in &mut module.errors {
+            error.documentation = vec![];
+        }
+    }
+}
+
+/// The modules which have calls, together with their position among all
+/// modules and their calls.
+fn call_modules(
+    modules: &[ModuleMetadata],
+) -> impl Iterator<Item = (usize, &ModuleMetadata, &Vec<FunctionMetadata>)> {
+    modules
+        .iter()
+        .enumerate()
+        .filter_map(|(position, mod_meta)| Some((position, mod_meta, mod_meta.calls.as_ref()?)))
+}
+
+pub(super) fn modules_extrinsics(modules: &[ModuleMetadata]) -> Vec<ExtrinsicInfo<'_>> {
+    call_modules(modules)
+        .enumerate()
+        .flat_map(|(module_id, (module_position, mod_meta, funcs_meta))| {
+            funcs_meta
+                .iter()
+                .enumerate()
+                .map(move |(dispatch_id, func_meta)| {
+                    func_meta.to_extrinsic_info(
+                        module_id,
+                        module_position,
+                        dispatch_id,
+                        mod_meta.name.as_str(),
+                    )
+                })
+        })
+        .collect()
+}
+
+pub(super) fn find_module_extrinsic<'a>(
+    modules: &'a [ModuleMetadata],
+    method: &str,
+    extrinsic: &str,
+) -> Option<ExtrinsicInfo<'a>> {
+    let (module_id, (module_position, mod_meta, funcs_meta)) = call_modules(modules)
+        .enumerate()
+        .find(|(_, (_, mod_meta, _))| mod_meta.name == method)?;
+
+    funcs_meta
+        .iter()
+        .enumerate()
+        .find(|(_, func_meta)| func_meta.name == extrinsic)
+        .map(|(dispatch_id, func_meta)| {
+            func_meta.to_extrinsic_info(
+                module_id,
+                module_position,
+                dispatch_id,
+                mod_meta.name.as_str(),
+            )
+        })
+}
+
+/// The index of a module is its position among the modules which have calls.
+pub(super) fn find_extrinsic_by_index(
+    modules: &[ModuleMetadata],
+    module_index: u8,
+    dispatch_index: u8,
+) -> Option<ExtrinsicInfo<'_>> {
+    let (module_position, mod_meta, funcs_meta) =
+        call_modules(modules).nth(module_index as usize)?;
+
+    funcs_meta.get(dispatch_index as usize).map(|func_meta| {
+        func_meta.to_extrinsic_info(
+            module_index as usize,
+            module_position,
+            dispatch_index as usize,
+            mod_meta.name.as_str(),
+        )
+    })
+}
+
+pub(super) fn modules_events(modules: &[ModuleMetadata]) -> Vec<EventInfo<'_>> {
+    modules
+        .iter()
+        .filter_map(|mod_meta| Some((mod_meta, mod_meta.events.as_ref()?)))
+        .enumerate()
+        .flat_map(|(module_index, (mod_meta, events_meta))| {
+            events_meta
+                .iter()
+                .enumerate()
+                .map(move |(event_index, event_meta)| {
+                    event_meta.to_event_info(module_index as u8, event_index as u8, &mod_meta.name)
+                })
+        })
+        .collect()
+}
+
+/// Unlike calls and events, errors are reported with the position of the
+/// module among all modules.
+pub(super) fn modules_errors(modules: &[ModuleMetadata]) -> Vec<ErrorInfo<'_>> {
+    modules
+        .iter()
+        .enumerate()
+        .flat_map(|(module_index, mod_meta)| {
+            mod_meta
+                .errors
+                .iter()
+                .enumerate()
+                .map(move |(error_index, error_meta)| {
+                    error_meta.to_error_info(module_index as u8, error_index as u8, &mod_meta.name)
+                })
+        })
+        .collect()
+}
+
+pub(super) fn constants(modules: &[ModuleMetadata]) -> Vec<ConstantInfo<'_>> {
+    super::v13::constants(modules, |mod_meta| (&mod_meta.name, &mod_meta.constants))
+}
+
+fn module_storage(mod_meta: &ModuleMetadata) -> ModuleStorage<'_> {
+    (&mod_meta.name, &mod_meta.storage)
+}
+
+pub(super) fn modules_storage(modules: &[ModuleMetadata]) -> Vec<StorageInfo<'_>> {
+    super::v13::modules_storage(modules, module_storage)
+}
+
+pub(super) fn find_module_storage<'a>(
+    modules: &'a [ModuleMetadata],
+    module: &str,
+    entry: &str,
+) -> Option<StorageInfo<'a>> {
+    super::v13::find_module_storage(modules, module, entry, module_storage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_hex_metadata, MetadataVersion};
+
+    // Kusama, from the test fixtures of the `frame-metadata` crate.
+    const DUMP: &str = include_str!("../../../interface/dumps/metadata_kusama_v11.hex");
+
+    #[test]
+    fn decode_kusama() {
+        let data = match parse_hex_metadata(DUMP).unwrap() {
+            MetadataVersion::V11(data) => data,
+            other => panic!("unexpected version {}", other.version_number()),
+        };
+
+        assert_eq!(data.modules_extrinsics().len(), 137);
+        assert_eq!(data.modules_events().len(), 114);
+        assert_eq!(data.modules_errors().len(), 188);
+
+        let transfer = data.find_module_extrinsic("Balances", "transfer").unwrap();
+        assert_eq!((transfer.module_id, transfer.dispatch_id), (4, 0));
+
+        // Without declared indices, the module Id counts the modules with
+        // calls only, unlike the position among all modules.
+        let timestamp = data.find_module_extrinsic("Timestamp", "set").unwrap();
+        assert_eq!((timestamp.module_id, timestamp.module_position), (2, 3));
+
+        assert_eq!(
+            transfer.args,
+            vec![
+                ("dest", "<T::Lookup as StaticLookup>::Source"),
+                ("value", "Compact<T::Balance>")
+            ]
+        );
+        assert_eq!(data.find_extrinsic_by_index(4, 0), Some(transfer));
+
+        assert_eq!(
+            data.decode_constant::<u128>("Balances", "ExistentialDeposit")
+                .unwrap(),
+            10_000_000_000
+        );
+
+        // Encodes back into the same dump.
+        assert_eq!(MetadataVersion::V11(data).to_hex(), DUMP);
+    }
+}
@@ -0,0 +1,128 @@
+//! Metadata V12. Equal to V13, except that storage entries cannot be NMaps.
+
+use crate::{
+    ConstantInfo, ConstantsBuilderExt, ErrorBuilderExt, ErrorInfo, EventInfo, EventMetadataExt,
+    ExtrinsicInfo, ModuleMetadataExt, StorageInfo, StorageMetadataExt,
+};
+
+pub use super::v13::{
+    ErrorMetadata, EventMetadata, ExtrinsicMetadata, FunctionArgumentMetadata, FunctionMetadata,
+    ModuleConstantMetadata, ModuleMetadata, StorageEntryMetadata, StorageEntryModifier,
+    StorageEntryType, StorageHasher, StorageMetadata,
+};
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetadataV12 {
+    pub modules: Vec<ModuleMetadata>,
+    pub extrinsics: ExtrinsicMetadata,
+}
+
+impl MetadataV12 {
+    /// Consumes the metadata and returns a smaller copy of it. See
+    /// [`MetadataVersion::pruned`](crate::MetadataVersion::pruned).
+    pub fn pruned(mut self, keep_docs: bool) -> Self {
+        super::v13::prune_modules(&mut self.modules, keep_docs);
+        self
+    }
+}
+
+impl ModuleMetadataExt for MetadataV12 {
+    fn modules_extrinsics<'a>(&'a self) -> Vec<ExtrinsicInfo<'a>> {
+        super::v13::modules_extrinsics(&self.modules)
+    }
+    fn find_module_extrinsic<'a>(
+        &'a self,
+        method: &str,
+        extrinsic: &str,
+    ) -> Option<ExtrinsicInfo<'a>> {
+        super::v13::find_module_extrinsic(&self.modules, method, extrinsic)
+    }
+    fn find_extrinsic_by_index<'a>(
+        &'a self,
+        module_index: u8,
+        dispatch_index: u8,
+    ) -> Option<ExtrinsicInfo<'a>> {
+        super::v13::find_extrinsic_by_index(&self.modules, module_index, dispatch_index)
+    }
+}
+
+impl EventMetadataExt for MetadataV12 {
+    fn modules_events<'a>(&'a self) -> Vec<EventInfo<'a>> {
+        super::v13::modules_events(&self.modules)
+    }
+}
+
+impl ErrorBuilderExt for MetadataV12 {
+    fn modules_errors<'a>(&'a self) -> Vec<ErrorInfo<'a>> {
+        super::v13::modules_errors(&self.modules)
+    }
+}
+
+impl ConstantsBuilderExt for MetadataV12 {
+    fn constants<'a>(&'a self) -> Vec<ConstantInfo<'a>> {
+        super::v13::constants(&self.modules, |mod_meta| {
+            (&mod_meta.name, &mod_meta.constants)
+        })
+    }
+}
+
+impl StorageMetadataExt for MetadataV12 {
+    fn modules_storage<'a>(&'a self) -> Vec<StorageInfo<'a>> {
+        super::v13::modules_storage(&self.modules, |mod_meta| {
+            (&mod_meta.name, &mod_meta.storage)
+        })
+    }
+    fn find_module_storage<'a>(&'a self, module: &str, entry: &str) -> Option<StorageInfo<'a>> {
+        super::v13::find_module_storage(&self.modules, module, entry, |mod_meta| {
+            (&mod_meta.name, &mod_meta.storage)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_hex_metadata, MetadataVersion};
+
+    // Kusama, from the test fixtures of the `frame-metadata` crate.
+    const DUMP: &str = include_str!("../../../interface/dumps/metadata_kusama_v12.hex");
+
+    #[test]
+    fn decode_kusama() {
+        let data = match parse_hex_metadata(DUMP).unwrap() {
+            MetadataVersion::V12(data) => data,
+            other => panic!("unexpected version {}", other.version_number()),
+        };
+
+        assert_eq!(data.modules_extrinsics().len(), 188);
+        assert_eq!(data.modules_events().len(), 142);
+        assert_eq!(data.modules_errors().len(), 234);
+
+        let transfer = data.find_module_extrinsic("Balances", "transfer").unwrap();
+        assert_eq!((transfer.module_id, transfer.dispatch_id), (4, 0));
+        assert_eq!(
+            transfer.args,
+            vec![
+                ("dest", "<T::Lookup as StaticLookup>::Source"),
+                ("value", "Compact<T::Balance>")
+            ]
+        );
+        assert_eq!(data.find_extrinsic_by_index(4, 0), Some(transfer));
+
+        assert_eq!(
+            data.decode_constant::<u128>("Balances", "ExistentialDeposit")
+                .unwrap(),
+            1_666_666_666
+        );
+
+        let account = data.find_module_storage("System", "Account").unwrap();
+        assert_eq!(
+            account.value_type(),
+            "AccountInfo<T::Index, T::AccountData>"
+        );
+
+        // Encodes back into the same dump.
+        assert_eq!(MetadataVersion::V12(data).to_hex(), DUMP);
+    }
+}
@@ -1,13 +1,20 @@
-use crate::{ExtrinsicInfo, ModuleMetadataExt};
+use crate::{
+    ConstantInfo, ConstantsBuilderExt, ErrorBuilderExt, ErrorInfo, EventInfo, EventMetadataExt,
+    ExtrinsicInfo, ModuleMetadataExt, StorageInfo, StorageMetadataExt,
+};
+use std::borrow::Cow;
+use std::hash::Hasher;
+use twox_hash::XxHash64;
 
-// TODO: Should implement Serialize/Deserialize.
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MetadataV13 {
     pub modules: Vec<ModuleMetadata>,
     pub extrinsics: ExtrinsicMetadata,
 }
 
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ModuleMetadata {
     pub name: String,
     pub storage: Option<StorageMetadata>,
@@ -19,27 +26,32 @@ pub struct ModuleMetadata {
 }
 
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StorageMetadata {
     pub prefix: String,
     pub entries: Vec<StorageEntryMetadata>,
 }
 
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StorageEntryMetadata {
     pub name: String,
     pub modifier: StorageEntryModifier,
     pub ty: StorageEntryType,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
     pub default: Vec<u8>,
     pub documentation: Vec<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum StorageEntryModifier {
     Optional,
     Default,
 }
 
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum StorageEntryType {
     Plain(String),
     Map {
@@ -63,6 +75,7 @@ pub enum StorageEntryType {
 }
 
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum StorageHasher {
     Blake2_128,
     Blake2_256,
@@ -73,7 +86,41 @@ pub enum StorageHasher {
     Identity,
 }
 
+impl StorageHasher {
+    /// Hashes the SCALE encoded key. The `*Concat` and `Identity` hashers
+    /// append the key itself.
+    pub fn hash(&self, key: &[u8]) -> Vec<u8> {
+        match self {
+            StorageHasher::Blake2_128 => blake2b(16, key),
+            StorageHasher::Blake2_256 => blake2b(32, key),
+            StorageHasher::Blake2_128Concat => [blake2b(16, key).as_slice(), key].concat(),
+            StorageHasher::Twox128 => twox(2, key),
+            StorageHasher::Twox256 => twox(4, key),
+            StorageHasher::Twox64Concat => [twox(1, key).as_slice(), key].concat(),
+            StorageHasher::Identity => key.to_vec(),
+        }
+    }
+}
+
+fn blake2b(len: usize, data: &[u8]) -> Vec<u8> {
+    blake2_rfc::blake2b::blake2b(len, &[], data)
+        .as_bytes()
+        .to_vec()
+}
+
+/// Concatenates the XxHash64 of the data for each seed, starting from 0.
+fn twox(seeds: u64, data: &[u8]) -> Vec<u8> {
+    (0..seeds)
+        .flat_map(|seed| {
+            let mut hasher = XxHash64::with_seed(seed);
+            hasher.write(data);
+            hasher.finish().to_le_bytes()
+        })
+        .collect()
+}
+
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FunctionMetadata {
     pub name: String,
     pub arguments: Vec<FunctionArgumentMetadata>,
@@ -84,13 +131,15 @@ impl FunctionMetadata {
     pub fn to_extrinsic_info<'a>(
         &'a self,
         module_id: usize,
+        module_position: usize,
         dispatch_id: usize,
         module_name: &'a str,
     ) -> ExtrinsicInfo<'a> {
         ExtrinsicInfo {
-            module_id: module_id,
-            dispatch_id: dispatch_id,
-            module_name: module_name,
+            module_id,
+            module_position,
+            dispatch_id,
+            module_name,
             extrinsic_name: self.name.as_str(),
             args: self
                 .arguments
@@ -103,12 +152,14 @@ impl FunctionMetadata {
 }
 
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FunctionArgumentMetadata {
     pub name: String,
     pub ty: String,
 }
 
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EventMetadata {
     pub name: String,
     pub arguments: Vec<String>,
@@ -116,76 +167,495 @@ pub struct EventMetadata {
 }
 
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ModuleConstantMetadata {
     pub name: String,
     pub ty: String,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
     pub value: Vec<u8>,
     pub documentation: Vec<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ErrorMetadata {
     pub name: String,
     pub documentation: Vec<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExtrinsicMetadata {
     pub version: u8,
     pub signed_extensions: Vec<String>,
 }
 
+impl MetadataV13 {
+    /// Consumes the metadata and returns a smaller copy of it. See
+    /// [`MetadataVersion::pruned`](crate::MetadataVersion::pruned).
+    pub fn pruned(mut self, keep_docs: bool) -> Self {
+        prune_modules(&mut self.modules, keep_docs);
+        self
+    }
+}
+
+/// Replaces the empty sections of the modules with `None` and, unless
+/// `keep_docs` is set, drops their documentation. Only applies to modules with
+/// declared indices, whose calls and events are not identified by position.
+pub(super) fn prune_modules(modules: &mut [ModuleMetadata], keep_docs: bool) {
+    for module in modules {
+        if module
+            .storage
+            .as_ref()
+            .map(|storage| storage.entries.is_empty())
+            .unwrap_or(false)
+        {
+            module.storage = None;
+        }
+        if module.calls.as_ref().map(Vec::is_empty).unwrap_or(false) {
+            module.calls = None;
+        }
+        if module.events.as_ref().map(Vec::is_empty).unwrap_or(false) {
+            module.events = None;
+        }
+
+        if keep_docs {
+            continue;
+        }
+
+        for entry in module.storage.iter_mut().flat_map(|s| s.entries.iter_mut()) {
+            entry.documentation = vec![];
+        }
+        for call in module.calls.iter_mut().flatten() {
+            call.documentation = vec![];
+        }
+        for event in module.events.iter_mut().flatten() {
+            event.documentation = vec![];
+        }
+        for constant in &mut module.constants {
+            constant.documentation = vec![];
+        }
+        for error in &mut module.errors {
+            error.documentation = vec![];
+        }
+    }
+}
+
 impl ModuleMetadataExt for MetadataV13 {
     fn modules_extrinsics<'a>(&'a self) -> Vec<ExtrinsicInfo<'a>> {
-        self.modules
-            .iter()
-            .enumerate()
-            .map(|(module_id, mod_meta)| {
-                mod_meta
-                    .calls
-                    .as_ref()
-                    .map(|funcs_meta| {
-                        funcs_meta
-                            .iter()
-                            .enumerate()
-                            .map(|(dispatch_id, func_meta)| {
-                                func_meta.to_extrinsic_info(
-                                    module_id,
-                                    dispatch_id,
-                                    mod_meta.name.as_str(),
-                                )
-                            })
-                            .collect()
-                    })
-                    .unwrap_or(vec![])
-            })
-            .flatten()
-            .collect()
+        modules_extrinsics(&self.modules)
     }
     fn find_module_extrinsic<'a>(
         &'a self,
         method: &str,
         extrinsic: &str,
     ) -> Option<ExtrinsicInfo<'a>> {
-        self.modules
+        find_module_extrinsic(&self.modules, method, extrinsic)
+    }
+    fn find_extrinsic_by_index<'a>(
+        &'a self,
+        module_index: u8,
+        dispatch_index: u8,
+    ) -> Option<ExtrinsicInfo<'a>> {
+        find_extrinsic_by_index(&self.modules, module_index, dispatch_index)
+    }
+}
+
+pub(super) fn modules_extrinsics(modules: &[ModuleMetadata]) -> Vec<ExtrinsicInfo<'_>> {
+    let count = modules
+        .iter()
+        .map(|mod_meta| mod_meta.calls.as_ref().map(Vec::len).unwrap_or(0))
+        .sum();
+
+    let mut extrinsics = Vec::with_capacity(count);
+    extrinsics.extend(
+        modules
             .iter()
             .enumerate()
-            .find(|(_, mod_meta)| mod_meta.name.as_str() == method)
-            .map(|(module_id, mod_meta)| {
-                mod_meta.calls.as_ref().map(|funcs_meta| {
-                    funcs_meta
-                        .iter()
-                        .enumerate()
-                        .find(|(_, func_meta)| func_meta.name.as_str() == extrinsic)
-                        .map(|(dispatch_id, func_meta)| {
-                            func_meta.to_extrinsic_info(
-                                module_id,
-                                dispatch_id,
-                                mod_meta.name.as_str(),
-                            )
-                        })
+            .flat_map(|(module_position, mod_meta)| {
+                mod_meta
+                    .calls
+                    .iter()
+                    .flatten()
+                    .enumerate()
+                    .map(move |(dispatch_id, func_meta)| {
+                        func_meta.to_extrinsic_info(
+                            mod_meta.index as usize,
+                            module_position,
+                            dispatch_id,
+                            mod_meta.name.as_str(),
+                        )
+                    })
+            }),
+    );
+
+    extrinsics
+}
+
+pub(super) fn find_module_extrinsic<'a>(
+    modules: &'a [ModuleMetadata],
+    method: &str,
+    extrinsic: &str,
+) -> Option<ExtrinsicInfo<'a>> {
+    modules
+        .iter()
+        .enumerate()
+        .find(|(_, mod_meta)| mod_meta.name.as_str() == method)
+        .map(|(module_position, mod_meta)| {
+            mod_meta.calls.as_ref().map(|funcs_meta| {
+                funcs_meta
+                    .iter()
+                    .enumerate()
+                    .find(|(_, func_meta)| func_meta.name.as_str() == extrinsic)
+                    .map(|(dispatch_id, func_meta)| {
+                        func_meta.to_extrinsic_info(
+                            mod_meta.index as usize,
+                            module_position,
+                            dispatch_id,
+                            mod_meta.name.as_str(),
+                        )
+                    })
+            })
+        })
+        .and_then(|res| res?)
+}
+
+pub(super) fn find_extrinsic_by_index(
+    modules: &[ModuleMetadata],
+    module_index: u8,
+    dispatch_index: u8,
+) -> Option<ExtrinsicInfo<'_>> {
+    let (module_position, mod_meta) = modules
+        .iter()
+        .enumerate()
+        .find(|(_, mod_meta)| mod_meta.index == module_index)?;
+
+    mod_meta
+        .calls
+        .as_ref()?
+        .get(dispatch_index as usize)
+        .map(|func_meta| {
+            func_meta.to_extrinsic_info(
+                module_index as usize,
+                module_position,
+                dispatch_index as usize,
+                mod_meta.name.as_str(),
+            )
+        })
+}
+
+impl EventMetadata {
+    pub fn to_event_info<'a>(
+        &'a self,
+        module_index: u8,
+        event_index: u8,
+        module_name: &'a str,
+    ) -> EventInfo<'a> {
+        EventInfo {
+            module_index,
+            event_index,
+            module_name,
+            event_name: self.name.as_str(),
+            args: self.arguments.iter().map(|s| s.as_str()).collect(),
+            documentation: self.documentation.iter().map(|s| s.as_str()).collect(),
+        }
+    }
+}
+
+impl EventMetadataExt for MetadataV13 {
+    fn modules_events<'a>(&'a self) -> Vec<EventInfo<'a>> {
+        modules_events(&self.modules)
+    }
+}
+
+pub(super) fn modules_events(modules: &[ModuleMetadata]) -> Vec<EventInfo<'_>> {
+    modules
+        .iter()
+        .flat_map(|mod_meta| {
+            mod_meta
+                .events
+                .iter()
+                .flatten()
+                .enumerate()
+                .map(move |(event_index, event_meta)| {
+                    event_meta.to_event_info(mod_meta.index, event_index as u8, &mod_meta.name)
+                })
+        })
+        .collect()
+}
+
+impl ErrorMetadata {
+    pub fn to_error_info<'a>(
+        &'a self,
+        module_index: u8,
+        error_index: u8,
+        module_name: &'a str,
+    ) -> ErrorInfo<'a> {
+        ErrorInfo {
+            module_index,
+            error_index,
+            module_name,
+            error_name: self.name.as_str(),
+            documentation: self.documentation.iter().map(|s| s.as_str()).collect(),
+        }
+    }
+}
+
+impl ErrorBuilderExt for MetadataV13 {
+    fn modules_errors<'a>(&'a self) -> Vec<ErrorInfo<'a>> {
+        modules_errors(&self.modules)
+    }
+}
+
+pub(super) fn modules_errors(modules: &[ModuleMetadata]) -> Vec<ErrorInfo<'_>> {
+    modules
+        .iter()
+        .flat_map(|mod_meta| {
+            mod_meta
+                .errors
+                .iter()
+                .enumerate()
+                .map(move |(error_index, error_meta)| {
+                    error_meta.to_error_info(mod_meta.index, error_index as u8, &mod_meta.name)
                 })
+        })
+        .collect()
+}
+
+impl ModuleConstantMetadata {
+    pub fn to_constant_info<'a>(&'a self, module_name: &'a str) -> ConstantInfo<'a> {
+        ConstantInfo {
+            module_name,
+            constant_name: self.name.as_str(),
+            ty: Cow::Borrowed(self.ty.as_str()),
+            value: self.value.as_slice(),
+            documentation: self.documentation.iter().map(|s| s.as_str()).collect(),
+        }
+    }
+}
+
+impl ConstantsBuilderExt for MetadataV13 {
+    fn constants<'a>(&'a self) -> Vec<ConstantInfo<'a>> {
+        constants(&self.modules, |mod_meta| {
+            (&mod_meta.name, &mod_meta.constants)
+        })
+    }
+}
+
+/// The constants of all modules, which are shared by all metadata versions up
+/// to V13.
+pub(super) fn constants<'a, M>(
+    modules: &'a [M],
+    constants: fn(&'a M) -> (&'a String, &'a Vec<ModuleConstantMetadata>),
+) -> Vec<ConstantInfo<'a>> {
+    modules
+        .iter()
+        .map(constants)
+        .flat_map(|(name, constants)| {
+            constants
+                .iter()
+                .map(move |constant| constant.to_constant_info(name))
+        })
+        .collect()
+}
+
+impl StorageMetadataExt for MetadataV13 {
+    fn modules_storage<'a>(&'a self) -> Vec<StorageInfo<'a>> {
+        modules_storage(&self.modules, |mod_meta| {
+            (&mod_meta.name, &mod_meta.storage)
+        })
+    }
+    fn find_module_storage<'a>(&'a self, module: &str, entry: &str) -> Option<StorageInfo<'a>> {
+        find_module_storage(&self.modules, module, entry, |mod_meta| {
+            (&mod_meta.name, &mod_meta.storage)
+        })
+    }
+}
+
+/// The name and the storage of a module, which is shared by all metadata
+/// versions up to V13.
+pub(super) type ModuleStorage<'a> = (&'a String, &'a Option<StorageMetadata>);
+
+pub(super) fn modules_storage<'a, M>(
+    modules: &'a [M],
+    storage: fn(&'a M) -> ModuleStorage<'a>,
+) -> Vec<StorageInfo<'a>> {
+    modules
+        .iter()
+        .map(storage)
+        .flat_map(|(name, storage)| {
+            storage.iter().flat_map(move |storage| {
+                storage
+                    .entries
+                    .iter()
+                    .map(move |entry| storage_info(name, storage, entry))
+            })
+        })
+        .collect()
+}
+
+pub(super) fn find_module_storage<'a, M>(
+    modules: &'a [M],
+    module: &str,
+    entry: &str,
+    storage: fn(&'a M) -> ModuleStorage<'a>,
+) -> Option<StorageInfo<'a>> {
+    let (name, storage) = modules
+        .iter()
+        .map(storage)
+        .find(|(name, _)| name.as_str() == module)?;
+    let storage = storage.as_ref()?;
+
+    storage
+        .entries
+        .iter()
+        .find(|entry_meta| entry_meta.name == entry)
+        .map(|entry| storage_info(name, storage, entry))
+}
+
+fn storage_info<'a>(
+    module_name: &'a str,
+    storage: &'a StorageMetadata,
+    entry: &'a StorageEntryMetadata,
+) -> StorageInfo<'a> {
+    StorageInfo {
+        module_name,
+        prefix: storage.prefix.as_str(),
+        entry_name: entry.name.as_str(),
+        modifier: &entry.modifier,
+        ty: Cow::Borrowed(&entry.ty),
+        default: entry.default.as_slice(),
+        documentation: entry.documentation.iter().map(|s| s.as_str()).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_hex_metadata, parse_raw_metadata, MetadataVersion};
+
+    const DUMP: &str = include_str!("../../../interface/dumps/metadata_kusama_9080.hex");
+
+    fn metadata() -> MetadataV13 {
+        parse_hex_metadata(DUMP.trim())
+            .unwrap()
+            .into_latest()
+            .unwrap()
+    }
+
+    /// The module, dispatch Ids and names of all extrinsics.
+    fn extrinsics(data: &MetadataV13) -> Vec<(usize, usize, &str, &str)> {
+        data.modules_extrinsics()
+            .into_iter()
+            .map(|info| {
+                (
+                    info.module_id,
+                    info.dispatch_id,
+                    info.module_name,
+                    info.extrinsic_name,
+                )
             })
-            .and_then(|res| res?)
+            .collect()
+    }
+
+    #[test]
+    fn encode_into_same_dump() {
+        let metadata = MetadataVersion::V13(metadata());
+        assert_eq!(metadata.to_hex(), DUMP.trim());
+        assert_eq!(parse_raw_metadata(metadata.to_raw()).unwrap(), metadata);
+    }
+
+    #[test]
+    fn pruned_without_docs() {
+        let data = metadata();
+        let pruned = data.clone().pruned(false);
+
+        // Empty sections are removed.
+        for module in &pruned.modules {
+            assert_ne!(module.calls.as_ref().map(Vec::is_empty), Some(true));
+            assert_ne!(module.events.as_ref().map(Vec::is_empty), Some(true));
+            assert_ne!(
+                module
+                    .storage
+                    .as_ref()
+                    .map(|storage| storage.entries.is_empty()),
+                Some(true)
+            );
+        }
+
+        assert!(pruned
+            .modules_extrinsics()
+            .iter()
+            .all(|info| info.documentation.is_empty()));
+        assert!(pruned
+            .modules_events()
+            .iter()
+            .all(|info| info.documentation.is_empty()));
+        assert!(pruned
+            .constants()
+            .iter()
+            .all(|info| info.documentation.is_empty()));
+
+        // Lookups are unaffected.
+        assert_eq!(extrinsics(&pruned), extrinsics(&data));
+        assert_eq!(pruned.modules_storage().len(), data.modules_storage().len());
+        assert_eq!(
+            pruned
+                .find_module_extrinsic("Balances", "transfer_keep_alive")
+                .map(|info| (info.module_id, info.dispatch_id)),
+            Some((4, 3))
+        );
+
+        // Re-encodes into smaller metadata, which parses as usual.
+        let original = MetadataVersion::V13(data).to_raw();
+        let pruned = MetadataVersion::V13(pruned);
+        assert!(pruned.to_raw().len() < original.len());
+        assert_eq!(parse_hex_metadata(pruned.to_hex()).unwrap(), pruned);
+    }
+
+    #[test]
+    fn pruned_with_docs() {
+        let data = metadata();
+        let pruned = data.clone().pruned(true);
+
+        let transfer = pruned
+            .find_module_extrinsic("Balances", "transfer")
+            .unwrap();
+        assert!(!transfer.documentation.is_empty());
+        assert_eq!(
+            Some(transfer),
+            data.find_module_extrinsic("Balances", "transfer")
+        );
+
+        // Only empty sections are removed.
+        let original = MetadataVersion::V13(data).to_raw();
+        let pruned = MetadataVersion::V13(pruned);
+        assert!(pruned.to_raw().len() <= original.len());
+        assert_eq!(parse_hex_metadata(pruned.to_hex()).unwrap(), pruned);
+    }
+
+    #[test]
+    fn module_id_is_declared_index() {
+        let mut data = metadata();
+
+        let position = data
+            .modules
+            .iter()
+            .position(|module| module.name == "Utility")
+            .unwrap();
+        let batch = data.find_module_extrinsic("Utility", "batch").unwrap();
+        assert_eq!((batch.module_id, batch.dispatch_id), (24, 0));
+        assert_eq!(batch.module_position, position);
+        assert_eq!(data.find_extrinsic_by_index(24, 0), Some(batch));
+
+        // Removing a module from the runtime shifts the positions of the later
+        // modules, but not their indices.
+        let index = data.modules.remove(1).index;
+        let batch = data.find_module_extrinsic("Utility", "batch").unwrap();
+        assert_eq!((batch.module_id, batch.dispatch_id), (24, 0));
+        assert_eq!(batch.module_position, position - 1);
+        assert_eq!(data.find_extrinsic_by_index(24, 0), Some(batch.clone()));
+        assert!(data.modules_extrinsics().contains(&batch));
+        assert_eq!(data.find_extrinsic_by_index(index, 0), None);
     }
 }
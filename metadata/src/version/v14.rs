@@ -0,0 +1,718 @@
+//! Metadata V14, which embeds the definitions of all types in a portable type
+//! registry. Types are referenced by their [`TypeId`] within the registry.
+
+use crate::{
+    ConstantInfo, ConstantsBuilderExt, ErrorBuilderExt, ErrorInfo, EventInfo, EventMetadataExt,
+    ExtrinsicInfo, ModuleMetadataExt, StorageInfo, StorageMetadataExt,
+};
+use std::borrow::Cow;
+
+pub use super::v13::{StorageEntryModifier, StorageHasher};
+
+/// The maximum nesting of type names, protecting against cyclic type
+/// parameters.
+const MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetadataV14 {
+    pub types: PortableRegistry,
+    pub pallets: Vec<PalletMetadata>,
+    pub extrinsic: ExtrinsicMetadata,
+    /// The type of the runtime.
+    pub ty: TypeId,
+}
+
+/// A reference to a type of the [`PortableRegistry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TypeId(#[codec(compact)] pub u32);
+
+/// The definitions of all types used by the runtime.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PortableRegistry {
+    pub types: Vec<PortableType>,
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PortableType {
+    #[codec(compact)]
+    pub id: u32,
+    pub ty: Type,
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Type {
+    /// The path of the type, such as `["sp_core", "crypto", "AccountId32"]`.
+    /// Empty for primitives, sequences, arrays, tuples and compacts.
+    pub path: Vec<String>,
+    pub type_params: Vec<TypeParameter>,
+    pub type_def: TypeDef,
+    pub docs: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TypeParameter {
+    pub name: String,
+    pub ty: Option<TypeId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TypeDef {
+    Composite {
+        fields: Vec<Field>,
+    },
+    Variant {
+        variants: Vec<Variant>,
+    },
+    Sequence {
+        type_param: TypeId,
+    },
+    Array {
+        len: u32,
+        type_param: TypeId,
+    },
+    Tuple {
+        fields: Vec<TypeId>,
+    },
+    Primitive(TypeDefPrimitive),
+    Compact {
+        type_param: TypeId,
+    },
+    BitSequence {
+        bit_store_type: TypeId,
+        bit_order_type: TypeId,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TypeDefPrimitive {
+    Bool,
+    Char,
+    Str,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    I256,
+}
+
+impl TypeDefPrimitive {
+    /// The name of the primitive, as used in Rust.
+    pub fn name(&self) -> &'static str {
+        use TypeDefPrimitive::*;
+
+        match self {
+            Bool => "bool",
+            Char => "char",
+            Str => "str",
+            U8 => "u8",
+            U16 => "u16",
+            U32 => "u32",
+            U64 => "u64",
+            U128 => "u128",
+            U256 => "u256",
+            I8 => "i8",
+            I16 => "i16",
+            I32 => "i32",
+            I64 => "i64",
+            I128 => "i128",
+            I256 => "i256",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Field {
+    /// The name of the field, or `None` for the fields of tuple structs.
+    pub name: Option<String>,
+    pub ty: TypeId,
+    /// The name of the type as written in the source code, such as
+    /// `T::AccountId`.
+    pub type_name: Option<String>,
+    pub docs: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Variant {
+    pub name: String,
+    pub fields: Vec<Field>,
+    pub index: u8,
+    pub docs: Vec<String>,
+}
+
+impl PortableRegistry {
+    /// Returns the type with the given Id, if it exists.
+    pub fn resolve(&self, id: TypeId) -> Option<&Type> {
+        // The Ids usually correspond to the position in the registry.
+        match self.types.get(id.0 as usize) {
+            Some(ty) if ty.id == id.0 => Some(&ty.ty),
+            _ => self.types.iter().find(|ty| ty.id == id.0).map(|ty| &ty.ty),
+        }
+    }
+    /// Returns the name of the type with the given Id, such as `Vec<u8>` or
+    /// `AccountId32`. Named types are referred to by the last segment of their
+    /// path, followed by their generic parameters, if any.
+    pub fn type_name(&self, id: TypeId) -> String {
+        self.type_name_nested(id, 0)
+    }
+    fn type_name_nested(&self, id: TypeId, depth: usize) -> String {
+        let ty = match self.resolve(id) {
+            Some(ty) if depth < MAX_DEPTH => ty,
+            Some(_) => return "..".to_string(),
+            None => return format!("<unknown type {}>", id.0),
+        };
+
+        let name = |id: TypeId| self.type_name_nested(id, depth + 1);
+
+        if let Some(path) = ty.path.last() {
+            let params: Vec<String> = ty
+                .type_params
+                .iter()
+                .filter_map(|param| param.ty)
+                .map(name)
+                .collect();
+
+            return if params.is_empty() {
+                path.clone()
+            } else {
+                format!("{}<{}>", path, params.join(", "))
+            };
+        }
+
+        match &ty.type_def {
+            TypeDef::Sequence { type_param } => format!("Vec<{}>", name(*type_param)),
+            TypeDef::Array { len, type_param } => format!("[{}; {}]", name(*type_param), len),
+            TypeDef::Tuple { fields } => format!(
+                "({})",
+                fields
+                    .iter()
+                    .map(|field| name(*field))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            TypeDef::Primitive(primitive) => primitive.name().to_string(),
+            TypeDef::Compact { type_param } => format!("Compact<{}>", name(*type_param)),
+            TypeDef::BitSequence { .. } => "BitVec".to_string(),
+            // Anonymous composites and variants.
+            TypeDef::Composite { .. } | TypeDef::Variant { .. } => format!("<type {}>", id.0),
+        }
+    }
+    /// Returns the variants of the enum with the given Id, such as the calls
+    /// of a pallet. Empty if the type is not an enum.
+    pub fn variants(&self, id: TypeId) -> &[Variant] {
+        match self.resolve(id).map(|ty| &ty.type_def) {
+            Some(TypeDef::Variant { variants }) => variants,
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PalletMetadata {
+    pub name: String,
+    pub storage: Option<PalletStorageMetadata>,
+    /// The enum of the calls of the pallet.
+    pub calls: Option<TypeId>,
+    /// The enum of the events of the pallet.
+    pub event: Option<TypeId>,
+    pub constants: Vec<PalletConstantMetadata>,
+    /// The enum of the errors of the pallet.
+    pub error: Option<TypeId>,
+    pub index: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PalletStorageMetadata {
+    pub prefix: String,
+    pub entries: Vec<StorageEntryMetadata>,
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StorageEntryMetadata {
+    pub name: String,
+    pub modifier: StorageEntryModifier,
+    pub ty: StorageEntryType,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
+    pub default: Vec<u8>,
+    pub docs: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StorageEntryType {
+    Plain(TypeId),
+    /// A map with one hasher per key. The key is a tuple if there are
+    /// multiple hashers.
+    Map {
+        hashers: Vec<StorageHasher>,
+        key: TypeId,
+        value: TypeId,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PalletConstantMetadata {
+    pub name: String,
+    pub ty: TypeId,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
+    pub value: Vec<u8>,
+    pub docs: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExtrinsicMetadata {
+    /// The type of the extrinsic.
+    pub ty: TypeId,
+    pub version: u8,
+    pub signed_extensions: Vec<SignedExtensionMetadata>,
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SignedExtensionMetadata {
+    pub identifier: String,
+    /// The type of the data which is part of the transaction.
+    pub ty: TypeId,
+    /// The type of the data which is only part of the signature payload.
+    pub additional_signed: TypeId,
+}
+
+impl MetadataV14 {
+    /// Consumes the metadata and returns a smaller copy of it. See
+    /// [`MetadataVersion::pruned`](crate::MetadataVersion::pruned). Besides
+    /// the documentation of the pallets, this drops the documentation of all
+    /// types of the registry, including the calls, events and errors.
+    pub fn pruned(mut self, keep_docs: bool) -> Self {
+        for pallet in &mut self.pallets {
+            if pallet
+                .storage
+                .as_ref()
+                .map(|storage| storage.entries.is_empty())
+                .unwrap_or(false)
+            {
+                pallet.storage = None;
+            }
+
+            if keep_docs {
+                continue;
+            }
+
+            for entry in pallet.storage.iter_mut().flat_map(|s| s.entries.iter_mut()) {
+                entry.docs = vec![];
+            }
+            for constant in &mut pallet.constants {
+                constant.docs = vec![];
+            }
+        }
+
+        if keep_docs {
+            return self;
+        }
+
+        for ty in &mut self.types.types {
+            let ty = &mut ty.ty;
+            ty.docs = vec![];
+
+            match &mut ty.type_def {
+                TypeDef::Composite { fields } => {
+                    fields.iter_mut().for_each(|field| field.docs = vec![]);
+                }
+                TypeDef::Variant { variants } => {
+                    for variant in variants {
+                        variant.docs = vec![];
+                        variant
+                            .fields
+                            .iter_mut()
+                            .for_each(|field| field.docs = vec![]);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        self
+    }
+}
+
+impl PalletMetadata {
+    fn extrinsic_info<'a>(&'a self, position: usize, call: &'a Variant) -> ExtrinsicInfo<'a> {
+        ExtrinsicInfo {
+            module_id: self.index as usize,
+            module_position: position,
+            dispatch_id: call.index as usize,
+            module_name: self.name.as_str(),
+            extrinsic_name: call.name.as_str(),
+            args: call
+                .fields
+                .iter()
+                .map(|field| {
+                    (
+                        field.name.as_deref().unwrap_or_default(),
+                        field.type_name.as_deref().unwrap_or_default(),
+                    )
+                })
+                .collect(),
+            documentation: call.docs.iter().map(|s| s.as_str()).collect(),
+        }
+    }
+    fn calls<'a>(&'a self, types: &'a PortableRegistry) -> &'a [Variant] {
+        self.calls.map(|ty| types.variants(ty)).unwrap_or_default()
+    }
+    fn storage_info<'a>(
+        &'a self,
+        types: &PortableRegistry,
+        entry: &'a StorageEntryMetadata,
+    ) -> Option<StorageInfo<'a>> {
+        use super::v13::StorageEntryType as Legacy;
+
+        let ty = match &entry.ty {
+            StorageEntryType::Plain(value) => Legacy::Plain(types.type_name(*value)),
+            StorageEntryType::Map {
+                hashers,
+                key,
+                value,
+            } if hashers.len() == 1 => Legacy::Map {
+                hasher: hashers[0].clone(),
+                key: types.type_name(*key),
+                value: types.type_name(*value),
+                unused: false,
+            },
+            StorageEntryType::Map {
+                hashers,
+                key,
+                value,
+            } => Legacy::NMap {
+                keys: types.type_name(*key),
+                hashers: hashers.clone(),
+                value: types.type_name(*value),
+            },
+        };
+
+        Some(StorageInfo {
+            module_name: self.name.as_str(),
+            prefix: self.storage.as_ref()?.prefix.as_str(),
+            entry_name: entry.name.as_str(),
+            modifier: &entry.modifier,
+            ty: Cow::Owned(ty),
+            default: entry.default.as_slice(),
+            documentation: entry.docs.iter().map(|s| s.as_str()).collect(),
+        })
+    }
+}
+
+impl ModuleMetadataExt for MetadataV14 {
+    fn modules_extrinsics<'a>(&'a self) -> Vec<ExtrinsicInfo<'a>> {
+        self.pallets
+            .iter()
+            .enumerate()
+            .flat_map(|(position, pallet)| {
+                pallet
+                    .calls(&self.types)
+                    .iter()
+                    .map(move |call| pallet.extrinsic_info(position, call))
+            })
+            .collect()
+    }
+    fn find_module_extrinsic<'a>(
+        &'a self,
+        method: &str,
+        extrinsic: &str,
+    ) -> Option<ExtrinsicInfo<'a>> {
+        let (position, pallet) = self
+            .pallets
+            .iter()
+            .enumerate()
+            .find(|(_, pallet)| pallet.name == method)?;
+        pallet
+            .calls(&self.types)
+            .iter()
+            .find(|call| call.name == extrinsic)
+            .map(|call| pallet.extrinsic_info(position, call))
+    }
+    fn find_extrinsic_by_index<'a>(
+        &'a self,
+        module_index: u8,
+        dispatch_index: u8,
+    ) -> Option<ExtrinsicInfo<'a>> {
+        let (position, pallet) = self
+            .pallets
+            .iter()
+            .enumerate()
+            .find(|(_, pallet)| pallet.index == module_index)?;
+        pallet
+            .calls(&self.types)
+            .iter()
+            .find(|call| call.index == dispatch_index)
+            .map(|call| pallet.extrinsic_info(position, call))
+    }
+}
+
+impl EventMetadataExt for MetadataV14 {
+    fn modules_events<'a>(&'a self) -> Vec<EventInfo<'a>> {
+        self.pallets
+            .iter()
+            .flat_map(|pallet| {
+                pallet
+                    .event
+                    .map(|ty| self.types.variants(ty))
+                    .unwrap_or_default()
+                    .iter()
+                    .map(move |event| EventInfo {
+                        module_index: pallet.index,
+                        event_index: event.index,
+                        module_name: pallet.name.as_str(),
+                        event_name: event.name.as_str(),
+                        args: event
+                            .fields
+                            .iter()
+                            .map(|field| field.type_name.as_deref().unwrap_or_default())
+                            .collect(),
+                        documentation: event.docs.iter().map(|s| s.as_str()).collect(),
+                    })
+            })
+            .collect()
+    }
+}
+
+impl ErrorBuilderExt for MetadataV14 {
+    fn modules_errors<'a>(&'a self) -> Vec<ErrorInfo<'a>> {
+        self.pallets
+            .iter()
+            .flat_map(|pallet| {
+                pallet
+                    .error
+                    .map(|ty| self.types.variants(ty))
+                    .unwrap_or_default()
+                    .iter()
+                    .map(move |error| ErrorInfo {
+                        module_index: pallet.index,
+                        error_index: error.index,
+                        module_name: pallet.name.as_str(),
+                        error_name: error.name.as_str(),
+                        documentation: error.docs.iter().map(|s| s.as_str()).collect(),
+                    })
+            })
+            .collect()
+    }
+}
+
+impl ConstantsBuilderExt for MetadataV14 {
+    fn constants<'a>(&'a self) -> Vec<ConstantInfo<'a>> {
+        self.pallets
+            .iter()
+            .flat_map(|pallet| {
+                pallet.constants.iter().map(move |constant| ConstantInfo {
+                    module_name: pallet.name.as_str(),
+                    constant_name: constant.name.as_str(),
+                    ty: Cow::Owned(self.types.type_name(constant.ty)),
+                    value: constant.value.as_slice(),
+                    documentation: constant.docs.iter().map(|s| s.as_str()).collect(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl StorageMetadataExt for MetadataV14 {
+    fn modules_storage<'a>(&'a self) -> Vec<StorageInfo<'a>> {
+        self.pallets
+            .iter()
+            .flat_map(|pallet| {
+                pallet
+                    .storage
+                    .iter()
+                    .flat_map(|storage| storage.entries.iter())
+                    .filter_map(move |entry| pallet.storage_info(&self.types, entry))
+            })
+            .collect()
+    }
+    fn find_module_storage<'a>(&'a self, module: &str, entry: &str) -> Option<StorageInfo<'a>> {
+        let pallet = self.pallets.iter().find(|pallet| pallet.name == module)?;
+
+        pallet
+            .storage
+            .as_ref()?
+            .entries
+            .iter()
+            .find(|entry_meta| entry_meta.name == entry)
+            .and_then(|entry| pallet.storage_info(&self.types, entry))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_hex_metadata, MetadataVersion};
+
+    // Kusama 9111, from the test fixtures of the `frame-metadata` crate.
+    const DUMP: &str = include_str!("../../../interface/dumps/metadata_kusama_9111.hex");
+
+    fn metadata() -> MetadataV14 {
+        parse_hex_metadata(DUMP).unwrap().into_v14().unwrap()
+    }
+
+    #[test]
+    fn decode_kusama_9111() {
+        let data = metadata();
+        assert_eq!(data.types.types.len(), 704);
+        assert_eq!(data.pallets.len(), 51);
+        assert_eq!(
+            data.pallets
+                .iter()
+                .take(5)
+                .map(|pallet| (pallet.name.as_str(), pallet.index))
+                .collect::<Vec<_>>(),
+            vec![
+                ("System", 0),
+                ("Babe", 1),
+                ("Timestamp", 2),
+                ("Indices", 3),
+                ("Balances", 4)
+            ]
+        );
+
+        // Encodes back into the same dump.
+        assert_eq!(MetadataVersion::V14(data).to_hex(), DUMP);
+    }
+
+    #[test]
+    fn type_registry() {
+        let data = metadata();
+        let types = &data.types;
+
+        let account = types.resolve(TypeId(0)).unwrap();
+        assert_eq!(account.path, vec!["sp_core", "crypto", "AccountId32"]);
+        assert_eq!(types.type_name(TypeId(0)), "AccountId32");
+        assert!(types.resolve(TypeId(704)).is_none());
+
+        assert_eq!(data.extrinsic.version, 4);
+        assert_eq!(
+            types.type_name(data.extrinsic.ty),
+            "UncheckedExtrinsic<MultiAddress<AccountId32, ()>, Call, MultiSignature, \
+             (CheckSpecVersion, CheckTxVersion, CheckGenesis, CheckMortality, CheckNonce, \
+             CheckWeight, ChargeTransactionPayment)>"
+        );
+        assert_eq!(
+            data.extrinsic
+                .signed_extensions
+                .iter()
+                .map(|ext| ext.identifier.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "CheckSpecVersion",
+                "CheckTxVersion",
+                "CheckGenesis",
+                "CheckMortality",
+                "CheckNonce",
+                "CheckWeight",
+                "ChargeTransactionPayment"
+            ]
+        );
+
+        // The calls of a pallet are the variants of its call enum.
+        let balances = &data.pallets[4];
+        let transfer = &types.variants(balances.calls.unwrap())[0];
+        assert_eq!(transfer.name, "transfer");
+        assert_eq!(types.type_name(transfer.fields[1].ty), "Compact<u128>");
+    }
+
+    #[test]
+    fn metadata_interfaces() {
+        let data = metadata();
+
+        let transfer = data.find_module_extrinsic("Balances", "transfer").unwrap();
+        assert_eq!((transfer.module_id, transfer.dispatch_id), (4, 0));
+        assert_eq!(
+            transfer.args,
+            vec![
+                ("dest", "<T::Lookup as StaticLookup>::Source"),
+                ("value", "T::Balance")
+            ]
+        );
+        assert_eq!(data.find_extrinsic_by_index(4, 0), Some(transfer));
+
+        let account = data.find_module_storage("System", "Account").unwrap();
+        assert_eq!(account.value_type(), "AccountInfo<u32, AccountData<u128>>");
+        assert_eq!(account.hashers(), vec![&StorageHasher::Blake2_128Concat]);
+
+        let deposit = data
+            .find_constant("Balances", "ExistentialDeposit")
+            .unwrap();
+        assert_eq!(deposit.ty, "u128");
+        assert_eq!(deposit.decode::<u128>().unwrap(), 33_333_333);
+
+        assert_eq!(data.modules_events().len(), 217);
+    }
+
+    #[test]
+    fn pruned_without_docs() {
+        let data = metadata();
+        let pruned = data.clone().pruned(false);
+
+        // The documentation of the types is dropped as well.
+        for ty in &pruned.types.types {
+            assert!(ty.ty.docs.is_empty());
+            if let TypeDef::Variant { variants } = &ty.ty.type_def {
+                assert!(variants.iter().all(|variant| variant.docs.is_empty()));
+            }
+        }
+        assert!(pruned
+            .modules_events()
+            .iter()
+            .all(|info| info.documentation.is_empty()));
+        assert!(pruned
+            .constants()
+            .iter()
+            .all(|info| info.documentation.is_empty()));
+
+        // Lookups are unaffected.
+        let transfer = pruned
+            .find_module_extrinsic("Balances", "transfer_keep_alive")
+            .unwrap();
+        assert!(transfer.documentation.is_empty());
+        assert_eq!((transfer.module_id, transfer.dispatch_id), (4, 3));
+        assert_eq!(pruned.types.types.len(), data.types.types.len());
+
+        let original = MetadataVersion::V14(data).to_raw();
+        let pruned = MetadataVersion::V14(pruned);
+        assert!(pruned.to_raw().len() < original.len() / 2);
+        assert_eq!(parse_hex_metadata(pruned.to_hex()).unwrap(), pruned);
+    }
+
+    #[test]
+    fn pruned_with_docs() {
+        let data = metadata();
+        let pruned = data.clone().pruned(true);
+
+        let transfer = pruned
+            .find_module_extrinsic("Balances", "transfer")
+            .unwrap();
+        assert!(!transfer.documentation.is_empty());
+        assert_eq!(
+            Some(transfer),
+            data.find_module_extrinsic("Balances", "transfer")
+        );
+        assert_eq!(pruned.types, data.types);
+    }
+}
@@ -0,0 +1,257 @@
+//! Python bindings for gekko, exposing metadata parsing, call encoding and
+//! transaction signing.
+//!
+//! The module can be built and installed with
+//! [maturin](https://github.com/PyO3/maturin), e.g. `maturin develop`.
+//!
+//! ```python
+//! import gekko
+//!
+//! metadata = gekko.Metadata.from_hex(open("metadata_kusama_9080.hex").read())
+//! call = metadata.encode_call("Balances", "transfer_keep_alive", {
+//!     "dest": bytes.fromhex("00..."),
+//!     "value": bytes.fromhex("..."),
+//! })
+//!
+//! tx = gekko.sign_transaction(
+//!     call, "//Alice", nonce=0, network="kusama",
+//!     spec_version=9080, transaction_version=5,
+//! )
+//! print(tx.hex())
+//! ```
+
+use gekko::common::sp_core::crypto::Pair;
+use gekko::common::{Ecdsa, Ed25519, Mortality, MultiKeyPair, Network, OpaqueCall, Sr25519};
+use gekko::metadata::{
+    parse_hex_metadata, parse_raw_metadata, version::MetadataV13, Error as MetadataError,
+    ExtrinsicInfo, MetadataVersion, ModuleMetadataExt,
+};
+use gekko::transaction::SignedTransactionBuilder;
+use parity_scale_codec::Encode;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use std::collections::HashMap;
+use std::error::Error;
+
+/// Parsed runtime metadata.
+#[pyclass(module = "gekko")]
+struct Metadata {
+    inner: MetadataV13,
+}
+
+#[pymethods]
+impl Metadata {
+    /// Parses the HEX encoded runtime metadata, with or without the `0x`
+    /// prefix.
+    #[staticmethod]
+    fn from_hex(hex: &str) -> PyResult<Self> {
+        Self::parse(parse_hex_metadata(hex.trim()))
+    }
+
+    /// Parses the raw runtime metadata, including the magic number.
+    #[staticmethod]
+    fn from_bytes(raw: &[u8]) -> PyResult<Self> {
+        Self::parse(parse_raw_metadata(raw))
+    }
+
+    /// Returns all extrinsics as a list of dicts.
+    fn extrinsics<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.inner
+            .modules_extrinsics()
+            .iter()
+            .map(|info| extrinsic_to_dict(py, info))
+            .collect()
+    }
+
+    /// Returns the extrinsic of the given module, or `None` if it does not
+    /// exist.
+    fn find_extrinsic<'py>(
+        &self,
+        py: Python<'py>,
+        module: &str,
+        extrinsic: &str,
+    ) -> PyResult<Option<Bound<'py, PyDict>>> {
+        self.inner
+            .find_module_extrinsic(module, extrinsic)
+            .map(|info| extrinsic_to_dict(py, &info))
+            .transpose()
+    }
+
+    /// Encodes the call by looking up its indices and argument order. Each
+    /// argument is passed on as SCALE encoded bytes, by argument name.
+    fn encode_call<'py>(
+        &self,
+        py: Python<'py>,
+        module: &str,
+        extrinsic: &str,
+        args: HashMap<String, Vec<u8>>,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let info = self
+            .inner
+            .find_module_extrinsic(module, extrinsic)
+            .ok_or_else(|| value_error(format!("{}::{} not found", module, extrinsic)))?;
+
+        if let Some(unknown) = args
+            .keys()
+            .find(|name| !info.args.iter().any(|(arg, _)| arg == name))
+        {
+            return Err(value_error(format!("unknown argument `{}`", unknown)));
+        }
+
+        let mut encoded = vec![info.module_id as u8, info.dispatch_id as u8];
+        for (name, ty) in &info.args {
+            let value = args
+                .get(*name)
+                .ok_or_else(|| value_error(format!("missing argument `{}` ({})", name, ty)))?;
+
+            encoded.extend_from_slice(value);
+        }
+
+        Ok(PyBytes::new(py, &encoded))
+    }
+}
+
+impl Metadata {
+    fn parse(result: Result<MetadataVersion, MetadataError>) -> PyResult<Self> {
+        result
+            .and_then(|data| data.into_latest())
+            .map(|inner| Metadata { inner })
+            .map_err(|err| error_chain("failed to parse metadata", &err))
+    }
+}
+
+/// Builds and signs a transaction, returning the encoded extrinsic.
+///
+/// `network` is either "polkadot", "kusama", "westend" or the 32-byte genesis
+/// hash of any other network. The transaction is immortal unless `mortality`
+/// is passed on as `(period, block_number, block_hash)`.
+#[pyfunction]
+#[pyo3(signature = (
+    call,
+    suri,
+    nonce,
+    network,
+    spec_version,
+    transaction_version,
+    tip = 0,
+    password = None,
+    scheme = "sr25519",
+    mortality = None,
+))]
+#[allow(clippy::too_many_arguments)]
+fn sign_transaction<'py>(
+    py: Python<'py>,
+    call: Vec<u8>,
+    suri: &str,
+    nonce: u32,
+    network: &Bound<'py, PyAny>,
+    spec_version: u32,
+    transaction_version: u32,
+    tip: u128,
+    password: Option<&str>,
+    scheme: &str,
+    mortality: Option<(u64, u64, Vec<u8>)>,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let signer = signer(suri, password, scheme)?;
+
+    let network = if let Ok(name) = network.extract::<String>() {
+        match name.to_lowercase().as_str() {
+            "polkadot" => Network::Polkadot,
+            "kusama" => Network::Kusama,
+            "westend" => Network::Westend,
+            _ => return Err(value_error(format!("unknown network: {}", name))),
+        }
+    } else {
+        Network::Custom(hash(network.extract()?, "genesis hash")?)
+    };
+
+    let mortality = match mortality {
+        Some((period, block_number, block_hash)) => {
+            if !period.is_power_of_two() || period < 4 {
+                return Err(value_error(
+                    "mortality period must be a power of two and at least 4",
+                ));
+            }
+
+            Mortality::Mortal(
+                period,
+                block_number % period,
+                Some(hash(block_hash, "block hash")?),
+            )
+        }
+        None => Mortality::Immortal,
+    };
+
+    let transaction = SignedTransactionBuilder::new()
+        .signer(signer)
+        .call(OpaqueCall(call))
+        .nonce(nonce)
+        .tip_base_unit(tip)
+        .network(network)
+        .mortality(mortality)
+        .spec_version(spec_version)
+        .transaction_version(transaction_version)
+        .build()
+        .map_err(|err| error_chain("failed to build transaction", &err))?;
+
+    Ok(PyBytes::new(py, &transaction.encode()))
+}
+
+/// Creates the key pair of the signer from the secret URI.
+fn signer(suri: &str, password: Option<&str>, scheme: &str) -> PyResult<MultiKeyPair> {
+    let err = |err| value_error(format!("invalid secret URI: {:?}", err));
+
+    Ok(match scheme.to_lowercase().as_str() {
+        "sr25519" => Sr25519::from_string(suri, password).map_err(err)?.into(),
+        "ed25519" => Ed25519::from_string(suri, password).map_err(err)?.into(),
+        "ecdsa" => Ecdsa::from_string(suri, password).map_err(err)?.into(),
+        _ => return Err(value_error(format!("unknown scheme: {}", scheme))),
+    })
+}
+
+fn hash(bytes: Vec<u8>, name: &str) -> PyResult<[u8; 32]> {
+    if bytes.len() != 32 {
+        return Err(value_error(format!("{} must be 32 bytes", name)));
+    }
+
+    let mut hash = [0; 32];
+    hash.copy_from_slice(&bytes);
+    Ok(hash)
+}
+
+fn extrinsic_to_dict<'py>(py: Python<'py>, info: &ExtrinsicInfo) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("module_id", info.module_id)?;
+    dict.set_item("module_position", info.module_position)?;
+    dict.set_item("dispatch_id", info.dispatch_id)?;
+    dict.set_item("module_name", info.module_name)?;
+    dict.set_item("extrinsic_name", info.extrinsic_name)?;
+    dict.set_item("args", info.args.clone())?;
+    dict.set_item("documentation", info.documentation.clone())?;
+    Ok(dict)
+}
+
+/// Converts the error and its sources into a `ValueError`.
+fn error_chain(context: &str, err: &dyn Error) -> PyErr {
+    let mut msg = format!("{}: {}", context, err);
+    let mut source = err.source();
+    while let Some(err) = source {
+        msg.push_str(&format!(": {}", err));
+        source = err.source();
+    }
+
+    value_error(msg)
+}
+
+fn value_error<T: Into<String>>(msg: T) -> PyErr {
+    PyValueError::new_err(msg.into())
+}
+
+#[pymodule]
+#[pyo3(name = "gekko")]
+fn gekko_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Metadata>()?;
+    m.add_function(wrap_pyfunction!(sign_transaction, m)?)?;
+    Ok(())
+}